@@ -0,0 +1,937 @@
+//! Import of ifupdown's `/etc/network/interfaces` (interfaces(5)).
+//!
+//! `iface` stanzas with the static, dhcp and manual methods become
+//! interfaces, bond, bridge and VLAN options become devices, and `up`
+//! commands that add routes become routes. `source` and
+//! `source-directory` are followed. Hook commands and options netctl can't
+//! translate are reported with the file and line they came from.
+
+use super::Imported;
+use crate::model::{
+    AddressAttributes, AddressConfig, AdminState, BondConfig, BondMode, DeviceConfig, DeviceKind,
+    DhcpMode, DnsConfig, InterfaceConfig, NetworkConfig, RouteConfig, VlanConfig,
+};
+use crate::systemd::networkd::glob_match;
+use netctl_types::Result;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+
+/// Read `path` and the files it sources, and convert them
+pub fn load(path: &Path) -> Result<Imported> {
+    let mut reader = Reader::default();
+    reader.read(path)?;
+
+    let mut converter = Converter {
+        config: NetworkConfig::default(),
+        warnings: reader.warnings,
+        auto: reader.auto,
+    };
+    converter.convert(&reader.stanzas);
+    Ok(Imported {
+        config: converter.config,
+        warnings: converter.warnings,
+    })
+}
+
+/// An `iface` stanza
+#[derive(Debug)]
+struct Stanza {
+    location: String,
+    name: String,
+    family: String,
+    method: String,
+    options: Vec<Opt>,
+}
+
+/// An option line in a stanza, with `_` in its name read as `-`
+#[derive(Debug)]
+struct Opt {
+    location: String,
+    key: String,
+    value: String,
+}
+
+#[derive(Default)]
+struct Reader {
+    stanzas: Vec<Stanza>,
+    /// Interfaces brought up at boot or on hotplug
+    auto: Vec<String>,
+    warnings: Vec<String>,
+    visited: HashSet<PathBuf>,
+}
+
+/// Where option lines go
+enum Context {
+    None,
+    Stanza,
+    /// A stanza netctl skips, like `mapping`
+    Skipped,
+}
+
+impl Reader {
+    fn read(&mut self, path: &Path) -> Result<()> {
+        let canonical = path.canonicalize()?;
+        if !self.visited.insert(canonical) {
+            self.warnings.push(format!(
+                "{} is sourced more than once, skipping",
+                path.display()
+            ));
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new("/"));
+
+        let mut context = Context::None;
+        for (number, line) in logical_lines(&content) {
+            let location = format!("{}:{}", path.display(), number);
+            let (keyword, rest) = match line.split_once(char::is_whitespace) {
+                Some((keyword, rest)) => (keyword, rest.trim()),
+                None => (line.as_str(), ""),
+            };
+            match keyword {
+                "iface" => {
+                    let words: Vec<&str> = rest.split_whitespace().collect();
+                    if words.len() != 3 {
+                        self.warnings.push(format!(
+                            "{}: 'iface {}' isn't supported, skipping",
+                            location, rest
+                        ));
+                        context = Context::Skipped;
+                        continue;
+                    }
+                    self.stanzas.push(Stanza {
+                        location,
+                        name: words[0].to_string(),
+                        family: words[1].to_string(),
+                        method: words[2].to_string(),
+                        options: Vec::new(),
+                    });
+                    context = Context::Stanza;
+                }
+                "auto" | "allow-auto" | "allow-hotplug" => {
+                    self.auto
+                        .extend(rest.split_whitespace().map(str::to_string));
+                    context = Context::None;
+                }
+                "source" => {
+                    for path in self.expand(dir, rest, &location)? {
+                        self.read(&path)?;
+                    }
+                    context = Context::None;
+                }
+                "source-directory" => {
+                    for path in source_directory(&dir.join(rest))? {
+                        self.read(&path)?;
+                    }
+                    context = Context::None;
+                }
+                _ if keyword.starts_with("allow-")
+                    || matches!(
+                        keyword,
+                        "mapping" | "no-auto-down" | "no-scripts" | "rename"
+                    ) =>
+                {
+                    self.warnings.push(format!(
+                        "{}: '{}' isn't supported, skipping",
+                        location, line
+                    ));
+                    context = if keyword == "mapping" {
+                        Context::Skipped
+                    } else {
+                        Context::None
+                    };
+                }
+                _ => match (&context, self.stanzas.last_mut()) {
+                    (Context::Stanza, Some(stanza)) => stanza.options.push(Opt {
+                        location,
+                        key: keyword.replace('_', "-"),
+                        value: rest.to_string(),
+                    }),
+                    (Context::Skipped, _) => {}
+                    _ => self.warnings.push(format!(
+                        "{}: '{}' is outside a stanza, skipping",
+                        location, line
+                    )),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Files a `source` pattern names, relative to the sourcing file's
+    /// directory. Wildcards are supported in the file name only.
+    fn expand(&mut self, dir: &Path, pattern: &str, location: &str) -> Result<Vec<PathBuf>> {
+        let pattern = dir.join(pattern);
+        let name = pattern
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(parent) = pattern.parent() else {
+            return Ok(Vec::new());
+        };
+        if parent.to_string_lossy().contains(['*', '?', '[']) {
+            self.warnings.push(format!(
+                "{}: wildcards in directories aren't supported, skipping 'source {}'",
+                location,
+                pattern.display()
+            ));
+            return Ok(Vec::new());
+        }
+        if !name.contains(['*', '?', '[']) {
+            return Ok(vec![pattern]);
+        }
+
+        let mut paths = Vec::new();
+        let entries = match std::fs::read_dir(parent) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(paths),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if !file_name.starts_with('.') && glob_match(&name, &file_name) {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Files in a `source-directory`, named as run-parts(8) accepts them
+fn source_directory(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(paths),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let valid = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if valid && entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Non-empty, non-comment lines with their line numbers, backslash
+/// continuations joined
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, raw) in content.lines().enumerate() {
+        let (number, line) = match pending.take() {
+            Some((number, mut line)) => {
+                line.push(' ');
+                line.push_str(raw.trim());
+                (number, line)
+            }
+            None => (index + 1, raw.trim().to_string()),
+        };
+        if let Some(stripped) = line.strip_suffix('\\') {
+            pending = Some((number, stripped.trim_end().to_string()));
+            continue;
+        }
+        if !line.is_empty() && !line.starts_with('#') {
+            lines.push((number, line));
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+struct Converter {
+    config: NetworkConfig,
+    warnings: Vec<String>,
+    auto: Vec<String>,
+}
+
+/// What one stanza's static address options add up to
+#[derive(Default)]
+struct StaticAddress {
+    addresses: Vec<String>,
+    netmask: Option<String>,
+    broadcast: Option<String>,
+    peer: Option<String>,
+    gateway: Option<String>,
+    metric: Option<u32>,
+}
+
+impl Converter {
+    fn unsupported(&mut self, opt: &Opt) {
+        self.warnings.push(format!(
+            "{}: '{} {}' isn't supported, skipping",
+            opt.location, opt.key, opt.value
+        ));
+    }
+
+    fn invalid(&mut self, opt: &Opt) {
+        self.warnings.push(format!(
+            "{}: invalid '{} {}', skipping",
+            opt.location, opt.key, opt.value
+        ));
+    }
+
+    fn interface(&mut self, name: &str) -> &mut InterfaceConfig {
+        let index = match self.config.interfaces.iter().position(|i| i.name == name) {
+            Some(index) => index,
+            None => {
+                self.config.interfaces.push(InterfaceConfig::new(name));
+                self.config.interfaces.len() - 1
+            }
+        };
+        &mut self.config.interfaces[index]
+    }
+
+    fn device(&mut self, name: &str, kind: DeviceKind) -> &mut DeviceConfig {
+        let index = match self.config.devices.iter().position(|d| d.name == name) {
+            Some(index) => index,
+            None => {
+                self.config.devices.push(DeviceConfig::new(name, kind));
+                self.config.devices.len() - 1
+            }
+        };
+        &mut self.config.devices[index]
+    }
+
+    fn convert(&mut self, stanzas: &[Stanza]) {
+        // The kernel brings up the loopback interface by itself
+        let loopback: HashSet<&str> = stanzas
+            .iter()
+            .filter(|stanza| stanza.method == "loopback")
+            .map(|stanza| stanza.name.as_str())
+            .collect();
+
+        let mut dhcp: Vec<(String, bool, bool)> = Vec::new();
+        for stanza in stanzas {
+            let (inet, inet6) = (stanza.family == "inet", stanza.family == "inet6");
+            match stanza.method.as_str() {
+                "static" if inet || inet6 => {}
+                "dhcp" if inet || inet6 => match dhcp.iter_mut().find(|d| d.0 == stanza.name) {
+                    Some(entry) => {
+                        entry.1 |= inet;
+                        entry.2 |= inet6;
+                    }
+                    None => dhcp.push((stanza.name.clone(), inet, inet6)),
+                },
+                // networkd accepts router advertisements by default
+                "auto" if inet6 => {}
+                "manual" | "loopback" => {}
+                method => {
+                    self.warnings.push(format!(
+                        "{}: the {} {} method isn't supported, skipping the stanza",
+                        stanza.location, stanza.family, method
+                    ));
+                    continue;
+                }
+            }
+            if !loopback.contains(stanza.name.as_str()) {
+                self.interface(&stanza.name);
+            }
+            self.stanza(stanza);
+        }
+
+        for (name, inet, inet6) in dhcp {
+            self.interface(&name).dhcp = Some(match (inet, inet6) {
+                (true, true) => DhcpMode::Yes,
+                (true, false) => DhcpMode::Ipv4,
+                _ => DhcpMode::Ipv6,
+            });
+        }
+        for name in std::mem::take(&mut self.auto) {
+            if let Some(iface) = self.config.interfaces.iter_mut().find(|i| i.name == name) {
+                iface.state = Some(AdminState::Up);
+            }
+        }
+
+        self.vlans(stanzas);
+
+        // Ports usually only need to exist
+        let empty: Vec<String> = self
+            .config
+            .interfaces
+            .iter()
+            .filter(|iface| **iface == InterfaceConfig::new(&iface.name))
+            .map(|iface| iface.name.clone())
+            .collect();
+        self.config
+            .interfaces
+            .retain(|iface| !empty.contains(&iface.name));
+    }
+
+    fn stanza(&mut self, stanza: &Stanza) {
+        let name = stanza.name.as_str();
+        let mut address = StaticAddress::default();
+
+        for opt in &stanza.options {
+            let value = opt.value.as_str();
+            match opt.key.as_str() {
+                "address" => address.addresses.push(value.to_string()),
+                "netmask" => address.netmask = Some(value.to_string()),
+                "broadcast" => address.broadcast = Some(value.to_string()),
+                "pointopoint" => address.peer = Some(value.to_string()),
+                "gateway" => address.gateway = Some(value.to_string()),
+                "metric" => match value.parse() {
+                    Ok(metric) => address.metric = Some(metric),
+                    Err(_) => self.invalid(opt),
+                },
+                "mtu" => match value.parse() {
+                    Ok(mtu) => self.interface(name).mtu = Some(mtu),
+                    Err(_) => self.invalid(opt),
+                },
+                "dns-nameservers" | "dns-nameserver" => {
+                    for server in value.split_whitespace() {
+                        match server.parse() {
+                            Ok(server) => self.dns(name).servers.push(server),
+                            Err(_) => self.invalid(opt),
+                        }
+                    }
+                }
+                "dns-search" | "dns-domain" => {
+                    let domains = value.split_whitespace().map(str::to_string);
+                    self.dns(name).domains.extend(domains);
+                }
+                // Ports may also name the bond with bond-master
+                "bond-slaves" => {
+                    let bond = self.device(name, DeviceKind::Bond);
+                    for port in value.split_whitespace().filter(|&port| port != "none") {
+                        if !bond.interfaces.iter().any(|p| p == port) {
+                            bond.interfaces.push(port.to_string());
+                        }
+                    }
+                }
+                "bond-master" => {
+                    let bond = self.device(value, DeviceKind::Bond);
+                    if !bond.interfaces.iter().any(|port| port == name) {
+                        bond.interfaces.push(name.to_string());
+                    }
+                }
+                "bond-mode" => match bond_mode(value) {
+                    Some(mode) => self.bond(name).mode = Some(mode),
+                    None => self.invalid(opt),
+                },
+                "bond-miimon" => match value.parse() {
+                    Ok(ms) => self.bond(name).mii_monitor_ms = Some(ms),
+                    Err(_) => self.invalid(opt),
+                },
+                "bridge-ports" => match value {
+                    "all" => self.unsupported(opt),
+                    "none" => {
+                        self.device(name, DeviceKind::Bridge);
+                    }
+                    _ => {
+                        let ports = value.split_whitespace().map(str::to_string);
+                        self.device(name, DeviceKind::Bridge)
+                            .interfaces
+                            .extend(ports);
+                    }
+                },
+                // Read with the VLANs once every stanza is known
+                "vlan-raw-device" => {}
+                "up" | "post-up" => match route_command(value) {
+                    Some((RouteAction::Add, dev, route)) => {
+                        let dev = dev.unwrap_or_else(|| name.to_string());
+                        self.interface(&dev).routes.push(route);
+                    }
+                    _ => self.script(opt),
+                },
+                // Routes are removed with the interface
+                "down" | "pre-down" | "post-down"
+                    if matches!(route_command(value), Some((RouteAction::Delete, _, _))) => {}
+                "pre-up" | "down" | "pre-down" | "post-down" => self.script(opt),
+                _ => self.unsupported(opt),
+            }
+        }
+
+        self.static_address(stanza, address);
+    }
+
+    fn script(&mut self, opt: &Opt) {
+        self.warnings.push(format!(
+            "{}: {} '{}' can't be translated, skipping",
+            opt.location, opt.key, opt.value
+        ));
+    }
+
+    fn dns(&mut self, name: &str) -> &mut DnsConfig {
+        self.interface(name)
+            .dns
+            .get_or_insert_with(DnsConfig::default)
+    }
+
+    fn bond(&mut self, name: &str) -> &mut BondConfig {
+        self.device(name, DeviceKind::Bond)
+            .bond
+            .get_or_insert_with(BondConfig::default)
+    }
+
+    fn static_address(&mut self, stanza: &Stanza, address: StaticAddress) {
+        let inet = stanza.family == "inet";
+        let prefix = match address.netmask.as_deref() {
+            None => None,
+            Some(netmask) if inet => netmask_prefix(netmask),
+            Some(netmask) => netmask.parse().ok(),
+        };
+        if address.netmask.is_some() && prefix.is_none() {
+            self.warnings.push(format!(
+                "{}: invalid netmask '{}' for {}",
+                stanza.location,
+                address.netmask.as_deref().unwrap_or_default(),
+                stanza.name
+            ));
+        }
+
+        for (i, addr) in address.addresses.iter().enumerate() {
+            let cidr = match (addr.contains('/'), prefix) {
+                (true, _) => addr.clone(),
+                (false, Some(prefix)) => format!("{}/{}", addr, prefix),
+                (false, None) => {
+                    let host = if inet { 32 } else { 128 };
+                    self.warnings.push(format!(
+                        "{}: {} has no netmask, assuming /{}",
+                        stanza.location, addr, host
+                    ));
+                    format!("{}/{}", addr, host)
+                }
+            };
+            // Broadcast and peer belong to the stanza's first address;
+            // "+" and "-" ask ifupdown to compute the broadcast address
+            let broadcast = address
+                .broadcast
+                .as_deref()
+                .filter(|_| i == 0)
+                .and_then(|b| b.parse::<Ipv4Addr>().ok());
+            let peer = address
+                .peer
+                .as_deref()
+                .filter(|_| i == 0)
+                .and_then(|p| p.parse::<IpAddr>().ok());
+            let config = if broadcast.is_some() || peer.is_some() {
+                AddressConfig::Detailed(AddressAttributes {
+                    address: cidr,
+                    peer,
+                    broadcast,
+                    label: None,
+                    scope: None,
+                    valid_lifetime: None,
+                    preferred_lifetime: None,
+                    flags: Vec::new(),
+                })
+            } else {
+                AddressConfig::Cidr(cidr)
+            };
+            self.interface(&stanza.name).addresses.push(config);
+        }
+
+        if let Some(gateway) = address.gateway {
+            match gateway.parse() {
+                Ok(via) => self.interface(&stanza.name).routes.push(RouteConfig {
+                    to: "default".to_string(),
+                    via: Some(via),
+                    metric: address.metric,
+                }),
+                Err(_) => self.warnings.push(format!(
+                    "{}: invalid gateway '{}', skipping",
+                    stanza.location, gateway
+                )),
+            }
+        }
+    }
+
+    /// VLANs are named `<link>.<id>`, or `vlan<id>` with a `vlan-raw-device`
+    fn vlans(&mut self, stanzas: &[Stanza]) {
+        let mut seen = HashSet::new();
+        for stanza in stanzas {
+            if !seen.insert(stanza.name.as_str()) {
+                continue;
+            }
+            let raw_device = stanzas
+                .iter()
+                .filter(|s| s.name == stanza.name)
+                .flat_map(|s| &s.options)
+                .find(|opt| opt.key == "vlan-raw-device")
+                .map(|opt| opt.value.clone());
+            let dotted = stanza
+                .name
+                .rsplit_once('.')
+                .and_then(|(link, id)| Some((link.to_string(), id.parse::<u16>().ok()?)));
+
+            let vlan = match (dotted, raw_device) {
+                (Some((link, id)), raw_device) => Some((raw_device.unwrap_or(link), id)),
+                (None, Some(link)) => match stanza
+                    .name
+                    .strip_prefix("vlan")
+                    .and_then(|id| id.parse::<u16>().ok())
+                {
+                    Some(id) => Some((link, id)),
+                    None => {
+                        self.warnings.push(format!(
+                            "{}: can't tell the VLAN id of {}, skipping the VLAN",
+                            stanza.location, stanza.name
+                        ));
+                        None
+                    }
+                },
+                (None, None) => None,
+            };
+            if let Some((link, id)) = vlan {
+                self.device(&stanza.name, DeviceKind::Vlan).vlan = Some(VlanConfig { id, link });
+            }
+        }
+    }
+}
+
+/// A dotted IPv4 netmask as a prefix length
+fn netmask_prefix(netmask: &str) -> Option<u8> {
+    if let Ok(prefix) = netmask.parse::<u8>() {
+        return (prefix <= 32).then_some(prefix);
+    }
+    let mask = u32::from(netmask.parse::<Ipv4Addr>().ok()?);
+    let prefix = mask.leading_ones();
+    (mask.checked_shl(prefix).unwrap_or(0) == 0).then_some(prefix as u8)
+}
+
+/// ifenslave accepts the kernel's mode names and numbers
+fn bond_mode(value: &str) -> Option<BondMode> {
+    let name = match value {
+        "0" => "balance-rr",
+        "1" => "active-backup",
+        "2" => "balance-xor",
+        "3" => "broadcast",
+        "4" => "802.3ad",
+        "5" => "balance-tlb",
+        "6" => "balance-alb",
+        name => name,
+    };
+    name.parse().ok()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RouteAction {
+    Add,
+    Delete,
+}
+
+/// A single `ip route` or `route` command adding or deleting a route, with
+/// the device it names. Anything else, including options that would change
+/// what the route means, isn't a route command.
+fn route_command(command: &str) -> Option<(RouteAction, Option<String>, RouteConfig)> {
+    // "|| true" only hides failures
+    let command = command
+        .split_once("||")
+        .map_or(command, |(command, _)| command);
+    if command.contains([';', '&', '|', '`', '$']) {
+        return None;
+    }
+    let mut words = command
+        .split_whitespace()
+        .filter(|word| !word.starts_with("2>") && !word.starts_with('>'));
+    let program = words.next()?.rsplit('/').next()?;
+    let words: Vec<&str> = words.collect();
+    match program {
+        "ip" => ip_route(&words),
+        "route" => net_tools_route(&words),
+        _ => None,
+    }
+}
+
+fn ip_route(mut words: &[&str]) -> Option<(RouteAction, Option<String>, RouteConfig)> {
+    while matches!(words.first(), Some(&"-4") | Some(&"-6")) {
+        words = &words[1..];
+    }
+    if !matches!(*words.first()?, "route" | "ro" | "r") {
+        return None;
+    }
+    let action = match *words.get(1)? {
+        "add" | "a" | "replace" | "append" => RouteAction::Add,
+        "del" | "delete" | "d" => RouteAction::Delete,
+        _ => return None,
+    };
+    let mut route = RouteConfig {
+        to: destination(words.get(2)?)?,
+        via: None,
+        metric: None,
+    };
+    let mut dev = None;
+    let mut rest = words[3..].iter();
+    while let Some(key) = rest.next() {
+        let value = rest.next()?;
+        match *key {
+            "via" => route.via = Some(value.parse().ok()?),
+            "dev" => dev = Some(value.to_string()),
+            "metric" | "preference" | "priority" => route.metric = Some(value.parse().ok()?),
+            "proto" => {}
+            _ => return None,
+        }
+    }
+    Some((action, dev, route))
+}
+
+fn net_tools_route(words: &[&str]) -> Option<(RouteAction, Option<String>, RouteConfig)> {
+    let action = match *words.first()? {
+        "add" => RouteAction::Add,
+        "del" => RouteAction::Delete,
+        _ => return None,
+    };
+    let mut target = None;
+    let mut netmask = None;
+    let mut host = false;
+    let mut route = RouteConfig {
+        to: String::new(),
+        via: None,
+        metric: None,
+    };
+    let mut dev = None;
+    let mut rest = words[1..].iter();
+    while let Some(word) = rest.next() {
+        match *word {
+            "-net" => {}
+            "-host" => host = true,
+            "netmask" => netmask = Some(netmask_prefix(rest.next()?)?),
+            "gw" => route.via = Some(rest.next()?.parse().ok()?),
+            "metric" => route.metric = Some(rest.next()?.parse().ok()?),
+            "dev" => dev = Some(rest.next()?.to_string()),
+            word if target.is_none() => target = Some(word),
+            // The device may also come last, without "dev"
+            word if dev.is_none() => dev = Some(word.to_string()),
+            _ => return None,
+        }
+    }
+    let target = target?;
+    route.to = match (target, netmask) {
+        ("default", _) => "default".to_string(),
+        (target, Some(prefix)) if !host && !target.contains('/') => {
+            format!("{}/{}", target, prefix)
+        }
+        (target, _) => destination(target)?,
+    };
+    Some((action, dev, route))
+}
+
+/// A route destination: `default`, a network, or a host address
+fn destination(target: &str) -> Option<String> {
+    if target == "default" {
+        return Some("default".to_string());
+    }
+    if target.contains('/') {
+        return Some(target.to_string());
+    }
+    Some(match target.parse::<IpAddr>().ok()? {
+        IpAddr::V4(_) => format!("{}/32", target),
+        IpAddr::V6(_) => format!("{}/128", target),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERFACES: &str = r#"# The loopback network interface
+auto lo
+iface lo inet loopback
+
+source interfaces.d/*
+
+auto eno1
+iface eno1 inet static
+    address 192.0.2.10
+    netmask 255.255.255.0
+    gateway 192.0.2.1
+    dns-nameservers 192.0.2.53 \
+        192.0.2.54
+    dns-search example.com
+    mtu 9000
+    up ip route add 10.0.0.0/8 via 192.0.2.254 metric 100 || true
+    post-up route add -net 172.16.0.0 netmask 255.240.0.0 gw 192.0.2.253
+    post-up /usr/local/bin/firewall.sh
+    down ip route del 10.0.0.0/8 via 192.0.2.254
+
+iface eno1 inet6 static
+    address 2001:db8::10/64
+    gateway 2001:db8::1
+
+auto eno1.10
+iface eno1.10 inet dhcp
+
+iface eno1.10 inet6 dhcp
+
+iface ppp0 inet ppp
+    provider isp
+"#;
+
+    const BOND: &str = r#"auto bond0
+iface bond0 inet static
+    address 198.51.100.2/24
+    bond-slaves none
+    bond-mode 4
+    bond_miimon 100
+    bond-lacp-rate fast
+    hwaddress ether 52:54:00:ab:cd:ef
+
+auto enp2s0
+iface enp2s0 inet manual
+    bond-master bond0
+
+auto enp3s0
+iface enp3s0 inet manual
+    bond-master bond0
+    pre-up ethtool -K enp3s0 gro off
+
+iface br0 inet dhcp
+    bridge_ports vlan20
+    bridge_stp off
+
+iface vlan20 inet manual
+    vlan-raw-device bond0
+"#;
+
+    #[test]
+    fn test_import_interfaces() {
+        let dir = std::env::temp_dir().join(format!("netctl-ifupdown-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("interfaces.d")).unwrap();
+        std::fs::write(dir.join("interfaces"), INTERFACES).unwrap();
+        std::fs::write(dir.join("interfaces.d/bond"), BOND).unwrap();
+
+        let imported = load(&dir.join("interfaces")).unwrap();
+        let config = &imported.config;
+
+        let names: Vec<_> = config.interfaces.iter().map(|i| i.name.as_str()).collect();
+        // Ports stay because they're brought up at boot
+        assert_eq!(
+            names,
+            ["bond0", "enp2s0", "enp3s0", "br0", "eno1", "eno1.10"]
+        );
+
+        let eno1 = config.interface("eno1").unwrap();
+        assert_eq!(eno1.state, Some(AdminState::Up));
+        assert_eq!(eno1.mtu, Some(9000));
+        let addresses: Vec<_> = eno1.addresses.iter().map(|a| a.address()).collect();
+        assert_eq!(addresses, ["192.0.2.10/24", "2001:db8::10/64"]);
+        let routes: Vec<_> = eno1
+            .routes
+            .iter()
+            .map(|r| (r.to.as_str(), r.via.unwrap().to_string(), r.metric))
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                ("10.0.0.0/8", "192.0.2.254".to_string(), Some(100)),
+                ("172.16.0.0/12", "192.0.2.253".to_string(), None),
+                ("default", "192.0.2.1".to_string(), None),
+                ("default", "2001:db8::1".to_string(), None),
+            ]
+        );
+        let dns = eno1.dns.as_ref().unwrap();
+        assert_eq!(dns.servers.len(), 2);
+        assert_eq!(dns.domains, ["example.com"]);
+        assert_eq!(
+            config.interface("eno1.10").unwrap().dhcp,
+            Some(DhcpMode::Yes)
+        );
+        assert_eq!(config.interface("br0").unwrap().dhcp, Some(DhcpMode::Ipv4));
+
+        let devices: Vec<_> = config
+            .devices
+            .iter()
+            .map(|d| (d.name.as_str(), d.kind, d.interfaces.clone()))
+            .collect();
+        assert_eq!(
+            devices,
+            [
+                (
+                    "bond0",
+                    DeviceKind::Bond,
+                    vec!["enp2s0".to_string(), "enp3s0".to_string()]
+                ),
+                ("br0", DeviceKind::Bridge, vec!["vlan20".to_string()]),
+                ("vlan20", DeviceKind::Vlan, vec![]),
+                ("eno1.10", DeviceKind::Vlan, vec![]),
+            ]
+        );
+        let bond = config.devices[0].bond.as_ref().unwrap();
+        assert_eq!(bond.mode, Some(BondMode::Lacp));
+        assert_eq!(bond.mii_monitor_ms, Some(100));
+        assert_eq!(config.vlans_on("bond0").next().unwrap().name, "vlan20");
+        assert_eq!(
+            config
+                .vlans_on("eno1")
+                .next()
+                .unwrap()
+                .vlan
+                .as_ref()
+                .unwrap()
+                .id,
+            10
+        );
+
+        let warnings: Vec<_> = imported
+            .warnings
+            .iter()
+            .map(|w| w.split_once(": ").unwrap().1)
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "'bond-lacp-rate fast' isn't supported, skipping",
+                "'hwaddress ether 52:54:00:ab:cd:ef' isn't supported, skipping",
+                "pre-up 'ethtool -K enp3s0 gro off' can't be translated, skipping",
+                "'bridge-stp off' isn't supported, skipping",
+                "post-up '/usr/local/bin/firewall.sh' can't be translated, skipping",
+                "the inet ppp method isn't supported, skipping the stanza",
+            ]
+        );
+        assert!(imported.warnings[0].contains("interfaces.d/bond:7"));
+        assert!(config.validate().iter().all(|issue| !issue.is_error()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bond_ports_named_twice() {
+        let dir = std::env::temp_dir().join(format!("netctl-ifupdown-bond-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("interfaces"),
+            "iface enp2s0 inet manual\n    bond-master bond0\n\n\
+             iface bond0 inet dhcp\n    bond-slaves enp2s0 enp3s0\n",
+        )
+        .unwrap();
+
+        let imported = load(&dir.join("interfaces")).unwrap();
+        let devices = &imported.config.devices;
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].name, "bond0");
+        assert_eq!(devices[0].interfaces, ["enp2s0", "enp3s0"]);
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_route_commands() {
+        let (action, dev, route) =
+            route_command("/sbin/ip -4 route add default via 192.0.2.1 dev eth1").unwrap();
+        assert_eq!(action, RouteAction::Add);
+        assert_eq!(dev.as_deref(), Some("eth1"));
+        assert_eq!(route.to, "default");
+
+        let (_, dev, route) =
+            route_command("route add -host 203.0.113.5 gw 192.0.2.1 eth0").unwrap();
+        assert_eq!(dev.as_deref(), Some("eth0"));
+        assert_eq!(route.to, "203.0.113.5/32");
+
+        assert!(route_command("ip route add 10.0.0.0/8 via 192.0.2.1 table 100").is_none());
+        assert!(route_command("ip route add 10.0.0.0/8 via 192.0.2.1; echo done").is_none());
+        assert!(route_command("iptables -A INPUT -j ACCEPT").is_none());
+        assert_eq!(netmask_prefix("255.255.240.0"), Some(20));
+        assert_eq!(netmask_prefix("255.0.255.0"), None);
+    }
+}
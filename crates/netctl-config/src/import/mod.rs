@@ -0,0 +1,14 @@
+//! Conversion of other tools' network configuration into netctl's model
+
+pub mod ifupdown;
+pub mod netplan;
+
+use crate::model::NetworkConfig;
+
+/// A configuration converted from another tool's files
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imported {
+    pub config: NetworkConfig,
+    /// Settings left out because netctl has no equivalent
+    pub warnings: Vec<String>,
+}
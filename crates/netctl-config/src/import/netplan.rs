@@ -0,0 +1,599 @@
+//! Import of netplan v2 YAML (netplan(5)).
+//!
+//! Ethernets, dummy devices, bonds, bridges and VLANs become interfaces and
+//! devices; every key netctl has no equivalent for is reported by its path,
+//! e.g. `ethernets.eth0.optional`, and left out.
+
+use super::Imported;
+use crate::model::{
+    AddressAttributes, AddressConfig, AdminState, BondConfig, BondMode, DeviceConfig, DeviceKind,
+    DhcpMode, Dhcpv4Config, DnsConfig, InterfaceConfig, MatchConfig, NetworkConfig, RouteConfig,
+    VlanConfig,
+};
+use netctl_types::{Error, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+
+/// Definition sections netctl converts, in the order devices depend on
+/// each other
+const SECTIONS: &[&str] = &["ethernets", "dummy-devices", "bonds", "bridges", "vlans"];
+
+/// Convert the contents of a netplan file
+pub fn parse(content: &str) -> Result<Imported> {
+    let root: Value = serde_yaml::from_str(content)
+        .map_err(|e| Error::Generic(format!("invalid netplan YAML: {}", e)))?;
+    let network = root
+        .get("network")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| Error::Generic("netplan files have a 'network' section".to_string()))?;
+    match network.get("version").and_then(Value::as_u64) {
+        Some(2) => {}
+        Some(version) => {
+            return Err(Error::Generic(format!(
+                "netplan version {} isn't supported, expected 2",
+                version
+            )))
+        }
+        None => return Err(Error::Generic("network.version is missing".to_string())),
+    }
+
+    let mut importer = Importer::default();
+    for (key, _) in network {
+        let key = key_str(key);
+        // netctl renders its own files, whichever backend netplan used
+        if !matches!(key.as_str(), "version" | "renderer") && !SECTIONS.contains(&key.as_str()) {
+            importer.unsupported(&format!("network.{}", key));
+        }
+    }
+
+    let sections: Vec<(&str, &Mapping)> = SECTIONS
+        .iter()
+        .filter_map(|section| Some((*section, network.get(*section)?.as_mapping()?)))
+        .collect();
+    // Definitions refer to each other by id, which set-name can change
+    for (section, definitions) in &sections {
+        for (id, definition) in *definitions {
+            let id = key_str(id);
+            let name = importer.interface_name(section, &id, definition);
+            importer.names.insert(id, name);
+        }
+    }
+    for (section, definitions) in &sections {
+        for (id, definition) in *definitions {
+            let id = key_str(id);
+            match definition.as_mapping() {
+                Some(definition) => importer.definition(section, &id, definition),
+                None => importer.invalid(&format!("{}.{}", section, id), definition),
+            }
+        }
+    }
+
+    Ok(Imported {
+        config: importer.config,
+        warnings: importer.warnings,
+    })
+}
+
+#[derive(Default)]
+struct Importer {
+    config: NetworkConfig,
+    warnings: Vec<String>,
+    /// Interface names by definition id
+    names: BTreeMap<String, String>,
+}
+
+impl Importer {
+    fn unsupported(&mut self, at: &str) {
+        self.warnings
+            .push(format!("{} isn't supported, skipping", at));
+    }
+
+    fn invalid(&mut self, at: &str, value: &Value) {
+        self.warnings
+            .push(format!("{}: invalid value {}, skipping", at, show(value)));
+    }
+
+    fn name(&self, id: &str) -> String {
+        self.names
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// The kernel name of a definition: its `set-name`, the name it
+    /// matches, or its id
+    fn interface_name(&mut self, section: &str, id: &str, definition: &Value) -> String {
+        if section != "ethernets" {
+            return id.to_string();
+        }
+        if let Some(name) = definition.get("set-name").and_then(Value::as_str) {
+            return name.to_string();
+        }
+        let Some(matches) = definition.get("match") else {
+            return id.to_string();
+        };
+        match matches.get("name").and_then(Value::as_str) {
+            Some(name) if !name.contains(['*', '?', '[']) => name.to_string(),
+            _ => {
+                self.warnings.push(format!(
+                    "ethernets.{}: matched without set-name, using '{}' as the interface name",
+                    id, id
+                ));
+                id.to_string()
+            }
+        }
+    }
+
+    fn definition(&mut self, section: &str, id: &str, definition: &Mapping) {
+        let path = format!("{}.{}", section, id);
+        let name = self.name(id);
+        let mut iface = InterfaceConfig::new(&name);
+        let mut device = match section {
+            "dummy-devices" => Some(DeviceConfig::new(&name, DeviceKind::Dummy)),
+            "bonds" => Some(DeviceConfig::new(&name, DeviceKind::Bond)),
+            "bridges" => Some(DeviceConfig::new(&name, DeviceKind::Bridge)),
+            "vlans" => Some(DeviceConfig::new(&name, DeviceKind::Vlan)),
+            _ => None,
+        };
+        let (mut dhcp4, mut dhcp6) = (None, None);
+        let (mut vlan_id, mut vlan_link) = (None, None);
+
+        for (key, value) in definition {
+            let key = key_str(key);
+            let at = format!("{}.{}", path, key);
+            match (section, key.as_str()) {
+                (_, "renderer") | ("ethernets", "set-name") => {}
+                ("ethernets", "match") => self.match_device(&at, definition, value, &mut iface),
+                (_, "dhcp4") => dhcp4 = self.boolean(&at, value),
+                (_, "dhcp6") => dhcp6 = self.boolean(&at, value),
+                (_, "addresses") => {
+                    for address in sequence(value) {
+                        if let Some(address) = self.address(&at, address) {
+                            iface.addresses.push(address);
+                        }
+                    }
+                }
+                (_, "gateway4") | (_, "gateway6") => match value.as_str().map(str::parse) {
+                    Some(Ok(via)) => iface.routes.push(RouteConfig {
+                        to: "default".to_string(),
+                        via: Some(via),
+                        metric: None,
+                    }),
+                    _ => self.invalid(&at, value),
+                },
+                (_, "routes") => {
+                    for (i, route) in sequence(value).enumerate() {
+                        if let Some(route) = self.route(&format!("{}[{}]", at, i), route) {
+                            iface.routes.push(route);
+                        }
+                    }
+                }
+                (_, "nameservers") => iface.dns = self.nameservers(&at, value),
+                (_, "mtu") => match value.as_u64().and_then(|mtu| u32::try_from(mtu).ok()) {
+                    Some(mtu) => iface.mtu = Some(mtu),
+                    None => self.invalid(&at, value),
+                },
+                (_, "dhcp4-overrides") => iface.dhcpv4 = self.dhcp4_overrides(&at, value),
+                (_, "activation-mode") => match value.as_str() {
+                    Some("off") => iface.state = Some(AdminState::Down),
+                    _ => self.unsupported(&format!("{} {}", at, show(value))),
+                },
+                ("bonds", "interfaces") | ("bridges", "interfaces") => {
+                    if let Some(device) = &mut device {
+                        device.interfaces = sequence(value)
+                            .map(|port| self.name(&key_str(port)))
+                            .collect();
+                    }
+                }
+                ("bonds", "parameters") => {
+                    if let Some(device) = &mut device {
+                        device.bond = self.bond_parameters(&at, value);
+                    }
+                }
+                ("vlans", "id") => match value.as_u64().and_then(|id| u16::try_from(id).ok()) {
+                    Some(id) => vlan_id = Some(id),
+                    None => self.invalid(&at, value),
+                },
+                ("vlans", "link") => vlan_link = value.as_str().map(|link| self.name(link)),
+                _ => self.unsupported(&at),
+            }
+        }
+
+        iface.dhcp = match (dhcp4, dhcp6) {
+            (None, None) => None,
+            (dhcp4, dhcp6) => Some(match (dhcp4.unwrap_or(false), dhcp6.unwrap_or(false)) {
+                (true, true) => DhcpMode::Yes,
+                (true, false) => DhcpMode::Ipv4,
+                (false, true) => DhcpMode::Ipv6,
+                (false, false) => DhcpMode::No,
+            }),
+        };
+
+        if let Some(mut device) = device {
+            if device.kind == DeviceKind::Vlan {
+                match (vlan_id, vlan_link) {
+                    (Some(id), Some(link)) => device.vlan = Some(VlanConfig { id, link }),
+                    _ => {
+                        self.warnings
+                            .push(format!("{}: VLANs need an id and a link, skipping", path));
+                        return;
+                    }
+                }
+            }
+            self.config.devices.push(device);
+        }
+        // Ports usually only need to exist
+        if iface != InterfaceConfig::new(&name) {
+            self.config.interfaces.push(iface);
+        }
+    }
+
+    fn match_device(
+        &mut self,
+        at: &str,
+        definition: &Mapping,
+        value: &Value,
+        iface: &mut InterfaceConfig,
+    ) {
+        let Some(matches) = value.as_mapping() else {
+            return self.invalid(at, value);
+        };
+        let renamed = definition.contains_key("set-name");
+        for (key, value) in matches {
+            let key = key_str(key);
+            match key.as_str() {
+                // Used as the interface name when there's no set-name
+                "name" if !renamed => {}
+                "macaddress" if renamed => {
+                    iface.match_device = Some(MatchConfig {
+                        mac_address: value.as_str().map(str::to_string),
+                        path: None,
+                    })
+                }
+                _ => self.unsupported(&format!("{}.{}", at, key)),
+            }
+        }
+    }
+
+    fn boolean(&mut self, at: &str, value: &Value) -> Option<bool> {
+        let parsed = match value {
+            Value::Bool(value) => Some(*value),
+            Value::String(value) => match value.as_str() {
+                "true" | "yes" | "on" | "y" => Some(true),
+                "false" | "no" | "off" | "n" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        };
+        if parsed.is_none() {
+            self.invalid(at, value);
+        }
+        parsed
+    }
+
+    fn address(&mut self, at: &str, value: &Value) -> Option<AddressConfig> {
+        if let Some(address) = value.as_str() {
+            return Some(AddressConfig::Cidr(address.to_string()));
+        }
+        // - "10.0.0.1/24": {label: "eth0:1", lifetime: 0}
+        let Some((address, options)) = value.as_mapping().and_then(|map| map.iter().next()) else {
+            self.invalid(at, value);
+            return None;
+        };
+        let address = key_str(address);
+        let mut attrs = AddressAttributes {
+            address: address.clone(),
+            peer: None,
+            broadcast: None,
+            label: None,
+            scope: None,
+            valid_lifetime: None,
+            preferred_lifetime: None,
+            flags: Vec::new(),
+        };
+        for (key, value) in options.as_mapping().into_iter().flatten() {
+            let key = key_str(key);
+            let at = format!("{}.{}.{}", at, address, key);
+            match (key.as_str(), value) {
+                ("label", Value::String(label)) => attrs.label = Some(label.clone()),
+                ("lifetime", value) if key_str(value) == "0" => attrs.preferred_lifetime = Some(0),
+                ("lifetime", value) if key_str(value) == "forever" => {}
+                _ => self.unsupported(&at),
+            }
+        }
+        Some(AddressConfig::Detailed(attrs))
+    }
+
+    fn route(&mut self, at: &str, value: &Value) -> Option<RouteConfig> {
+        let Some(route) = value.as_mapping() else {
+            self.invalid(at, value);
+            return None;
+        };
+        let mut config = RouteConfig {
+            to: "default".to_string(),
+            via: None,
+            metric: None,
+        };
+        for (key, value) in route {
+            let key = key_str(key);
+            let at = format!("{}.{}", at, key);
+            match key.as_str() {
+                // Kept as written: without a gateway "default" means IPv4,
+                // so ::/0 would lose its family
+                "to" => match value.as_str() {
+                    Some(to) => config.to = to.to_string(),
+                    None => self.invalid(&at, value),
+                },
+                "via" => match value.as_str().map(str::parse) {
+                    Some(Ok(via)) => config.via = Some(via),
+                    _ => self.invalid(&at, value),
+                },
+                "metric" => match value.as_u64().and_then(|m| u32::try_from(m).ok()) {
+                    Some(metric) => config.metric = Some(metric),
+                    None => self.invalid(&at, value),
+                },
+                _ => self.unsupported(&at),
+            }
+        }
+        if route.get("to").is_none() {
+            self.warnings
+                .push(format!("{}: routes need a 'to', skipping", at));
+            return None;
+        }
+        Some(config)
+    }
+
+    fn nameservers(&mut self, at: &str, value: &Value) -> Option<DnsConfig> {
+        let mut dns = DnsConfig::default();
+        for (key, value) in value.as_mapping().into_iter().flatten() {
+            let key = key_str(key);
+            let at = format!("{}.{}", at, key);
+            match key.as_str() {
+                "addresses" => {
+                    for server in sequence(value) {
+                        match server.as_str().map(str::parse) {
+                            Some(Ok(server)) => dns.servers.push(server),
+                            _ => self.invalid(&at, server),
+                        }
+                    }
+                }
+                "search" => dns.domains = sequence(value).map(key_str).collect(),
+                _ => self.unsupported(&at),
+            }
+        }
+        (dns != DnsConfig::default()).then_some(dns)
+    }
+
+    fn dhcp4_overrides(&mut self, at: &str, value: &Value) -> Option<Dhcpv4Config> {
+        let mut dhcpv4 = Dhcpv4Config::default();
+        for (key, value) in value.as_mapping().into_iter().flatten() {
+            let key = key_str(key);
+            let at = format!("{}.{}", at, key);
+            match key.as_str() {
+                "use-dns" => dhcpv4.use_dns = self.boolean(&at, value),
+                "use-routes" => dhcpv4.use_routes = self.boolean(&at, value),
+                "route-metric" => match value.as_u64().and_then(|m| u32::try_from(m).ok()) {
+                    Some(metric) => dhcpv4.route_metric = Some(metric),
+                    None => self.invalid(&at, value),
+                },
+                _ => self.unsupported(&at),
+            }
+        }
+        (dhcpv4 != Dhcpv4Config::default()).then_some(dhcpv4)
+    }
+
+    fn bond_parameters(&mut self, at: &str, value: &Value) -> Option<BondConfig> {
+        let mut bond = BondConfig::default();
+        for (key, value) in value.as_mapping().into_iter().flatten() {
+            let key = key_str(key);
+            let at = format!("{}.{}", at, key);
+            match key.as_str() {
+                "mode" => match key_str(value).parse::<BondMode>() {
+                    Ok(mode) => bond.mode = Some(mode),
+                    Err(_) => self.invalid(&at, value),
+                },
+                "mii-monitor-interval" => match parse_msec(&key_str(value)) {
+                    Some(ms) => bond.mii_monitor_ms = Some(ms),
+                    None => self.invalid(&at, value),
+                },
+                _ => self.unsupported(&at),
+            }
+        }
+        (bond != BondConfig::default()).then_some(bond)
+    }
+}
+
+/// A scalar as text; YAML keys and values needn't be strings
+fn key_str(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        other => show(other),
+    }
+}
+
+fn show(value: &Value) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn sequence(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_sequence().into_iter().flatten()
+}
+
+/// netplan intervals are milliseconds unless they carry a unit
+fn parse_msec(value: &str) -> Option<u32> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms.parse().ok();
+    }
+    if let Some(s) = value.strip_suffix('s') {
+        return s.parse::<u32>().ok()?.checked_mul(1000);
+    }
+    value.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETPLAN: &str = r#"
+network:
+  version: 2
+  renderer: networkd
+  ethernets:
+    eno1:
+      match:
+        macaddress: "52:54:00:12:34:56"
+      set-name: lan0
+      dhcp4: no
+      addresses:
+        - 192.0.2.10/24
+        - "192.0.2.11/24":
+            label: lan0:1
+            lifetime: 0
+      routes:
+        - to: default
+          via: 192.0.2.1
+        - to: 10.0.0.0/8
+          via: 192.0.2.254
+          metric: 100
+          on-link: true
+      nameservers:
+        addresses: [192.0.2.53, "2001:db8::53"]
+        search: [example.com]
+      mtu: 9000
+      optional: true
+    enp2s0: {}
+    enp3s0: {}
+  bonds:
+    bond0:
+      interfaces: [enp2s0, enp3s0]
+      parameters:
+        mode: 802.3ad
+        mii-monitor-interval: 100
+        lacp-rate: fast
+      macaddress: "52:54:00:ab:cd:ef"
+      dhcp4: true
+      dhcp4-overrides:
+        use-dns: false
+        route-metric: 50
+  vlans:
+    vlan10:
+      id: 10
+      link: eno1
+      addresses: [198.51.100.2/24]
+  bridges:
+    br0:
+      interfaces: [vlan10]
+      dhcp6: true
+  wifis:
+    wlp1s0: {}
+"#;
+
+    #[test]
+    fn test_import_netplan() {
+        let imported = parse(NETPLAN).unwrap();
+        let config = &imported.config;
+
+        let lan0 = config.interface("lan0").unwrap();
+        assert_eq!(
+            lan0.match_device.as_ref().unwrap().mac_address.as_deref(),
+            Some("52:54:00:12:34:56")
+        );
+        assert_eq!(lan0.dhcp, Some(DhcpMode::No));
+        assert_eq!(lan0.mtu, Some(9000));
+        assert_eq!(lan0.addresses.len(), 2);
+        assert_eq!(lan0.routes.len(), 2);
+        assert_eq!(lan0.routes[0].to, "default");
+        assert_eq!(lan0.dns.as_ref().unwrap().servers.len(), 2);
+        assert!(config.interface("enp2s0").is_none());
+
+        let bond0 = config.interface("bond0").unwrap();
+        assert_eq!(bond0.dhcp, Some(DhcpMode::Ipv4));
+        assert_eq!(bond0.dhcpv4.as_ref().unwrap().route_metric, Some(50));
+        assert_eq!(config.interface("br0").unwrap().dhcp, Some(DhcpMode::Ipv6));
+
+        let kinds: Vec<_> = config
+            .devices
+            .iter()
+            .map(|d| (d.name.as_str(), d.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("bond0", DeviceKind::Bond),
+                ("br0", DeviceKind::Bridge),
+                ("vlan10", DeviceKind::Vlan)
+            ]
+        );
+        assert_eq!(config.master_of("enp3s0").unwrap().name, "bond0");
+        assert_eq!(config.master_of("vlan10").unwrap().name, "br0");
+        // VLAN links follow set-name
+        assert_eq!(config.vlans_on("lan0").count(), 1);
+        let bond = config.devices[0].bond.as_ref().unwrap();
+        assert_eq!(bond.mode, Some(BondMode::Lacp));
+        assert_eq!(bond.mii_monitor_ms, Some(100));
+
+        assert_eq!(
+            imported.warnings,
+            [
+                "network.wifis isn't supported, skipping",
+                "ethernets.eno1.routes[1].on-link isn't supported, skipping",
+                "ethernets.eno1.optional isn't supported, skipping",
+                "bonds.bond0.parameters.lacp-rate isn't supported, skipping",
+                "bonds.bond0.macaddress isn't supported, skipping",
+            ]
+        );
+        assert!(config.validate().iter().all(|issue| !issue.is_error()));
+    }
+
+    #[test]
+    fn test_route_families_and_invalid_metrics() {
+        let imported = parse(
+            r#"
+network:
+  version: 2
+  ethernets:
+    eth0:
+      dhcp4: true
+      dhcp4-overrides:
+        route-metric: lowest
+      routes:
+        - to: "::/0"
+          scope: link
+        - to: 0.0.0.0/0
+          via: 192.0.2.1
+"#,
+        )
+        .unwrap();
+
+        let eth0 = imported.config.interface("eth0").unwrap();
+        let destinations: Vec<_> = eth0
+            .routes
+            .iter()
+            .map(|route| route.destination().unwrap().to_string())
+            .collect();
+        assert_eq!(destinations, ["::/0", "0.0.0.0/0"]);
+        assert!(eth0.dhcpv4.is_none());
+        assert_eq!(
+            imported.warnings,
+            [
+                "ethernets.eth0.dhcp4-overrides.route-metric: invalid value lowest, skipping",
+                "ethernets.eth0.routes[0].scope isn't supported, skipping",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reject_other_versions() {
+        assert!(parse("network:\n  version: 1\n").is_err());
+        assert!(parse("ethernets: {}\n").is_err());
+        assert!(parse("network:\n  version: 2\n")
+            .unwrap()
+            .config
+            .interfaces
+            .is_empty());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod import;
+pub mod model;
 pub mod systemd;
-pub mod yaml;
+
+pub use model::{ConfigFormat, NetworkConfig, CONFIG_VERSION};
@@ -0,0 +1,1079 @@
+//! The declarative configuration format read by `apply` and `validate`,
+//! written by `export`, and stored in profiles and backups.
+//!
+//! Files carry a `version`; files without one are version 1, the format
+//! netctl has always read. Addresses, routes, MAC addresses and DNS domains
+//! are kept as written and checked by [`NetworkConfig::validate`], so every
+//! problem in a file is reported at once instead of only the first.
+
+use netctl_types::{
+    validate_ifname, validate_pretty_hostname, validate_static_hostname, AddressFlag, AddressScope,
+    AddressSpec, Chassis, DnsDomain, DnsOverTlsMode, DnssecMode, Error, IpNetwork, LinkInfo,
+    LinkKind, LinkState, MacAddress, ResolveMode, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::str::FromStr;
+
+pub use netctl_types::DhcpMode;
+
+/// The newest configuration version this build understands
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Smallest MTU IPv4 allows (RFC 791)
+const MTU_MIN: u32 = 68;
+/// Largest MTU in use, the loopback device's default
+const MTU_MAX: u32 = 65536;
+const MTU_JUMBO: u32 = 9000;
+
+/// Serialization of a [`NetworkConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Pick the format from the file extension, defaulting to YAML
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::Toml,
+            Some("json") => Self::Json,
+            _ => Self::Yaml,
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Json => "json",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::Generic(format!(
+                "invalid format '{}' (expected yaml, toml or json)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A complete network configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Where the file came from; informational only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<HostnameConfig>,
+    /// Virtual devices to create before the interfaces are configured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<DeviceConfig>,
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceConfig>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            metadata: None,
+            hostname: None,
+            devices: Vec::new(),
+            interfaces: Vec::new(),
+        }
+    }
+}
+
+/// Provenance recorded by `export`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub exported_at: String,
+    pub exported_by: String,
+    /// The host the configuration was exported from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+/// Host names and the other hostnamed settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostnameConfig {
+    #[serde(rename = "static", default, skip_serializing_if = "Option::is_none")]
+    pub static_hostname: Option<String>,
+    #[serde(rename = "pretty", default, skip_serializing_if = "Option::is_none")]
+    pub pretty_hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chassis: Option<Chassis>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// A virtual device netctl creates if it doesn't exist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    pub name: String,
+    pub kind: DeviceKind,
+    /// Ports of a bond or bridge
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vlan: Option<VlanConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bond: Option<BondConfig>,
+}
+
+impl DeviceConfig {
+    pub fn new(name: impl Into<String>, kind: DeviceKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            interfaces: Vec::new(),
+            vlan: None,
+            bond: None,
+        }
+    }
+}
+
+/// Kinds of virtual device. Only the ones with a [`LinkKind`] can be
+/// created at runtime; the rest are created by systemd-networkd.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Dummy,
+    Ifb,
+    Nlmon,
+    Bridge,
+    Bond,
+    Vlan,
+}
+
+impl DeviceKind {
+    pub fn link_kind(self) -> Option<LinkKind> {
+        match self {
+            Self::Dummy => Some(LinkKind::Dummy),
+            Self::Ifb => Some(LinkKind::Ifb),
+            Self::Nlmon => Some(LinkKind::Nlmon),
+            Self::Bridge | Self::Bond | Self::Vlan => None,
+        }
+    }
+
+    /// Whether the device has ports listed in `interfaces`
+    pub fn has_ports(self) -> bool {
+        matches!(self, Self::Bridge | Self::Bond)
+    }
+}
+
+impl From<LinkKind> for DeviceKind {
+    fn from(kind: LinkKind) -> Self {
+        match kind {
+            LinkKind::Dummy => Self::Dummy,
+            LinkKind::Ifb => Self::Ifb,
+            LinkKind::Nlmon => Self::Nlmon,
+        }
+    }
+}
+
+impl fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dummy => "dummy",
+            Self::Ifb => "ifb",
+            Self::Nlmon => "nlmon",
+            Self::Bridge => "bridge",
+            Self::Bond => "bond",
+            Self::Vlan => "vlan",
+        })
+    }
+}
+
+impl FromStr for DeviceKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dummy" => Ok(Self::Dummy),
+            "ifb" => Ok(Self::Ifb),
+            "nlmon" => Ok(Self::Nlmon),
+            "bridge" => Ok(Self::Bridge),
+            "bond" => Ok(Self::Bond),
+            "vlan" => Ok(Self::Vlan),
+            _ => Err(Error::Generic(format!(
+                "unsupported device kind '{}' (expected dummy, ifb, nlmon, bridge, bond or vlan)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A VLAN on top of `link`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanConfig {
+    pub id: u16,
+    pub link: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<BondMode>,
+    /// Link monitoring interval in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mii_monitor_ms: Option<u32>,
+}
+
+/// Bonding modes, named as the kernel and networkd name them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BondMode {
+    BalanceRr,
+    ActiveBackup,
+    BalanceXor,
+    Broadcast,
+    #[serde(rename = "802.3ad")]
+    Lacp,
+    BalanceTlb,
+    BalanceAlb,
+}
+
+impl fmt::Display for BondMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::BalanceRr => "balance-rr",
+            Self::ActiveBackup => "active-backup",
+            Self::BalanceXor => "balance-xor",
+            Self::Broadcast => "broadcast",
+            Self::Lacp => "802.3ad",
+            Self::BalanceTlb => "balance-tlb",
+            Self::BalanceAlb => "balance-alb",
+        })
+    }
+}
+
+impl FromStr for BondMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "balance-rr" => Ok(Self::BalanceRr),
+            "active-backup" => Ok(Self::ActiveBackup),
+            "balance-xor" => Ok(Self::BalanceXor),
+            "broadcast" => Ok(Self::Broadcast),
+            "802.3ad" => Ok(Self::Lacp),
+            "balance-tlb" => Ok(Self::BalanceTlb),
+            "balance-alb" => Ok(Self::BalanceAlb),
+            _ => Err(Error::Generic(format!("unknown bond mode '{}'", s))),
+        }
+    }
+}
+
+/// Administrative state of an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminState {
+    // Profiles and backups used to store the state capitalized
+    #[serde(alias = "Up")]
+    Up,
+    #[serde(alias = "Down")]
+    Down,
+}
+
+impl From<LinkState> for AdminState {
+    fn from(state: LinkState) -> Self {
+        match state {
+            LinkState::Up => Self::Up,
+            LinkState::Down => Self::Down,
+        }
+    }
+}
+
+impl fmt::Display for AdminState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<AdminState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    /// Recorded by `export`, profiles and backups; not applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<AddressConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dhcp: Option<DhcpMode>,
+    /// DHCPv4 client settings, used when `dhcp` enables DHCPv4
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dhcpv4: Option<Dhcpv4Config>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns: Option<DnsConfig>,
+    /// Identify the device by hardware instead of by name. With
+    /// `apply --persist` a `.link` file gives it `name` on boot.
+    #[serde(rename = "match", default, skip_serializing_if = "Option::is_none")]
+    pub match_device: Option<MatchConfig>,
+}
+
+impl InterfaceConfig {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            state: None,
+            mtu: None,
+            mac_address: None,
+            addresses: Vec::new(),
+            routes: Vec::new(),
+            dhcp: None,
+            dhcpv4: None,
+            dns: None,
+            match_device: None,
+        }
+    }
+
+    /// The configuration that reproduces a link's current state
+    pub fn from_link(link: &LinkInfo) -> Self {
+        Self {
+            state: Some(link.state.into()),
+            mtu: Some(link.mtu),
+            mac_address: link.mac_address.map(|mac| mac.to_string()),
+            addresses: link
+                .addresses
+                .iter()
+                .map(|addr| AddressConfig::Cidr(addr.to_string()))
+                .collect(),
+            ..Self::new(&link.name)
+        }
+    }
+}
+
+/// An address entry: either a plain CIDR string or an object with attributes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AddressConfig {
+    Cidr(String),
+    Detailed(AddressAttributes),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressAttributes {
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer: Option<IpAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broadcast: Option<Ipv4Addr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<AddressScope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_lifetime: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_lifetime: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<AddressFlag>,
+}
+
+impl AddressConfig {
+    /// The address in CIDR notation
+    pub fn address(&self) -> &str {
+        match self {
+            Self::Cidr(cidr) => cidr,
+            Self::Detailed(attrs) => &attrs.address,
+        }
+    }
+
+    pub fn to_spec(&self) -> Result<AddressSpec> {
+        let network: IpNetwork = self.address().parse()?;
+        let mut spec = AddressSpec::new(network);
+
+        if let Self::Detailed(attrs) = self {
+            spec.peer = attrs.peer;
+            spec.broadcast = attrs.broadcast;
+            spec.label = attrs.label.clone();
+            spec.scope = attrs.scope;
+            spec.valid_lifetime = attrs.valid_lifetime;
+            spec.preferred_lifetime = attrs.preferred_lifetime;
+            spec.flags = attrs.flags.clone();
+        }
+
+        Ok(spec)
+    }
+}
+
+/// A static route out of the interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteConfig {
+    /// Destination in CIDR notation, or `default`
+    pub to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<IpAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metric: Option<u32>,
+}
+
+impl RouteConfig {
+    /// The destination network; `default` is 0.0.0.0/0, or ::/0 when the
+    /// gateway is an IPv6 address
+    pub fn destination(&self) -> Result<IpNetwork> {
+        if self.to == "default" {
+            let any = match self.via {
+                Some(IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                _ => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            };
+            return IpNetwork::new(any, 0);
+        }
+        self.to.parse()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dhcpv4Config {
+    /// Use the DNS servers the server offers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_dns: Option<bool>,
+    /// Install the routes the server offers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_routes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_metric: Option<u32>,
+}
+
+/// Hardware to match; exactly one key is set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchConfig {
+    /// Permanent (burned-in) MAC address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    /// udev `ID_PATH`, e.g. `pci-0000:02:00.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Per-link DNS settings, applied through systemd-resolved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<IpAddr>,
+    /// Search domains; a leading `~` makes a domain routing-only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<String>,
+    /// Use the link for queries that match no routing domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_route: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llmnr: Option<ResolveMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mdns: Option<ResolveMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec: Option<DnssecMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_over_tls: Option<DnsOverTlsMode>,
+}
+
+impl DnsConfig {
+    pub fn parsed_domains(&self) -> Result<Vec<DnsDomain>> {
+        self.domains.iter().map(|domain| domain.parse()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem [`NetworkConfig::validate`] found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl NetworkConfig {
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        let config: Self = match format {
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| {
+            Error::Generic(format!(
+                "invalid {} configuration: {}",
+                format.to_string().to_uppercase(),
+                e
+            ))
+        })?;
+        config.check_version()?;
+        Ok(config)
+    }
+
+    /// Read a configuration file, in the format its extension names
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content, ConfigFormat::from_path(path))
+    }
+
+    pub fn to_string(&self, format: ConfigFormat) -> Result<String> {
+        match format {
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| Error::Generic(format!("failed to serialize configuration: {}", e)))
+    }
+
+    /// Refuse files written for a newer netctl
+    pub fn check_version(&self) -> Result<()> {
+        if self.version == 0 || self.version > CONFIG_VERSION {
+            return Err(Error::Generic(format!(
+                "unsupported configuration version {} (this netctl reads up to version {})",
+                self.version, CONFIG_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// The configuration that reproduces the links' current state
+    pub fn from_links(links: &[LinkInfo]) -> Self {
+        Self {
+            interfaces: links.iter().map(InterfaceConfig::from_link).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn interface(&self, name: &str) -> Option<&InterfaceConfig> {
+        self.interfaces.iter().find(|iface| iface.name == name)
+    }
+
+    /// The bond or bridge `ifname` is a port of
+    pub fn master_of(&self, ifname: &str) -> Option<&DeviceConfig> {
+        self.devices
+            .iter()
+            .find(|device| device.interfaces.iter().any(|port| port == ifname))
+    }
+
+    /// The VLANs on top of `ifname`
+    pub fn vlans_on<'a>(&'a self, ifname: &'a str) -> impl Iterator<Item = &'a DeviceConfig> {
+        self.devices
+            .iter()
+            .filter(move |device| device.vlan.as_ref().is_some_and(|vlan| vlan.link == ifname))
+    }
+
+    /// Check everything serde can't: names, ranges, address syntax and
+    /// combinations, duplicates
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+
+        if let Some(hostname) = &self.hostname {
+            if let Some(name) = &hostname.static_hostname {
+                if let Err(e) = validate_static_hostname(name) {
+                    issues.push(Issue::error(e.to_string()));
+                }
+            }
+            if let Some(name) = &hostname.pretty_hostname {
+                if let Err(e) = validate_pretty_hostname(name) {
+                    issues.push(Issue::error(e.to_string()));
+                }
+            }
+        }
+
+        let mut devices = HashSet::new();
+        for device in &self.devices {
+            if let Err(e) = validate_ifname(&device.name) {
+                issues.push(Issue::error(e.to_string()));
+            }
+            if !devices.insert(device.name.as_str()) {
+                issues.push(Issue::error(format!(
+                    "Device '{}' is defined more than once",
+                    device.name
+                )));
+            }
+            validate_device(device, &mut issues);
+        }
+
+        let mut ports = HashSet::new();
+        for port in self.devices.iter().flat_map(|device| &device.interfaces) {
+            if !ports.insert(port.as_str()) {
+                issues.push(Issue::error(format!(
+                    "Interface '{}' is a port of more than one bond or bridge",
+                    port
+                )));
+            }
+        }
+
+        let mut interfaces = HashSet::new();
+        for iface in &self.interfaces {
+            if !interfaces.insert(iface.name.as_str()) {
+                issues.push(Issue::error(format!(
+                    "Interface '{}' is defined more than once",
+                    iface.name
+                )));
+            }
+            let port = self.master_of(&iface.name).is_some();
+            validate_interface(iface, port, &mut issues);
+        }
+
+        issues
+    }
+}
+
+fn validate_device(device: &DeviceConfig, issues: &mut Vec<Issue>) {
+    let name = &device.name;
+    match (&device.vlan, device.kind) {
+        (None, DeviceKind::Vlan) => issues.push(Issue::error(format!(
+            "VLAN '{}' needs a 'vlan' section with its id and link",
+            name
+        ))),
+        (Some(vlan), DeviceKind::Vlan) => {
+            if !(1..=4094).contains(&vlan.id) {
+                issues.push(Issue::error(format!(
+                    "VLAN id {} of '{}' is out of range (1-4094)",
+                    vlan.id, name
+                )));
+            }
+            if let Err(e) = validate_ifname(&vlan.link) {
+                issues.push(Issue::error(format!("VLAN '{}': {}", name, e)));
+            }
+        }
+        (Some(_), kind) => issues.push(Issue::error(format!(
+            "Device '{}' is a {}, not a VLAN, but has a 'vlan' section",
+            name, kind
+        ))),
+        (None, _) => {}
+    }
+
+    if device.bond.is_some() && device.kind != DeviceKind::Bond {
+        issues.push(Issue::error(format!(
+            "Device '{}' is a {}, not a bond, but has a 'bond' section",
+            name, device.kind
+        )));
+    }
+
+    if !device.kind.has_ports() && !device.interfaces.is_empty() {
+        issues.push(Issue::error(format!(
+            "Device '{}' is a {} and can't have ports",
+            name, device.kind
+        )));
+    }
+    for port in &device.interfaces {
+        if port == name {
+            issues.push(Issue::error(format!(
+                "Device '{}' can't be a port of itself",
+                name
+            )));
+        } else if let Err(e) = validate_ifname(port) {
+            issues.push(Issue::error(format!("Device '{}': {}", name, e)));
+        }
+    }
+}
+
+/// `port` is set for bond and bridge ports, whose addresses are on the master
+fn validate_interface(iface: &InterfaceConfig, port: bool, issues: &mut Vec<Issue>) {
+    let name = &iface.name;
+    if let Err(e) = validate_ifname(name) {
+        issues.push(Issue::error(e.to_string()));
+    }
+
+    if let Some(mtu) = iface.mtu {
+        if mtu < MTU_MIN {
+            issues.push(Issue::error(format!(
+                "MTU {} for interface '{}' is too small (minimum {})",
+                mtu, name, MTU_MIN
+            )));
+        } else if mtu > MTU_MAX {
+            issues.push(Issue::error(format!(
+                "MTU {} for interface '{}' is too large (maximum {})",
+                mtu, name, MTU_MAX
+            )));
+        } else if mtu > MTU_JUMBO && mtu < 65535 {
+            issues.push(Issue::warning(format!(
+                "MTU {} for interface '{}' is unusually large (standard max is {})",
+                mtu, name, MTU_JUMBO
+            )));
+        } else if mtu == MTU_JUMBO {
+            issues.push(Issue::warning(format!(
+                "Jumbo frames (MTU {}) on interface '{}' require network infrastructure support",
+                MTU_JUMBO, name
+            )));
+        }
+    }
+
+    if let Some(mac) = &iface.mac_address {
+        if mac.parse::<MacAddress>().is_err() {
+            issues.push(Issue::error(format!(
+                "Invalid MAC address '{}' for interface '{}'",
+                mac, name
+            )));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for addr in &iface.addresses {
+        match addr.to_spec() {
+            Err(e) => issues.push(Issue::error(format!(
+                "Invalid address '{}' for interface '{}': {}",
+                addr.address(),
+                name,
+                e
+            ))),
+            Ok(spec) => {
+                if let Err(e) = spec.validate(name) {
+                    issues.push(Issue::error(format!("Interface '{}': {}", name, e)));
+                }
+            }
+        }
+        if !seen.insert(addr.address()) {
+            issues.push(Issue::warning(format!(
+                "Duplicate address '{}' on interface '{}'",
+                addr.address(),
+                name
+            )));
+        }
+    }
+
+    let dhcp = iface.dhcp.is_some_and(|dhcp| dhcp != DhcpMode::No);
+    if iface.state == Some(AdminState::Up) && iface.addresses.is_empty() && !dhcp && !port {
+        issues.push(Issue::warning(format!(
+            "Interface '{}' has no addresses configured",
+            name
+        )));
+    }
+
+    for route in &iface.routes {
+        match route.destination() {
+            Err(e) => issues.push(Issue::error(format!(
+                "Invalid route destination '{}' for interface '{}': {}",
+                route.to, name, e
+            ))),
+            Ok(destination) => {
+                if let Some(via) = route.via {
+                    if via.is_ipv4() != destination.addr.is_ipv4() {
+                        issues.push(Issue::error(format!(
+                            "Route to {} on interface '{}' has a gateway of the other address family ({})",
+                            route.to, name, via
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    if iface.dhcpv4.is_some() && !iface.dhcp.is_some_and(DhcpMode::ipv4) {
+        issues.push(Issue::warning(format!(
+            "Interface '{}' has DHCPv4 settings but DHCPv4 isn't enabled",
+            name
+        )));
+    }
+
+    if let Some(matches) = &iface.match_device {
+        match (&matches.mac_address, &matches.path) {
+            (Some(mac), None) => {
+                if mac.parse::<MacAddress>().is_err() {
+                    issues.push(Issue::error(format!(
+                        "Invalid MAC address '{}' to match for interface '{}'",
+                        mac, name
+                    )));
+                }
+            }
+            (None, Some(_)) => {}
+            _ => issues.push(Issue::error(format!(
+                "The 'match' section of interface '{}' needs exactly one of mac_address and path",
+                name
+            ))),
+        }
+    }
+
+    if let Some(dns) = &iface.dns {
+        for domain in &dns.domains {
+            if let Err(e) = domain.parse::<DnsDomain>() {
+                issues.push(Issue::error(format!("Interface '{}': {}", name, e)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+version: 1
+hostname:
+  static: web01
+  chassis: server
+devices:
+  - name: dummy0
+    kind: dummy
+interfaces:
+  - name: eth0
+    state: up
+    mtu: 1500
+    addresses:
+      - 192.168.1.100/24
+      - address: 192.168.1.200/24
+        label: eth0:vip
+        flags: [noprefixroute]
+    routes:
+      - to: default
+        via: 192.168.1.1
+      - to: 10.0.0.0/8
+        via: 192.168.1.254
+        metric: 100
+    dns:
+      servers: [192.168.1.1, 2001:db8::53]
+      domains: [example.com, "~corp.example"]
+      dnssec: allow-downgrade
+  - name: eth1
+    dhcp: ipv4
+"#;
+
+    #[test]
+    fn test_parse_full_example() {
+        let config = NetworkConfig::parse(EXAMPLE, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.version, 1);
+        assert_eq!(
+            config.hostname.as_ref().unwrap().chassis,
+            Some(Chassis::Server)
+        );
+        assert_eq!(config.devices[0].kind, DeviceKind::Dummy);
+
+        let eth0 = config.interface("eth0").unwrap();
+        assert_eq!(eth0.state, Some(AdminState::Up));
+        assert_eq!(eth0.addresses.len(), 2);
+        assert_eq!(
+            eth0.routes[0].destination().unwrap().to_string(),
+            "0.0.0.0/0"
+        );
+        let dns = eth0.dns.as_ref().unwrap();
+        assert_eq!(dns.dnssec, Some(DnssecMode::AllowDowngrade));
+        assert!(dns.parsed_domains().unwrap()[1].routing_only);
+        assert_eq!(config.interface("eth1").unwrap().dhcp, Some(DhcpMode::Ipv4));
+
+        assert_eq!(config.validate(), Vec::new());
+    }
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let config = NetworkConfig::parse(EXAMPLE, ConfigFormat::Yaml).unwrap();
+        for format in [ConfigFormat::Yaml, ConfigFormat::Toml, ConfigFormat::Json] {
+            let content = config.to_string(format).unwrap();
+            assert_eq!(
+                NetworkConfig::parse(&content, format).unwrap(),
+                config,
+                "{}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_unversioned_and_legacy_files() {
+        // Profiles and backups from before the shared model
+        let legacy = r#"
+interfaces:
+  - name: eth0
+    index: 2
+    state: Up
+    mtu: 1500
+    mac_address: 00:11:22:33:44:55
+    addresses:
+      - 192.168.1.10/24
+"#;
+        let config = NetworkConfig::parse(legacy, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.version, 1);
+        assert_eq!(config.interfaces[0].state, Some(AdminState::Up));
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_newer_version_rejected() {
+        let err =
+            NetworkConfig::parse("version: 2\ninterfaces: []\n", ConfigFormat::Yaml).unwrap_err();
+        assert!(err.to_string().contains("version 2"));
+    }
+
+    #[test]
+    fn test_export_is_valid() {
+        let link = LinkInfo {
+            index: 1,
+            name: "lo".to_string(),
+            state: LinkState::Up,
+            carrier: true,
+            flags: netctl_types::network::IFF_LOOPBACK,
+            mtu: 65536,
+            mac_address: Some("00:00:00:00:00:00".parse().unwrap()),
+            permanent_mac_address: None,
+            altnames: Vec::new(),
+            addresses: vec!["127.0.0.1/8".parse().unwrap()],
+            kind: None,
+            vfs: Vec::new(),
+        };
+        let config = NetworkConfig::from_links(&[link]);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.validate(), Vec::new());
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let config = NetworkConfig::parse(
+            r#"
+interfaces:
+  - name: eth0
+    mtu: 40
+    mac_address: nonsense
+    addresses: [10.0.0.1/33, 10.0.0.2/24, 10.0.0.2/24]
+    routes:
+      - to: 2001:db8::/32
+        via: 10.0.0.1
+    dns:
+      domains: ["."]
+  - name: eth0
+  - name: this-name-is-too-long
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+
+        let issues = config.validate();
+        let errors: Vec<_> = issues.iter().filter(|i| i.is_error()).collect();
+        let warnings: Vec<_> = issues.iter().filter(|i| !i.is_error()).collect();
+        assert_eq!(errors.len(), 7, "{:#?}", issues);
+        assert_eq!(warnings.len(), 1, "{:#?}", issues);
+        assert!(warnings[0].message.contains("Duplicate address"));
+    }
+
+    #[test]
+    fn test_validate_devices() {
+        let config = NetworkConfig::parse(
+            r#"
+devices:
+  - name: bond0
+    kind: bond
+    interfaces: [eth0, eth1]
+    bond: {mode: 802.3ad}
+  - name: br0
+    kind: bridge
+    interfaces: [eth1]
+  - name: vlan0
+    kind: vlan
+    vlan: {id: 4095, link: eth0}
+  - name: vlan1
+    kind: vlan
+  - name: dummy0
+    kind: dummy
+    interfaces: [eth2]
+    bond: {}
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(
+            config.devices[0].bond.as_ref().unwrap().mode,
+            Some(BondMode::Lacp)
+        );
+        assert_eq!(config.master_of("eth0").unwrap().name, "bond0");
+        assert_eq!(config.vlans_on("eth0").count(), 1);
+
+        let messages: Vec<_> = config.validate().into_iter().map(|i| i.message).collect();
+        assert_eq!(messages.len(), 5, "{:#?}", messages);
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("more than one bond or bridge")));
+        assert!(messages.iter().any(|m| m.contains("out of range")));
+    }
+
+    #[test]
+    fn test_ports_need_no_addresses() {
+        let config = NetworkConfig::parse(
+            r#"
+devices:
+  - name: bond0
+    kind: bond
+    interfaces: [eth0]
+interfaces:
+  - name: eth0
+    state: up
+  - name: eth1
+    state: up
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+
+        let messages: Vec<_> = config.validate().into_iter().map(|i| i.message).collect();
+        assert_eq!(messages, ["Interface 'eth1' has no addresses configured"]);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("net.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("net.conf")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!("JSON".parse::<ConfigFormat>().unwrap(), ConfigFormat::Json);
+        assert!("ini".parse::<ConfigFormat>().is_err());
+    }
+}
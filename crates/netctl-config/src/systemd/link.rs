@@ -0,0 +1,289 @@
+//! Generation of systemd `.link` files (systemd.link(5)).
+
+use super::{Installed, FILE_PREFIX};
+use netctl_types::{Error, MacAddress, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{info, instrument};
+
+/// How a `.link` file identifies its device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkMatch {
+    /// Burned-in hardware address
+    PermanentMacAddress(MacAddress),
+    /// udev `ID_PATH`, e.g. `pci-0000:02:00.0`
+    Path(String),
+}
+
+/// What wrote a `.link` file, recorded in its first line so that each
+/// only cleans up its own files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkOwner {
+    /// A configuration's `match:`, installed by `apply --persist`
+    Config,
+    /// `link rename --persist`
+    Rename,
+}
+
+impl LinkOwner {
+    fn marker(self) -> &'static str {
+        match self {
+            LinkOwner::Config => "# Generated by netctl",
+            LinkOwner::Rename => "# Generated by netctl link rename",
+        }
+    }
+
+    /// The command that writes this owner's files
+    fn command(self) -> &'static str {
+        match self {
+            LinkOwner::Config => "netctl apply --persist",
+            LinkOwner::Rename => "netctl link rename --persist",
+        }
+    }
+
+    /// Who wrote a `.link` file, from its contents; `None` if not netctl
+    pub fn of(contents: &str) -> Option<Self> {
+        let first = contents.lines().next()?;
+        [LinkOwner::Config, LinkOwner::Rename]
+            .into_iter()
+            .find(|owner| owner.marker() == first)
+    }
+}
+
+/// A `.link` file that gives a device a fixed name on every boot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkFile {
+    pub owner: LinkOwner,
+    pub matches: LinkMatch,
+    pub name: String,
+}
+
+impl LinkFile {
+    pub fn new(owner: LinkOwner, matches: LinkMatch, name: impl Into<String>) -> Self {
+        Self {
+            owner,
+            matches,
+            name: name.into(),
+        }
+    }
+
+    /// File name sorting ahead of the distribution's 99-default.link
+    pub fn file_name(&self) -> String {
+        format!("10-netctl-{}.link", self.name)
+    }
+
+    /// Write the file into `dir`, creating it if needed, and return its path
+    #[instrument(skip(self), fields(name = %self.name))]
+    pub async fn write_to(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(self.file_name());
+        info!(path = %path.display(), "writing link file");
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&path, self.to_string()).await?;
+        Ok(path)
+    }
+
+    /// `.link` files with the same owner in `dir` that match the same
+    /// device under another name, e.g. from an earlier rename
+    pub async fn superseded(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let match_line = self.match_line();
+        Ok(netctl_link_files(dir)
+            .await?
+            .into_iter()
+            .filter(|(path, contents)| {
+                LinkOwner::of(contents) == Some(self.owner)
+                    && path.file_name() != Some(self.file_name().as_ref())
+                    && contents.lines().any(|line| line == match_line)
+            })
+            .map(|(path, _)| path)
+            .collect())
+    }
+
+    /// `.link` files in `dir` that this one would overwrite or compete with
+    /// for the device, but that another owner wrote. Each owner only
+    /// replaces its own files, so these have to be removed by hand.
+    pub async fn conflicts(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let match_line = self.match_line();
+        Ok(netctl_link_files(dir)
+            .await?
+            .into_iter()
+            .filter(|(path, contents)| {
+                LinkOwner::of(contents) != Some(self.owner)
+                    && (path.file_name() == Some(self.file_name().as_ref())
+                        || contents.lines().any(|line| line == match_line))
+            })
+            .map(|(path, _)| path)
+            .collect())
+    }
+
+    /// Fail if another owner's `.link` file is in the way, see
+    /// [`LinkFile::conflicts`]
+    pub async fn check_conflicts(&self, dir: &Path) -> Result<()> {
+        match self.conflicts(dir).await?.first() {
+            Some(path) => Err(Error::Generic(format!(
+                "'{}' names the same device or interface but wasn't written by '{}'; remove it first",
+                path.display(),
+                self.owner.command()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the file into `dir` and remove the ones it supersedes.
+    /// Refuses to touch files another owner wrote.
+    pub async fn install(&self, dir: &Path) -> Result<Installed> {
+        self.check_conflicts(dir).await?;
+        let removed = self.superseded(dir).await?;
+        let written = vec![self.write_to(dir).await?];
+        for path in &removed {
+            info!(path = %path.display(), "removing superseded link file");
+            tokio::fs::remove_file(path).await?;
+        }
+        Ok(Installed { written, removed })
+    }
+
+    fn match_line(&self) -> String {
+        match &self.matches {
+            LinkMatch::PermanentMacAddress(mac) => format!("PermanentMACAddress={}", mac),
+            LinkMatch::Path(path) => format!("Path={}", path),
+        }
+    }
+}
+
+/// Every `.link` file in `dir` with netctl's file name prefix, sorted, with
+/// its contents
+async fn netctl_link_files(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(e) => return Err(e.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(FILE_PREFIX) && name.ends_with(".link") {
+            let contents = tokio::fs::read_to_string(entry.path()).await?;
+            files.push((entry.path(), contents));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl fmt::Display for LinkFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.owner.marker())?;
+        writeln!(f, "[Match]")?;
+        writeln!(f, "{}", self.match_line())?;
+        writeln!(f)?;
+        writeln!(f, "[Link]")?;
+        writeln!(f, "Name={}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_link_file() {
+        let mac: MacAddress = "00:11:22:33:44:55".parse().unwrap();
+        let file = LinkFile::new(
+            LinkOwner::Config,
+            LinkMatch::PermanentMacAddress(mac),
+            "wan0",
+        );
+        assert_eq!(file.file_name(), "10-netctl-wan0.link");
+        assert_eq!(
+            file.to_string(),
+            "# Generated by netctl\n[Match]\nPermanentMACAddress=00:11:22:33:44:55\n\n[Link]\nName=wan0\n"
+        );
+
+        let file = LinkFile::new(
+            LinkOwner::Rename,
+            LinkMatch::Path("pci-0000:02:00.0".to_string()),
+            "lan0",
+        );
+        assert!(file.to_string().contains("Path=pci-0000:02:00.0\n"));
+        assert_eq!(LinkOwner::of(&file.to_string()), Some(LinkOwner::Rename));
+        assert_eq!(LinkOwner::of("[Match]\nPath=pci-0000:02:00.0\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rename_replaces_earlier_rename() {
+        let dir = std::env::temp_dir().join(format!("netctl-link-{}", std::process::id()));
+        let mac: MacAddress = "00:11:22:33:44:55".parse().unwrap();
+        let other: MacAddress = "00:11:22:33:44:66".parse().unwrap();
+        let third: MacAddress = "00:11:22:33:44:77".parse().unwrap();
+        let rename =
+            |mac, name| LinkFile::new(LinkOwner::Rename, LinkMatch::PermanentMacAddress(mac), name);
+
+        rename(mac, "wan0").install(&dir).await.unwrap();
+        rename(other, "lan0").install(&dir).await.unwrap();
+        LinkFile::new(
+            LinkOwner::Config,
+            LinkMatch::PermanentMacAddress(third),
+            "eth0",
+        )
+        .write_to(&dir)
+        .await
+        .unwrap();
+
+        let installed = rename(mac, "uplink").install(&dir).await.unwrap();
+        assert_eq!(installed.written, [dir.join("10-netctl-uplink.link")]);
+        assert_eq!(installed.removed, [dir.join("10-netctl-wan0.link")]);
+        assert!(dir.join("10-netctl-lan0.link").exists());
+        assert!(dir.join("10-netctl-eth0.link").exists());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_other_owners_file_not_overwritten() {
+        let dir = std::env::temp_dir().join(format!("netctl-link-owner-{}", std::process::id()));
+        let mac: MacAddress = "00:11:22:33:44:55".parse().unwrap();
+        let other: MacAddress = "00:11:22:33:44:66".parse().unwrap();
+        let config = LinkFile::new(
+            LinkOwner::Config,
+            LinkMatch::PermanentMacAddress(mac),
+            "wan0",
+        );
+        config.install(&dir).await.unwrap();
+        let path = dir.join("10-netctl-wan0.link");
+
+        // Same file name, another device
+        let rename = LinkFile::new(
+            LinkOwner::Rename,
+            LinkMatch::PermanentMacAddress(other),
+            "wan0",
+        );
+        assert_eq!(
+            rename.conflicts(&dir).await.unwrap(),
+            [dir.join("10-netctl-wan0.link")]
+        );
+        let err = rename.install(&dir).await.unwrap_err();
+        assert!(
+            err.to_string().contains("netctl link rename --persist"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(&path).await.unwrap(),
+            config.to_string()
+        );
+
+        // Same device, another name: both files would try to name it
+        let rename = LinkFile::new(
+            LinkOwner::Rename,
+            LinkMatch::PermanentMacAddress(mac),
+            "uplink",
+        );
+        assert!(rename.install(&dir).await.is_err());
+        assert!(!dir.join("10-netctl-uplink.link").exists());
+
+        // Once removed, the rename is free to go ahead
+        tokio::fs::remove_file(&path).await.unwrap();
+        rename.install(&dir).await.unwrap();
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
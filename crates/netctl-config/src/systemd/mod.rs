@@ -1 +1,285 @@
-// TODO: systemd config types
+//! systemd configuration files
+
+pub mod link;
+pub mod netdev;
+pub mod network;
+pub mod networkd;
+pub mod unit;
+
+pub use link::{LinkFile, LinkMatch, LinkOwner};
+pub use netdev::NetdevFile;
+pub use network::NetworkFile;
+pub use networkd::{Assignment, NetworkdConfig, Resolved, SEARCH_PATH};
+pub use unit::UnitFile;
+
+use crate::model::{InterfaceConfig, NetworkConfig};
+use netctl_types::{Error, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, instrument};
+
+/// Directory for administrator-provided networkd and udev link files
+pub const NETWORK_DIR: &str = "/etc/systemd/network";
+
+/// Prefix of every file netctl writes
+const FILE_PREFIX: &str = "10-netctl-";
+
+/// The networkd files that make a configuration persistent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitFiles {
+    pub links: Vec<LinkFile>,
+    pub netdevs: Vec<NetdevFile>,
+    pub networks: Vec<NetworkFile>,
+    /// Settings left out because networkd can't express them
+    pub warnings: Vec<String>,
+}
+
+/// What [`UnitFiles::install`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Installed {
+    pub written: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+impl UnitFiles {
+    /// Render `config`, which should have passed validation: a `.netdev`
+    /// per device, a `.link` per interface matched by hardware, and a
+    /// `.network` per interface and per bond, bridge or VLAN port.
+    pub fn render(config: &NetworkConfig) -> Result<Self> {
+        let mut files = Self {
+            netdevs: config.devices.iter().map(NetdevFile::from).collect(),
+            ..Self::default()
+        };
+
+        for iface in &config.interfaces {
+            if let Some(matches) = &iface.match_device {
+                let matches = match (&matches.mac_address, &matches.path) {
+                    (Some(mac), _) => LinkMatch::PermanentMacAddress(mac.parse()?),
+                    (None, Some(path)) => LinkMatch::Path(path.clone()),
+                    (None, None) => {
+                        return Err(Error::Generic(format!(
+                            "nothing to match interface '{}' on",
+                            iface.name
+                        )))
+                    }
+                };
+                files
+                    .links
+                    .push(LinkFile::new(LinkOwner::Config, matches, &iface.name));
+            }
+            files.networks.push(NetworkFile::new(iface, config)?);
+        }
+
+        // Ports and VLAN parents need a .network file to be attached, even
+        // when the configuration says nothing else about them
+        let mut attached = BTreeSet::new();
+        for device in &config.devices {
+            attached.extend(device.interfaces.iter().map(String::as_str));
+            if let Some(vlan) = &device.vlan {
+                attached.insert(vlan.link.as_str());
+            }
+        }
+        for ifname in attached {
+            if config.interface(ifname).is_none() {
+                let iface = InterfaceConfig::new(ifname);
+                files.networks.push(NetworkFile::new(&iface, config)?);
+            }
+        }
+
+        files.warnings = files.networks.iter().flat_map(|n| n.warnings()).collect();
+        Ok(files)
+    }
+
+    /// Every file as (file name, contents)
+    pub fn files(&self) -> Vec<(String, String)> {
+        let links = self
+            .links
+            .iter()
+            .map(|file| (file.file_name(), file.to_string()));
+        let netdevs = self
+            .netdevs
+            .iter()
+            .map(|file| (file.file_name(), file.to_string()));
+        let networks = self
+            .networks
+            .iter()
+            .map(|file| (file.file_name(), file.to_string()));
+        links.chain(netdevs).chain(networks).collect()
+    }
+
+    /// Files netctl wrote to `dir` earlier that this set no longer
+    /// contains. `.link` files written by `link rename --persist` are left
+    /// alone.
+    pub async fn stale_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let current: BTreeSet<String> = self.files().into_iter().map(|(name, _)| name).collect();
+        let mut stale = Vec::new();
+
+        let mut entries = match tokio::fs::read_dir(dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(stale),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(FILE_PREFIX) || current.contains(&name) {
+                continue;
+            }
+            let ours = if name.ends_with(".link") {
+                let contents = tokio::fs::read_to_string(entry.path()).await?;
+                LinkOwner::of(&contents) == Some(LinkOwner::Config)
+            } else {
+                name.ends_with(".network") || name.ends_with(".netdev")
+            };
+            if ours {
+                stale.push(entry.path());
+            }
+        }
+        stale.sort();
+        Ok(stale)
+    }
+
+    /// Write every file into `dir` and remove the stale ones. Nothing is
+    /// written if a `.link` file would replace one `link rename --persist`
+    /// wrote.
+    #[instrument(skip(self))]
+    pub async fn install(&self, dir: &Path) -> Result<Installed> {
+        for link in &self.links {
+            link.check_conflicts(dir).await?;
+        }
+        let mut installed = Installed {
+            removed: self.stale_files(dir).await?,
+            ..Installed::default()
+        };
+
+        tokio::fs::create_dir_all(dir).await?;
+        for (name, contents) in self.files() {
+            let path = dir.join(name);
+            info!(path = %path.display(), "writing networkd file");
+            tokio::fs::write(&path, contents).await?;
+            installed.written.push(path);
+        }
+        for path in &installed.removed {
+            info!(path = %path.display(), "removing stale networkd file");
+            tokio::fs::remove_file(path).await?;
+        }
+
+        Ok(installed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ConfigFormat;
+
+    const CONFIG: &str = r#"
+devices:
+  - name: br0
+    kind: bridge
+    interfaces: [eth1]
+  - name: vlan10
+    kind: vlan
+    vlan: {id: 10, link: eth0}
+interfaces:
+  - name: eth0
+    match: {mac_address: "00:11:22:33:44:55"}
+    addresses:
+      - address: 192.168.1.10/24
+        valid_lifetime: 3600
+  - name: br0
+    dhcp: yes
+"#;
+
+    #[test]
+    fn test_render_config() {
+        let config = NetworkConfig::parse(CONFIG, ConfigFormat::Yaml).unwrap();
+        let files = UnitFiles::render(&config).unwrap();
+
+        let names: Vec<_> = files.files().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(
+            names,
+            [
+                "10-netctl-eth0.link",
+                "10-netctl-br0.netdev",
+                "10-netctl-vlan10.netdev",
+                "10-netctl-eth0.network",
+                "10-netctl-br0.network",
+                "10-netctl-eth1.network",
+            ]
+        );
+        assert_eq!(files.networks[0].vlans, ["vlan10"]);
+        assert_eq!(files.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_install_replaces_stale_files() {
+        let dir = std::env::temp_dir().join(format!("netctl-systemd-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        for name in ["10-netctl-old.network", "50-admin.network"] {
+            tokio::fs::write(dir.join(name), "").await.unwrap();
+        }
+        let mac: netctl_types::MacAddress = "00:11:22:33:44:66".parse().unwrap();
+        for (owner, name) in [(LinkOwner::Config, "old"), (LinkOwner::Rename, "wan0")] {
+            LinkFile::new(owner, LinkMatch::PermanentMacAddress(mac), name)
+                .write_to(&dir)
+                .await
+                .unwrap();
+        }
+
+        let config = NetworkConfig::parse(CONFIG, ConfigFormat::Yaml).unwrap();
+        let installed = UnitFiles::render(&config)
+            .unwrap()
+            .install(&dir)
+            .await
+            .unwrap();
+        assert_eq!(installed.written.len(), 6);
+        assert_eq!(
+            installed.removed,
+            [
+                dir.join("10-netctl-old.link"),
+                dir.join("10-netctl-old.network")
+            ]
+        );
+        assert!(dir.join("10-netctl-wan0.link").exists());
+        assert!(dir.join("50-admin.network").exists());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_keeps_renamed_link() {
+        let dir =
+            std::env::temp_dir().join(format!("netctl-systemd-rename-{}", std::process::id()));
+        let mac: netctl_types::MacAddress = "00:11:22:33:44:66".parse().unwrap();
+        let rename = LinkFile::new(
+            LinkOwner::Rename,
+            LinkMatch::PermanentMacAddress(mac),
+            "eth0",
+        );
+        rename.write_to(&dir).await.unwrap();
+
+        let config = NetworkConfig::parse(CONFIG, ConfigFormat::Yaml).unwrap();
+        let err = UnitFiles::render(&config)
+            .unwrap()
+            .install(&dir)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("10-netctl-eth0.link"), "{}", err);
+
+        // Nothing was written, and the rename's file is intact
+        let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        assert_eq!(names, ["10-netctl-eth0.link"]);
+        assert_eq!(
+            tokio::fs::read_to_string(dir.join("10-netctl-eth0.link"))
+                .await
+                .unwrap(),
+            rename.to_string()
+        );
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
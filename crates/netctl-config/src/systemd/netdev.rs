@@ -0,0 +1,87 @@
+//! Generation of systemd-networkd `.netdev` files (systemd.netdev(5)).
+
+use crate::model::{BondConfig, DeviceConfig, DeviceKind};
+use std::fmt;
+
+/// A `.netdev` file creating one virtual device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetdevFile {
+    pub name: String,
+    pub kind: DeviceKind,
+    pub vlan_id: Option<u16>,
+    pub bond: Option<BondConfig>,
+}
+
+impl NetdevFile {
+    pub fn file_name(&self) -> String {
+        format!("10-netctl-{}.netdev", self.name)
+    }
+}
+
+impl From<&DeviceConfig> for NetdevFile {
+    fn from(device: &DeviceConfig) -> Self {
+        Self {
+            name: device.name.clone(),
+            kind: device.kind,
+            vlan_id: device.vlan.as_ref().map(|vlan| vlan.id),
+            bond: device.bond.clone(),
+        }
+    }
+}
+
+impl fmt::Display for NetdevFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Generated by netctl")?;
+        writeln!(f, "[NetDev]")?;
+        writeln!(f, "Name={}", self.name)?;
+        writeln!(f, "Kind={}", self.kind)?;
+
+        if let Some(id) = self.vlan_id {
+            writeln!(f)?;
+            writeln!(f, "[VLAN]")?;
+            writeln!(f, "Id={}", id)?;
+        }
+
+        if let Some(bond) = &self.bond {
+            writeln!(f)?;
+            writeln!(f, "[Bond]")?;
+            if let Some(mode) = bond.mode {
+                writeln!(f, "Mode={}", mode)?;
+            }
+            if let Some(interval) = bond.mii_monitor_ms {
+                writeln!(f, "MIIMonitorSec={}ms", interval)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BondMode, VlanConfig};
+
+    #[test]
+    fn test_render_netdev_files() {
+        let mut vlan = DeviceConfig::new("vlan10", DeviceKind::Vlan);
+        vlan.vlan = Some(VlanConfig {
+            id: 10,
+            link: "eth0".to_string(),
+        });
+        let file = NetdevFile::from(&vlan);
+        assert_eq!(file.file_name(), "10-netctl-vlan10.netdev");
+        assert_eq!(
+            file.to_string(),
+            "# Generated by netctl\n[NetDev]\nName=vlan10\nKind=vlan\n\n[VLAN]\nId=10\n"
+        );
+
+        let mut bond = DeviceConfig::new("bond0", DeviceKind::Bond);
+        bond.bond = Some(BondConfig {
+            mode: Some(BondMode::Lacp),
+            mii_monitor_ms: Some(100),
+        });
+        assert!(NetdevFile::from(&bond)
+            .to_string()
+            .ends_with("[Bond]\nMode=802.3ad\nMIIMonitorSec=100ms\n"));
+    }
+}
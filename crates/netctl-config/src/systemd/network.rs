@@ -0,0 +1,354 @@
+//! Generation of systemd-networkd `.network` files (systemd.network(5)).
+
+use crate::model::{
+    AdminState, DeviceKind, DhcpMode, Dhcpv4Config, DnsConfig, InterfaceConfig, NetworkConfig,
+    RouteConfig,
+};
+use netctl_types::{AddressFlag, AddressSpec, Result};
+use std::fmt;
+
+/// A `.network` file configuring one interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkFile {
+    pub name: String,
+    pub state: Option<AdminState>,
+    pub mtu: Option<u32>,
+    pub addresses: Vec<AddressSpec>,
+    pub routes: Vec<RouteConfig>,
+    pub dhcp: Option<DhcpMode>,
+    pub dhcpv4: Option<Dhcpv4Config>,
+    pub dns: Option<DnsConfig>,
+    /// The bond or bridge the interface is a port of
+    pub master: Option<(DeviceKind, String)>,
+    /// VLANs on top of the interface
+    pub vlans: Vec<String>,
+}
+
+impl NetworkFile {
+    /// The file for `iface`, with its ports and VLANs from `config`
+    pub fn new(iface: &InterfaceConfig, config: &NetworkConfig) -> Result<Self> {
+        let addresses = iface
+            .addresses
+            .iter()
+            .map(|addr| addr.to_spec())
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name: iface.name.clone(),
+            state: iface.state,
+            mtu: iface.mtu,
+            addresses,
+            routes: iface.routes.clone(),
+            dhcp: iface.dhcp,
+            dhcpv4: iface.dhcpv4.clone(),
+            dns: iface.dns.clone(),
+            master: config
+                .master_of(&iface.name)
+                .map(|device| (device.kind, device.name.clone())),
+            vlans: config
+                .vlans_on(&iface.name)
+                .map(|device| device.name.clone())
+                .collect(),
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("10-netctl-{}.network", self.name)
+    }
+
+    /// Settings networkd has no equivalent for, which the file leaves out
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for spec in &self.addresses {
+            if spec.valid_lifetime.is_some() {
+                warnings.push(format!(
+                    "{}: networkd can't set the valid lifetime of {}, leaving it out",
+                    self.name, spec.network
+                ));
+            }
+            if spec
+                .preferred_lifetime
+                .is_some_and(|lifetime| lifetime != 0)
+            {
+                warnings.push(format!(
+                    "{}: networkd only supports a preferred lifetime of 0, leaving it out for {}",
+                    self.name, spec.network
+                ));
+            }
+            if spec.flags.contains(&AddressFlag::Optimistic) {
+                warnings.push(format!(
+                    "{}: networkd has no optimistic DAD setting, leaving it out for {}",
+                    self.name, spec.network
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl NetworkFile {
+    fn write_link(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.state.is_none() && self.mtu.is_none() {
+            return Ok(());
+        }
+        writeln!(f)?;
+        writeln!(f, "[Link]")?;
+        if let Some(mtu) = self.mtu {
+            writeln!(f, "MTUBytes={}", mtu)?;
+        }
+        if let Some(state) = self.state {
+            writeln!(f, "ActivationPolicy={}", state)?;
+        }
+        Ok(())
+    }
+
+    fn write_network(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = Vec::new();
+        if let Some(dhcp) = self.dhcp {
+            lines.push(format!("DHCP={}", dhcp));
+        }
+        if let Some(dns) = &self.dns {
+            if !dns.servers.is_empty() {
+                lines.push(format!("DNS={}", join(&dns.servers)));
+            }
+            if !dns.domains.is_empty() {
+                lines.push(format!("Domains={}", join(&dns.domains)));
+            }
+            if let Some(enable) = dns.default_route {
+                lines.push(format!("DNSDefaultRoute={}", yes_no(enable)));
+            }
+            if let Some(mode) = dns.llmnr {
+                lines.push(format!("LLMNR={}", mode));
+            }
+            if let Some(mode) = dns.mdns {
+                lines.push(format!("MulticastDNS={}", mode));
+            }
+            if let Some(mode) = dns.dnssec {
+                lines.push(format!("DNSSEC={}", mode));
+            }
+            if let Some(mode) = dns.dns_over_tls {
+                lines.push(format!("DNSOverTLS={}", mode));
+            }
+        }
+        match &self.master {
+            Some((DeviceKind::Bond, bond)) => lines.push(format!("Bond={}", bond)),
+            Some((DeviceKind::Bridge, bridge)) => lines.push(format!("Bridge={}", bridge)),
+            _ => {}
+        }
+        for vlan in &self.vlans {
+            lines.push(format!("VLAN={}", vlan));
+        }
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+        writeln!(f)?;
+        writeln!(f, "[Network]")?;
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn write_address(f: &mut fmt::Formatter, spec: &AddressSpec) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "[Address]")?;
+        writeln!(f, "Address={}", spec.network)?;
+        if let Some(peer) = spec.peer {
+            writeln!(f, "Peer={}", peer)?;
+        }
+        if let Some(broadcast) = spec.broadcast {
+            writeln!(f, "Broadcast={}", broadcast)?;
+        }
+        if let Some(label) = &spec.label {
+            writeln!(f, "Label={}", label)?;
+        }
+        if let Some(scope) = spec.scope {
+            writeln!(f, "Scope={}", scope)?;
+        }
+        if spec.preferred_lifetime == Some(0) {
+            writeln!(f, "PreferredLifetime=0")?;
+        }
+        for flag in &spec.flags {
+            match flag {
+                AddressFlag::NoPrefixRoute => writeln!(f, "AddPrefixRoute=no")?,
+                AddressFlag::NoDad => writeln!(f, "DuplicateAddressDetection=none")?,
+                AddressFlag::Home => writeln!(f, "HomeAddress=yes")?,
+                AddressFlag::MngTmpAddr => writeln!(f, "ManageTemporaryAddress=yes")?,
+                AddressFlag::Optimistic => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn write_route(f: &mut fmt::Formatter, route: &RouteConfig) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "[Route]")?;
+        // Without a destination networkd adds a default route
+        if route.to != "default" {
+            writeln!(f, "Destination={}", route.to)?;
+        }
+        if let Some(via) = route.via {
+            writeln!(f, "Gateway={}", via)?;
+        }
+        if let Some(metric) = route.metric {
+            writeln!(f, "Metric={}", metric)?;
+        }
+        Ok(())
+    }
+
+    fn write_dhcpv4(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(dhcpv4) = &self.dhcpv4 else {
+            return Ok(());
+        };
+        writeln!(f)?;
+        writeln!(f, "[DHCPv4]")?;
+        if let Some(enable) = dhcpv4.use_dns {
+            writeln!(f, "UseDNS={}", yes_no(enable))?;
+        }
+        if let Some(enable) = dhcpv4.use_routes {
+            writeln!(f, "UseRoutes={}", yes_no(enable))?;
+        }
+        if let Some(metric) = dhcpv4.route_metric {
+            writeln!(f, "RouteMetric={}", metric)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NetworkFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Generated by netctl")?;
+        writeln!(f, "[Match]")?;
+        writeln!(f, "Name={}", self.name)?;
+        self.write_link(f)?;
+        self.write_network(f)?;
+        for spec in &self.addresses {
+            Self::write_address(f, spec)?;
+        }
+        for route in &self.routes {
+            Self::write_route(f, route)?;
+        }
+        self.write_dhcpv4(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ConfigFormat;
+
+    fn render(yaml: &str, ifname: &str) -> String {
+        let config = NetworkConfig::parse(yaml, ConfigFormat::Yaml).unwrap();
+        let iface = config
+            .interface(ifname)
+            .cloned()
+            .unwrap_or_else(|| InterfaceConfig::new(ifname));
+        NetworkFile::new(&iface, &config).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_render_network_file() {
+        let rendered = render(
+            r#"
+interfaces:
+  - name: eth0
+    state: up
+    mtu: 1500
+    addresses:
+      - 192.168.1.100/24
+      - address: 2001:db8::1/64
+        flags: [noprefixroute, nodad]
+    routes:
+      - to: default
+        via: 192.168.1.1
+      - to: 10.0.0.0/8
+        via: 192.168.1.254
+        metric: 100
+    dhcp: ipv6
+    dns:
+      servers: [192.168.1.1, 2001:db8::53]
+      domains: [example.com, "~corp.example"]
+      default_route: false
+      dnssec: allow-downgrade
+"#,
+            "eth0",
+        );
+        assert_eq!(
+            rendered,
+            "# Generated by netctl
+[Match]
+Name=eth0
+
+[Link]
+MTUBytes=1500
+ActivationPolicy=up
+
+[Network]
+DHCP=ipv6
+DNS=192.168.1.1 2001:db8::53
+Domains=example.com ~corp.example
+DNSDefaultRoute=no
+DNSSEC=allow-downgrade
+
+[Address]
+Address=192.168.1.100/24
+
+[Address]
+Address=2001:db8::1/64
+AddPrefixRoute=no
+DuplicateAddressDetection=none
+
+[Route]
+Gateway=192.168.1.1
+
+[Route]
+Destination=10.0.0.0/8
+Gateway=192.168.1.254
+Metric=100
+"
+        );
+    }
+
+    #[test]
+    fn test_render_ports_and_vlans() {
+        let yaml = r#"
+devices:
+  - name: bond0
+    kind: bond
+    interfaces: [eth0, eth1]
+  - name: vlan10
+    kind: vlan
+    vlan: {id: 10, link: bond0}
+interfaces:
+  - name: bond0
+    dhcp: ipv4
+    dhcpv4:
+      use_dns: false
+      route_metric: 50
+"#;
+        assert_eq!(
+            render(yaml, "eth1"),
+            "# Generated by netctl\n[Match]\nName=eth1\n\n[Network]\nBond=bond0\n"
+        );
+        assert!(render(yaml, "bond0").ends_with(
+            "[Network]\nDHCP=ipv4\nVLAN=vlan10\n\n[DHCPv4]\nUseDNS=no\nRouteMetric=50\n"
+        ));
+    }
+}
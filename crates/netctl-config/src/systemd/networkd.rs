@@ -0,0 +1,832 @@
+//! Reading the systemd-networkd configuration on disk into netctl's model.
+//!
+//! Files are looked up as networkd does (systemd.network(5)): a file in an
+//! earlier directory of [`SEARCH_PATH`] hides one with the same name in a
+//! later one, an empty file or a symlink to `/dev/null` masks the name, and
+//! `<file>.d/*.conf` drop-ins from every directory are read after the file
+//! in file-name order. An interface is configured by the first `.network`
+//! file, in file-name order, whose `[Match]` section matches it.
+
+use super::unit::{parse_bool, Section, UnitFile};
+use crate::model::{
+    AddressAttributes, AddressConfig, AdminState, BondConfig, BondMode, DeviceConfig, DeviceKind,
+    DhcpMode, Dhcpv4Config, DnsConfig, InterfaceConfig, NetworkConfig, RouteConfig, VlanConfig,
+};
+use netctl_types::{AddressFlag, LinkInfo, MacAddress, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use tracing::{debug, instrument};
+
+/// Where networkd looks for configuration, highest precedence first
+pub const SEARCH_PATH: &[&str] = &[
+    "/etc/systemd/network",
+    "/run/systemd/network",
+    "/usr/local/lib/systemd/network",
+    "/usr/lib/systemd/network",
+];
+
+/// `[Match]` keys netctl evaluates; a file using any other key is assumed
+/// not to match
+const MATCH_KEYS: &[&str] = &["Name", "MACAddress", "PermanentMACAddress", "Kind"];
+
+/// A file and the drop-ins read with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkdFile {
+    pub path: PathBuf,
+    pub dropins: Vec<PathBuf>,
+    pub unit: UnitFile,
+}
+
+impl NetworkdFile {
+    /// `[Match]` keys in this file netctl can't evaluate
+    fn unsupported_match_keys(&self) -> BTreeSet<&str> {
+        self.unit
+            .sections("Match")
+            .flat_map(|section| &section.entries)
+            .map(|(key, _)| key.as_str())
+            .filter(|key| !MATCH_KEYS.contains(key))
+            .collect()
+    }
+}
+
+/// Which file configures an interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub ifname: String,
+    pub path: PathBuf,
+    pub dropins: Vec<PathBuf>,
+}
+
+/// The networkd configuration read as netctl's model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub config: NetworkConfig,
+    pub assignments: Vec<Assignment>,
+    /// Settings and files netctl couldn't represent or evaluate
+    pub warnings: Vec<String>,
+}
+
+/// The `.network` and `.netdev` files in effect, in file-name order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkdConfig {
+    pub networks: Vec<NetworkdFile>,
+    pub netdevs: Vec<NetworkdFile>,
+}
+
+impl NetworkdConfig {
+    /// Read the files from [`SEARCH_PATH`]
+    pub async fn load() -> Result<Self> {
+        let dirs: Vec<PathBuf> = SEARCH_PATH.iter().map(PathBuf::from).collect();
+        Self::load_from(&dirs).await
+    }
+
+    /// Read the files from `dirs`, highest precedence first
+    #[instrument]
+    pub async fn load_from(dirs: &[PathBuf]) -> Result<Self> {
+        Ok(Self {
+            networks: load_files(dirs, "network").await?,
+            netdevs: load_files(dirs, "netdev").await?,
+        })
+    }
+
+    /// The configuration networkd would apply to `links` and to the devices
+    /// its `.netdev` files create
+    pub fn resolve(&self, links: &[LinkInfo]) -> Resolved {
+        let mut warnings = Vec::new();
+        let mut devices = Vec::new();
+        for file in &self.netdevs {
+            if let Some(device) = device_config(file, &mut warnings) {
+                devices.push(device);
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = links.iter().map(Candidate::from).collect();
+        for device in &devices {
+            if !candidates.iter().any(|c| c.name == device.name) {
+                candidates.push(Candidate {
+                    name: device.name.clone(),
+                    altnames: Vec::new(),
+                    mac: None,
+                    permanent_mac: None,
+                    kind: Some(device.kind.to_string()),
+                });
+            }
+        }
+
+        let mut config = NetworkConfig::default();
+        let mut assignments = Vec::new();
+        let mut vlan_links = BTreeMap::new();
+        // Interfaces each file might configure, if netctl could tell
+        let mut uncertain: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for candidate in &candidates {
+            let mut matched = None;
+            for (index, file) in self.networks.iter().enumerate() {
+                if !candidate.matches_supported(&file.unit) {
+                    continue;
+                }
+                if file.unsupported_match_keys().is_empty() {
+                    matched = Some(file);
+                    break;
+                }
+                uncertain.entry(index).or_default().push(&candidate.name);
+            }
+            let Some(file) = matched else {
+                continue;
+            };
+            debug!(ifname = %candidate.name, path = %file.path.display(), "matched");
+            assignments.push(Assignment {
+                ifname: candidate.name.clone(),
+                path: file.path.clone(),
+                dropins: file.dropins.clone(),
+            });
+
+            let unit = &file.unit;
+            for master in ["Bond", "Bridge"] {
+                if let Some(name) = unit.value("Network", master) {
+                    match devices.iter_mut().find(|device| device.name == name) {
+                        Some(device) => device.interfaces.push(candidate.name.clone()),
+                        None => warnings.push(format!(
+                            "{}: {} '{}' has no .netdev file",
+                            candidate.name,
+                            master.to_lowercase(),
+                            name
+                        )),
+                    }
+                }
+            }
+            for vlan in unit.list("Network", "VLAN") {
+                vlan_links.insert(vlan, candidate.name.clone());
+            }
+            config
+                .interfaces
+                .push(interface_config(&candidate.name, unit, &mut warnings));
+        }
+
+        for (index, ifnames) in uncertain {
+            let file = &self.networks[index];
+            warnings.push(format!(
+                "{} matches on {}, which netctl can't evaluate; assuming it doesn't apply to {}",
+                file.path.display(),
+                file.unsupported_match_keys()
+                    .into_iter()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                ifnames.join(", ")
+            ));
+        }
+
+        for device in &mut devices {
+            if device.kind != DeviceKind::Vlan {
+                continue;
+            }
+            match (vlan_links.get(&device.name), device.vlan.as_mut()) {
+                (Some(parent), Some(vlan)) => vlan.link = parent.clone(),
+                _ => warnings.push(format!(
+                    "VLAN '{}' has no id or isn't attached to an interface, leaving it out",
+                    device.name
+                )),
+            }
+        }
+        devices.retain(|device| {
+            device.kind != DeviceKind::Vlan
+                || device
+                    .vlan
+                    .as_ref()
+                    .is_some_and(|vlan| !vlan.link.is_empty())
+        });
+        config.devices = devices;
+
+        Resolved {
+            config,
+            assignments,
+            warnings,
+        }
+    }
+}
+
+async fn load_files(dirs: &[PathBuf], extension: &str) -> Result<Vec<NetworkdFile>> {
+    let suffix = format!(".{}", extension);
+    let mut paths = BTreeMap::new();
+    for dir in dirs {
+        for (name, path) in read_dir(dir).await? {
+            if name.ends_with(&suffix) {
+                paths.entry(name).or_insert(path);
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for (name, path) in paths {
+        if is_masked(&path).await {
+            continue;
+        }
+        let mut unit = UnitFile::parse(&tokio::fs::read_to_string(&path).await?);
+
+        let mut dropin_paths = BTreeMap::new();
+        for dir in dirs {
+            for (conf, conf_path) in read_dir(&dir.join(format!("{}.d", name))).await? {
+                if conf.ends_with(".conf") {
+                    dropin_paths.entry(conf).or_insert(conf_path);
+                }
+            }
+        }
+        let mut dropins = Vec::new();
+        for dropin in dropin_paths.into_values() {
+            if is_masked(&dropin).await {
+                continue;
+            }
+            unit.extend(UnitFile::parse(&tokio::fs::read_to_string(&dropin).await?));
+            dropins.push(dropin);
+        }
+
+        files.push(NetworkdFile {
+            path,
+            dropins,
+            unit,
+        });
+    }
+    Ok(files)
+}
+
+/// File names and paths in `dir`; a missing directory is empty
+async fn read_dir(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut names = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        names.push((
+            entry.file_name().to_string_lossy().into_owned(),
+            entry.path(),
+        ));
+    }
+    Ok(names)
+}
+
+/// Empty files and symlinks to /dev/null mask a name
+async fn is_masked(path: &Path) -> bool {
+    if let Ok(target) = tokio::fs::read_link(path).await {
+        if target == Path::new("/dev/null") {
+            return true;
+        }
+    }
+    tokio::fs::metadata(path)
+        .await
+        .map(|meta| meta.len() == 0)
+        .unwrap_or(true)
+}
+
+/// What a `[Match]` section is compared against
+struct Candidate {
+    name: String,
+    altnames: Vec<String>,
+    mac: Option<MacAddress>,
+    permanent_mac: Option<MacAddress>,
+    kind: Option<String>,
+}
+
+impl From<&LinkInfo> for Candidate {
+    fn from(link: &LinkInfo) -> Self {
+        Self {
+            name: link.name.clone(),
+            altnames: link.altnames.clone(),
+            mac: link.mac_address,
+            permanent_mac: link.permanent_mac_address,
+            kind: link.kind.clone(),
+        }
+    }
+}
+
+impl Candidate {
+    /// Whether the `[Match]` keys netctl evaluates all match
+    fn matches_supported(&self, unit: &UnitFile) -> bool {
+        let names: Vec<&str> = std::iter::once(self.name.as_str())
+            .chain(self.altnames.iter().map(String::as_str))
+            .collect();
+        let mac = self.mac.map(|mac| mac.to_string());
+        let permanent_mac = self.permanent_mac.map(|mac| mac.to_string());
+
+        match_list(&unit.list("Match", "Name"), &names, glob_match)
+            && match_list(
+                &unit.list("Match", "MACAddress"),
+                &option_slice(&mac),
+                mac_eq,
+            )
+            && match_list(
+                &unit.list("Match", "PermanentMACAddress"),
+                &option_slice(&permanent_mac),
+                mac_eq,
+            )
+            && match_list(
+                &unit.list("Match", "Kind"),
+                &option_slice(&self.kind),
+                glob_match,
+            )
+    }
+}
+
+fn option_slice(value: &Option<String>) -> Vec<&str> {
+    value.iter().map(String::as_str).collect()
+}
+
+fn mac_eq(pattern: &str, mac: &str) -> bool {
+    pattern.eq_ignore_ascii_case(mac)
+}
+
+/// An unset list matches everything; a list starting with `!` matches what
+/// none of its patterns match
+fn match_list(patterns: &[String], values: &[&str], eq: fn(&str, &str) -> bool) -> bool {
+    let Some(first) = patterns.first() else {
+        return true;
+    };
+    let (invert, first) = match first.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, first.as_str()),
+    };
+    let found = std::iter::once(first)
+        .chain(patterns[1..].iter().map(String::as_str))
+        .any(|pattern| values.iter().any(|value| eq(pattern, value)));
+    found != invert
+}
+
+/// Shell-style glob matching with `*`, `?` and `[...]`, as fnmatch(3)
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob(&pattern, &text)
+}
+
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| glob(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && glob(&pattern[1..], &text[1..]),
+        Some('[') => {
+            let Some(close) = pattern.iter().skip(2).position(|&c| c == ']') else {
+                return text.first() == Some(&'[') && glob(&pattern[1..], &text[1..]);
+            };
+            let class = &pattern[1..close + 2];
+            let Some(&c) = text.first() else {
+                return false;
+            };
+            let (negate, class) = match class.first() {
+                Some('!') | Some('^') => (true, &class[1..]),
+                _ => (false, class),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    found |= class[i] <= c && c <= class[i + 2];
+                    i += 3;
+                } else {
+                    found |= class[i] == c;
+                    i += 1;
+                }
+            }
+            found != negate && glob(&pattern[close + 3..], &text[1..])
+        }
+        Some(&p) => text.first() == Some(&p) && glob(&pattern[1..], &text[1..]),
+    }
+}
+
+fn interface_config(name: &str, unit: &UnitFile, warnings: &mut Vec<String>) -> InterfaceConfig {
+    let mut iface = InterfaceConfig::new(name);
+
+    if let Some(mtu) = unit.value("Link", "MTUBytes") {
+        match parse_bytes(mtu) {
+            Some(mtu) => iface.mtu = Some(mtu),
+            None => warnings.push(format!("{}: invalid MTUBytes={}", name, mtu)),
+        }
+    }
+    iface.state = match unit.value("Link", "ActivationPolicy") {
+        Some("up") | Some("always-up") => Some(AdminState::Up),
+        Some("down") | Some("always-down") => Some(AdminState::Down),
+        _ => None,
+    };
+
+    if let Some(dhcp) = unit.value("Network", "DHCP") {
+        iface.dhcp = match (parse_bool(dhcp), dhcp) {
+            (Some(true), _) | (None, "both") => Some(DhcpMode::Yes),
+            (Some(false), _) => Some(DhcpMode::No),
+            (None, "ipv4") => Some(DhcpMode::Ipv4),
+            (None, "ipv6") => Some(DhcpMode::Ipv6),
+            _ => {
+                warnings.push(format!("{}: invalid DHCP={}", name, dhcp));
+                None
+            }
+        };
+    }
+
+    iface.addresses = unit
+        .list("Network", "Address")
+        .into_iter()
+        .map(AddressConfig::Cidr)
+        .collect();
+    iface
+        .addresses
+        .extend(unit.sections("Address").filter_map(address_config));
+
+    iface.routes = unit
+        .list("Network", "Gateway")
+        .into_iter()
+        .filter_map(|gateway| match gateway.parse() {
+            Ok(via) => Some(RouteConfig {
+                to: "default".to_string(),
+                via: Some(via),
+                metric: None,
+            }),
+            Err(_) => {
+                warnings.push(format!("{}: unsupported Gateway={}", name, gateway));
+                None
+            }
+        })
+        .collect();
+    for section in unit.sections("Route") {
+        match route_config(section) {
+            Some(route) => iface.routes.push(route),
+            None => warnings.push(format!(
+                "{}: a [Route] section netctl can't represent was left out",
+                name
+            )),
+        }
+    }
+
+    if unit.has_section("DHCPv4") {
+        let bool_value = |key| unit.value("DHCPv4", key).and_then(parse_bool);
+        iface.dhcpv4 = Some(Dhcpv4Config {
+            use_dns: bool_value("UseDNS"),
+            use_routes: bool_value("UseRoutes"),
+            route_metric: unit
+                .value("DHCPv4", "RouteMetric")
+                .and_then(|metric| metric.parse().ok()),
+        });
+    }
+
+    let dns = dns_config(name, unit, warnings);
+    if dns != DnsConfig::default() {
+        iface.dns = Some(dns);
+    }
+
+    iface
+}
+
+fn dns_config(name: &str, unit: &UnitFile, warnings: &mut Vec<String>) -> DnsConfig {
+    let mut dns = DnsConfig::default();
+    for server in unit.list("Network", "DNS") {
+        match parse_dns_server(&server) {
+            Some(addr) => dns.servers.push(addr),
+            None => warnings.push(format!("{}: unsupported DNS={}", name, server)),
+        }
+    }
+    dns.domains = unit.list("Network", "Domains");
+    dns.default_route = unit
+        .value("Network", "DNSDefaultRoute")
+        .and_then(parse_bool);
+
+    // Modes also accept booleans
+    fn mode<T: std::str::FromStr>(value: Option<&str>) -> Option<T> {
+        let value = value?;
+        let value = match parse_bool(value) {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => value,
+        };
+        value.parse().ok()
+    }
+    dns.llmnr = mode(unit.value("Network", "LLMNR"));
+    dns.mdns = mode(unit.value("Network", "MulticastDNS"));
+    dns.dnssec = mode(unit.value("Network", "DNSSEC"));
+    dns.dns_over_tls = mode(unit.value("Network", "DNSOverTLS"));
+    dns
+}
+
+/// A `DNS=` entry's address, without the port, interface and server name
+/// networkd allows after it
+fn parse_dns_server(server: &str) -> Option<IpAddr> {
+    let server = server.split('#').next()?;
+    let server = server.split('%').next()?;
+    if let Some(rest) = server.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    server.parse().ok().or_else(|| {
+        let (addr, _port) = server.rsplit_once(':')?;
+        addr.parse().ok()
+    })
+}
+
+fn address_config(section: &Section) -> Option<AddressConfig> {
+    let address = section.value("Address")?.to_string();
+    let mut attrs = AddressAttributes {
+        address,
+        peer: section.value("Peer").and_then(|peer| peer.parse().ok()),
+        broadcast: section.value("Broadcast").and_then(|b| b.parse().ok()),
+        label: section.value("Label").map(str::to_string),
+        scope: section.value("Scope").and_then(|scope| scope.parse().ok()),
+        valid_lifetime: None,
+        preferred_lifetime: match section.value("PreferredLifetime") {
+            Some("0") => Some(0),
+            _ => None,
+        },
+        flags: Vec::new(),
+    };
+    let flag = |key, value| section.value(key).and_then(parse_bool) == Some(value);
+    if flag("AddPrefixRoute", false) {
+        attrs.flags.push(AddressFlag::NoPrefixRoute);
+    }
+    if section.value("DuplicateAddressDetection") == Some("none") {
+        attrs.flags.push(AddressFlag::NoDad);
+    }
+    if flag("HomeAddress", true) {
+        attrs.flags.push(AddressFlag::Home);
+    }
+    if flag("ManageTemporaryAddress", true) {
+        attrs.flags.push(AddressFlag::MngTmpAddr);
+    }
+
+    let plain = attrs.peer.is_none()
+        && attrs.broadcast.is_none()
+        && attrs.label.is_none()
+        && attrs.scope.is_none()
+        && attrs.preferred_lifetime.is_none()
+        && attrs.flags.is_empty();
+    Some(if plain {
+        AddressConfig::Cidr(attrs.address)
+    } else {
+        AddressConfig::Detailed(attrs)
+    })
+}
+
+fn route_config(section: &Section) -> Option<RouteConfig> {
+    let via = match section.value("Gateway") {
+        Some(gateway) => Some(gateway.parse().ok()?),
+        None => None,
+    };
+    let to = match section.value("Destination") {
+        Some("0.0.0.0/0") | Some("::/0") | None => "default".to_string(),
+        Some(destination) => destination.to_string(),
+    };
+    let metric = match section.value("Metric") {
+        Some(metric) => Some(metric.parse().ok()?),
+        None => None,
+    };
+    Some(RouteConfig { to, via, metric })
+}
+
+fn device_config(file: &NetworkdFile, warnings: &mut Vec<String>) -> Option<DeviceConfig> {
+    let unit = &file.unit;
+    let (Some(name), Some(kind)) = (unit.value("NetDev", "Name"), unit.value("NetDev", "Kind"))
+    else {
+        warnings.push(format!("{} has no Name= or Kind=", file.path.display()));
+        return None;
+    };
+    let Ok(kind) = kind.parse::<DeviceKind>() else {
+        warnings.push(format!(
+            "{}: {} devices aren't supported by netctl, leaving '{}' out",
+            file.path.display(),
+            kind,
+            name
+        ));
+        return None;
+    };
+
+    let mut device = DeviceConfig::new(name, kind);
+    if kind == DeviceKind::Vlan {
+        device.vlan = unit
+            .value("VLAN", "Id")
+            .and_then(|id| id.parse().ok())
+            .map(|id| VlanConfig {
+                id,
+                link: String::new(),
+            });
+    }
+    if kind == DeviceKind::Bond && unit.has_section("Bond") {
+        device.bond = Some(BondConfig {
+            mode: unit
+                .value("Bond", "Mode")
+                .and_then(|mode| mode.parse::<BondMode>().ok()),
+            mii_monitor_ms: unit.value("Bond", "MIIMonitorSec").and_then(parse_msec),
+        });
+    }
+    Some(device)
+}
+
+/// A byte count with an optional K, M or G suffix (base 1024)
+fn parse_bytes(value: &str) -> Option<u32> {
+    let (digits, multiplier) = match value.chars().last()? {
+        'K' => (&value[..value.len() - 1], 1024),
+        'M' => (&value[..value.len() - 1], 1024 * 1024),
+        'G' => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits.trim().parse::<u32>().ok()?.checked_mul(multiplier)
+}
+
+/// A time span in milliseconds; plain numbers are seconds
+fn parse_msec(value: &str) -> Option<u32> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms.trim().parse().ok();
+    }
+    let seconds = value.strip_suffix('s').unwrap_or(value).trim();
+    let seconds: f64 = seconds.parse().ok()?;
+    Some((seconds * 1000.0).round() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netctl_types::LinkState;
+
+    fn link(name: &str, mac: &str) -> LinkInfo {
+        LinkInfo {
+            index: 2,
+            name: name.to_string(),
+            state: LinkState::Up,
+            carrier: true,
+            flags: 0,
+            mtu: 1500,
+            mac_address: Some(mac.parse().unwrap()),
+            permanent_mac_address: None,
+            altnames: Vec::new(),
+            addresses: Vec::new(),
+            kind: None,
+            vfs: Vec::new(),
+        }
+    }
+
+    async fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(path, content).await.unwrap();
+    }
+
+    #[test]
+    fn test_glob_and_match_lists() {
+        assert!(glob_match("en*", "enp1s0"));
+        assert!(glob_match("eth[0-3]", "eth2"));
+        assert!(!glob_match("eth[!0-3]", "eth2"));
+        assert!(glob_match("wl?", "wlp"));
+        assert!(!glob_match("en*", "eth0"));
+
+        let patterns = vec!["!lo".to_string(), "veth*".to_string()];
+        assert!(match_list(&patterns, &["eth0"], glob_match));
+        assert!(!match_list(&patterns, &["veth3"], glob_match));
+        assert!(match_list(&[], &[], glob_match));
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse_bytes("9000"), Some(9000));
+        assert_eq!(parse_bytes("9K"), Some(9216));
+        assert_eq!(parse_msec("100ms"), Some(100));
+        assert_eq!(parse_msec("1.5s"), Some(1500));
+        assert_eq!(parse_msec("2"), Some(2000));
+        assert_eq!(
+            parse_dns_server("1.1.1.1:853#cloudflare-dns.com"),
+            Some("1.1.1.1".parse().unwrap())
+        );
+        assert_eq!(
+            parse_dns_server("[2001:db8::53]:53%eth0"),
+            Some("2001:db8::53".parse().unwrap())
+        );
+        assert_eq!(
+            parse_dns_server("2001:db8::53"),
+            Some("2001:db8::53".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_and_resolve() {
+        let root = std::env::temp_dir().join(format!("netctl-networkd-{}", std::process::id()));
+        let etc = root.join("etc");
+        let lib = root.join("lib");
+
+        // Overridden by /etc, masked, and matched second
+        write(
+            &lib,
+            "10-eth0.network",
+            "[Match]\nName=eth0\n[Network]\nDHCP=yes\n",
+        )
+        .await;
+        write(
+            &lib,
+            "20-masked.network",
+            "[Match]\nName=*\n[Network]\nDHCP=no\n",
+        )
+        .await;
+        write(&etc, "20-masked.network", "").await;
+        write(
+            &lib,
+            "99-default.network",
+            "[Match]\nName=e*\n[Network]\nDHCP=ipv4\n",
+        )
+        .await;
+        write(&lib, "80-wifi.network", "[Match]\nName=wl*\nType=wlan\n").await;
+        write(
+            &lib,
+            "80-e1000.network",
+            "[Match]\nName=eth1\nDriver=e1000\n",
+        )
+        .await;
+
+        write(
+            &etc,
+            "10-eth0.network",
+            "[Match]\nMACAddress=00:11:22:33:44:55\n\n[Link]\nMTUBytes=9000\n\n\
+             [Network]\nAddress=192.0.2.10/24\nGateway=192.0.2.1\nDNS=192.0.2.53\nBond=bond0\n\
+             VLAN=vlan10\n\n[Route]\nDestination=10.0.0.0/8\nGateway=192.0.2.254\nMetric=100\n",
+        )
+        .await;
+        write(
+            &lib,
+            "10-eth0.network.d/50-dns.conf",
+            "[Network]\nDNS=\nDNS=192.0.2.54\n",
+        )
+        .await;
+        write(
+            &etc,
+            "10-eth0.network.d/60-domains.conf",
+            "[Network]\nDomains=example.com\n",
+        )
+        .await;
+        write(
+            &lib,
+            "10-eth0.network.d/60-domains.conf",
+            "[Network]\nDomains=ignored\n",
+        )
+        .await;
+
+        write(
+            &lib,
+            "bond0.netdev",
+            "[NetDev]\nName=bond0\nKind=bond\n[Bond]\nMode=active-backup\nMIIMonitorSec=0.1\n",
+        )
+        .await;
+        write(
+            &lib,
+            "vlan10.netdev",
+            "[NetDev]\nName=vlan10\nKind=vlan\n[VLAN]\nId=10\n",
+        )
+        .await;
+        write(&lib, "wg0.netdev", "[NetDev]\nName=wg0\nKind=wireguard\n").await;
+
+        let networkd = NetworkdConfig::load_from(&[etc.clone(), lib.clone()])
+            .await
+            .unwrap();
+        assert_eq!(networkd.networks.len(), 4);
+        assert_eq!(networkd.networks[0].path, etc.join("10-eth0.network"));
+        assert_eq!(
+            networkd.networks[0].dropins,
+            [
+                lib.join("10-eth0.network.d/50-dns.conf"),
+                etc.join("10-eth0.network.d/60-domains.conf")
+            ]
+        );
+
+        let links = [
+            link("eth0", "00:11:22:33:44:55"),
+            link("eth1", "00:11:22:33:44:66"),
+        ];
+        let resolved = networkd.resolve(&links);
+        let assigned: Vec<_> = resolved
+            .assignments
+            .iter()
+            .map(|a| {
+                (
+                    a.ifname.as_str(),
+                    a.path.file_name().unwrap().to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            assigned,
+            [("eth0", "10-eth0.network"), ("eth1", "99-default.network")]
+        );
+
+        let config = &resolved.config;
+        let eth0 = config.interface("eth0").unwrap();
+        assert_eq!(eth0.mtu, Some(9000));
+        assert_eq!(eth0.routes.len(), 2);
+        let dns = eth0.dns.as_ref().unwrap();
+        assert_eq!(dns.servers, ["192.0.2.54".parse::<IpAddr>().unwrap()]);
+        assert_eq!(dns.domains, ["example.com"]);
+        assert_eq!(config.interface("eth1").unwrap().dhcp, Some(DhcpMode::Ipv4));
+
+        assert_eq!(config.master_of("eth0").unwrap().name, "bond0");
+        let bond = config.devices[0].bond.as_ref().unwrap();
+        assert_eq!(bond.mode, Some(BondMode::ActiveBackup));
+        assert_eq!(bond.mii_monitor_ms, Some(100));
+        assert_eq!(config.vlans_on("eth0").count(), 1);
+        assert_eq!(config.devices.len(), 2);
+        assert_eq!(resolved.warnings.len(), 2, "{:#?}", resolved.warnings);
+        assert!(resolved.warnings[1].ends_with("assuming it doesn't apply to eth1"));
+        assert!(config.validate().iter().all(|issue| !issue.is_error()));
+
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
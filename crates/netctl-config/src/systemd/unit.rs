@@ -0,0 +1,179 @@
+//! Reading systemd unit-style files (systemd.syntax(7)).
+
+/// One `[Section]` and its assignments, in file order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+/// A parsed file, or a file and its drop-ins read as one. Sections that may
+/// appear more than once, like `[Address]`, stay separate; the others are
+/// read through [`UnitFile::value`] and [`UnitFile::list`], which see every
+/// section of that name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitFile {
+    pub sections: Vec<Section>,
+}
+
+impl UnitFile {
+    /// Parse `content`. Comments, blank lines, assignments outside a section
+    /// and lines without `=` are skipped, as systemd does after warning.
+    pub fn parse(content: &str) -> Self {
+        let mut unit = Self::default();
+        let mut lines = content.lines();
+
+        while let Some(line) = lines.next() {
+            let mut line = line.trim().to_string();
+            // A trailing backslash continues the line; comment lines inside
+            // a continuation are dropped
+            while line.ends_with('\\') {
+                line.pop();
+                line.push(' ');
+                let next = lines
+                    .by_ref()
+                    .map(str::trim)
+                    .find(|next| !next.starts_with('#') && !next.starts_with(';'));
+                match next {
+                    Some(next) => line.push_str(next),
+                    None => break,
+                }
+            }
+
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                unit.sections.push(Section {
+                    name: name.to_string(),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+            let (Some(section), Some((key, value))) =
+                (unit.sections.last_mut(), line.split_once('='))
+            else {
+                continue;
+            };
+            section
+                .entries
+                .push((key.trim().to_string(), value.trim().to_string()));
+        }
+
+        unit
+    }
+
+    /// Append a drop-in, as if its text followed this file's
+    pub fn extend(&mut self, dropin: UnitFile) {
+        self.sections.extend(dropin.sections);
+    }
+
+    /// Every section named `name`
+    pub fn sections<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Section> + 'a {
+        self.sections
+            .iter()
+            .filter(move |section| section.name == name)
+    }
+
+    pub fn has_section(&self, name: &str) -> bool {
+        self.sections(name).next().is_some()
+    }
+
+    /// The last value of a single-valued setting
+    pub fn value(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .rev()
+            .filter(|s| s.name == section)
+            .find_map(|s| s.value(key))
+    }
+
+    /// A whitespace-separated list setting. Assignments accumulate and an
+    /// empty assignment clears what came before.
+    pub fn list(&self, section: &str, key: &str) -> Vec<String> {
+        list_values(
+            self.sections(section)
+                .flat_map(|section| section.value_entries(key)),
+        )
+    }
+}
+
+impl Section {
+    fn value_entries<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The last value of `key` in this section
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn list(&self, key: &str) -> Vec<String> {
+        list_values(self.value_entries(key))
+    }
+}
+
+fn list_values<'a>(values: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut list = Vec::new();
+    for value in values {
+        if value.is_empty() {
+            list.clear();
+        } else {
+            list.extend(value.split_whitespace().map(str::to_string));
+        }
+    }
+    list
+}
+
+/// A systemd boolean (systemd.syntax(7))
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "yes" | "y" | "true" | "t" | "on" => Some(true),
+        "0" | "no" | "n" | "false" | "f" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unit_file() {
+        let mut unit = UnitFile::parse(
+            "# comment\n\
+             [Match]\n\
+             Name=eth0 \\\n\
+             ; dropped\n\
+             eth1\n\
+             \n\
+             [Network]\n\
+             DNS=192.0.2.1\n\
+             DNS=192.0.2.2 192.0.2.3\n\
+             DHCP=no\n\
+             Garbage\n\
+             [Address]\n\
+             Address=192.0.2.10/24\n\
+             [Address]\n\
+             Address=192.0.2.11/24\n",
+        );
+        unit.extend(UnitFile::parse(
+            "[Network]\nDNS=\nDNS=198.51.100.1\nDHCP=yes\n",
+        ));
+
+        assert_eq!(unit.list("Match", "Name"), ["eth0", "eth1"]);
+        assert_eq!(unit.list("Network", "DNS"), ["198.51.100.1"]);
+        assert_eq!(unit.value("Network", "DHCP"), Some("yes"));
+        assert_eq!(unit.sections("Address").count(), 2);
+        assert_eq!(unit.value("Link", "MTUBytes"), None);
+        assert_eq!(parse_bool("On"), Some(true));
+        assert_eq!(parse_bool("maybe"), None);
+    }
+}
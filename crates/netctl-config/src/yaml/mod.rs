@@ -1 +0,0 @@
-// TODO: YAML config parsing
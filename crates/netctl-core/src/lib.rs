@@ -1,3 +1,4 @@
 pub mod manager;
 
 pub use manager::NetworkManager;
+pub use netctl_dbus::polkit;
@@ -1,11 +1,27 @@
+use futures::stream::{self, BoxStream, StreamExt};
+use netctl_config::systemd::{Installed, LinkFile, LinkMatch, LinkOwner, UnitFiles, NETWORK_DIR};
 use netctl_dbus::{
-    services::{HostnamedOps, NetworkdOps, ResolvedOps},
+    services::{HostnamedOps, NetworkdOps, NmOps, ResolvedOps, SystemdOps},
     DbusClient,
 };
-use netctl_netlink::{AddressOps, LinkOps, NetlinkClient};
-use netctl_types::{IpNetwork, LinkInfo, Result};
+use netctl_netlink::{
+    kernel_events, AddressOps, LinkOps, MptcpOps, NetlinkClient, RouteOps, SriovOps,
+};
+use netctl_types::{
+    validate_altname, validate_hostname, validate_ifname, validate_pretty_hostname,
+    validate_static_hostname, AddressResolution, AddressSpec, Chassis, Dhcp4Lease, DnsDomain,
+    DnsOverTlsMode, DnsRecordType, DnsStatistics, DnssecMode, Error, GlobalDnsSettings,
+    HostnameInfo, HostnameResolution, LinkBackend, LinkDescription, LinkDnsSettings, LinkInfo,
+    LinkKind, LinkReadiness, LinkState, MptcpEndpoint, MptcpEndpointSpec, MptcpLimits,
+    NetworkEvent, NetworkdDescription, NetworkdLinkState, NmActiveConnection, NmDevice,
+    RecordResolution, ResolveMode, Result, ServiceUnit, UnitStatus, VfSettings, VirtualFunction,
+    WaitOnlineSpec, WaitOnlineStatus,
+};
+use std::collections::HashMap;
 use std::net::IpAddr;
-use tracing::{info, instrument};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, instrument, warn};
 
 pub struct NetworkManager {
     netlink: NetlinkClient,
@@ -14,23 +30,50 @@ pub struct NetworkManager {
 
 impl NetworkManager {
     pub async fn new() -> Result<Self> {
+        // Connects on first use, so netlink-only commands work without a bus
+        Self::with_dbus(DbusClient::new()).await
+    }
+
+    /// Talk to the daemons through `dbus` instead of the system bus, e.g. a
+    /// private bus or the fakes of `netctl_dbus::testing`
+    pub async fn with_dbus(dbus: DbusClient) -> Result<Self> {
         let (netlink, conn) = NetlinkClient::new()?;
         tokio::spawn(conn);
 
-        let dbus = DbusClient::new().await?;
-
         Ok(Self { netlink, dbus })
     }
 
+    /// Connect to the system bus now instead of on first use, to find out
+    /// whether D-Bus features are available
+    #[instrument(skip(self))]
+    pub async fn connect_dbus(&self) -> Result<()> {
+        info!("connecting to the system bus");
+        self.dbus.connection().await.map(|_| ())
+    }
+
     // Link queries
 
     #[instrument(skip(self))]
     pub async fn list_links(&self) -> Result<Vec<LinkInfo>> {
-        info!("listing all network links");
+        debug!("listing all network links");
         let handle = self.netlink.cloneable_handle();
         handle.list_links().await
     }
 
+    /// Like [`NetworkManager::list_links`], with each link's addresses
+    #[instrument(skip(self))]
+    pub async fn list_links_with_addresses(&self) -> Result<Vec<LinkInfo>> {
+        debug!("listing links with addresses");
+        let handle = self.netlink.cloneable_handle();
+        let mut links = handle.list_links().await?;
+        for (index, address) in handle.list_addresses().await? {
+            if let Some(link) = links.iter_mut().find(|link| link.index == index) {
+                link.addresses.push(address);
+            }
+        }
+        Ok(links)
+    }
+
     #[instrument(skip(self))]
     pub async fn get_link_info(&self, ifname: &str) -> Result<LinkInfo> {
         info!(%ifname, "getting link information");
@@ -65,11 +108,213 @@ impl NetworkManager {
     }
 
     #[instrument(skip(self))]
-    pub async fn add_address(&self, ifname: &str, network: IpNetwork) -> Result<()> {
-        info!(%ifname, %network, "adding address");
+    pub async fn add_link(&self, ifname: &str, kind: LinkKind) -> Result<()> {
+        info!(%ifname, %kind, "creating virtual link");
+        let handle = self.netlink.cloneable_handle();
+        handle.add_link(ifname, kind).await
+    }
+
+    /// Delete a link. Physical devices are refused unless `force` is set.
+    #[instrument(skip(self))]
+    pub async fn delete_link(&self, ifname: &str, force: bool) -> Result<()> {
+        info!(%ifname, force, "deleting link");
+        let handle = self.netlink.cloneable_handle();
+        let link = handle.get_link_info(ifname).await?;
+        if is_physical(ifname) && !force {
+            return Err(Error::PhysicalInterface {
+                name: ifname.to_string(),
+            });
+        }
+        handle.delete_link(link.index).await
+    }
+
+    /// Rename a link. The kernel only allows this while the link is down.
+    #[instrument(skip(self))]
+    pub async fn rename_link(&self, ifname: &str, new_name: &str) -> Result<()> {
+        info!(%ifname, %new_name, "renaming link");
+        validate_ifname(new_name)?;
+        let handle = self.netlink.cloneable_handle();
+        let link = handle.get_link_info(ifname).await?;
+        if link.state == LinkState::Up {
+            return Err(Error::Generic(format!(
+                "'{}' is up; bring it down before renaming",
+                ifname
+            )));
+        }
+        handle.set_link_name(link.index, new_name).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn add_altname(&self, ifname: &str, altname: &str) -> Result<()> {
+        info!(%ifname, %altname, "adding alternative name");
+        validate_altname(altname)?;
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        handle.add_altname(index, altname).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_altname(&self, ifname: &str, altname: &str) -> Result<()> {
+        info!(%ifname, %altname, "deleting alternative name");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        handle.delete_altname(index, altname).await
+    }
+
+    /// Build a `.link` file naming the device currently called `ifname`
+    /// `new_name` on boot. Matches on the permanent MAC address unless
+    /// `by_path` is set or the device has none, in which case the udev
+    /// `ID_PATH` is used.
+    #[instrument(skip(self))]
+    pub async fn link_file(&self, ifname: &str, new_name: &str, by_path: bool) -> Result<LinkFile> {
+        info!(%ifname, %new_name, by_path, "building link file");
+        validate_ifname(new_name)?;
+        let handle = self.netlink.cloneable_handle();
+        let link = handle.get_link_info(ifname).await?;
+
+        let matches = match link.permanent_mac_address {
+            Some(mac) if !by_path => LinkMatch::PermanentMacAddress(mac),
+            _ => match udev_id_path(link.index).await {
+                Some(path) => LinkMatch::Path(path),
+                None => {
+                    return Err(Error::Generic(format!(
+                        "'{}' has no permanent MAC address or udev path to match on",
+                        ifname
+                    )))
+                }
+            },
+        };
+        Ok(LinkFile::new(LinkOwner::Rename, matches, new_name))
+    }
+
+    /// Write `file` to /etc/systemd/network, replacing the file an earlier
+    /// rename of the same device wrote
+    #[instrument(skip(self, file), fields(name = %file.name))]
+    pub async fn write_link_file(&self, file: &LinkFile) -> Result<Installed> {
+        info!(name = %file.name, "persisting link name");
+        file.install(Path::new(NETWORK_DIR)).await
+    }
+
+    /// Install networkd files in /etc/systemd/network, replacing the ones
+    /// netctl wrote before, and reload networkd so they take effect
+    #[instrument(skip(self, files))]
+    pub async fn persist_networkd_files(&self, files: &UnitFiles) -> Result<Installed> {
+        info!(
+            networks = files.networks.len(),
+            netdevs = files.netdevs.len(),
+            "persisting networkd configuration"
+        );
+        let installed = files.install(Path::new(NETWORK_DIR)).await?;
+        self.reload_networkd().await?;
+        Ok(installed)
+    }
+
+    // SR-IOV
+
+    #[instrument(skip(self))]
+    pub async fn list_vfs(&self, ifname: &str) -> Result<Vec<VirtualFunction>> {
+        info!(%ifname, "listing virtual functions");
+        let handle = self.netlink.cloneable_handle();
+        Ok(handle.get_link_info(ifname).await?.vfs)
+    }
+
+    /// Configure VF `vf` of `ifname`. Settings left unset keep their current
+    /// values, including the other half of the VLAN/QoS and rate pairs.
+    #[instrument(skip(self, settings))]
+    pub async fn set_vf(&self, ifname: &str, vf: u32, mut settings: VfSettings) -> Result<()> {
+        info!(%ifname, vf, "configuring virtual function");
+        settings.validate()?;
+        let handle = self.netlink.cloneable_handle();
+        let link = handle.get_link_info(ifname).await?;
+        let current = link.vfs.iter().find(|v| v.id == vf).ok_or_else(|| {
+            Error::Generic(format!("'{}' has no virtual function {}", ifname, vf))
+        })?;
+
+        if settings.qos.is_some() && settings.vlan.is_none() {
+            settings.vlan = Some(current.vlan);
+        }
+        if settings.vlan.is_some() && settings.qos.is_none() {
+            settings.qos = Some(current.qos);
+        }
+        if settings.min_tx_rate.is_some() || settings.max_tx_rate.is_some() {
+            settings.min_tx_rate.get_or_insert(current.min_tx_rate);
+            settings.max_tx_rate.get_or_insert(current.max_tx_rate);
+            settings.validate()?;
+        }
+
+        handle.set_vf(link.index, vf, &settings).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_num_vfs(&self, ifname: &str, count: u32) -> Result<()> {
+        info!(%ifname, count, "setting number of virtual functions");
+        let handle = self.netlink.cloneable_handle();
+        handle.set_num_vfs(ifname, count).await
+    }
+
+    #[instrument(skip(self, spec), fields(ifname = %ifname, network = %spec.network))]
+    pub async fn add_address(&self, ifname: &str, spec: AddressSpec) -> Result<()> {
+        info!(%ifname, network = %spec.network, "adding address");
+        spec.validate(ifname)?;
         let handle = self.netlink.cloneable_handle();
         let index = handle.get_link_by_name(ifname).await?;
-        handle.add_address(index, network).await
+        handle.add_address(index, &spec).await
+    }
+
+    // MPTCP path manager
+
+    #[instrument(skip(self))]
+    pub async fn list_mptcp_endpoints(&self) -> Result<Vec<MptcpEndpoint>> {
+        info!("listing MPTCP endpoints");
+        let handle = self.netlink.cloneable_handle();
+        handle.list_mptcp_endpoints().await
+    }
+
+    #[instrument(skip(self, spec), fields(address = %spec.address))]
+    pub async fn add_mptcp_endpoint(&self, spec: MptcpEndpointSpec) -> Result<()> {
+        info!(address = %spec.address, "adding MPTCP endpoint");
+        spec.validate()?;
+        let handle = self.netlink.cloneable_handle();
+        let ifindex = match spec.interface {
+            Some(ref ifname) => Some(handle.get_link_by_name(ifname).await?),
+            None => None,
+        };
+        handle.add_mptcp_endpoint(&spec, ifindex).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_mptcp_endpoint(&self, id: u8) -> Result<()> {
+        info!(id, "deleting MPTCP endpoint");
+        let handle = self.netlink.cloneable_handle();
+        handle.delete_mptcp_endpoint(id).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_mptcp_limits(&self) -> Result<MptcpLimits> {
+        info!("getting MPTCP limits");
+        let handle = self.netlink.cloneable_handle();
+        handle.get_mptcp_limits().await
+    }
+
+    /// Update the MPTCP limits; a limit left as `None` keeps its current value.
+    #[instrument(skip(self))]
+    pub async fn set_mptcp_limits(
+        &self,
+        subflows: Option<u32>,
+        add_addr_accepted: Option<u32>,
+    ) -> Result<MptcpLimits> {
+        info!("setting MPTCP limits");
+        let handle = self.netlink.cloneable_handle();
+        let mut limits = handle.get_mptcp_limits().await?;
+        if let Some(subflows) = subflows {
+            limits.subflows = subflows;
+        }
+        if let Some(add_addr_accepted) = add_addr_accepted {
+            limits.add_addr_accepted = add_addr_accepted;
+        }
+        limits.validate()?;
+        handle.set_mptcp_limits(limits).await?;
+        Ok(limits)
     }
 
     // D-Bus operations - systemd-networkd
@@ -77,15 +322,151 @@ impl NetworkManager {
     #[instrument(skip(self))]
     pub async fn reload_networkd(&self) -> Result<()> {
         info!("reloading systemd-networkd");
-        self.dbus.networkd().reload().await
+        self.dbus.networkd().await?.reload().await
     }
 
+    /// Reconfigure a link through whichever daemon manages it
     #[instrument(skip(self))]
     pub async fn reconfigure_link(&self, ifname: &str) -> Result<()> {
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            info!(%ifname, "reconfiguring link via NetworkManager");
+            return self.dbus.nm().await?.reapply_device(ifname).await;
+        }
+
         info!(%ifname, "reconfiguring link via networkd");
         let handle = self.netlink.cloneable_handle();
         let index = handle.get_link_by_name(ifname).await?;
-        self.dbus.networkd().reconfigure_link(index).await
+        self.dbus.networkd().await?.reconfigure_link(index).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_networkd_state(&self, ifname: &str) -> Result<NetworkdLinkState> {
+        info!(%ifname, "getting networkd link state");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        self.dbus.networkd().await?.get_link_state(index).await
+    }
+
+    /// networkd link states keyed by interface index
+    #[instrument(skip(self))]
+    pub async fn list_networkd_states(&self) -> Result<HashMap<u32, NetworkdLinkState>> {
+        debug!("listing networkd link states");
+        self.dbus.networkd().await?.list_link_states().await
+    }
+
+    /// networkd's description of a link: matched files, addresses, DHCP leases, ...
+    #[instrument(skip(self))]
+    pub async fn describe_networkd_link(&self, ifname: &str) -> Result<LinkDescription> {
+        info!(%ifname, "describing link via networkd");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        self.dbus.networkd().await?.describe_link(index).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn describe_networkd(&self) -> Result<NetworkdDescription> {
+        info!("describing networkd links");
+        self.dbus.networkd().await?.describe().await
+    }
+
+    // DHCP (via systemd-networkd)
+
+    #[instrument(skip(self))]
+    pub async fn renew_dhcp(&self, ifname: &str) -> Result<()> {
+        info!(%ifname, "renewing DHCP lease");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        self.dbus.networkd().await?.renew_link(index).await
+    }
+
+    /// Ask clients of the networkd DHCP server on `ifname` to renew
+    #[instrument(skip(self))]
+    pub async fn force_renew_dhcp(&self, ifname: &str) -> Result<()> {
+        info!(%ifname, "forcing DHCP clients to renew");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+        self.dbus.networkd().await?.force_renew_link(index).await
+    }
+
+    /// The DHCPv4 lease networkd currently holds for `ifname`
+    #[instrument(skip(self))]
+    pub async fn get_dhcp_lease(&self, ifname: &str) -> Result<Dhcp4Lease> {
+        info!(%ifname, "reading DHCP lease");
+        let handle = self.netlink.cloneable_handle();
+        let index = handle.get_link_by_name(ifname).await?;
+
+        let link = self.dbus.networkd().await?.describe_link(index).await?;
+        link.dhcp4_lease(booted_at().await)
+            .ok_or_else(|| Error::Generic(format!("no DHCPv4 lease for '{}'", ifname)))
+    }
+
+    // D-Bus operations - NetworkManager
+
+    /// Which daemon configures `ifname`. NetworkManager wins if it manages
+    /// the device; otherwise networkd if a .network file matched it.
+    #[instrument(skip(self))]
+    pub async fn link_backend(&self, ifname: &str) -> Result<LinkBackend> {
+        let index = self.link_index(ifname).await?;
+
+        let nm = self.dbus.nm().await?;
+        if nm.is_running().await.unwrap_or(false) {
+            if let Ok(device) = nm.get_device(ifname).await {
+                if device.state.is_managed() {
+                    return Ok(LinkBackend::NetworkManager);
+                }
+            }
+        }
+
+        match self.dbus.networkd().await?.get_link_state(index).await {
+            Ok(state) if state.is_managed() => Ok(LinkBackend::Networkd),
+            _ => Ok(LinkBackend::Unmanaged),
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_nm_devices(&self) -> Result<Vec<NmDevice>> {
+        info!("listing NetworkManager devices");
+        self.dbus.nm().await?.list_devices().await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_nm_device(&self, ifname: &str) -> Result<NmDevice> {
+        info!(%ifname, "getting NetworkManager device");
+        self.dbus.nm().await?.get_device(ifname).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_nm_connections(&self) -> Result<Vec<NmActiveConnection>> {
+        info!("listing active NetworkManager connections");
+        self.dbus.nm().await?.list_active_connections().await
+    }
+
+    /// Activate a saved connection by ID or UUID, optionally on `ifname`
+    #[instrument(skip(self))]
+    pub async fn activate_nm_connection(
+        &self,
+        connection: &str,
+        ifname: Option<&str>,
+    ) -> Result<()> {
+        info!(%connection, "activating NetworkManager connection");
+        if let Some(ifname) = ifname {
+            self.link_index(ifname).await?;
+        }
+        self.dbus
+            .nm()
+            .await?
+            .activate_connection(connection, ifname)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn deactivate_nm_connection(&self, connection: &str) -> Result<()> {
+        info!(%connection, "deactivating NetworkManager connection");
+        self.dbus
+            .nm()
+            .await?
+            .deactivate_connection(connection)
+            .await
     }
 
     // D-Bus operations - systemd-resolved
@@ -93,50 +474,640 @@ impl NetworkManager {
     #[instrument(skip(self, servers), fields(ifname = %ifname, server_count = servers.len()))]
     pub async fn set_dns_servers(&self, ifname: &str, servers: Vec<IpAddr>) -> Result<()> {
         info!(%ifname, server_count = servers.len(), "setting DNS servers");
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self.dbus.nm().await?.set_device_dns(ifname, servers).await;
+        }
         let handle = self.netlink.cloneable_handle();
         let index = handle.get_link_by_name(ifname).await?;
-        self.dbus.resolved().set_link_dns(index, servers).await
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_dns(index, servers)
+            .await
     }
 
     #[instrument(skip(self, domains), fields(ifname = %ifname, domain_count = domains.len()))]
-    pub async fn set_dns_domains(&self, ifname: &str, domains: Vec<String>) -> Result<()> {
+    pub async fn set_dns_domains(&self, ifname: &str, domains: Vec<DnsDomain>) -> Result<()> {
         info!(%ifname, domain_count = domains.len(), "setting DNS domains");
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self
+                .dbus
+                .nm()
+                .await?
+                .set_device_domains(ifname, domains)
+                .await;
+        }
         let handle = self.netlink.cloneable_handle();
         let index = handle.get_link_by_name(ifname).await?;
-        self.dbus.resolved().set_link_domains(index, domains).await
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_domains(index, domains)
+            .await
+    }
+
+    /// Whether queries matching no routing domain may go to `ifname`
+    #[instrument(skip(self))]
+    pub async fn set_dns_default_route(&self, ifname: &str, enable: bool) -> Result<()> {
+        info!(%ifname, enable, "setting DNS default route");
+        self.ensure_not_nm(ifname, "the DNS default route").await?;
+        let index = self.link_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_default_route(index, enable)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_llmnr(&self, ifname: &str, mode: ResolveMode) -> Result<()> {
+        info!(%ifname, %mode, "setting LLMNR");
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self.dbus.nm().await?.set_device_llmnr(ifname, mode).await;
+        }
+        let index = self.link_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_llmnr(index, mode)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_mdns(&self, ifname: &str, mode: ResolveMode) -> Result<()> {
+        info!(%ifname, %mode, "setting MulticastDNS");
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self.dbus.nm().await?.set_device_mdns(ifname, mode).await;
+        }
+        let index = self.link_index(ifname).await?;
+        self.dbus.resolved().await?.set_link_mdns(index, mode).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_dnssec(&self, ifname: &str, mode: DnssecMode) -> Result<()> {
+        info!(%ifname, %mode, "setting DNSSEC");
+        self.ensure_not_nm(ifname, "DNSSEC").await?;
+        let index = self.link_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_dnssec(index, mode)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_dns_over_tls(&self, ifname: &str, mode: DnsOverTlsMode) -> Result<()> {
+        info!(%ifname, %mode, "setting DNS-over-TLS");
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self
+                .dbus
+                .nm()
+                .await?
+                .set_device_dns_over_tls(ifname, mode)
+                .await;
+        }
+        let index = self.link_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_dns_over_tls(index, mode)
+            .await
+    }
+
+    /// Domains for which DNSSEC validation is skipped on `ifname`
+    #[instrument(skip(self, names), fields(ifname = %ifname, count = names.len()))]
+    pub async fn set_dnssec_negative_trust_anchors(
+        &self,
+        ifname: &str,
+        names: Vec<String>,
+    ) -> Result<()> {
+        info!(%ifname, count = names.len(), "setting DNSSEC negative trust anchors");
+        self.ensure_not_nm(ifname, "DNSSEC negative trust anchors")
+            .await?;
+        let index = self.link_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .set_link_negative_trust_anchors(index, names)
+            .await
+    }
+
+    /// Fail for settings NetworkManager's connection settings can't carry:
+    /// set in resolved directly, NetworkManager would overwrite them on its
+    /// next reconfiguration of the link
+    async fn ensure_not_nm(&self, ifname: &str, setting: &str) -> Result<()> {
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return Err(Error::Generic(format!(
+                "setting {} is not supported for NetworkManager-managed links like '{}'",
+                setting, ifname
+            )));
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_link_dns_settings(&self, ifname: &str) -> Result<LinkDnsSettings> {
+        info!(%ifname, "reading link DNS settings");
+        let index = self.link_index(ifname).await?;
+        self.dbus.resolved().await?.get_link_settings(index).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_global_dns_settings(&self) -> Result<GlobalDnsSettings> {
+        info!("reading global DNS settings");
+        self.dbus.resolved().await?.get_global_settings().await
     }
 
     #[instrument(skip(self))]
     pub async fn revert_dns(&self, ifname: &str) -> Result<()> {
         info!(%ifname, "reverting DNS configuration");
+        // Runtime DNS changes on NetworkManager links live in the applied
+        // connection; re-applying the saved profile drops them
+        if self.link_backend(ifname).await? == LinkBackend::NetworkManager {
+            return self.dbus.nm().await?.reapply_device(ifname).await;
+        }
         let handle = self.netlink.cloneable_handle();
         let index = handle.get_link_by_name(ifname).await?;
-        self.dbus.resolved().revert_link(index).await
+        self.dbus.resolved().await?.revert_link(index).await
     }
 
     #[instrument(skip(self))]
     pub async fn flush_dns_caches(&self) -> Result<()> {
         info!("flushing DNS caches");
-        self.dbus.resolved().flush_caches().await
+        self.dbus.resolved().await?.flush_caches().await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_dns_statistics(&self) -> Result<DnsStatistics> {
+        debug!("reading DNS statistics");
+        self.dbus.resolved().await?.get_statistics().await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn reset_dns_statistics(&self) -> Result<()> {
+        info!("resetting DNS statistics");
+        self.dbus.resolved().await?.reset_statistics().await
+    }
+
+    async fn link_index(&self, ifname: &str) -> Result<u32> {
+        let handle = self.netlink.cloneable_handle();
+        handle.get_link_by_name(ifname).await
+    }
+
+    /// Index of `ifname`, or `None` (any link) when no interface is given
+    async fn optional_index(&self, ifname: Option<&str>) -> Result<Option<u32>> {
+        match ifname {
+            Some(ifname) => Ok(Some(self.link_index(ifname).await?)),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn resolve_hostname(
+        &self,
+        name: &str,
+        ifname: Option<&str>,
+    ) -> Result<HostnameResolution> {
+        info!(%name, "resolving host name");
+        let index = self.optional_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .resolve_hostname(index, name)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn resolve_address(
+        &self,
+        address: IpAddr,
+        ifname: Option<&str>,
+    ) -> Result<AddressResolution> {
+        info!(%address, "resolving address");
+        let index = self.optional_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .resolve_address(index, address)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn resolve_record(
+        &self,
+        name: &str,
+        record_type: DnsRecordType,
+        ifname: Option<&str>,
+    ) -> Result<RecordResolution> {
+        info!(%name, %record_type, "resolving records");
+        let index = self.optional_index(ifname).await?;
+        self.dbus
+            .resolved()
+            .await?
+            .resolve_record(index, name, record_type)
+            .await
     }
 
     // D-Bus operations - systemd-hostnamed
 
+    /// Set the static hostname; an empty string clears it
     #[instrument(skip(self))]
-    pub async fn set_hostname(&self, hostname: &str) -> Result<()> {
+    pub async fn set_static_hostname(&self, hostname: &str) -> Result<()> {
         info!(hostname = %hostname, "setting static hostname");
-        self.dbus.hostnamed().set_static_hostname(hostname).await
+        validate_static_hostname(hostname)?;
+        self.dbus
+            .hostnamed()
+            .await?
+            .set_static_hostname(hostname)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_transient_hostname(&self, hostname: &str) -> Result<()> {
+        info!(hostname = %hostname, "setting transient hostname");
+        validate_hostname(hostname)?;
+        self.dbus
+            .hostnamed()
+            .await?
+            .set_transient_hostname(hostname)
+            .await
+    }
+
+    /// Set the pretty hostname; an empty string clears it
+    #[instrument(skip(self))]
+    pub async fn set_pretty_hostname(&self, hostname: &str) -> Result<()> {
+        info!(hostname = %hostname, "setting pretty hostname");
+        validate_pretty_hostname(hostname)?;
+        self.dbus
+            .hostnamed()
+            .await?
+            .set_pretty_hostname(hostname)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_icon_name(&self, icon: &str) -> Result<()> {
+        info!(%icon, "setting icon name");
+        self.dbus.hostnamed().await?.set_icon_name(icon).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_chassis(&self, chassis: Chassis) -> Result<()> {
+        info!(%chassis, "setting chassis");
+        self.dbus.hostnamed().await?.set_chassis(chassis).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_deployment(&self, deployment: &str) -> Result<()> {
+        info!(%deployment, "setting deployment");
+        self.dbus
+            .hostnamed()
+            .await?
+            .set_deployment(deployment)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn set_location(&self, location: &str) -> Result<()> {
+        info!(%location, "setting location");
+        self.dbus.hostnamed().await?.set_location(location).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_hostname_info(&self) -> Result<HostnameInfo> {
+        info!("getting hostname information");
+        self.dbus.hostnamed().await?.get_info().await
     }
 
     #[instrument(skip(self))]
     pub async fn get_hostname(&self) -> Result<String> {
         info!("getting hostname");
-        self.dbus.hostnamed().get_hostname().await
+        self.dbus.hostnamed().await?.get_hostname().await
     }
 
     #[instrument(skip(self))]
     pub async fn get_machine_id(&self) -> Result<String> {
         info!("getting machine ID");
-        self.dbus.hostnamed().get_machine_id().await
+        self.dbus.hostnamed().await?.get_machine_id().await
+    }
+
+    // D-Bus operations - systemd
+
+    #[instrument(skip(self))]
+    pub async fn get_unit_status(&self, unit: ServiceUnit) -> Result<UnitStatus> {
+        info!(%unit, "getting unit status");
+        self.dbus
+            .systemd()
+            .await?
+            .get_unit_status(unit.unit_name())
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn start_unit(&self, unit: ServiceUnit) -> Result<()> {
+        info!(%unit, "starting unit");
+        self.dbus
+            .systemd()
+            .await?
+            .start_unit(unit.unit_name())
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn stop_unit(&self, unit: ServiceUnit) -> Result<()> {
+        info!(%unit, "stopping unit");
+        self.dbus.systemd().await?.stop_unit(unit.unit_name()).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn restart_unit(&self, unit: ServiceUnit) -> Result<()> {
+        info!(%unit, "restarting unit");
+        self.dbus
+            .systemd()
+            .await?
+            .restart_unit(unit.unit_name())
+            .await
+    }
+
+    // Events
+
+    /// Kernel link and address changes merged with networkd link state
+    /// transitions and resolved setting changes, in arrival order. The D-Bus
+    /// sources are best effort; without them only kernel events arrive.
+    #[instrument(skip(self))]
+    pub async fn subscribe_events(&self) -> Result<BoxStream<'static, NetworkEvent>> {
+        info!("subscribing to network events");
+        let mut sources = vec![kernel_events()?];
+
+        match self.dbus.networkd_link_events().await {
+            Ok(events) => sources.push(events),
+            Err(e) => warn!(error = %e, "networkd events unavailable"),
+        }
+        match self.dbus.resolved_events().await {
+            Ok(events) => sources.push(events),
+            Err(e) => warn!(error = %e, "resolved events unavailable"),
+        }
+
+        Ok(stream::select_all(sources).boxed())
+    }
+
+    // Wait online
+
+    /// Per-link state `wait-online` checks: kernel operstate and addresses,
+    /// default routes, and networkd's operational and online state when
+    /// networkd is reachable
+    #[instrument(skip(self))]
+    pub async fn link_readiness(&self) -> Result<Vec<LinkReadiness>> {
+        let links = self.list_links_with_addresses().await?;
+        let handle = self.netlink.cloneable_handle();
+        let routes = handle.list_routes().await?;
+        let networkd = match self.list_networkd_states().await {
+            Ok(states) => states,
+            Err(e) => {
+                warn!(error = %e, "networkd link states unavailable");
+                HashMap::new()
+            }
+        };
+
+        Ok(links
+            .iter()
+            .map(|link| LinkReadiness::new(link, &routes, networkd.get(&link.index)))
+            .collect())
+    }
+
+    /// Re-check `spec` on every network event until it's met or `timeout`
+    /// passes. The returned status says which: on timeout it isn't ready and
+    /// lists the links still pending.
+    #[instrument(skip(self))]
+    pub async fn wait_online(
+        &self,
+        spec: &WaitOnlineSpec,
+        timeout: Option<Duration>,
+    ) -> Result<WaitOnlineStatus> {
+        info!("waiting for the network to come online");
+        // Subscribe before the first check so no transition is missed
+        let mut events = self.subscribe_events().await?;
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+
+        loop {
+            let status = spec.evaluate(&self.link_readiness().await?);
+            if status.ready {
+                info!("network is online");
+                return Ok(status);
+            }
+
+            let next = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, events.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        warn!(pending = ?status.pending, "timed out waiting for the network");
+                        return Ok(status);
+                    }
+                },
+                None => events.next().await,
+            };
+            if next.is_none() {
+                return Err(Error::netlink("event stream ended"));
+            }
+        }
+    }
+}
+
+/// Whether a link is backed by a device, i.e. sysfs links it to one. Virtual
+/// links have no `device` entry, whether or not they report a kind.
+fn is_physical(ifname: &str) -> bool {
+    Path::new("/sys/class/net")
+        .join(ifname)
+        .join("device")
+        .exists()
+}
+
+/// When the system booted, in seconds since the Unix epoch. /proc/uptime
+/// counts CLOCK_BOOTTIME, the clock networkd dates leases with.
+async fn booted_at() -> Option<u64> {
+    let uptime = tokio::fs::read_to_string("/proc/uptime").await.ok()?;
+    let uptime: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    now.as_secs().checked_sub(uptime as u64)
+}
+
+/// Look up the udev `ID_PATH` property of a network device
+async fn udev_id_path(index: u32) -> Option<String> {
+    let data = tokio::fs::read_to_string(format!("/run/udev/data/n{}", index))
+        .await
+        .ok()?;
+    data.lines()
+        .find_map(|line| line.strip_prefix("E:ID_PATH="))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netctl_dbus::testing::{FakeBus, FakeNetworkd};
+    use netctl_types::{InterfaceRequirement, NetworkdLinkProperty, OperationalState};
+
+    /// The loopback device is index 1 in every network namespace
+    const LO: u32 = 1;
+
+    async fn setup() -> (FakeBus, NetworkManager) {
+        let bus = FakeBus::new().await.unwrap();
+        let mgr = NetworkManager::with_dbus(bus.client()).await.unwrap();
+        (bus, mgr)
+    }
+
+    async fn manage_lo(networkd: &FakeNetworkd, online_state: &str) {
+        let state = NetworkdLinkState {
+            administrative_state: "configured".to_string(),
+            operational_state: "carrier".to_string(),
+            online_state: online_state.to_string(),
+            ..Default::default()
+        };
+        networkd.add_link(LO, "lo", state).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_link_backend() {
+        let (bus, mgr) = setup().await;
+        assert_eq!(
+            mgr.link_backend("lo").await.unwrap(),
+            LinkBackend::Unmanaged
+        );
+
+        let networkd = bus.add_networkd().await.unwrap();
+        manage_lo(&networkd, "online").await;
+        assert_eq!(mgr.link_backend("lo").await.unwrap(), LinkBackend::Networkd);
+    }
+
+    #[tokio::test]
+    async fn test_dhcp_lease_from_describe_link() {
+        use netctl_types::networkd::{AddressDescription, Dhcp4Client, DhcpLease};
+
+        let (bus, mgr) = setup().await;
+        let networkd = bus.add_networkd().await.unwrap();
+        manage_lo(&networkd, "online").await;
+        assert!(mgr.get_dhcp_lease("lo").await.is_err());
+
+        networkd
+            .set_link_description(LinkDescription {
+                index: LO,
+                name: "lo".to_string(),
+                addresses: vec![AddressDescription {
+                    address: "192.0.2.10".parse().unwrap(),
+                    peer: None,
+                    prefix_length: 24,
+                    scope_string: None,
+                    flags_string: None,
+                    label: None,
+                    config_source: Some("DHCPv4".to_string()),
+                    config_state: None,
+                    config_provider: Some("192.0.2.1".parse().unwrap()),
+                    valid_lifetime_usec: Some(3_700_000_000),
+                }],
+                dhcpv4_client: Some(Dhcp4Client {
+                    lease: Some(DhcpLease {
+                        lease_timestamp_usec: Some(100_000_000),
+                        t1_usec: Some(1_800_000_000),
+                        t2_usec: None,
+                    }),
+                }),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let lease = mgr.get_dhcp_lease("lo").await.unwrap();
+        assert_eq!(lease.address.to_string(), "192.0.2.10");
+        assert_eq!(lease.server_address.unwrap().to_string(), "192.0.2.1");
+        assert_eq!(lease.lifetime, Some(3600));
+        assert_eq!(lease.t1, Some(1800));
+        assert_eq!(networkd.calls().last().unwrap().member, "DescribeLink");
+    }
+
+    #[tokio::test]
+    async fn test_set_dns_servers_resolves_ifindex() {
+        let (bus, mgr) = setup().await;
+        let resolved = bus.add_resolved().await.unwrap();
+        let server: IpAddr = "192.0.2.53".parse().unwrap();
+
+        mgr.set_dns_servers("lo", vec![server]).await.unwrap();
+
+        let calls = resolved.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].member, "SetLinkDNS");
+        assert_eq!(resolved.link(LO).unwrap().servers, vec![server]);
+        assert!(mgr
+            .set_dns_servers("nonexistent0", vec![server])
+            .await
+            .is_err());
+        assert_eq!(resolved.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolved_only_settings_on_unmanaged_link() {
+        let (bus, mgr) = setup().await;
+        let resolved = bus.add_resolved().await.unwrap();
+
+        mgr.set_dnssec("lo", DnssecMode::AllowDowngrade)
+            .await
+            .unwrap();
+        mgr.set_dns_default_route("lo", false).await.unwrap();
+        mgr.set_dnssec_negative_trust_anchors("lo", vec!["corp.example".to_string()])
+            .await
+            .unwrap();
+
+        let members: Vec<_> = resolved.calls().into_iter().map(|c| c.member).collect();
+        assert_eq!(
+            members,
+            [
+                "SetLinkDNSSEC",
+                "SetLinkDefaultRoute",
+                "SetLinkDNSSECNegativeTrustAnchors"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hostname_validated_before_calling_hostnamed() {
+        let (bus, mgr) = setup().await;
+        let hostnamed = bus.add_hostnamed().await.unwrap();
+
+        assert!(mgr.set_static_hostname("web 01").await.is_err());
+        assert!(hostnamed.calls().is_empty());
+
+        mgr.set_static_hostname("web01").await.unwrap();
+        assert_eq!(hostnamed.info().static_hostname.as_deref(), Some("web01"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_online_follows_networkd() {
+        let (bus, mgr) = setup().await;
+        let networkd = bus.add_networkd().await.unwrap();
+        manage_lo(&networkd, "offline").await;
+        let spec = WaitOnlineSpec {
+            interfaces: vec![InterfaceRequirement {
+                name: "lo".to_string(),
+                min_state: Some(OperationalState::Carrier),
+            }],
+            require_online: true,
+            ..Default::default()
+        };
+
+        let status = mgr
+            .wait_online(&spec, Some(Duration::from_millis(50)))
+            .await
+            .unwrap();
+        assert!(!status.ready);
+        assert_eq!(status.pending, vec!["lo".to_string()]);
+
+        let wait = mgr.wait_online(&spec, Some(Duration::from_secs(10)));
+        let announce = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            networkd
+                .set_link_state(LO, NetworkdLinkProperty::OnlineState, "online")
+                .await
+                .unwrap();
+        };
+        let (status, ()) = tokio::join!(wait, announce);
+        assert!(status.unwrap().ready);
     }
 }
@@ -1,37 +1,110 @@
-use crate::services::{HostnamedService, NetworkdService, ResolvedService};
-use netctl_types::Result;
+use crate::events;
+use crate::services::{
+    HostnamedService, NetworkdService, NmService, ResolvedService, SystemdService,
+};
+use futures::stream::BoxStream;
+use netctl_types::{Error, NetworkEvent, Result};
 use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tracing::debug;
 use zbus::Connection;
 
-#[derive(Clone)]
+/// The system bus connection, or why connecting to it failed
+type Connected = std::result::Result<Arc<Connection>, String>;
+
+/// Client for the system bus. The connection is opened on first use, so
+/// netlink-only callers work on hosts without D-Bus; if connecting fails,
+/// every service accessor returns [`Error::ServiceUnavailable`].
+#[derive(Clone, Default)]
 pub struct DbusClient {
-    connection: Arc<Connection>,
+    connection: Arc<OnceCell<Connected>>,
 }
 
 impl DbusClient {
-    pub async fn new() -> Result<Self> {
-        let conn = Connection::system()
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use an existing connection instead of the system bus, e.g. a private
+    /// bus or a peer-to-peer connection to fake services in tests
+    pub fn from_connection(connection: Connection) -> Self {
+        Self {
+            connection: Arc::new(OnceCell::new_with(Some(Ok(Arc::new(connection))))),
+        }
+    }
+
+    /// The system bus connection, connecting if this is the first use. A
+    /// failed attempt isn't retried: callers polling for state would
+    /// otherwise try to connect on every call on hosts without a bus.
+    pub async fn connection(&self) -> Result<&Connection> {
+        self.shared().await.map(|conn| conn.as_ref())
+    }
+
+    async fn shared(&self) -> Result<&Arc<Connection>> {
+        self.connection
+            .get_or_init(|| async {
+                debug!("connecting to the system bus");
+                Connection::system().await.map(Arc::new).map_err(|e| {
+                    debug!(error = %e, "system bus unavailable");
+                    e.to_string()
+                })
+            })
             .await
-            .map_err(|e| netctl_types::Error::dbus(format!("{}", e)))?;
+            .as_ref()
+            .map_err(|reason| Error::unavailable("D-Bus system bus", reason.clone()))
+    }
 
-        Ok(Self {
-            connection: Arc::new(conn),
-        })
+    pub async fn networkd(&self) -> Result<NetworkdService> {
+        Ok(NetworkdService::new(Arc::clone(self.shared().await?)))
     }
 
-    pub fn connection(&self) -> &Connection {
-        &self.connection
+    pub async fn resolved(&self) -> Result<ResolvedService> {
+        Ok(ResolvedService::new(Arc::clone(self.shared().await?)))
     }
 
-    pub fn networkd(&self) -> NetworkdService {
-        NetworkdService::new(Arc::clone(&self.connection))
+    pub async fn hostnamed(&self) -> Result<HostnamedService> {
+        Ok(HostnamedService::new(Arc::clone(self.shared().await?)))
     }
 
-    pub fn resolved(&self) -> ResolvedService {
-        ResolvedService::new(Arc::clone(&self.connection))
+    pub async fn systemd(&self) -> Result<SystemdService> {
+        Ok(SystemdService::new(Arc::clone(self.shared().await?)))
     }
 
-    pub fn hostnamed(&self) -> HostnamedService {
-        HostnamedService::new(Arc::clone(&self.connection))
+    pub async fn nm(&self) -> Result<NmService> {
+        Ok(NmService::new(Arc::clone(self.shared().await?)))
+    }
+
+    /// Stream networkd link state transitions (operational, carrier,
+    /// address, online and setup state) as they are announced
+    pub async fn networkd_link_events(&self) -> Result<BoxStream<'static, NetworkEvent>> {
+        events::networkd_link_events(self.connection().await?).await
+    }
+
+    /// Stream changes of resolved's global DNS settings
+    pub async fn resolved_events(&self) -> Result<BoxStream<'static, NetworkEvent>> {
+        events::resolved_events(self.connection().await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connect_failure_cached() {
+        std::env::set_var(
+            "DBUS_SYSTEM_BUS_ADDRESS",
+            "unix:path=/nonexistent/netctl-test",
+        );
+        let client = DbusClient::new();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                client.connection().await,
+                Err(Error::ServiceUnavailable { .. })
+            ));
+        }
+        // The failure is remembered rather than retried
+        assert!(matches!(client.connection.get(), Some(Err(_))));
     }
 }
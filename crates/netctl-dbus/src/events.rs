@@ -0,0 +1,143 @@
+//! PropertiesChanged subscriptions for networkd links and resolved, turned
+//! into typed [`NetworkEvent`]s.
+
+use futures::stream::{self, BoxStream, StreamExt};
+use netctl_types::events::ifindex_from_link_path;
+use netctl_types::{
+    Error, NetworkEvent, NetworkEventKind, NetworkdLinkProperty, ResolvedProperty, Result,
+};
+use std::collections::BTreeMap;
+use tracing::{debug, info};
+use zbus::fdo::PropertiesChanged;
+use zbus::message::Type as MessageType;
+use zbus::zvariant::Value;
+use zbus::{Connection, MatchRule, Message, MessageStream};
+
+const NETWORKD_LINK_INTERFACE: &str = "org.freedesktop.network1.Link";
+const RESOLVED_MANAGER_INTERFACE: &str = "org.freedesktop.resolve1.Manager";
+
+/// State changes of every networkd link object
+pub(crate) async fn networkd_link_events(
+    connection: &Connection,
+) -> Result<BoxStream<'static, NetworkEvent>> {
+    info!("subscribing to networkd link property changes");
+
+    let rule =
+        properties_changed_rule("org.freedesktop.network1", "/org/freedesktop/network1/link")?;
+    let messages = subscribe(connection, rule).await?;
+
+    Ok(messages
+        .flat_map(|message| stream::iter(networkd_link_changes(&message)))
+        .boxed())
+}
+
+/// Changes of the resolved Manager's global settings
+pub(crate) async fn resolved_events(
+    connection: &Connection,
+) -> Result<BoxStream<'static, NetworkEvent>> {
+    info!("subscribing to resolved property changes");
+
+    let rule = properties_changed_rule("org.freedesktop.resolve1", "/org/freedesktop/resolve1")?;
+    let messages = subscribe(connection, rule).await?;
+
+    Ok(messages
+        .flat_map(|message| stream::iter(resolved_changes(&message)))
+        .boxed())
+}
+
+fn properties_changed_rule(sender: &'static str, path: &'static str) -> Result<MatchRule<'static>> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(sender)
+        .and_then(|b| b.interface("org.freedesktop.DBus.Properties"))
+        .and_then(|b| b.member("PropertiesChanged"))
+        .and_then(|b| b.path_namespace(path))
+        .map_err(|e| Error::dbus(format!("Invalid match rule: {}", e)))?;
+    Ok(rule.build())
+}
+
+/// Install the match rule and yield matching messages, dropping errors
+async fn subscribe(
+    connection: &Connection,
+    rule: MatchRule<'static>,
+) -> Result<BoxStream<'static, Message>> {
+    let messages = MessageStream::for_match_rule(rule, connection, None)
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to subscribe to PropertiesChanged: {}", e)))?;
+
+    Ok(messages.filter_map(|m| async move { m.ok() }).boxed())
+}
+
+fn networkd_link_changes(message: &Message) -> Vec<NetworkEvent> {
+    let header = message.header();
+    let Some(index) = header
+        .path()
+        .and_then(|p| ifindex_from_link_path(p.as_str()))
+    else {
+        return Vec::new();
+    };
+    let Some(signal) = PropertiesChanged::from_message(message.clone()) else {
+        return Vec::new();
+    };
+    let Ok(args) = signal.args() else {
+        return Vec::new();
+    };
+    if args.interface_name() != NETWORKD_LINK_INTERFACE {
+        return Vec::new();
+    }
+
+    args.changed_properties()
+        .iter()
+        .filter_map(|(name, value)| {
+            let property = NetworkdLinkProperty::from_dbus_name(name)?;
+            let value = string_value(value)?;
+            debug!(ifindex = index, %property, %value, "networkd link changed");
+            Some(NetworkEvent::now(NetworkEventKind::NetworkdLinkChanged {
+                index,
+                property,
+                value,
+            }))
+        })
+        .collect()
+}
+
+fn resolved_changes(message: &Message) -> Vec<NetworkEvent> {
+    let Some(signal) = PropertiesChanged::from_message(message.clone()) else {
+        return Vec::new();
+    };
+    let Ok(args) = signal.args() else {
+        return Vec::new();
+    };
+    if args.interface_name() != RESOLVED_MANAGER_INTERFACE {
+        return Vec::new();
+    }
+
+    // DNS and DNSEx (and friends) change together; report each once
+    let mut changes = BTreeMap::new();
+    for (name, value) in args.changed_properties() {
+        if let Some(property) = ResolvedProperty::from_dbus_name(name) {
+            changes.insert(property, string_value(value));
+        }
+    }
+    for name in args.invalidated_properties() {
+        if let Some(property) = ResolvedProperty::from_dbus_name(name) {
+            changes.entry(property).or_insert(None);
+        }
+    }
+
+    changes
+        .into_iter()
+        .map(|(property, value)| {
+            debug!(%property, "resolved changed");
+            NetworkEvent::now(NetworkEventKind::ResolvedChanged { property, value })
+        })
+        .collect()
+}
+
+fn string_value(value: &Value<'_>) -> Option<String> {
+    match value {
+        Value::Str(s) => Some(s.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
@@ -1,4 +1,8 @@
 pub mod client;
+mod events;
+pub mod polkit;
 pub mod services;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use client::DbusClient;
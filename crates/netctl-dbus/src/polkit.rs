@@ -0,0 +1,337 @@
+//! polkit interactive authorization.
+//!
+//! Privileged method calls go through [`call`], which sets
+//! ALLOW_INTERACTIVE_AUTHORIZATION when [`set_interactive`] allowed it, so
+//! polkit may ask the user for a password instead of refusing outright. For
+//! that to work from a terminal, a text agent (`pkttyagent`) has to be
+//! registered for the session. After [`enable_agent`], the first such call
+//! starts a [`PolkitAgent`], so commands that need no authorization never
+//! spawn one.
+//!
+//! On the service side, [`check_authorization`] asks polkit whether the
+//! sender of a call to netctl's own service may perform an action.
+
+use netctl_types::{Error, Result};
+use nix::fcntl::OFlag;
+use nix::libc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{IsTerminal, Read};
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::debug;
+use zbus::fdo::DBusProxy;
+use zbus::names::BusName;
+use zbus::proxy;
+use zbus::proxy::MethodFlags;
+use zbus::zvariant::{DynamicDeserialize, DynamicType, OwnedValue, Value};
+
+const PKTTYAGENT: &str = "pkttyagent";
+
+/// D-Bus errors meaning polkit (or the service's own check) said no
+const DENIED_ERRORS: &[&str] = &[
+    "org.freedesktop.DBus.Error.AccessDenied",
+    "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired",
+    "org.freedesktop.PolicyKit1.Error.NotAuthorized",
+];
+
+static INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The agent [`call`] starts on demand
+static AGENT: Mutex<AgentState> = Mutex::new(AgentState::Disabled);
+
+/// Held while an agent starts, so concurrent calls wait for it to register
+/// without blocking the runtime
+static STARTING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+enum AgentState {
+    Disabled,
+    Enabled,
+    /// Held only to be dropped, which stops the agent
+    Running(#[allow(dead_code)] PolkitAgent),
+    Unavailable,
+}
+
+/// Allow or forbid polkit from prompting for a password. Off by default so
+/// library users never block on a prompt they didn't ask for.
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Call a privileged method that returns nothing; see [`call_with_reply`]
+pub(crate) async fn call<B>(proxy: &zbus::Proxy<'_>, method: &str, body: &B) -> zbus::Result<()>
+where
+    B: Serialize + DynamicType,
+{
+    call_with_reply(proxy, method, body).await
+}
+
+/// Call a privileged method, allowing polkit to prompt only in interactive
+/// mode. Proxies leave these methods out so the flag can't be forgotten.
+pub(crate) async fn call_with_reply<B, R>(
+    proxy: &zbus::Proxy<'_>,
+    method: &str,
+    body: &B,
+) -> zbus::Result<R>
+where
+    B: Serialize + DynamicType,
+    R: for<'d> DynamicDeserialize<'d>,
+{
+    let flags = if is_interactive() {
+        start_agent().await;
+        MethodFlags::AllowInteractiveAuth.into()
+    } else {
+        Default::default()
+    };
+    proxy
+        .call_with_flags(method, flags, body)
+        .await?
+        .ok_or(zbus::Error::InvalidReply)
+}
+
+/// Map a failed method call to [`Error::PolkitDenied`] when authorization
+/// was refused, or a generic D-Bus error otherwise. `action` completes
+/// "Failed to ..." / "not authorized to ...".
+pub(crate) fn method_error(action: &str, e: zbus::Error) -> Error {
+    if let zbus::Error::MethodError(ref name, ref message, _) = e {
+        if DENIED_ERRORS.contains(&name.as_str()) {
+            return Error::PolkitDenied {
+                action: action.to_string(),
+                reason: message.clone().unwrap_or_else(|| name.to_string()),
+            };
+        }
+    }
+    Error::dbus(format!("Failed to {}: {}", action, e))
+}
+
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait Authority {
+    /// Returns (is_authorized, is_challenge, details)
+    fn check_authorization(
+        &self,
+        subject: &(&str, HashMap<&str, Value<'_>>),
+        action_id: &str,
+        details: HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, OwnedValue>)>;
+}
+
+/// CheckAuthorizationFlags: let polkit ask the user through their agent
+const ALLOW_USER_INTERACTION: u32 = 1;
+
+/// Ask polkit whether the bus client `sender` may perform `action_id`.
+/// Root is always allowed, like systemd's own services do. `interactive`
+/// should reflect the caller's ALLOW_INTERACTIVE_AUTHORIZATION flag.
+pub async fn check_authorization(
+    connection: &zbus::Connection,
+    sender: &str,
+    action_id: &str,
+    interactive: bool,
+) -> Result<()> {
+    let bus_name = BusName::try_from(sender)
+        .map_err(|e| Error::dbus(format!("Invalid sender '{}': {}", sender, e)))?;
+    let uid = DBusProxy::new(connection)
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to connect to the bus daemon: {}", e)))?
+        .get_connection_unix_user(bus_name)
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to look up the caller of {}: {}", sender, e)))?;
+    if uid == 0 {
+        return Ok(());
+    }
+
+    let authority = AuthorityProxy::new(connection)
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to connect to polkit: {}", e)))?;
+    let subject = (
+        "system-bus-name",
+        HashMap::from([("name", Value::from(sender))]),
+    );
+    let flags = if interactive {
+        ALLOW_USER_INTERACTION
+    } else {
+        0
+    };
+
+    let (authorized, challenge, _) = authority
+        .check_authorization(&subject, action_id, HashMap::new(), flags, "")
+        .await
+        .map_err(|e| method_error(&format!("check authorization for {}", action_id), e))?;
+    debug!(%sender, %action_id, authorized, challenge, "polkit authorization checked");
+
+    if authorized {
+        Ok(())
+    } else {
+        Err(Error::PolkitDenied {
+            action: format!("perform {}", action_id),
+            reason: if challenge {
+                "interactive authentication required".to_string()
+            } else {
+                "not authorized".to_string()
+            },
+        })
+    }
+}
+
+/// Let privileged calls start a text agent the first time they need one.
+/// The agent, if any, is stopped when the returned guard is dropped.
+pub fn enable_agent() -> AgentGuard {
+    *AGENT.lock().unwrap() = AgentState::Enabled;
+    AgentGuard(())
+}
+
+/// Returned by [`enable_agent`]
+pub struct AgentGuard(());
+
+impl Drop for AgentGuard {
+    fn drop(&mut self) {
+        *AGENT.lock().unwrap() = AgentState::Disabled;
+    }
+}
+
+async fn start_agent() {
+    let _starting = STARTING.lock().await;
+    if !matches!(*AGENT.lock().unwrap(), AgentState::Enabled) {
+        return;
+    }
+
+    let agent = PolkitAgent::spawn().await;
+    let mut state = AGENT.lock().unwrap();
+    // The guard may have been dropped while the agent registered, in which
+    // case the agent is dropped here too
+    if matches!(*state, AgentState::Enabled) {
+        *state = match agent {
+            Some(agent) => AgentState::Running(agent),
+            None => AgentState::Unavailable,
+        };
+    }
+}
+
+/// A `pkttyagent` child registered as the session's authentication agent;
+/// killed on drop
+pub struct PolkitAgent {
+    child: Child,
+}
+
+impl PolkitAgent {
+    /// Start a text agent if prompting makes sense: interactive mode is on,
+    /// stdin is a terminal and we aren't root. Returns `None` otherwise, or
+    /// when pkttyagent isn't installed.
+    pub async fn spawn() -> Option<Self> {
+        if !is_interactive() || !std::io::stdin().is_terminal() {
+            return None;
+        }
+        if nix::unistd::geteuid().is_root() {
+            return None;
+        }
+
+        // pkttyagent closes --notify-fd once it has registered, so reading
+        // the pipe to EOF waits until prompts can actually be shown. Both
+        // ends are close-on-exec so no other child inherits them; only the
+        // agent gets the write end, cleared in the forked child.
+        let (notify_read, notify_write) = nix::unistd::pipe2(OFlag::O_CLOEXEC).ok()?;
+        let notify_fd = notify_write.as_raw_fd();
+        let mut command = Command::new(PKTTYAGENT);
+        command
+            .arg("--notify-fd")
+            .arg(notify_fd.to_string())
+            .arg("--fallback");
+        // SAFETY: fcntl is async-signal-safe and only touches the child's
+        // copy of the descriptor
+        unsafe {
+            command.pre_exec(move || {
+                if libc::fcntl(notify_fd, libc::F_SETFD, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn();
+        drop(notify_write);
+
+        let child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                debug!(error = %e, "polkit text agent unavailable");
+                return None;
+            }
+        };
+
+        let registered = tokio::task::spawn_blocking(move || {
+            File::from(notify_read).read_to_end(&mut Vec::new())
+        });
+        let _ = registered.await;
+        debug!(pid = child.id(), "polkit text agent registered");
+
+        Some(Self { child })
+    }
+}
+
+impl Drop for PolkitAgent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::connection::Builder;
+    use zbus::message::{Flags, Header};
+    use zbus::{interface, Guid};
+
+    struct Probe;
+
+    #[interface(name = "org.netctl1.Probe")]
+    impl Probe {
+        /// Whether the call allowed interactive authorization
+        fn interactive(&self, #[zbus(header)] header: Header<'_>) -> bool {
+            header
+                .primary()
+                .flags()
+                .contains(Flags::AllowInteractiveAuth)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interactive_flag_follows_setting() {
+        let (server, client) = tokio::net::UnixStream::pair().unwrap();
+        let server = Builder::unix_stream(server)
+            .server(Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at("/probe", Probe)
+            .unwrap()
+            .build();
+        let client = Builder::unix_stream(client).p2p().build();
+        let (_server, client) = tokio::try_join!(server, client).unwrap();
+        let proxy = zbus::Proxy::new(&client, "org.netctl1", "/probe", "org.netctl1.Probe")
+            .await
+            .unwrap();
+
+        let interactive = || async {
+            call_with_reply::<_, bool>(&proxy, "Interactive", &())
+                .await
+                .unwrap()
+        };
+        assert!(!interactive().await);
+        set_interactive(true);
+        assert!(interactive().await);
+        set_interactive(false);
+        assert!(!interactive().await);
+    }
+}
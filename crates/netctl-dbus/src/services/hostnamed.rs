@@ -1,5 +1,6 @@
+use crate::polkit::{self, method_error};
 use async_trait::async_trait;
-use netctl_types::{Error, Result};
+use netctl_types::{Chassis, Error, HostnameInfo, Result};
 use std::sync::Arc;
 use tracing::{debug, info, instrument};
 use zbus::{proxy, Connection};
@@ -11,11 +12,7 @@ use zbus::{proxy, Connection};
     default_path = "/org/freedesktop/hostname1"
 )]
 trait Hostname {
-    /// Set the static (configured) hostname
-    fn set_static_hostname(&self, hostname: &str, interactive: bool) -> zbus::Result<()>;
-
-    /// Set the pretty (human-readable) hostname
-    fn set_pretty_hostname(&self, hostname: &str, interactive: bool) -> zbus::Result<()>;
+    // Called through polkit::call instead: Set*
 
     /// Get the static hostname
     #[zbus(property)]
@@ -29,19 +26,72 @@ trait Hostname {
     #[zbus(property)]
     fn hostname(&self) -> zbus::Result<String>;
 
-    /// Get the machine ID
+    /// Hostname used when no static hostname is configured
+    #[zbus(property)]
+    fn default_hostname(&self) -> zbus::Result<String>;
+
+    /// "static", "transient" or "default"
+    #[zbus(property)]
+    fn hostname_source(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn icon_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn chassis(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn deployment(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn location(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn kernel_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn kernel_release(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn kernel_version(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn operating_system_pretty_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "OperatingSystemCPEName")]
+    fn operating_system_cpe_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "HomeURL")]
+    fn home_url(&self) -> zbus::Result<String>;
+
     #[zbus(property)]
-    fn machine_id(&self) -> zbus::Result<String>;
+    fn hardware_vendor(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn hardware_model(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn firmware_version(&self) -> zbus::Result<String>;
+
+    /// Get the machine ID (16 raw bytes)
+    #[zbus(property, name = "MachineID")]
+    fn machine_id(&self) -> zbus::Result<Vec<u8>>;
 }
 
 #[async_trait]
 pub trait HostnamedOps {
+    async fn set_transient_hostname(&self, hostname: &str) -> Result<()>;
     async fn set_static_hostname(&self, hostname: &str) -> Result<()>;
     async fn set_pretty_hostname(&self, hostname: &str) -> Result<()>;
+    async fn set_icon_name(&self, icon: &str) -> Result<()>;
+    async fn set_chassis(&self, chassis: Chassis) -> Result<()>;
+    async fn set_deployment(&self, deployment: &str) -> Result<()>;
+    async fn set_location(&self, location: &str) -> Result<()>;
     async fn get_static_hostname(&self) -> Result<String>;
     async fn get_pretty_hostname(&self) -> Result<String>;
     async fn get_hostname(&self) -> Result<String>;
     async fn get_machine_id(&self) -> Result<String>;
+    async fn get_info(&self) -> Result<HostnameInfo>;
 }
 
 #[derive(Clone)]
@@ -63,15 +113,35 @@ impl HostnamedService {
 
 #[async_trait]
 impl HostnamedOps for HostnamedService {
+    #[instrument(skip(self), fields(hostname = %hostname))]
+    async fn set_transient_hostname(&self, hostname: &str) -> Result<()> {
+        info!(hostname = %hostname, "setting transient hostname");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetHostname",
+            &(hostname, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set transient hostname", e))?;
+
+        debug!(hostname = %hostname, "transient hostname set successfully");
+        Ok(())
+    }
+
     #[instrument(skip(self), fields(hostname = %hostname))]
     async fn set_static_hostname(&self, hostname: &str) -> Result<()> {
         info!(hostname = %hostname, "setting static hostname");
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .set_static_hostname(hostname, false)
-            .await
-            .map_err(|e| Error::dbus(format!("Failed to set static hostname: {}", e)))?;
+        polkit::call(
+            proxy.inner(),
+            "SetStaticHostname",
+            &(hostname, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set static hostname", e))?;
 
         debug!(hostname = %hostname, "static hostname set successfully");
         Ok(())
@@ -82,15 +152,86 @@ impl HostnamedOps for HostnamedService {
         info!(hostname = %hostname, "setting pretty hostname");
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .set_pretty_hostname(hostname, false)
-            .await
-            .map_err(|e| Error::dbus(format!("Failed to set pretty hostname: {}", e)))?;
+        polkit::call(
+            proxy.inner(),
+            "SetPrettyHostname",
+            &(hostname, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set pretty hostname", e))?;
 
         debug!(hostname = %hostname, "pretty hostname set successfully");
         Ok(())
     }
 
+    #[instrument(skip(self))]
+    async fn set_icon_name(&self, icon: &str) -> Result<()> {
+        info!(%icon, "setting icon name");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetIconName",
+            &(icon, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set icon name", e))?;
+
+        debug!(%icon, "icon name set successfully");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_chassis(&self, chassis: Chassis) -> Result<()> {
+        info!(%chassis, "setting chassis");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetChassis",
+            &(&chassis.to_string(), polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set chassis", e))?;
+
+        debug!(%chassis, "chassis set successfully");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_deployment(&self, deployment: &str) -> Result<()> {
+        info!(%deployment, "setting deployment");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetDeployment",
+            &(deployment, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set deployment", e))?;
+
+        debug!(%deployment, "deployment set successfully");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_location(&self, location: &str) -> Result<()> {
+        info!(%location, "setting location");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLocation",
+            &(location, polkit::is_interactive()),
+        )
+        .await
+        .map_err(|e| method_error("set location", e))?;
+
+        debug!(%location, "location set successfully");
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn get_static_hostname(&self) -> Result<String> {
         debug!("getting static hostname");
@@ -99,7 +240,7 @@ impl HostnamedOps for HostnamedService {
         let hostname = proxy
             .static_hostname()
             .await
-            .map_err(|e| Error::dbus(format!("Failed to get static hostname: {}", e)))?;
+            .map_err(|e| method_error("get static hostname", e))?;
 
         Ok(hostname)
     }
@@ -112,7 +253,7 @@ impl HostnamedOps for HostnamedService {
         let hostname = proxy
             .pretty_hostname()
             .await
-            .map_err(|e| Error::dbus(format!("Failed to get pretty hostname: {}", e)))?;
+            .map_err(|e| method_error("get pretty hostname", e))?;
 
         Ok(hostname)
     }
@@ -125,7 +266,7 @@ impl HostnamedOps for HostnamedService {
         let hostname = proxy
             .hostname()
             .await
-            .map_err(|e| Error::dbus(format!("Failed to get hostname: {}", e)))?;
+            .map_err(|e| method_error("get hostname", e))?;
 
         Ok(hostname)
     }
@@ -138,8 +279,118 @@ impl HostnamedOps for HostnamedService {
         let machine_id = proxy
             .machine_id()
             .await
-            .map_err(|e| Error::dbus(format!("Failed to get machine ID: {}", e)))?;
+            .map_err(|e| method_error("get machine ID", e))?;
+
+        Ok(hex(&machine_id))
+    }
+
+    #[instrument(skip(self))]
+    async fn get_info(&self) -> Result<HostnameInfo> {
+        debug!("getting hostnamed properties");
+
+        let proxy = self.create_proxy().await?;
+        let hostname = proxy
+            .hostname()
+            .await
+            .map_err(|e| method_error("get hostname", e))?;
+
+        // Everything else is optional: older hostnamed versions lack many of
+        // these properties, and unset ones read back as empty strings
+        Ok(HostnameInfo {
+            hostname,
+            static_hostname: optional(proxy.static_hostname().await),
+            pretty_hostname: optional(proxy.pretty_hostname().await),
+            default_hostname: optional(proxy.default_hostname().await),
+            hostname_source: optional(proxy.hostname_source().await),
+            icon_name: optional(proxy.icon_name().await),
+            chassis: optional(proxy.chassis().await),
+            deployment: optional(proxy.deployment().await),
+            location: optional(proxy.location().await),
+            kernel_name: optional(proxy.kernel_name().await),
+            kernel_release: optional(proxy.kernel_release().await),
+            kernel_version: optional(proxy.kernel_version().await),
+            operating_system: optional(proxy.operating_system_pretty_name().await),
+            operating_system_cpe_name: optional(proxy.operating_system_cpe_name().await),
+            operating_system_home_url: optional(proxy.home_url().await),
+            hardware_vendor: optional(proxy.hardware_vendor().await),
+            hardware_model: optional(proxy.hardware_model().await),
+            firmware_version: optional(proxy.firmware_version().await),
+            machine_id: proxy.machine_id().await.ok().map(|id| hex(&id)),
+        })
+    }
+}
+
+fn optional(value: zbus::Result<String>) -> Option<String> {
+    value.ok().filter(|s| !s.is_empty())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::FakeBus;
+    use zbus::fdo;
+
+    #[tokio::test]
+    async fn test_set_and_get() {
+        let bus = FakeBus::new().await.unwrap();
+        let hostnamed = bus.add_hostnamed().await.unwrap();
+        let service = bus.client().hostnamed().await.unwrap();
+
+        service.set_static_hostname("web01").await.unwrap();
+        service.set_pretty_hostname("Web server 1").await.unwrap();
+        service.set_chassis(Chassis::Server).await.unwrap();
+
+        assert_eq!(service.get_static_hostname().await.unwrap(), "web01");
+        assert_eq!(service.get_pretty_hostname().await.unwrap(), "Web server 1");
+        assert_eq!(hostnamed.info().chassis.as_deref(), Some("server"));
+
+        // Not interactive unless the CLI asked for it
+        let calls = hostnamed.calls();
+        assert_eq!(calls[0].member, "SetStaticHostname");
+        assert_eq!(calls[0].args, "(\"web01\", false)");
+    }
+
+    #[tokio::test]
+    async fn test_info() {
+        let bus = FakeBus::new().await.unwrap();
+        let hostnamed = bus.add_hostnamed().await.unwrap();
+        let service = bus.client().hostnamed().await.unwrap();
+        let info = HostnameInfo {
+            hostname: "web01".to_string(),
+            static_hostname: Some("web01".to_string()),
+            kernel_name: Some("Linux".to_string()),
+            machine_id: Some("0123456789abcdef0123456789abcdef".to_string()),
+            ..Default::default()
+        };
+        hostnamed.set_info(info.clone());
+
+        assert_eq!(service.get_info().await.unwrap(), info);
+        assert_eq!(
+            service.get_machine_id().await.unwrap(),
+            "0123456789abcdef0123456789abcdef"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_denied() {
+        let bus = FakeBus::new().await.unwrap();
+        let hostnamed = bus.add_hostnamed().await.unwrap();
+        let service = bus.client().hostnamed().await.unwrap();
+        hostnamed.fail(
+            "SetHostname",
+            fdo::Error::InteractiveAuthorizationRequired(
+                "Interactive authentication required.".to_string(),
+            ),
+        );
 
-        Ok(machine_id)
+        match service.set_transient_hostname("web01").await {
+            Err(Error::PolkitDenied { action, .. }) => assert_eq!(action, "set transient hostname"),
+            other => panic!("expected PolkitDenied, got {:?}", other),
+        }
+        assert_eq!(hostnamed.info().hostname, "localhost");
     }
 }
@@ -1,7 +1,11 @@
 pub mod hostnamed;
 pub mod networkd;
+pub mod nm;
 pub mod resolved;
+pub mod systemd;
 
 pub use hostnamed::{HostnamedOps, HostnamedService};
 pub use networkd::{NetworkdOps, NetworkdService};
+pub use nm::{NmOps, NmService};
 pub use resolved::{ResolvedOps, ResolvedService};
+pub use systemd::{SystemdOps, SystemdService};
@@ -1,9 +1,16 @@
+use crate::polkit::{self, method_error};
 use async_trait::async_trait;
-use netctl_types::{Error, Result};
+use netctl_types::{Error, LinkDescription, NetworkdDescription, NetworkdLinkState, Result};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info, instrument};
+use zbus::fdo::DBusProxy;
+use zbus::names::WellKnownName;
+use zbus::zvariant::OwnedObjectPath;
 use zbus::{proxy, Connection};
 
+const NETWORKD_SERVICE: &str = "org.freedesktop.network1";
+
 /// D-Bus proxy for systemd-networkd Manager interface
 #[proxy(
     interface = "org.freedesktop.network1.Manager",
@@ -11,14 +18,47 @@ use zbus::{proxy, Connection};
     default_path = "/org/freedesktop/network1"
 )]
 trait Manager {
-    /// Reload networkd configuration files
-    fn reload(&self) -> zbus::Result<()>;
+    // Called through polkit::call instead: Reload, ReconfigureLink, RenewLink, ForceRenewLink
+
+    /// Get link name and object path by interface index
+    fn get_link_by_index(&self, ifindex: i32) -> zbus::Result<(String, OwnedObjectPath)>;
+
+    /// List managed links as (ifindex, name, object path)
+    fn list_links(&self) -> zbus::Result<Vec<(i32, String, OwnedObjectPath)>>;
+
+    /// Describe all links as JSON
+    fn describe(&self) -> zbus::Result<String>;
+
+    /// Describe a single link as JSON
+    fn describe_link(&self, ifindex: i32) -> zbus::Result<String>;
+}
+
+/// D-Bus proxy for a systemd-networkd Link object
+#[proxy(
+    interface = "org.freedesktop.network1.Link",
+    default_service = "org.freedesktop.network1"
+)]
+trait Link {
+    #[zbus(property)]
+    fn operational_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn carrier_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn address_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "IPv4AddressState")]
+    fn ipv4_address_state(&self) -> zbus::Result<String>;
 
-    /// Reconfigure a specific link by interface index
-    fn reconfigure_link(&self, ifindex: i32) -> zbus::Result<()>;
+    #[zbus(property, name = "IPv6AddressState")]
+    fn ipv6_address_state(&self) -> zbus::Result<String>;
 
-    /// Get link object path by interface index
-    fn get_link(&self, ifindex: i32) -> zbus::Result<zbus::zvariant::OwnedObjectPath>;
+    #[zbus(property)]
+    fn online_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn administrative_state(&self) -> zbus::Result<String>;
 }
 
 #[async_trait]
@@ -26,6 +66,12 @@ pub trait NetworkdOps {
     async fn reload(&self) -> Result<()>;
     async fn reconfigure_link(&self, index: u32) -> Result<()>;
     async fn get_link_path(&self, index: u32) -> Result<String>;
+    async fn get_link_state(&self, index: u32) -> Result<NetworkdLinkState>;
+    async fn list_link_states(&self) -> Result<HashMap<u32, NetworkdLinkState>>;
+    async fn renew_link(&self, index: u32) -> Result<()>;
+    async fn force_renew_link(&self, index: u32) -> Result<()>;
+    async fn describe(&self) -> Result<NetworkdDescription>;
+    async fn describe_link(&self, index: u32) -> Result<LinkDescription>;
 }
 
 #[derive(Clone)]
@@ -43,6 +89,46 @@ impl NetworkdService {
             .await
             .map_err(|e| Error::dbus(format!("Failed to create networkd proxy: {}", e)))
     }
+
+    /// Fail instead of D-Bus activating networkd on hosts that don't run it
+    async fn ensure_running(&self) -> Result<()> {
+        let dbus = DBusProxy::new(&self.connection)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create D-Bus proxy: {}", e)))?;
+        let running = dbus
+            .name_has_owner(WellKnownName::from_static_str_unchecked(NETWORKD_SERVICE).into())
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to query networkd: {}", e)))?;
+        if running {
+            Ok(())
+        } else {
+            Err(Error::unavailable(
+                "systemd-networkd",
+                "org.freedesktop.network1 has no owner on the system bus",
+            ))
+        }
+    }
+
+    async fn read_link_state(&self, path: OwnedObjectPath) -> Result<NetworkdLinkState> {
+        let link = LinkProxy::builder(&self.connection)
+            .path(path)
+            .map_err(|e| Error::dbus(format!("Invalid networkd link path: {}", e)))?
+            .build()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create networkd link proxy: {}", e)))?;
+        let err =
+            |e: zbus::Error| Error::dbus(format!("Failed to read networkd link state: {}", e));
+
+        Ok(NetworkdLinkState {
+            administrative_state: link.administrative_state().await.map_err(err)?,
+            operational_state: link.operational_state().await.map_err(err)?,
+            carrier_state: link.carrier_state().await.map_err(err)?,
+            address_state: link.address_state().await.map_err(err)?,
+            ipv4_address_state: link.ipv4_address_state().await.map_err(err)?,
+            ipv6_address_state: link.ipv6_address_state().await.map_err(err)?,
+            online_state: link.online_state().await.map_err(err)?,
+        })
+    }
 }
 
 #[async_trait]
@@ -50,12 +136,12 @@ impl NetworkdOps for NetworkdService {
     #[instrument(skip(self))]
     async fn reload(&self) -> Result<()> {
         info!("reloading systemd-networkd configuration");
+        self.ensure_running().await?;
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .reload()
+        polkit::call(proxy.inner(), "Reload", &())
             .await
-            .map_err(|e| Error::dbus(format!("Failed to reload networkd: {}", e)))?;
+            .map_err(|e| method_error("reload networkd", e))?;
 
         debug!("systemd-networkd reloaded successfully");
         Ok(())
@@ -64,12 +150,12 @@ impl NetworkdOps for NetworkdService {
     #[instrument(skip(self), fields(ifindex = %index))]
     async fn reconfigure_link(&self, index: u32) -> Result<()> {
         info!(ifindex = %index, "reconfiguring link via networkd");
+        self.ensure_running().await?;
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .reconfigure_link(index as i32)
+        polkit::call(proxy.inner(), "ReconfigureLink", &(index as i32,))
             .await
-            .map_err(|e| Error::dbus(format!("Failed to reconfigure link {}: {}", index, e)))?;
+            .map_err(|e| method_error(&format!("reconfigure link {}", index), e))?;
 
         debug!(ifindex = %index, "link reconfigured successfully");
         Ok(())
@@ -78,13 +164,249 @@ impl NetworkdOps for NetworkdService {
     #[instrument(skip(self), fields(ifindex = %index))]
     async fn get_link_path(&self, index: u32) -> Result<String> {
         debug!(ifindex = %index, "getting link D-Bus path");
+        self.ensure_running().await?;
 
         let proxy = self.create_proxy().await?;
-        let path = proxy
-            .get_link(index as i32)
+        let (_, path) = proxy
+            .get_link_by_index(index as i32)
             .await
             .map_err(|e| Error::dbus(format!("Failed to get link path for {}: {}", index, e)))?;
 
         Ok(path.to_string())
     }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn get_link_state(&self, index: u32) -> Result<NetworkdLinkState> {
+        debug!(ifindex = %index, "getting networkd link state");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let (_, path) = proxy
+            .get_link_by_index(index as i32)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to get link path for {}: {}", index, e)))?;
+
+        self.read_link_state(path).await
+    }
+
+    #[instrument(skip(self))]
+    async fn list_link_states(&self) -> Result<HashMap<u32, NetworkdLinkState>> {
+        debug!("listing networkd link states");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let links = proxy
+            .list_links()
+            .await
+            .map_err(|e| method_error("list networkd links", e))?;
+
+        let mut states = HashMap::new();
+        for (index, _, path) in links {
+            states.insert(index as u32, self.read_link_state(path).await?);
+        }
+        Ok(states)
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn renew_link(&self, index: u32) -> Result<()> {
+        info!(ifindex = %index, "renewing DHCP lease via networkd");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(proxy.inner(), "RenewLink", &(index as i32,))
+            .await
+            .map_err(|e| method_error(&format!("renew link {}", index), e))?;
+
+        debug!(ifindex = %index, "DHCP renewal requested");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn force_renew_link(&self, index: u32) -> Result<()> {
+        info!(ifindex = %index, "sending DHCP FORCERENEW via networkd");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(proxy.inner(), "ForceRenewLink", &(index as i32,))
+            .await
+            .map_err(|e| method_error(&format!("force renew on link {}", index), e))?;
+
+        debug!(ifindex = %index, "DHCP FORCERENEW sent");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn describe(&self) -> Result<NetworkdDescription> {
+        debug!("describing networkd links");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let json = proxy
+            .describe()
+            .await
+            .map_err(|e| method_error("describe networkd links", e))?;
+
+        serde_json::from_str(&json)
+            .map_err(|e| Error::dbus(format!("Failed to parse networkd description: {}", e)))
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn describe_link(&self, index: u32) -> Result<LinkDescription> {
+        debug!(ifindex = %index, "describing networkd link");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let json = proxy
+            .describe_link(index as i32)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to describe link {}: {}", index, e)))?;
+
+        serde_json::from_str(&json).map_err(|e| {
+            Error::dbus(format!(
+                "Failed to parse description of link {}: {}",
+                index, e
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{FakeBus, FakeNetworkd};
+    use futures::StreamExt;
+    use netctl_types::{NetworkEventKind, NetworkdLinkProperty};
+    use zbus::fdo;
+
+    fn configured() -> NetworkdLinkState {
+        NetworkdLinkState {
+            administrative_state: "configured".to_string(),
+            operational_state: "routable".to_string(),
+            carrier_state: "carrier".to_string(),
+            address_state: "routable".to_string(),
+            ipv4_address_state: "routable".to_string(),
+            ipv6_address_state: "off".to_string(),
+            online_state: "online".to_string(),
+        }
+    }
+
+    async fn setup() -> (FakeBus, FakeNetworkd, NetworkdService) {
+        let bus = FakeBus::new().await.unwrap();
+        let networkd = bus.add_networkd().await.unwrap();
+        networkd.add_link(2, "eth0", configured()).await.unwrap();
+        let service = bus.client().networkd().await.unwrap();
+        (bus, networkd, service)
+    }
+
+    #[tokio::test]
+    async fn test_link_states() {
+        let (_bus, networkd, service) = setup().await;
+        networkd
+            .add_link(3, "eth1", NetworkdLinkState::default())
+            .await
+            .unwrap();
+
+        assert_eq!(service.get_link_state(2).await.unwrap(), configured());
+        let states = service.list_link_states().await.unwrap();
+        assert_eq!(states.len(), 2);
+        assert!(states[&2].is_managed());
+        assert!(!states[&3].is_managed());
+        assert_eq!(
+            service.get_link_path(2).await.unwrap(),
+            "/org/freedesktop/network1/link/_32"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_calls_recorded() {
+        let (_bus, networkd, service) = setup().await;
+
+        service.reconfigure_link(2).await.unwrap();
+        service.renew_link(2).await.unwrap();
+        let calls = networkd.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].member, "ReconfigureLink");
+        assert_eq!(calls[0].args, "(2,)");
+        assert_eq!(calls[1].member, "RenewLink");
+
+        networkd.reset();
+        assert!(networkd.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_access_denied_is_polkit_denied() {
+        let (_bus, networkd, service) = setup().await;
+        networkd.fail(
+            "Reload",
+            fdo::Error::AccessDenied("Interactive authentication required.".to_string()),
+        );
+
+        match service.reload().await {
+            Err(Error::PolkitDenied { action, .. }) => assert_eq!(action, "reload networkd"),
+            other => panic!("expected PolkitDenied, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_describe_link() {
+        let (_bus, networkd, service) = setup().await;
+        networkd
+            .set_link_description(LinkDescription {
+                index: 2,
+                name: "eth0".to_string(),
+                driver: Some("virtio_net".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let link = service.describe_link(2).await.unwrap();
+        assert_eq!(link.driver.as_deref(), Some("virtio_net"));
+        let all = service.describe().await.unwrap();
+        assert_eq!(all.interfaces, vec![link]);
+        assert!(service.describe_link(9).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_not_running() {
+        let bus = FakeBus::new().await.unwrap();
+        let service = bus.client().networkd().await.unwrap();
+
+        assert!(matches!(
+            service.list_link_states().await,
+            Err(Error::ServiceUnavailable { .. })
+        ));
+        assert!(matches!(
+            service.reload().await,
+            Err(Error::ServiceUnavailable { .. })
+        ));
+        assert!(matches!(
+            service.reconfigure_link(2).await,
+            Err(Error::ServiceUnavailable { .. })
+        ));
+        assert!(matches!(
+            service.get_link_path(2).await,
+            Err(Error::ServiceUnavailable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_link_events() {
+        let (bus, networkd, _service) = setup().await;
+        let mut events = bus.client().networkd_link_events().await.unwrap();
+
+        networkd
+            .set_link_state(2, NetworkdLinkProperty::OperationalState, "degraded")
+            .await
+            .unwrap();
+
+        let event = events.next().await.unwrap();
+        assert_eq!(
+            event.kind,
+            NetworkEventKind::NetworkdLinkChanged {
+                index: 2,
+                property: NetworkdLinkProperty::OperationalState,
+                value: "degraded".to_string(),
+            }
+        );
+    }
 }
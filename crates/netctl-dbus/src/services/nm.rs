@@ -0,0 +1,739 @@
+use crate::polkit::{self, method_error};
+use async_trait::async_trait;
+use futures::StreamExt;
+use netctl_types::{
+    nm::nm_device_type_name, DnsDomain, DnsOverTlsMode, Error, IpNetwork, NmActiveConnection,
+    NmConnectionState, NmDevice, NmDeviceState, NmIpConfig, ResolveMode, Result,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, instrument};
+use zbus::fdo::DBusProxy;
+use zbus::names::WellKnownName;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::{proxy, Connection};
+
+const NM_SERVICE: &str = "org.freedesktop.NetworkManager";
+
+/// How long to wait for a connection to activate, like nmcli's default
+const ACTIVATION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Connection settings as NetworkManager exchanges them: setting name ->
+/// property -> value
+type ConnectionSettings = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// D-Bus proxy for the NetworkManager root object
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait Manager {
+    // Called through polkit::call instead: ActivateConnection, DeactivateConnection
+
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    fn get_device_by_ip_iface(&self, iface: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn active_connections(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+}
+
+/// D-Bus proxy for the saved connection profiles
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/Settings"
+)]
+trait Settings {
+    fn list_connections(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+}
+
+/// D-Bus proxy for a saved connection profile
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings.Connection",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait SettingsConnection {
+    fn get_settings(&self) -> zbus::Result<ConnectionSettings>;
+}
+
+/// D-Bus proxy for a NetworkManager Device object
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Device {
+    // Called through polkit::call instead: Reapply
+
+    /// The settings currently in effect on the device and their version
+    fn get_applied_connection(&self, flags: u32) -> zbus::Result<(ConnectionSettings, u64)>;
+
+    #[zbus(property)]
+    fn interface(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn driver(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn device_type(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn active_connection(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn ip4_config(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn ip6_config(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// D-Bus proxy for an active connection
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Connection.Active",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait ActiveConnection {
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn uuid(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "Type")]
+    fn connection_type(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    #[zbus(property)]
+    fn default(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn default6(&self) -> zbus::Result<bool>;
+}
+
+/// D-Bus proxy for an IPv4 configuration object
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.IP4Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Ip4Config {
+    #[zbus(property)]
+    fn address_data(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+
+    #[zbus(property)]
+    fn gateway(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn nameserver_data(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+
+    #[zbus(property)]
+    fn domains(&self) -> zbus::Result<Vec<String>>;
+
+    #[zbus(property)]
+    fn searches(&self) -> zbus::Result<Vec<String>>;
+}
+
+/// D-Bus proxy for an IPv6 configuration object
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.IP6Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Ip6Config {
+    #[zbus(property)]
+    fn address_data(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+
+    #[zbus(property)]
+    fn gateway(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn nameservers(&self) -> zbus::Result<Vec<Vec<u8>>>;
+
+    #[zbus(property)]
+    fn domains(&self) -> zbus::Result<Vec<String>>;
+
+    #[zbus(property)]
+    fn searches(&self) -> zbus::Result<Vec<String>>;
+}
+
+#[async_trait]
+pub trait NmOps {
+    /// Whether NetworkManager owns its bus name
+    async fn is_running(&self) -> Result<bool>;
+    async fn list_devices(&self) -> Result<Vec<NmDevice>>;
+    async fn get_device(&self, ifname: &str) -> Result<NmDevice>;
+    async fn list_active_connections(&self) -> Result<Vec<NmActiveConnection>>;
+    /// Activate a saved connection by ID or UUID and wait until it is up
+    async fn activate_connection(&self, connection: &str, ifname: Option<&str>) -> Result<()>;
+    /// Deactivate an active connection by ID or UUID
+    async fn deactivate_connection(&self, connection: &str) -> Result<()>;
+    /// Re-apply the saved profile to a device, dropping runtime changes
+    async fn reapply_device(&self, ifname: &str) -> Result<()>;
+    async fn set_device_dns(&self, ifname: &str, servers: Vec<IpAddr>) -> Result<()>;
+    async fn set_device_domains(&self, ifname: &str, domains: Vec<DnsDomain>) -> Result<()>;
+    async fn set_device_llmnr(&self, ifname: &str, mode: ResolveMode) -> Result<()>;
+    async fn set_device_mdns(&self, ifname: &str, mode: ResolveMode) -> Result<()>;
+    async fn set_device_dns_over_tls(&self, ifname: &str, mode: DnsOverTlsMode) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct NmService {
+    connection: Arc<Connection>,
+}
+
+impl NmService {
+    pub fn new(connection: Arc<Connection>) -> Self {
+        Self { connection }
+    }
+
+    async fn create_proxy(&self) -> Result<ManagerProxy<'_>> {
+        ManagerProxy::new(&self.connection)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create NetworkManager proxy: {}", e)))
+    }
+
+    /// Fail instead of D-Bus activating NetworkManager on hosts that don't
+    /// run it
+    async fn ensure_running(&self) -> Result<()> {
+        if self.is_running().await? {
+            Ok(())
+        } else {
+            Err(Error::unavailable(
+                "NetworkManager",
+                "org.freedesktop.NetworkManager has no owner on the system bus",
+            ))
+        }
+    }
+
+    async fn device_proxy(&self, ifname: &str) -> Result<DeviceProxy<'static>> {
+        self.ensure_running().await?;
+        let proxy = self.create_proxy().await?;
+        let path = proxy.get_device_by_ip_iface(ifname).await.map_err(|e| {
+            Error::dbus(format!("NetworkManager has no device '{}': {}", ifname, e))
+        })?;
+        self.object_proxy(DeviceProxy::builder(&self.connection), path)
+            .await
+    }
+
+    async fn object_proxy<P>(
+        &self,
+        builder: zbus::proxy::Builder<'static, P>,
+        path: OwnedObjectPath,
+    ) -> Result<P>
+    where
+        P: From<zbus::Proxy<'static>>,
+    {
+        builder
+            .path(path)
+            .map_err(|e| Error::dbus(format!("Invalid NetworkManager object path: {}", e)))?
+            .build()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create NetworkManager proxy: {}", e)))
+    }
+
+    async fn read_device(&self, path: OwnedObjectPath) -> Result<NmDevice> {
+        let device = self
+            .object_proxy(DeviceProxy::builder(&self.connection), path)
+            .await?;
+        let err = |e: zbus::Error| Error::dbus(format!("Failed to read device: {}", e));
+
+        let active = device.active_connection().await.map_err(err)?;
+        let connection = if is_null(&active) {
+            None
+        } else {
+            let active = self
+                .object_proxy(ActiveConnectionProxy::builder(&self.connection), active)
+                .await?;
+            active.id().await.ok()
+        };
+
+        let ip4 = device.ip4_config().await.map_err(err)?;
+        let ip4 = if is_null(&ip4) {
+            None
+        } else {
+            Some(self.read_ip4_config(ip4).await?)
+        };
+        let ip6 = device.ip6_config().await.map_err(err)?;
+        let ip6 = if is_null(&ip6) {
+            None
+        } else {
+            Some(self.read_ip6_config(ip6).await?)
+        };
+
+        Ok(NmDevice {
+            ifname: device.interface().await.map_err(err)?,
+            device_type: nm_device_type_name(device.device_type().await.map_err(err)?).to_string(),
+            driver: device.driver().await.map_err(err)?,
+            state: NmDeviceState::from_u32(device.state().await.map_err(err)?),
+            connection,
+            ip4,
+            ip6,
+        })
+    }
+
+    async fn read_ip4_config(&self, path: OwnedObjectPath) -> Result<NmIpConfig> {
+        let config = self
+            .object_proxy(Ip4ConfigProxy::builder(&self.connection), path)
+            .await?;
+        let err = |e: zbus::Error| Error::dbus(format!("Failed to read IPv4 config: {}", e));
+
+        let mut domains = config.domains().await.map_err(err)?;
+        domains.extend(config.searches().await.map_err(err)?);
+
+        Ok(NmIpConfig {
+            addresses: address_data(&config.address_data().await.map_err(err)?),
+            gateway: config.gateway().await.map_err(err)?.parse().ok(),
+            nameservers: config
+                .nameserver_data()
+                .await
+                .map_err(err)?
+                .iter()
+                .filter_map(|entry| entry_str(entry, "address")?.parse().ok())
+                .collect(),
+            domains,
+        })
+    }
+
+    async fn read_ip6_config(&self, path: OwnedObjectPath) -> Result<NmIpConfig> {
+        let config = self
+            .object_proxy(Ip6ConfigProxy::builder(&self.connection), path)
+            .await?;
+        let err = |e: zbus::Error| Error::dbus(format!("Failed to read IPv6 config: {}", e));
+
+        let mut domains = config.domains().await.map_err(err)?;
+        domains.extend(config.searches().await.map_err(err)?);
+
+        Ok(NmIpConfig {
+            addresses: address_data(&config.address_data().await.map_err(err)?),
+            gateway: config.gateway().await.map_err(err)?.parse().ok(),
+            nameservers: config
+                .nameservers()
+                .await
+                .map_err(err)?
+                .into_iter()
+                .filter_map(|bytes| <[u8; 16]>::try_from(bytes).ok())
+                .map(|octets| IpAddr::V6(Ipv6Addr::from(octets)))
+                .collect(),
+            domains,
+        })
+    }
+
+    async fn read_active_connection(&self, path: OwnedObjectPath) -> Result<NmActiveConnection> {
+        let active = self
+            .object_proxy(ActiveConnectionProxy::builder(&self.connection), path)
+            .await?;
+        let err = |e: zbus::Error| Error::dbus(format!("Failed to read active connection: {}", e));
+
+        let mut devices = Vec::new();
+        for path in active.devices().await.map_err(err)? {
+            let device = self
+                .object_proxy(DeviceProxy::builder(&self.connection), path)
+                .await?;
+            devices.push(device.interface().await.map_err(err)?);
+        }
+
+        Ok(NmActiveConnection {
+            id: active.id().await.map_err(err)?,
+            uuid: active.uuid().await.map_err(err)?,
+            connection_type: active.connection_type().await.map_err(err)?,
+            state: NmConnectionState::from_u32(active.state().await.map_err(err)?),
+            devices,
+            default4: active.default().await.map_err(err)?,
+            default6: active.default6().await.map_err(err)?,
+        })
+    }
+
+    /// Path of the saved profile whose ID or UUID is `connection`
+    async fn find_saved_connection(&self, connection: &str) -> Result<OwnedObjectPath> {
+        let settings = SettingsProxy::new(&self.connection)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create NetworkManager proxy: {}", e)))?;
+        let paths = settings
+            .list_connections()
+            .await
+            .map_err(|e| method_error("list connections", e))?;
+
+        for path in paths {
+            let profile = self
+                .object_proxy(
+                    SettingsConnectionProxy::builder(&self.connection),
+                    path.clone(),
+                )
+                .await?;
+            let Ok(settings) = profile.get_settings().await else {
+                continue;
+            };
+            let Some(section) = settings.get("connection") else {
+                continue;
+            };
+            let matches = ["id", "uuid"]
+                .iter()
+                .any(|key| entry_str(section, key) == Some(connection));
+            if matches {
+                return Ok(path);
+            }
+        }
+
+        Err(Error::Generic(format!("no connection '{}'", connection)))
+    }
+
+    /// Path of the active connection whose ID or UUID is `connection`
+    async fn find_active_connection(&self, connection: &str) -> Result<OwnedObjectPath> {
+        let proxy = self.create_proxy().await?;
+        let paths = proxy
+            .active_connections()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to list active connections: {}", e)))?;
+
+        for path in paths {
+            let active = self
+                .object_proxy(
+                    ActiveConnectionProxy::builder(&self.connection),
+                    path.clone(),
+                )
+                .await?;
+            let id = active.id().await.unwrap_or_default();
+            let uuid = active.uuid().await.unwrap_or_default();
+            if id == connection || uuid == connection {
+                return Ok(path);
+            }
+        }
+
+        Err(Error::Generic(format!(
+            "connection '{}' is not active",
+            connection
+        )))
+    }
+
+    /// Wait for an activation to settle: activated or deactivated (failed)
+    async fn wait_activated(&self, path: OwnedObjectPath, connection: &str) -> Result<()> {
+        let active = self
+            .object_proxy(ActiveConnectionProxy::builder(&self.connection), path)
+            .await?;
+        let mut changes = active.receive_state_changed().await;
+
+        let wait = async {
+            let mut state = active.state().await.ok().map(NmConnectionState::from_u32);
+            loop {
+                match state {
+                    Some(NmConnectionState::Activated) => return Ok(()),
+                    Some(NmConnectionState::Deactivated) => {
+                        return Err(Error::Generic(format!(
+                            "activation of '{}' failed",
+                            connection
+                        )))
+                    }
+                    _ => {}
+                }
+                let Some(change) = changes.next().await else {
+                    return Err(Error::dbus(format!(
+                        "Lost connection to NetworkManager while activating '{}'",
+                        connection
+                    )));
+                };
+                state = change.get().await.ok().map(NmConnectionState::from_u32);
+                debug!(?state, "active connection state changed");
+            }
+        };
+
+        tokio::time::timeout(ACTIVATION_TIMEOUT, wait)
+            .await
+            .map_err(|_| {
+                Error::Generic(format!(
+                    "timed out waiting for '{}' to activate",
+                    connection
+                ))
+            })?
+    }
+
+    /// Change the settings applied to a device at runtime, leaving the saved
+    /// profile alone (what `nmcli device modify` does)
+    async fn modify_applied<F>(&self, ifname: &str, action: &str, modify: F) -> Result<()>
+    where
+        F: FnOnce(&mut ConnectionSettings) -> Result<()> + Send,
+    {
+        let device = self.device_proxy(ifname).await?;
+        let (mut settings, version) = device
+            .get_applied_connection(0)
+            .await
+            .map_err(|e| method_error(&format!("read applied connection of {}", ifname), e))?;
+
+        modify(&mut settings)?;
+
+        polkit::call(device.inner(), "Reapply", &(&settings, version, 0u32))
+            .await
+            .map_err(|e| method_error(action, e))
+    }
+}
+
+#[async_trait]
+impl NmOps for NmService {
+    async fn is_running(&self) -> Result<bool> {
+        let dbus = DBusProxy::new(&self.connection)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create D-Bus proxy: {}", e)))?;
+        dbus.name_has_owner(WellKnownName::from_static_str_unchecked(NM_SERVICE).into())
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to query NetworkManager: {}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_devices(&self) -> Result<Vec<NmDevice>> {
+        info!("listing NetworkManager devices");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let paths = proxy
+            .get_devices()
+            .await
+            .map_err(|e| method_error("list devices", e))?;
+
+        let mut devices = Vec::with_capacity(paths.len());
+        for path in paths {
+            devices.push(self.read_device(path).await?);
+        }
+        debug!(count = devices.len(), "devices listed");
+        Ok(devices)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_device(&self, ifname: &str) -> Result<NmDevice> {
+        debug!(%ifname, "reading NetworkManager device");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let path = proxy.get_device_by_ip_iface(ifname).await.map_err(|e| {
+            Error::dbus(format!("NetworkManager has no device '{}': {}", ifname, e))
+        })?;
+        self.read_device(path).await
+    }
+
+    #[instrument(skip(self))]
+    async fn list_active_connections(&self) -> Result<Vec<NmActiveConnection>> {
+        info!("listing active connections");
+        self.ensure_running().await?;
+
+        let proxy = self.create_proxy().await?;
+        let paths = proxy
+            .active_connections()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to list active connections: {}", e)))?;
+
+        let mut connections = Vec::with_capacity(paths.len());
+        for path in paths {
+            connections.push(self.read_active_connection(path).await?);
+        }
+        Ok(connections)
+    }
+
+    #[instrument(skip(self))]
+    async fn activate_connection(&self, connection: &str, ifname: Option<&str>) -> Result<()> {
+        info!(%connection, "activating connection");
+        self.ensure_running().await?;
+
+        let profile = self.find_saved_connection(connection).await?;
+        let proxy = self.create_proxy().await?;
+        let device = match ifname {
+            Some(ifname) => proxy.get_device_by_ip_iface(ifname).await.map_err(|e| {
+                Error::dbus(format!("NetworkManager has no device '{}': {}", ifname, e))
+            })?,
+            None => root_path(),
+        };
+
+        let active: OwnedObjectPath = polkit::call_with_reply(
+            proxy.inner(),
+            "ActivateConnection",
+            &(&profile, &device, &root_path()),
+        )
+        .await
+        .map_err(|e| method_error(&format!("activate {}", connection), e))?;
+        self.wait_activated(active, connection).await?;
+
+        debug!(%connection, "connection activated");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn deactivate_connection(&self, connection: &str) -> Result<()> {
+        info!(%connection, "deactivating connection");
+        self.ensure_running().await?;
+
+        let active = self.find_active_connection(connection).await?;
+        let proxy = self.create_proxy().await?;
+        polkit::call(proxy.inner(), "DeactivateConnection", &(&active,))
+            .await
+            .map_err(|e| method_error(&format!("deactivate {}", connection), e))?;
+
+        debug!(%connection, "connection deactivated");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn reapply_device(&self, ifname: &str) -> Result<()> {
+        info!(%ifname, "reapplying device configuration");
+        let device = self.device_proxy(ifname).await?;
+        polkit::call(
+            device.inner(),
+            "Reapply",
+            &(&ConnectionSettings::new(), 0u64, 0u32),
+        )
+        .await
+        .map_err(|e| method_error(&format!("reapply {}", ifname), e))?;
+
+        debug!(%ifname, "device configuration reapplied");
+        Ok(())
+    }
+
+    /// Replaces the device's DNS servers; servers learned from DHCP or RA
+    /// are ignored while any are set
+    #[instrument(skip(self, servers), fields(ifname = %ifname, server_count = servers.len()))]
+    async fn set_device_dns(&self, ifname: &str, servers: Vec<IpAddr>) -> Result<()> {
+        info!(%ifname, server_count = servers.len(), "setting device DNS servers");
+
+        // ipv4.dns is a list of addresses in network byte order
+        let v4: Vec<u32> = servers
+            .iter()
+            .filter_map(|addr| match addr {
+                IpAddr::V4(v4) => Some(u32::from_ne_bytes(v4.octets())),
+                IpAddr::V6(_) => None,
+            })
+            .collect();
+        let v6: Vec<Vec<u8>> = servers
+            .iter()
+            .filter_map(|addr| match addr {
+                IpAddr::V6(v6) => Some(v6.octets().to_vec()),
+                IpAddr::V4(_) => None,
+            })
+            .collect();
+        let ignore_auto = !servers.is_empty();
+
+        self.modify_applied(
+            ifname,
+            &format!("set DNS servers of {}", ifname),
+            |settings| {
+                if let Some(ipv4) = settings.get_mut("ipv4") {
+                    ipv4.insert("dns".into(), owned(Value::from(v4))?);
+                    ipv4.insert("ignore-auto-dns".into(), owned(Value::from(ignore_auto))?);
+                }
+                if let Some(ipv6) = settings.get_mut("ipv6") {
+                    ipv6.insert("dns".into(), owned(Value::from(v6))?);
+                    ipv6.insert("ignore-auto-dns".into(), owned(Value::from(ignore_auto))?);
+                }
+                Ok(())
+            },
+        )
+        .await
+    }
+
+    #[instrument(skip(self, domains), fields(ifname = %ifname, domain_count = domains.len()))]
+    async fn set_device_domains(&self, ifname: &str, domains: Vec<DnsDomain>) -> Result<()> {
+        info!(%ifname, domain_count = domains.len(), "setting device DNS domains");
+
+        // NetworkManager marks routing-only domains with "~" like we do
+        let search: Vec<String> = domains.iter().map(|d| d.to_string()).collect();
+        self.modify_applied(
+            ifname,
+            &format!("set DNS domains of {}", ifname),
+            |settings| {
+                settings
+                    .entry("ipv4".into())
+                    .or_default()
+                    .insert("dns-search".into(), owned(Value::from(search))?);
+                Ok(())
+            },
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn set_device_llmnr(&self, ifname: &str, mode: ResolveMode) -> Result<()> {
+        info!(%ifname, %mode, "setting device LLMNR");
+        self.modify_applied(ifname, &format!("set LLMNR of {}", ifname), |settings| {
+            set_connection_property(settings, "llmnr", resolve_mode_value(mode))
+        })
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn set_device_mdns(&self, ifname: &str, mode: ResolveMode) -> Result<()> {
+        info!(%ifname, %mode, "setting device MulticastDNS");
+        self.modify_applied(
+            ifname,
+            &format!("set MulticastDNS of {}", ifname),
+            |settings| set_connection_property(settings, "mdns", resolve_mode_value(mode)),
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    async fn set_device_dns_over_tls(&self, ifname: &str, mode: DnsOverTlsMode) -> Result<()> {
+        info!(%ifname, %mode, "setting device DNS-over-TLS");
+        let value = match mode {
+            DnsOverTlsMode::No => 0,
+            DnsOverTlsMode::Opportunistic => 1,
+            DnsOverTlsMode::Yes => 2,
+        };
+        self.modify_applied(
+            ifname,
+            &format!("set DNS-over-TLS of {}", ifname),
+            |settings| set_connection_property(settings, "dns-over-tls", value),
+        )
+        .await
+    }
+}
+
+/// connection.llmnr / connection.mdns values
+fn resolve_mode_value(mode: ResolveMode) -> i32 {
+    match mode {
+        ResolveMode::No => 0,
+        ResolveMode::Resolve => 1,
+        ResolveMode::Yes => 2,
+    }
+}
+
+fn set_connection_property(settings: &mut ConnectionSettings, key: &str, value: i32) -> Result<()> {
+    settings
+        .entry("connection".into())
+        .or_default()
+        .insert(key.into(), owned(Value::from(value))?);
+    Ok(())
+}
+
+fn owned(value: Value<'_>) -> Result<OwnedValue> {
+    OwnedValue::try_from(value)
+        .map_err(|e| Error::dbus(format!("Invalid connection setting: {}", e)))
+}
+
+/// NetworkManager uses "/" for "no object"
+fn is_null(path: &OwnedObjectPath) -> bool {
+    path.as_str() == "/"
+}
+
+fn root_path() -> OwnedObjectPath {
+    OwnedObjectPath::from(ObjectPath::from_static_str_unchecked("/"))
+}
+
+fn entry_str<'a>(entry: &'a HashMap<String, OwnedValue>, key: &str) -> Option<&'a str> {
+    entry.get(key).and_then(|v| <&str>::try_from(&**v).ok())
+}
+
+/// Parse AddressData: a list of {"address": s, "prefix": u}
+fn address_data(entries: &[HashMap<String, OwnedValue>]) -> Vec<IpNetwork> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let address: IpAddr = entry_str(entry, "address")?.parse().ok()?;
+            let prefix = entry.get("prefix").and_then(|v| u32::try_from(&**v).ok())?;
+            IpNetwork::new(address, u8::try_from(prefix).ok()?).ok()
+        })
+        .collect()
+}
@@ -1,10 +1,23 @@
+use crate::polkit::{self, method_error};
 use async_trait::async_trait;
-use netctl_types::{Error, Result};
-use std::net::IpAddr;
+use netctl_types::dns::{ResolvedAddress, ResolvedName};
+use netctl_types::{
+    AddressResolution, DnsDomain, DnsOverTlsMode, DnsRecord, DnsRecordType, DnsStatistics,
+    DnssecMode, Error, GlobalDnsSettings, HostnameResolution, LinkDnsSettings, RecordResolution,
+    ResolveMode, Result,
+};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
 use tracing::{debug, info, instrument};
+use zbus::zvariant::OwnedObjectPath;
 use zbus::{proxy, Connection};
 
+/// (ifindex, family, address bytes) tuples from ResolveHostname
+type WireAddresses = Vec<(i32, i32, Vec<u8>)>;
+
+/// (ifindex, class, type, wire-format RR) tuples from ResolveRecord
+type WireRecords = Vec<(i32, u16, u16, Vec<u8>)>;
+
 /// D-Bus proxy for systemd-resolved Manager interface
 #[proxy(
     interface = "org.freedesktop.resolve1.Manager",
@@ -12,25 +25,148 @@ use zbus::{proxy, Connection};
     default_path = "/org/freedesktop/resolve1"
 )]
 trait ResolveManager {
-    /// Set DNS servers for a specific link
-    fn set_link_dns(&self, ifindex: i32, addresses: Vec<(i32, Vec<u8>)>) -> zbus::Result<()>;
+    // Called through polkit::call instead: SetLink*, RevertLink, FlushCaches, ResetStatistics
+
+    /// Get the object path of a link
+    fn get_link(&self, ifindex: i32) -> zbus::Result<OwnedObjectPath>;
+
+    /// Resolve a host name to addresses, the canonical name and answer flags
+    fn resolve_hostname(
+        &self,
+        ifindex: i32,
+        name: &str,
+        family: i32,
+        flags: u64,
+    ) -> zbus::Result<(WireAddresses, String, u64)>;
+
+    /// Resolve an address to (ifindex, name) tuples and the answer flags
+    fn resolve_address(
+        &self,
+        ifindex: i32,
+        family: i32,
+        address: Vec<u8>,
+        flags: u64,
+    ) -> zbus::Result<(Vec<(i32, String)>, u64)>;
+
+    /// Look up raw resource records
+    fn resolve_record(
+        &self,
+        ifindex: i32,
+        name: &str,
+        class: u16,
+        rtype: u16,
+        flags: u64,
+    ) -> zbus::Result<(WireRecords, u64)>;
+
+    /// Configured servers of all links; ifindex 0 marks global ones
+    #[zbus(property, name = "DNS")]
+    fn dns(&self) -> zbus::Result<WireAddresses>;
+
+    #[zbus(property, name = "FallbackDNS")]
+    fn fallback_dns(&self) -> zbus::Result<WireAddresses>;
+
+    #[zbus(property, name = "CurrentDNSServer")]
+    fn current_dns_server(&self) -> zbus::Result<(i32, i32, Vec<u8>)>;
+
+    /// (ifindex, domain, routing-only) for all links; ifindex 0 marks global ones
+    #[zbus(property)]
+    fn domains(&self) -> zbus::Result<Vec<(i32, String, bool)>>;
 
-    /// Set DNS search domains for a specific link
-    fn set_link_domains(&self, ifindex: i32, domains: Vec<(String, bool)>) -> zbus::Result<()>;
+    #[zbus(property, name = "LLMNR")]
+    fn llmnr(&self) -> zbus::Result<String>;
 
-    /// Revert DNS settings for a link to defaults
-    fn revert_link(&self, ifindex: i32) -> zbus::Result<()>;
+    #[zbus(property, name = "MulticastDNS")]
+    fn multicast_dns(&self) -> zbus::Result<String>;
 
-    /// Flush all caches
-    fn flush_caches(&self) -> zbus::Result<()>;
+    #[zbus(property, name = "DNSSEC")]
+    fn dnssec(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "DNSOverTLS")]
+    fn dns_over_tls(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn resolv_conf_mode(&self) -> zbus::Result<String>;
+
+    /// (current, total) transactions
+    #[zbus(property)]
+    fn transaction_statistics(&self) -> zbus::Result<(u64, u64)>;
+
+    /// (size, hits, misses)
+    #[zbus(property)]
+    fn cache_statistics(&self) -> zbus::Result<(u64, u64, u64)>;
+
+    /// (secure, insecure, bogus, indeterminate)
+    #[zbus(property, name = "DNSSECStatistics")]
+    fn dnssec_statistics(&self) -> zbus::Result<(u64, u64, u64, u64)>;
+}
+
+/// D-Bus proxy for a systemd-resolved Link object
+#[proxy(
+    interface = "org.freedesktop.resolve1.Link",
+    default_service = "org.freedesktop.resolve1"
+)]
+trait ResolveLink {
+    #[zbus(property, name = "DNS")]
+    fn dns(&self) -> zbus::Result<Vec<(i32, Vec<u8>)>>;
+
+    #[zbus(property, name = "CurrentDNSServer")]
+    fn current_dns_server(&self) -> zbus::Result<(i32, Vec<u8>)>;
+
+    /// (domain, routing-only) pairs
+    #[zbus(property)]
+    fn domains(&self) -> zbus::Result<Vec<(String, bool)>>;
+
+    #[zbus(property)]
+    fn default_route(&self) -> zbus::Result<bool>;
+
+    #[zbus(property, name = "LLMNR")]
+    fn llmnr(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "MulticastDNS")]
+    fn multicast_dns(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "DNSSEC")]
+    fn dnssec(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "DNSOverTLS")]
+    fn dns_over_tls(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "DNSSECNegativeTrustAnchors")]
+    fn dnssec_negative_trust_anchors(&self) -> zbus::Result<Vec<String>>;
+
+    #[zbus(property, name = "DNSSECSupported")]
+    fn dnssec_supported(&self) -> zbus::Result<bool>;
 }
 
 #[async_trait]
 pub trait ResolvedOps {
     async fn set_link_dns(&self, index: u32, servers: Vec<IpAddr>) -> Result<()>;
-    async fn set_link_domains(&self, index: u32, domains: Vec<String>) -> Result<()>;
+    async fn set_link_domains(&self, index: u32, domains: Vec<DnsDomain>) -> Result<()>;
+    async fn set_link_default_route(&self, index: u32, enable: bool) -> Result<()>;
+    async fn set_link_llmnr(&self, index: u32, mode: ResolveMode) -> Result<()>;
+    async fn set_link_mdns(&self, index: u32, mode: ResolveMode) -> Result<()>;
+    async fn set_link_dnssec(&self, index: u32, mode: DnssecMode) -> Result<()>;
+    async fn set_link_dns_over_tls(&self, index: u32, mode: DnsOverTlsMode) -> Result<()>;
+    async fn set_link_negative_trust_anchors(&self, index: u32, names: Vec<String>) -> Result<()>;
     async fn revert_link(&self, index: u32) -> Result<()>;
     async fn flush_caches(&self) -> Result<()>;
+    async fn get_link_settings(&self, index: u32) -> Result<LinkDnsSettings>;
+    async fn get_global_settings(&self) -> Result<GlobalDnsSettings>;
+    async fn get_statistics(&self) -> Result<DnsStatistics>;
+    async fn reset_statistics(&self) -> Result<()>;
+    /// Resolve on link `index`, or on any link if `None`
+    async fn resolve_hostname(&self, index: Option<u32>, name: &str) -> Result<HostnameResolution>;
+    async fn resolve_address(
+        &self,
+        index: Option<u32>,
+        address: IpAddr,
+    ) -> Result<AddressResolution>;
+    async fn resolve_record(
+        &self,
+        index: Option<u32>,
+        name: &str,
+        record_type: DnsRecordType,
+    ) -> Result<RecordResolution>;
 }
 
 #[derive(Clone)]
@@ -55,6 +191,31 @@ impl ResolvedService {
             IpAddr::V6(ipv6) => (10, ipv6.octets().to_vec()), // AF_INET6 = 10
         }
     }
+
+    fn ip_from_dbus_format(family: i32, bytes: &[u8]) -> Option<IpAddr> {
+        match family {
+            2 => <[u8; 4]>::try_from(bytes)
+                .ok()
+                .map(|o| Ipv4Addr::from(o).into()),
+            10 => <[u8; 16]>::try_from(bytes)
+                .ok()
+                .map(|o| Ipv6Addr::from(o).into()),
+            _ => None,
+        }
+    }
+}
+
+fn property_error(e: zbus::Error) -> Error {
+    Error::dbus(format!("Failed to read resolved property: {}", e))
+}
+
+/// resolved uses ifindex 0 for "any link" both ways
+fn ifindex_to_dbus(index: Option<u32>) -> i32 {
+    index.map(|i| i as i32).unwrap_or(0)
+}
+
+fn ifindex_from_dbus(ifindex: i32) -> Option<u32> {
+    (ifindex > 0).then_some(ifindex as u32)
 }
 
 #[async_trait]
@@ -70,46 +231,133 @@ impl ResolvedOps for ResolvedService {
         let addresses: Vec<_> = servers.iter().map(Self::ip_to_dbus_format).collect();
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .set_link_dns(index as i32, addresses)
+        polkit::call(proxy.inner(), "SetLinkDNS", &(index as i32, addresses))
             .await
-            .map_err(|e| Error::dbus(format!("Failed to set DNS servers: {}", e)))?;
+            .map_err(|e| method_error("set DNS servers", e))?;
 
         debug!(ifindex = %index, "DNS servers configured successfully");
         Ok(())
     }
 
     #[instrument(skip(self, domains), fields(ifindex = %index, domain_count = domains.len()))]
-    async fn set_link_domains(&self, index: u32, domains: Vec<String>) -> Result<()> {
+    async fn set_link_domains(&self, index: u32, domains: Vec<DnsDomain>) -> Result<()> {
         info!(
             ifindex = %index,
             domain_count = domains.len(),
             "setting DNS domains for link"
         );
 
-        // Convert to (domain, routing_only) tuples
         // routing_only=false means the domain is used for both search and routing
-        let domain_tuples: Vec<_> = domains.into_iter().map(|d| (d, false)).collect();
+        let domain_tuples: Vec<_> = domains
+            .into_iter()
+            .map(|d| (d.domain, d.routing_only))
+            .collect();
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .set_link_domains(index as i32, domain_tuples)
-            .await
-            .map_err(|e| Error::dbus(format!("Failed to set DNS domains: {}", e)))?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkDomains",
+            &(index as i32, domain_tuples),
+        )
+        .await
+        .map_err(|e| method_error("set DNS domains", e))?;
 
         debug!(ifindex = %index, "DNS domains configured successfully");
         Ok(())
     }
 
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn set_link_default_route(&self, index: u32, enable: bool) -> Result<()> {
+        info!(ifindex = %index, enable, "setting DNS default route for link");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkDefaultRoute",
+            &(index as i32, enable),
+        )
+        .await
+        .map_err(|e| method_error("set DNS default route", e))
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn set_link_llmnr(&self, index: u32, mode: ResolveMode) -> Result<()> {
+        info!(ifindex = %index, %mode, "setting LLMNR for link");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkLLMNR",
+            &(index as i32, &mode.to_string()),
+        )
+        .await
+        .map_err(|e| method_error("set LLMNR", e))
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn set_link_mdns(&self, index: u32, mode: ResolveMode) -> Result<()> {
+        info!(ifindex = %index, %mode, "setting MulticastDNS for link");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkMulticastDNS",
+            &(index as i32, &mode.to_string()),
+        )
+        .await
+        .map_err(|e| method_error("set MulticastDNS", e))
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn set_link_dnssec(&self, index: u32, mode: DnssecMode) -> Result<()> {
+        info!(ifindex = %index, %mode, "setting DNSSEC for link");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkDNSSEC",
+            &(index as i32, &mode.to_string()),
+        )
+        .await
+        .map_err(|e| method_error("set DNSSEC", e))
+    }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn set_link_dns_over_tls(&self, index: u32, mode: DnsOverTlsMode) -> Result<()> {
+        info!(ifindex = %index, %mode, "setting DNS-over-TLS for link");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkDNSOverTLS",
+            &(index as i32, &mode.to_string()),
+        )
+        .await
+        .map_err(|e| method_error("set DNS-over-TLS", e))
+    }
+
+    #[instrument(skip(self, names), fields(ifindex = %index, count = names.len()))]
+    async fn set_link_negative_trust_anchors(&self, index: u32, names: Vec<String>) -> Result<()> {
+        info!(ifindex = %index, count = names.len(), "setting DNSSEC negative trust anchors");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(
+            proxy.inner(),
+            "SetLinkDNSSECNegativeTrustAnchors",
+            &(index as i32, names),
+        )
+        .await
+        .map_err(|e| method_error("set DNSSEC negative trust anchors", e))
+    }
+
     #[instrument(skip(self), fields(ifindex = %index))]
     async fn revert_link(&self, index: u32) -> Result<()> {
         info!(ifindex = %index, "reverting DNS configuration for link");
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .revert_link(index as i32)
+        polkit::call(proxy.inner(), "RevertLink", &(index as i32,))
             .await
-            .map_err(|e| Error::dbus(format!("Failed to revert DNS config: {}", e)))?;
+            .map_err(|e| method_error("revert DNS config", e))?;
 
         debug!(ifindex = %index, "DNS configuration reverted successfully");
         Ok(())
@@ -120,12 +368,405 @@ impl ResolvedOps for ResolvedService {
         info!("flushing DNS caches");
 
         let proxy = self.create_proxy().await?;
-        proxy
-            .flush_caches()
+        polkit::call(proxy.inner(), "FlushCaches", &())
             .await
-            .map_err(|e| Error::dbus(format!("Failed to flush DNS caches: {}", e)))?;
+            .map_err(|e| method_error("flush DNS caches", e))?;
 
         debug!("DNS caches flushed successfully");
         Ok(())
     }
+
+    #[instrument(skip(self), fields(ifindex = %index))]
+    async fn get_link_settings(&self, index: u32) -> Result<LinkDnsSettings> {
+        debug!(ifindex = %index, "reading link DNS settings");
+
+        let proxy = self.create_proxy().await?;
+        let path = proxy
+            .get_link(index as i32)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to get resolved link {}: {}", index, e)))?;
+        let link = ResolveLinkProxy::builder(&self.connection)
+            .path(path)
+            .map_err(|e| Error::dbus(format!("Invalid resolved link path: {}", e)))?
+            .build()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create resolved link proxy: {}", e)))?;
+
+        let (family, bytes) = link.current_dns_server().await.map_err(property_error)?;
+        Ok(LinkDnsSettings {
+            ifindex: index,
+            servers: link
+                .dns()
+                .await
+                .map_err(property_error)?
+                .into_iter()
+                .filter_map(|(family, bytes)| Self::ip_from_dbus_format(family, &bytes))
+                .collect(),
+            current_server: Self::ip_from_dbus_format(family, &bytes),
+            domains: link
+                .domains()
+                .await
+                .map_err(property_error)?
+                .into_iter()
+                .map(|(domain, routing_only)| DnsDomain {
+                    domain,
+                    routing_only,
+                })
+                .collect(),
+            default_route: link.default_route().await.map_err(property_error)?,
+            llmnr: link.llmnr().await.map_err(property_error)?,
+            mdns: link.multicast_dns().await.map_err(property_error)?,
+            dnssec: link.dnssec().await.map_err(property_error)?,
+            dns_over_tls: link.dns_over_tls().await.map_err(property_error)?,
+            negative_trust_anchors: link
+                .dnssec_negative_trust_anchors()
+                .await
+                .map_err(property_error)?,
+            dnssec_supported: link.dnssec_supported().await.map_err(property_error)?,
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_global_settings(&self) -> Result<GlobalDnsSettings> {
+        debug!("reading global DNS settings");
+
+        let proxy = self.create_proxy().await?;
+        let global_servers = |servers: WireAddresses| -> Vec<IpAddr> {
+            servers
+                .into_iter()
+                .filter(|(ifindex, _, _)| *ifindex == 0)
+                .filter_map(|(_, family, bytes)| Self::ip_from_dbus_format(family, &bytes))
+                .collect()
+        };
+
+        let (_, family, bytes) = proxy.current_dns_server().await.map_err(property_error)?;
+        Ok(GlobalDnsSettings {
+            servers: global_servers(proxy.dns().await.map_err(property_error)?),
+            fallback_servers: global_servers(proxy.fallback_dns().await.map_err(property_error)?),
+            current_server: Self::ip_from_dbus_format(family, &bytes),
+            domains: proxy
+                .domains()
+                .await
+                .map_err(property_error)?
+                .into_iter()
+                .filter(|(ifindex, _, _)| *ifindex == 0)
+                .map(|(_, domain, routing_only)| DnsDomain {
+                    domain,
+                    routing_only,
+                })
+                .collect(),
+            llmnr: proxy.llmnr().await.map_err(property_error)?,
+            mdns: proxy.multicast_dns().await.map_err(property_error)?,
+            dnssec: proxy.dnssec().await.map_err(property_error)?,
+            dns_over_tls: proxy.dns_over_tls().await.map_err(property_error)?,
+            resolv_conf_mode: proxy.resolv_conf_mode().await.map_err(property_error)?,
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_statistics(&self) -> Result<DnsStatistics> {
+        debug!("reading resolved statistics");
+
+        let proxy = self.create_proxy().await?;
+        let (current_transactions, total_transactions) = proxy
+            .transaction_statistics()
+            .await
+            .map_err(property_error)?;
+        let (cache_size, cache_hits, cache_misses) =
+            proxy.cache_statistics().await.map_err(property_error)?;
+        let (dnssec_secure, dnssec_insecure, dnssec_bogus, dnssec_indeterminate) =
+            proxy.dnssec_statistics().await.map_err(property_error)?;
+
+        Ok(DnsStatistics {
+            current_transactions,
+            total_transactions,
+            cache_size,
+            cache_hits,
+            cache_misses,
+            dnssec_secure,
+            dnssec_insecure,
+            dnssec_bogus,
+            dnssec_indeterminate,
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn reset_statistics(&self) -> Result<()> {
+        info!("resetting resolved statistics");
+
+        let proxy = self.create_proxy().await?;
+        polkit::call(proxy.inner(), "ResetStatistics", &())
+            .await
+            .map_err(|e| method_error("reset DNS statistics", e))?;
+
+        debug!("resolved statistics reset");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn resolve_hostname(&self, index: Option<u32>, name: &str) -> Result<HostnameResolution> {
+        debug!(%name, "resolving host name");
+
+        let proxy = self.create_proxy().await?;
+        let (addresses, canonical_name, flags) = proxy
+            .resolve_hostname(ifindex_to_dbus(index), name, 0, 0)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to resolve {}: {}", name, e)))?;
+
+        let addresses = addresses
+            .into_iter()
+            .filter_map(|(ifindex, family, bytes)| {
+                Some(ResolvedAddress {
+                    ifindex: ifindex_from_dbus(ifindex),
+                    address: Self::ip_from_dbus_format(family, &bytes)?,
+                })
+            })
+            .collect();
+
+        Ok(HostnameResolution {
+            name: name.to_string(),
+            canonical_name,
+            addresses,
+            flags: flags.into(),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn resolve_address(
+        &self,
+        index: Option<u32>,
+        address: IpAddr,
+    ) -> Result<AddressResolution> {
+        debug!(%address, "resolving address");
+
+        let (family, bytes) = Self::ip_to_dbus_format(&address);
+        let proxy = self.create_proxy().await?;
+        let (names, flags) = proxy
+            .resolve_address(ifindex_to_dbus(index), family, bytes, 0)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to resolve {}: {}", address, e)))?;
+
+        let names = names
+            .into_iter()
+            .map(|(ifindex, name)| ResolvedName {
+                ifindex: ifindex_from_dbus(ifindex),
+                name,
+            })
+            .collect();
+
+        Ok(AddressResolution {
+            address,
+            names,
+            flags: flags.into(),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn resolve_record(
+        &self,
+        index: Option<u32>,
+        name: &str,
+        record_type: DnsRecordType,
+    ) -> Result<RecordResolution> {
+        debug!(%name, %record_type, "resolving records");
+
+        let proxy = self.create_proxy().await?;
+        let (records, flags) = proxy
+            .resolve_record(
+                ifindex_to_dbus(index),
+                name,
+                record_type.class(),
+                record_type.code(),
+                0,
+            )
+            .await
+            .map_err(|e| {
+                Error::dbus(format!(
+                    "Failed to resolve {} record for {}: {}",
+                    record_type, name, e
+                ))
+            })?;
+
+        let records = records
+            .into_iter()
+            .map(|(ifindex, _, _, wire)| DnsRecord::from_wire(ifindex_from_dbus(ifindex), &wire))
+            .collect::<Result<_>>()?;
+
+        Ok(RecordResolution {
+            name: name.to_string(),
+            record_type,
+            records,
+            flags: flags.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{FakeBus, FakeResolved};
+    use netctl_types::DnsRecordData;
+    use zbus::fdo;
+
+    async fn setup() -> (FakeBus, FakeResolved, ResolvedService) {
+        let bus = FakeBus::new().await.unwrap();
+        let resolved = bus.add_resolved().await.unwrap();
+        let service = bus.client().resolved().await.unwrap();
+        (bus, resolved, service)
+    }
+
+    #[tokio::test]
+    async fn test_link_settings_round_trip() {
+        let (_bus, resolved, service) = setup().await;
+        let servers: Vec<IpAddr> = vec![
+            "192.0.2.53".parse().unwrap(),
+            "2001:db8::53".parse().unwrap(),
+        ];
+        let domains = vec![
+            DnsDomain {
+                domain: "example.com".to_string(),
+                routing_only: false,
+            },
+            DnsDomain {
+                domain: "corp.example".to_string(),
+                routing_only: true,
+            },
+        ];
+
+        service.set_link_dns(3, servers.clone()).await.unwrap();
+        service.set_link_domains(3, domains.clone()).await.unwrap();
+        service.set_link_default_route(3, true).await.unwrap();
+        service
+            .set_link_dnssec(3, DnssecMode::AllowDowngrade)
+            .await
+            .unwrap();
+
+        let settings = service.get_link_settings(3).await.unwrap();
+        assert_eq!(settings.servers, servers);
+        assert_eq!(settings.current_server, Some(servers[0]));
+        assert_eq!(settings.domains, domains);
+        assert!(settings.default_route);
+        assert_eq!(settings.dnssec, "allow-downgrade");
+        assert_eq!(resolved.link(3), Some(settings));
+
+        let calls = resolved.calls();
+        assert_eq!(calls[0].member, "SetLinkDNS");
+        assert_eq!(
+            calls[0].args,
+            "(3, [(2, [192, 0, 2, 53]), (10, [32, 1, 13, 184, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 83])])"
+        );
+
+        service.revert_link(3).await.unwrap();
+        assert!(resolved.link(3).unwrap().servers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_global_settings() {
+        let (_bus, resolved, service) = setup().await;
+        let global = GlobalDnsSettings {
+            servers: vec!["192.0.2.1".parse().unwrap()],
+            fallback_servers: vec!["198.51.100.1".parse().unwrap()],
+            current_server: Some("192.0.2.1".parse().unwrap()),
+            llmnr: "yes".to_string(),
+            resolv_conf_mode: "stub".to_string(),
+            ..Default::default()
+        };
+        resolved.set_global(global.clone());
+        // Link servers are listed too, and must not leak into the global ones
+        service
+            .set_link_dns(2, vec!["203.0.113.1".parse().unwrap()])
+            .await
+            .unwrap();
+
+        assert_eq!(service.get_global_settings().await.unwrap(), global);
+    }
+
+    #[tokio::test]
+    async fn test_statistics_reset() {
+        let (_bus, resolved, service) = setup().await;
+        resolved.set_statistics(DnsStatistics {
+            cache_hits: 7,
+            total_transactions: 9,
+            ..Default::default()
+        });
+
+        assert_eq!(service.get_statistics().await.unwrap().cache_hits, 7);
+        service.reset_statistics().await.unwrap();
+        assert_eq!(
+            service.get_statistics().await.unwrap(),
+            DnsStatistics::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let (_bus, resolved, service) = setup().await;
+        let address: IpAddr = "192.0.2.10".parse().unwrap();
+        resolved.add_host("host.example", vec![address]);
+        resolved.add_address(address, vec!["host.example".to_string()]);
+        // host.example. IN A 192.0.2.10, TTL 300
+        let mut wire = b"\x04host\x07example\x00\x00\x01\x00\x01\x00\x00\x01\x2c\x00\x04".to_vec();
+        wire.extend([192, 0, 2, 10]);
+        resolved.add_records("host.example", 1, vec![wire]);
+
+        let hostname = service
+            .resolve_hostname(None, "host.example")
+            .await
+            .unwrap();
+        assert_eq!(hostname.addresses[0].address, address);
+        assert_eq!(hostname.addresses[0].ifindex, None);
+
+        let names = service.resolve_address(Some(2), address).await.unwrap();
+        assert_eq!(names.names[0].name, "host.example");
+        assert_eq!(names.names[0].ifindex, Some(2));
+
+        let records = service
+            .resolve_record(None, "host.example", DnsRecordType::A)
+            .await
+            .unwrap();
+        assert_eq!(records.records[0].ttl, 300);
+        assert_eq!(
+            records.records[0].data,
+            DnsRecordData::A {
+                address: "192.0.2.10".parse().unwrap()
+            }
+        );
+
+        assert!(service
+            .resolve_hostname(None, "missing.example")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures() {
+        let (_bus, resolved, service) = setup().await;
+        resolved.fail(
+            "FlushCaches",
+            fdo::Error::AccessDenied("Access denied".to_string()),
+        );
+        resolved.fail(
+            "SetLinkDNSSECNegativeTrustAnchors",
+            fdo::Error::AccessDenied("Access denied".to_string()),
+        );
+        resolved.fail("SetLinkLLMNR", fdo::Error::Failed("broken".to_string()));
+
+        assert!(matches!(
+            service.flush_caches().await,
+            Err(Error::PolkitDenied { .. })
+        ));
+        assert!(matches!(
+            service
+                .set_link_negative_trust_anchors(2, vec!["corp.example".to_string()])
+                .await,
+            Err(Error::PolkitDenied { .. })
+        ));
+        assert!(matches!(
+            service.set_link_llmnr(2, ResolveMode::Resolve).await,
+            Err(Error::Dbus(_))
+        ));
+        // Failed calls are still recorded, and change nothing
+        assert_eq!(resolved.calls().len(), 3);
+        assert_eq!(resolved.link(2), None);
+    }
 }
@@ -0,0 +1,243 @@
+use crate::polkit::{self, method_error};
+use async_trait::async_trait;
+use futures::StreamExt;
+use netctl_types::{Error, Result, UnitStatus};
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, info, instrument};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath};
+use zbus::{proxy, Connection};
+
+/// D-Bus proxy for the systemd Manager interface
+#[proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait SystemdManager {
+    // Called through polkit::call instead: StartUnit, StopUnit, RestartUnit
+
+    /// Object path of a unit, loading it if necessary (works for inactive
+    /// units, unlike GetUnit)
+    fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// Ask systemd to emit job and unit signals to us
+    fn subscribe(&self) -> zbus::Result<()>;
+
+    /// A job finished; `result` is done, canceled, timeout, failed,
+    /// dependency or skipped
+    #[zbus(signal)]
+    fn job_removed(
+        &self,
+        id: u32,
+        job: ObjectPath<'_>,
+        unit: &str,
+        result: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// D-Bus proxy for a systemd Unit object
+#[proxy(
+    interface = "org.freedesktop.systemd1.Unit",
+    default_service = "org.freedesktop.systemd1"
+)]
+trait SystemdUnit {
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn description(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn load_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn active_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn sub_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn unit_file_state(&self) -> zbus::Result<String>;
+
+    /// CLOCK_REALTIME microseconds; 0 if never active
+    #[zbus(property)]
+    fn active_enter_timestamp(&self) -> zbus::Result<u64>;
+}
+
+/// D-Bus proxy for the Service interface of a service unit object
+#[proxy(
+    interface = "org.freedesktop.systemd1.Service",
+    default_service = "org.freedesktop.systemd1"
+)]
+trait SystemdServiceUnit {
+    #[zbus(property, name = "MainPID")]
+    fn main_pid(&self) -> zbus::Result<u32>;
+}
+
+#[async_trait]
+pub trait SystemdOps {
+    async fn get_unit_status(&self, unit: &str) -> Result<UnitStatus>;
+    async fn start_unit(&self, unit: &str) -> Result<()>;
+    async fn stop_unit(&self, unit: &str) -> Result<()>;
+    async fn restart_unit(&self, unit: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy)]
+enum JobKind {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Clone)]
+pub struct SystemdService {
+    connection: Arc<Connection>,
+}
+
+impl SystemdService {
+    pub fn new(connection: Arc<Connection>) -> Self {
+        Self { connection }
+    }
+
+    async fn create_proxy(&self) -> Result<SystemdManagerProxy<'_>> {
+        SystemdManagerProxy::new(&self.connection)
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create systemd proxy: {}", e)))
+    }
+
+    /// Queue a job in "replace" mode and wait until systemd reports it
+    /// finished, like `systemctl start/stop/restart` does
+    async fn run_job(&self, kind: JobKind, unit: &str) -> Result<()> {
+        let proxy = self.create_proxy().await?;
+
+        // JobRemoved is only sent once someone subscribed; an error here
+        // just means we already are
+        let _ = proxy.subscribe().await;
+        let mut removed = proxy
+            .receive_job_removed()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to watch systemd jobs: {}", e)))?;
+
+        let action = format!("{} {}", kind, unit);
+        let method = match kind {
+            JobKind::Start => "StartUnit",
+            JobKind::Stop => "StopUnit",
+            JobKind::Restart => "RestartUnit",
+        };
+        let job: OwnedObjectPath =
+            polkit::call_with_reply(proxy.inner(), method, &(unit, "replace"))
+                .await
+                .map_err(|e| method_error(&action, e))?;
+        debug!(job = %job.as_str(), "job queued");
+
+        while let Some(signal) = removed.next().await {
+            let Ok(args) = signal.args() else {
+                continue;
+            };
+            if args.job().as_str() != job.as_str() {
+                continue;
+            }
+
+            return match *args.result() {
+                "done" | "skipped" => Ok(()),
+                result => Err(Error::Generic(format!(
+                    "job to {} finished with result '{}'",
+                    action, result
+                ))),
+            };
+        }
+
+        Err(Error::dbus(format!(
+            "Lost connection to systemd while waiting to {}",
+            action
+        )))
+    }
+}
+
+#[async_trait]
+impl SystemdOps for SystemdService {
+    #[instrument(skip(self))]
+    async fn get_unit_status(&self, unit: &str) -> Result<UnitStatus> {
+        debug!(%unit, "reading unit status");
+
+        let proxy = self.create_proxy().await?;
+        let path = proxy
+            .load_unit(unit)
+            .await
+            .map_err(|e| method_error(&format!("load {}", unit), e))?;
+
+        let unit_proxy = SystemdUnitProxy::builder(&self.connection)
+            .path(path.clone())
+            .map_err(|e| Error::dbus(format!("Invalid systemd unit path: {}", e)))?
+            .build()
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to create systemd unit proxy: {}", e)))?;
+        let err = |e: zbus::Error| Error::dbus(format!("Failed to read unit state: {}", e));
+
+        // Only service units have a main PID
+        let main_pid = match SystemdServiceUnitProxy::builder(&self.connection)
+            .path(path)
+            .map_err(|e| Error::dbus(format!("Invalid systemd unit path: {}", e)))?
+            .build()
+            .await
+        {
+            Ok(service) => service.main_pid().await.ok().filter(|pid| *pid != 0),
+            Err(_) => None,
+        };
+
+        Ok(UnitStatus {
+            name: unit_proxy.id().await.map_err(err)?,
+            description: unit_proxy.description().await.map_err(err)?,
+            load_state: unit_proxy.load_state().await.map_err(err)?,
+            active_state: unit_proxy.active_state().await.map_err(err)?,
+            sub_state: unit_proxy.sub_state().await.map_err(err)?,
+            unit_file_state: unit_proxy
+                .unit_file_state()
+                .await
+                .ok()
+                .filter(|s| !s.is_empty()),
+            main_pid,
+            active_enter_timestamp: unit_proxy
+                .active_enter_timestamp()
+                .await
+                .ok()
+                .filter(|ts| *ts != 0),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn start_unit(&self, unit: &str) -> Result<()> {
+        info!(%unit, "starting unit");
+        self.run_job(JobKind::Start, unit).await?;
+        debug!(%unit, "unit started");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn stop_unit(&self, unit: &str) -> Result<()> {
+        info!(%unit, "stopping unit");
+        self.run_job(JobKind::Stop, unit).await?;
+        debug!(%unit, "unit stopped");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn restart_unit(&self, unit: &str) -> Result<()> {
+        info!(%unit, "restarting unit");
+        self.run_job(JobKind::Restart, unit).await?;
+        debug!(%unit, "unit restarted");
+        Ok(())
+    }
+}
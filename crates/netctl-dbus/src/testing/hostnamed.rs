@@ -0,0 +1,205 @@
+use super::{recorder_methods, serve_at, Recorder};
+use netctl_types::{HostnameInfo, Result};
+use std::sync::{Arc, Mutex};
+use zbus::{fdo, interface, Connection};
+
+const PATH: &str = "/org/freedesktop/hostname1";
+
+/// Fake systemd-hostnamed, serving a [`HostnameInfo`] that the setters
+/// update. Unset fields read back as empty strings, like hostnamed's.
+pub struct FakeHostnamed {
+    recorder: Recorder,
+    info: Arc<Mutex<HostnameInfo>>,
+}
+
+impl FakeHostnamed {
+    pub(crate) async fn serve(server: Connection) -> Result<Self> {
+        let recorder = Recorder::default();
+        let info = Arc::new(Mutex::new(HostnameInfo {
+            hostname: "localhost".to_string(),
+            ..Default::default()
+        }));
+        let hostname1 = Hostname1 {
+            recorder: recorder.clone(),
+            info: Arc::clone(&info),
+        };
+        serve_at(&server, PATH, hostname1).await?;
+
+        Ok(Self { recorder, info })
+    }
+
+    recorder_methods!();
+
+    pub fn info(&self) -> HostnameInfo {
+        self.info.lock().unwrap().clone()
+    }
+
+    /// Replace everything hostnamed reports. `machine_id` is hex, as
+    /// [`HostnameInfo`] carries it.
+    pub fn set_info(&self, info: HostnameInfo) {
+        *self.info.lock().unwrap() = info;
+    }
+}
+
+struct Hostname1 {
+    recorder: Recorder,
+    info: Arc<Mutex<HostnameInfo>>,
+}
+
+impl Hostname1 {
+    fn get(&self, field: impl Fn(&HostnameInfo) -> &Option<String>) -> String {
+        field(&self.info.lock().unwrap())
+            .clone()
+            .unwrap_or_default()
+    }
+
+    fn set(
+        &self,
+        member: &str,
+        value: &str,
+        interactive: bool,
+        field: impl Fn(&mut HostnameInfo) -> &mut Option<String>,
+    ) -> fdo::Result<()> {
+        self.recorder.record(member, (value, interactive))?;
+        *field(&mut self.info.lock().unwrap()) = (!value.is_empty()).then(|| value.to_string());
+        Ok(())
+    }
+}
+
+#[interface(name = "org.freedesktop.hostname1")]
+impl Hostname1 {
+    fn set_hostname(&self, hostname: &str, interactive: bool) -> fdo::Result<()> {
+        self.recorder
+            .record("SetHostname", (hostname, interactive))?;
+        self.info.lock().unwrap().hostname = hostname.to_string();
+        Ok(())
+    }
+
+    fn set_static_hostname(&self, hostname: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetStaticHostname", hostname, interactive, |i| {
+            &mut i.static_hostname
+        })
+    }
+
+    fn set_pretty_hostname(&self, hostname: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetPrettyHostname", hostname, interactive, |i| {
+            &mut i.pretty_hostname
+        })
+    }
+
+    fn set_icon_name(&self, icon: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetIconName", icon, interactive, |i| &mut i.icon_name)
+    }
+
+    fn set_chassis(&self, chassis: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetChassis", chassis, interactive, |i| &mut i.chassis)
+    }
+
+    fn set_deployment(&self, deployment: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetDeployment", deployment, interactive, |i| {
+            &mut i.deployment
+        })
+    }
+
+    fn set_location(&self, location: &str, interactive: bool) -> fdo::Result<()> {
+        self.set("SetLocation", location, interactive, |i| &mut i.location)
+    }
+
+    #[zbus(property)]
+    fn static_hostname(&self) -> String {
+        self.get(|i| &i.static_hostname)
+    }
+
+    #[zbus(property)]
+    fn pretty_hostname(&self) -> String {
+        self.get(|i| &i.pretty_hostname)
+    }
+
+    #[zbus(property)]
+    fn hostname(&self) -> String {
+        self.info.lock().unwrap().hostname.clone()
+    }
+
+    #[zbus(property)]
+    fn default_hostname(&self) -> String {
+        self.get(|i| &i.default_hostname)
+    }
+
+    #[zbus(property)]
+    fn hostname_source(&self) -> String {
+        self.get(|i| &i.hostname_source)
+    }
+
+    #[zbus(property)]
+    fn icon_name(&self) -> String {
+        self.get(|i| &i.icon_name)
+    }
+
+    #[zbus(property)]
+    fn chassis(&self) -> String {
+        self.get(|i| &i.chassis)
+    }
+
+    #[zbus(property)]
+    fn deployment(&self) -> String {
+        self.get(|i| &i.deployment)
+    }
+
+    #[zbus(property)]
+    fn location(&self) -> String {
+        self.get(|i| &i.location)
+    }
+
+    #[zbus(property)]
+    fn kernel_name(&self) -> String {
+        self.get(|i| &i.kernel_name)
+    }
+
+    #[zbus(property)]
+    fn kernel_release(&self) -> String {
+        self.get(|i| &i.kernel_release)
+    }
+
+    #[zbus(property)]
+    fn kernel_version(&self) -> String {
+        self.get(|i| &i.kernel_version)
+    }
+
+    #[zbus(property)]
+    fn operating_system_pretty_name(&self) -> String {
+        self.get(|i| &i.operating_system)
+    }
+
+    #[zbus(property, name = "OperatingSystemCPEName")]
+    fn operating_system_cpe_name(&self) -> String {
+        self.get(|i| &i.operating_system_cpe_name)
+    }
+
+    #[zbus(property, name = "HomeURL")]
+    fn home_url(&self) -> String {
+        self.get(|i| &i.operating_system_home_url)
+    }
+
+    #[zbus(property)]
+    fn hardware_vendor(&self) -> String {
+        self.get(|i| &i.hardware_vendor)
+    }
+
+    #[zbus(property)]
+    fn hardware_model(&self) -> String {
+        self.get(|i| &i.hardware_model)
+    }
+
+    #[zbus(property)]
+    fn firmware_version(&self) -> String {
+        self.get(|i| &i.firmware_version)
+    }
+
+    #[zbus(property, name = "MachineID")]
+    fn machine_id(&self) -> Vec<u8> {
+        let hex = self.get(|i| &i.machine_id);
+        (0..hex.len() / 2)
+            .filter_map(|i| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok())
+            .collect()
+    }
+}
@@ -0,0 +1,329 @@
+//! In-process fakes of systemd-networkd, systemd-resolved,
+//! systemd-hostnamed and polkit for tests that must not depend on a running
+//! system.
+//!
+//! [`FakeBus`] connects a [`DbusClient`] peer-to-peer to an object server
+//! hosting the fakes, so no bus daemon is needed. It also answers the bus
+//! daemon's `NameHasOwner`, which the services use to tell whether a daemon
+//! is running, and `GetConnectionUnixUser`, which services check callers
+//! with. Every fake records the method calls it receives and can be
+//! told to fail a method with a given D-Bus error:
+//!
+//! ```ignore
+//! let bus = FakeBus::new().await?;
+//! let resolved = bus.add_resolved().await?;
+//! resolved.fail("FlushCaches", fdo::Error::AccessDenied("denied".into()));
+//!
+//! let client = bus.client();
+//! assert!(client.resolved().await?.flush_caches().await.is_err());
+//! assert_eq!(resolved.calls()[0].member, "FlushCaches");
+//! ```
+
+mod hostnamed;
+mod networkd;
+mod polkit;
+mod resolved;
+
+pub use hostnamed::FakeHostnamed;
+pub use networkd::FakeNetworkd;
+pub use polkit::FakePolkit;
+pub use resolved::FakeResolved;
+
+use crate::DbusClient;
+use futures::TryStreamExt;
+use netctl_types::{Error, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zbus::connection::Builder;
+use zbus::message::{Flags, Type};
+use zbus::zvariant::{DynamicDeserialize, DynamicType, Value};
+use zbus::{fdo, interface, Connection, Guid, Message, MessageStream};
+
+/// Unique name [`FakeBus::call`] sends as
+pub const CALLER: &str = ":1.42";
+
+/// A method call received by a fake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    /// D-Bus member name, e.g. `SetLinkDNS`
+    pub member: String,
+    /// The arguments, `Debug`-formatted as a tuple
+    pub args: String,
+}
+
+/// Call log and injected failures, shared by a fake's objects and its handle
+#[derive(Clone, Default)]
+pub(crate) struct Recorder {
+    calls: Arc<Mutex<Vec<Call>>>,
+    failures: Arc<Mutex<HashMap<String, fdo::Error>>>,
+}
+
+impl Recorder {
+    /// Log a call, then return the failure injected for `member`, if any
+    pub(crate) fn record(&self, member: &str, args: impl Debug) -> fdo::Result<()> {
+        self.calls.lock().unwrap().push(Call {
+            member: member.to_string(),
+            args: format!("{:?}", args),
+        });
+        match self.failures.lock().unwrap().get(member) {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn fail(&self, member: &str, error: fdo::Error) {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(member.to_string(), error);
+    }
+
+    fn clear(&self) {
+        self.calls.lock().unwrap().clear();
+        self.failures.lock().unwrap().clear();
+    }
+}
+
+/// Call recording and error injection, common to all fakes
+macro_rules! recorder_methods {
+    () => {
+        /// Method calls received so far, oldest first
+        pub fn calls(&self) -> Vec<$crate::testing::Call> {
+            self.recorder.calls()
+        }
+
+        /// Make every call of `member` fail with `error` until [`Self::reset`]
+        pub fn fail(&self, member: &str, error: zbus::fdo::Error) {
+            self.recorder.fail(member, error)
+        }
+
+        /// Forget recorded calls and injected failures
+        pub fn reset(&self) {
+            self.recorder.clear()
+        }
+    };
+}
+pub(crate) use recorder_methods;
+
+/// The bus daemon's `NameHasOwner`, answered from the fakes registered, and
+/// `GetConnectionUnixUser`, answered with the same uid for every caller
+struct FakeBusDaemon {
+    names: Arc<Mutex<HashSet<String>>>,
+    uid: Arc<AtomicU32>,
+}
+
+#[interface(name = "org.freedesktop.DBus")]
+impl FakeBusDaemon {
+    fn name_has_owner(&self, name: &str) -> bool {
+        self.names.lock().unwrap().contains(name)
+    }
+
+    fn get_connection_unix_user(&self, _name: &str) -> u32 {
+        self.uid.load(Ordering::Relaxed)
+    }
+}
+
+/// The server side of a peer-to-peer connection hosting fake services
+pub struct FakeBus {
+    server: Connection,
+    client: Connection,
+    names: Arc<Mutex<HashSet<String>>>,
+    uid: Arc<AtomicU32>,
+}
+
+impl FakeBus {
+    pub async fn new() -> Result<Self> {
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair()?;
+        let guid = Guid::generate();
+        let server = Builder::unix_stream(server_stream)
+            .server(guid)
+            .map_err(|e| Error::dbus(format!("Failed to set up fake bus: {}", e)))?
+            .p2p()
+            .build();
+        let client = Builder::unix_stream(client_stream).p2p().build();
+        let (server, client) = tokio::try_join!(server, client)
+            .map_err(|e| Error::dbus(format!("Failed to connect to fake bus: {}", e)))?;
+
+        let names = Arc::new(Mutex::new(HashSet::new()));
+        let uid = Arc::new(AtomicU32::new(1000));
+        server
+            .object_server()
+            .at(
+                "/org/freedesktop/DBus",
+                FakeBusDaemon {
+                    names: Arc::clone(&names),
+                    uid: Arc::clone(&uid),
+                },
+            )
+            .await
+            .map_err(|e| Error::dbus(format!("Failed to serve fake bus daemon: {}", e)))?;
+
+        Ok(Self {
+            server,
+            client,
+            names,
+            uid,
+        })
+    }
+
+    /// A client talking to the fakes on this bus
+    pub fn client(&self) -> DbusClient {
+        DbusClient::from_connection(self.client.clone())
+    }
+
+    /// Serve `object` at `path` on the client's connection, for a service
+    /// under test whose own calls must reach the fakes. zbus starts the
+    /// object server on first use and drops calls arriving before it is
+    /// listening, so this returns only once `path` answers a ping.
+    pub async fn serve<I: zbus::object_server::Interface>(
+        &self,
+        path: &str,
+        object: I,
+    ) -> Result<()> {
+        serve_at(&self.client, path, object).await?;
+        loop {
+            let ping = self.server.call_method(
+                None::<()>,
+                path,
+                Some("org.freedesktop.DBus.Peer"),
+                "Ping",
+                &(),
+            );
+            match tokio::time::timeout(Duration::from_millis(10), ping).await {
+                Ok(Ok(_)) => return Ok(()),
+                Ok(Err(e)) => return Err(Error::dbus(format!("Failed to serve {}: {}", path, e))),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    /// Call `method` of a service served with [`Self::serve`], as
+    /// the bus client [`CALLER`]. Peer-to-peer calls carry no sender, so
+    /// services checking their callers can only be tested this way.
+    pub async fn call<B, R>(
+        &self,
+        path: &str,
+        interface: &str,
+        method: &str,
+        interactive: bool,
+        body: &B,
+    ) -> zbus::Result<R>
+    where
+        B: Serialize + DynamicType,
+        R: for<'d> DynamicDeserialize<'d>,
+    {
+        let mut builder = Message::method(path, method)?
+            .interface(interface)?
+            .sender(CALLER)?;
+        if interactive {
+            builder = builder.with_flags(Flags::AllowInteractiveAuth)?;
+        }
+        let call = builder.build(body)?;
+        let serial = call.primary_header().serial_num();
+
+        let mut replies = MessageStream::from(&self.server);
+        self.server.send(&call).await?;
+        while let Some(reply) = replies.try_next().await? {
+            if reply.header().reply_serial() != Some(serial) {
+                continue;
+            }
+            return match reply.message_type() {
+                Type::MethodReturn => reply.body().deserialize(),
+                Type::Error => Err(reply.into()),
+                _ => continue,
+            };
+        }
+        Err(zbus::Error::Failure(format!("no reply to {}", method)))
+    }
+
+    /// The uid `GetConnectionUnixUser` reports, 1000 unless set
+    pub fn set_caller_uid(&self, uid: u32) {
+        self.uid.store(uid, Ordering::Relaxed);
+    }
+
+    pub async fn add_networkd(&self) -> Result<FakeNetworkd> {
+        let fake = FakeNetworkd::serve(self.server.clone()).await?;
+        self.register("org.freedesktop.network1");
+        Ok(fake)
+    }
+
+    pub async fn add_resolved(&self) -> Result<FakeResolved> {
+        let fake = FakeResolved::serve(self.server.clone()).await?;
+        self.register("org.freedesktop.resolve1");
+        Ok(fake)
+    }
+
+    pub async fn add_hostnamed(&self) -> Result<FakeHostnamed> {
+        let fake = FakeHostnamed::serve(self.server.clone()).await?;
+        self.register("org.freedesktop.hostname1");
+        Ok(fake)
+    }
+
+    pub async fn add_polkit(&self) -> Result<FakePolkit> {
+        let fake = FakePolkit::serve(self.server.clone()).await?;
+        self.register("org.freedesktop.PolicyKit1");
+        Ok(fake)
+    }
+
+    fn register(&self, name: &str) {
+        self.names.lock().unwrap().insert(name.to_string());
+    }
+}
+
+/// Serve `object` at `path` on the fake bus
+pub(crate) async fn serve_at<I: zbus::object_server::Interface>(
+    server: &Connection,
+    path: &str,
+    object: I,
+) -> Result<()> {
+    server
+        .object_server()
+        .at(path, object)
+        .await
+        .map(|_| ())
+        .map_err(|e| Error::dbus(format!("Failed to serve {}: {}", path, e)))
+}
+
+/// Emit PropertiesChanged for `path`, as the service owning it would
+pub(crate) async fn emit_properties_changed(
+    server: &Connection,
+    path: &str,
+    interface: &str,
+    changed: HashMap<&str, Value<'_>>,
+) -> Result<()> {
+    server
+        .emit_signal(
+            None::<()>,
+            path,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &(interface, changed, Vec::<&str>::new()),
+        )
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to emit PropertiesChanged: {}", e)))
+}
+
+/// sd-bus object path label escaping, as networkd and resolved use for
+/// link paths: anything but ASCII letters, and digits after the first
+/// character, becomes `_xx`
+pub(crate) fn bus_label_escape(label: &str) -> String {
+    label
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| {
+            if b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()) {
+                (b as char).to_string()
+            } else {
+                format!("_{:02x}", b)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,276 @@
+use super::{bus_label_escape, emit_properties_changed, recorder_methods, serve_at, Recorder};
+use netctl_types::{
+    Error, LinkDescription, NetworkdDescription, NetworkdLinkProperty, NetworkdLinkState, Result,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use zbus::zvariant::{OwnedObjectPath, Value};
+use zbus::{fdo, interface, Connection};
+
+const MANAGER_PATH: &str = "/org/freedesktop/network1";
+const LINK_INTERFACE: &str = "org.freedesktop.network1.Link";
+
+struct Link {
+    state: NetworkdLinkState,
+    description: LinkDescription,
+}
+
+type Links = Arc<Mutex<BTreeMap<i32, Link>>>;
+
+fn link_path(index: i32) -> String {
+    format!(
+        "{}/link/{}",
+        MANAGER_PATH,
+        bus_label_escape(&index.to_string())
+    )
+}
+
+fn object_path(index: i32) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(link_path(index)).expect("link paths are valid object paths")
+}
+
+fn no_such_link(index: i32) -> fdo::Error {
+    fdo::Error::UnknownObject(format!("Link {} not known", index))
+}
+
+/// Fake systemd-networkd. Links added with [`FakeNetworkd::add_link`] are
+/// listed by the Manager, described by `Describe`/`DescribeLink` and served
+/// as Link objects whose state can be changed, with PropertiesChanged, by
+/// [`FakeNetworkd::set_link_state`].
+pub struct FakeNetworkd {
+    server: Connection,
+    recorder: Recorder,
+    links: Links,
+}
+
+impl FakeNetworkd {
+    pub(crate) async fn serve(server: Connection) -> Result<Self> {
+        let recorder = Recorder::default();
+        let links = Links::default();
+        let manager = Manager {
+            recorder: recorder.clone(),
+            links: Arc::clone(&links),
+        };
+        serve_at(&server, MANAGER_PATH, manager).await?;
+
+        Ok(Self {
+            server,
+            recorder,
+            links,
+        })
+    }
+
+    recorder_methods!();
+
+    /// Make networkd manage link `index`, named `name`, in `state`
+    pub async fn add_link(&self, index: u32, name: &str, state: NetworkdLinkState) -> Result<()> {
+        let index = index as i32;
+        let description = LinkDescription {
+            index: index as u32,
+            name: name.to_string(),
+            ..Default::default()
+        };
+        self.links
+            .lock()
+            .unwrap()
+            .insert(index, Link { state, description });
+
+        let link = LinkObject {
+            index,
+            links: Arc::clone(&self.links),
+        };
+        serve_at(&self.server, &link_path(index), link).await
+    }
+
+    /// Replace what `DescribeLink` (and `Describe`) return for a link
+    pub fn set_link_description(&self, description: LinkDescription) -> Result<()> {
+        let index = description.index as i32;
+        let mut links = self.links.lock().unwrap();
+        let link = links
+            .get_mut(&index)
+            .ok_or_else(|| Error::InterfaceNotFound {
+                name: index.to_string(),
+            })?;
+        link.description = description;
+        Ok(())
+    }
+
+    /// Change one state of a link and announce it like networkd does
+    pub async fn set_link_state(
+        &self,
+        index: u32,
+        property: NetworkdLinkProperty,
+        value: &str,
+    ) -> Result<()> {
+        let index = index as i32;
+        {
+            let mut links = self.links.lock().unwrap();
+            let link = links
+                .get_mut(&index)
+                .ok_or_else(|| Error::InterfaceNotFound {
+                    name: index.to_string(),
+                })?;
+            *state_field(&mut link.state, property) = value.to_string();
+        }
+
+        let changed = HashMap::from([(dbus_name(property), Value::from(value))]);
+        emit_properties_changed(&self.server, &link_path(index), LINK_INTERFACE, changed).await
+    }
+}
+
+fn state_field(state: &mut NetworkdLinkState, property: NetworkdLinkProperty) -> &mut String {
+    match property {
+        NetworkdLinkProperty::OperationalState => &mut state.operational_state,
+        NetworkdLinkProperty::CarrierState => &mut state.carrier_state,
+        NetworkdLinkProperty::AddressState => &mut state.address_state,
+        NetworkdLinkProperty::Ipv4AddressState => &mut state.ipv4_address_state,
+        NetworkdLinkProperty::Ipv6AddressState => &mut state.ipv6_address_state,
+        NetworkdLinkProperty::OnlineState => &mut state.online_state,
+        NetworkdLinkProperty::AdministrativeState => &mut state.administrative_state,
+    }
+}
+
+fn dbus_name(property: NetworkdLinkProperty) -> &'static str {
+    match property {
+        NetworkdLinkProperty::OperationalState => "OperationalState",
+        NetworkdLinkProperty::CarrierState => "CarrierState",
+        NetworkdLinkProperty::AddressState => "AddressState",
+        NetworkdLinkProperty::Ipv4AddressState => "IPv4AddressState",
+        NetworkdLinkProperty::Ipv6AddressState => "IPv6AddressState",
+        NetworkdLinkProperty::OnlineState => "OnlineState",
+        NetworkdLinkProperty::AdministrativeState => "AdministrativeState",
+    }
+}
+
+struct Manager {
+    recorder: Recorder,
+    links: Links,
+}
+
+impl Manager {
+    fn description(&self, index: i32) -> fdo::Result<LinkDescription> {
+        self.links
+            .lock()
+            .unwrap()
+            .get(&index)
+            .map(|link| link.description.clone())
+            .ok_or_else(|| no_such_link(index))
+    }
+
+    fn ensure_link(&self, index: i32) -> fdo::Result<()> {
+        self.description(index).map(|_| ())
+    }
+}
+
+fn json(result: serde_json::Result<String>) -> fdo::Result<String> {
+    result.map_err(|e| fdo::Error::Failed(e.to_string()))
+}
+
+#[interface(name = "org.freedesktop.network1.Manager")]
+impl Manager {
+    fn reload(&self) -> fdo::Result<()> {
+        self.recorder.record("Reload", ())
+    }
+
+    fn reconfigure_link(&self, ifindex: i32) -> fdo::Result<()> {
+        self.recorder.record("ReconfigureLink", (ifindex,))?;
+        self.ensure_link(ifindex)
+    }
+
+    fn get_link_by_index(&self, ifindex: i32) -> fdo::Result<(String, OwnedObjectPath)> {
+        self.recorder.record("GetLinkByIndex", (ifindex,))?;
+        let name = self.description(ifindex)?.name;
+        Ok((name, object_path(ifindex)))
+    }
+
+    fn list_links(&self) -> fdo::Result<Vec<(i32, String, OwnedObjectPath)>> {
+        self.recorder.record("ListLinks", ())?;
+        Ok(self
+            .links
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(index, link)| (*index, link.description.name.clone(), object_path(*index)))
+            .collect())
+    }
+
+    fn renew_link(&self, ifindex: i32) -> fdo::Result<()> {
+        self.recorder.record("RenewLink", (ifindex,))?;
+        self.ensure_link(ifindex)
+    }
+
+    fn force_renew_link(&self, ifindex: i32) -> fdo::Result<()> {
+        self.recorder.record("ForceRenewLink", (ifindex,))?;
+        self.ensure_link(ifindex)
+    }
+
+    fn describe(&self) -> fdo::Result<String> {
+        self.recorder.record("Describe", ())?;
+        let interfaces = self
+            .links
+            .lock()
+            .unwrap()
+            .values()
+            .map(|link| link.description.clone())
+            .collect();
+        json(serde_json::to_string(&NetworkdDescription { interfaces }))
+    }
+
+    fn describe_link(&self, ifindex: i32) -> fdo::Result<String> {
+        self.recorder.record("DescribeLink", (ifindex,))?;
+        json(serde_json::to_string(&self.description(ifindex)?))
+    }
+}
+
+struct LinkObject {
+    index: i32,
+    links: Links,
+}
+
+impl LinkObject {
+    fn state(&self, property: NetworkdLinkProperty) -> String {
+        let mut links = self.links.lock().unwrap();
+        links
+            .get_mut(&self.index)
+            .map(|link| state_field(&mut link.state, property).clone())
+            .unwrap_or_default()
+    }
+}
+
+#[interface(name = "org.freedesktop.network1.Link")]
+impl LinkObject {
+    #[zbus(property)]
+    fn operational_state(&self) -> String {
+        self.state(NetworkdLinkProperty::OperationalState)
+    }
+
+    #[zbus(property)]
+    fn carrier_state(&self) -> String {
+        self.state(NetworkdLinkProperty::CarrierState)
+    }
+
+    #[zbus(property)]
+    fn address_state(&self) -> String {
+        self.state(NetworkdLinkProperty::AddressState)
+    }
+
+    #[zbus(property, name = "IPv4AddressState")]
+    fn ipv4_address_state(&self) -> String {
+        self.state(NetworkdLinkProperty::Ipv4AddressState)
+    }
+
+    #[zbus(property, name = "IPv6AddressState")]
+    fn ipv6_address_state(&self) -> String {
+        self.state(NetworkdLinkProperty::Ipv6AddressState)
+    }
+
+    #[zbus(property)]
+    fn online_state(&self) -> String {
+        self.state(NetworkdLinkProperty::OnlineState)
+    }
+
+    #[zbus(property)]
+    fn administrative_state(&self) -> String {
+        self.state(NetworkdLinkProperty::AdministrativeState)
+    }
+}
@@ -0,0 +1,64 @@
+use super::{recorder_methods, serve_at, Recorder};
+use netctl_types::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::{fdo, interface, Connection};
+
+const PATH: &str = "/org/freedesktop/PolicyKit1/Authority";
+
+/// Fake polkit authority, answering every `CheckAuthorization` the same
+/// way. Denies until told otherwise, like polkit for a caller no rule
+/// covers.
+pub struct FakePolkit {
+    recorder: Recorder,
+    authorized: Arc<AtomicBool>,
+}
+
+impl FakePolkit {
+    pub(crate) async fn serve(server: Connection) -> Result<Self> {
+        let recorder = Recorder::default();
+        let authorized = Arc::new(AtomicBool::new(false));
+        let authority = Authority {
+            recorder: recorder.clone(),
+            authorized: Arc::clone(&authorized),
+        };
+        serve_at(&server, PATH, authority).await?;
+
+        Ok(Self {
+            recorder,
+            authorized,
+        })
+    }
+
+    recorder_methods!();
+
+    pub fn set_authorized(&self, authorized: bool) {
+        self.authorized.store(authorized, Ordering::Relaxed);
+    }
+}
+
+struct Authority {
+    recorder: Recorder,
+    authorized: Arc<AtomicBool>,
+}
+
+#[interface(name = "org.freedesktop.PolicyKit1.Authority")]
+impl Authority {
+    /// Recorded as (action_id, flags); a denial is a challenge when the
+    /// caller allowed user interaction
+    fn check_authorization(
+        &self,
+        _subject: (&str, HashMap<&str, Value<'_>>),
+        action_id: &str,
+        _details: HashMap<&str, &str>,
+        flags: u32,
+        _cancellation_id: &str,
+    ) -> fdo::Result<(bool, bool, HashMap<String, OwnedValue>)> {
+        self.recorder
+            .record("CheckAuthorization", (action_id, flags))?;
+        let authorized = self.authorized.load(Ordering::Relaxed);
+        Ok((authorized, !authorized && flags & 1 != 0, HashMap::new()))
+    }
+}
@@ -0,0 +1,515 @@
+use super::{bus_label_escape, recorder_methods, serve_at, Recorder};
+use netctl_types::{DnsDomain, DnsStatistics, GlobalDnsSettings, LinkDnsSettings, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use zbus::object_server::ObjectServer;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{fdo, interface, Connection};
+
+const MANAGER_PATH: &str = "/org/freedesktop/resolve1";
+
+/// (ifindex, family, address bytes), as in the Manager's DNS property
+type WireAddresses = Vec<(i32, i32, Vec<u8>)>;
+
+/// (ifindex, class, type, wire-format RR), as ResolveRecord returns them
+type WireRecords = Vec<(i32, u16, u16, Vec<u8>)>;
+
+#[derive(Default)]
+struct State {
+    global: GlobalDnsSettings,
+    links: BTreeMap<i32, LinkDnsSettings>,
+    statistics: DnsStatistics,
+    hosts: HashMap<String, Vec<IpAddr>>,
+    names: HashMap<IpAddr, Vec<String>>,
+    records: HashMap<(String, u16), Vec<Vec<u8>>>,
+}
+
+impl State {
+    /// resolved knows every kernel link; the fake makes them up on first use
+    fn link(&mut self, index: i32) -> &mut LinkDnsSettings {
+        self.links.entry(index).or_insert_with(|| LinkDnsSettings {
+            ifindex: index as u32,
+            ..Default::default()
+        })
+    }
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+fn link_path(index: i32) -> String {
+    format!(
+        "{}/link/{}",
+        MANAGER_PATH,
+        bus_label_escape(&index.to_string())
+    )
+}
+
+fn family_and_bytes(address: &IpAddr) -> (i32, Vec<u8>) {
+    match address {
+        IpAddr::V4(v4) => (2, v4.octets().to_vec()),
+        IpAddr::V6(v6) => (10, v6.octets().to_vec()),
+    }
+}
+
+fn address_from_wire(family: i32, bytes: &[u8]) -> fdo::Result<IpAddr> {
+    let invalid = || fdo::Error::InvalidArgs(format!("invalid address for family {}", family));
+    match family {
+        2 => Ok(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).map_err(|_| invalid())?).into()),
+        10 => Ok(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).map_err(|_| invalid())?).into()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Fake systemd-resolved. Per-link settings are kept as the setters leave
+/// them and read back through the Link objects; global settings,
+/// statistics and lookup answers are whatever the test configures.
+pub struct FakeResolved {
+    recorder: Recorder,
+    state: SharedState,
+}
+
+impl FakeResolved {
+    pub(crate) async fn serve(server: Connection) -> Result<Self> {
+        let recorder = Recorder::default();
+        let state = SharedState::default();
+        let manager = Manager {
+            recorder: recorder.clone(),
+            state: Arc::clone(&state),
+        };
+        serve_at(&server, MANAGER_PATH, manager).await?;
+
+        Ok(Self { recorder, state })
+    }
+
+    recorder_methods!();
+
+    /// Settings of link `index` as the setters left them
+    pub fn link(&self, index: u32) -> Option<LinkDnsSettings> {
+        self.state
+            .lock()
+            .unwrap()
+            .links
+            .get(&(index as i32))
+            .cloned()
+    }
+
+    pub fn set_link(&self, settings: LinkDnsSettings) {
+        let index = settings.ifindex as i32;
+        self.state.lock().unwrap().links.insert(index, settings);
+    }
+
+    pub fn set_global(&self, settings: GlobalDnsSettings) {
+        self.state.lock().unwrap().global = settings;
+    }
+
+    pub fn set_statistics(&self, statistics: DnsStatistics) {
+        self.state.lock().unwrap().statistics = statistics;
+    }
+
+    /// Answer `ResolveHostname` for `name` with `addresses`
+    pub fn add_host(&self, name: &str, addresses: Vec<IpAddr>) {
+        let mut state = self.state.lock().unwrap();
+        state.hosts.insert(name.to_string(), addresses);
+    }
+
+    /// Answer `ResolveAddress` for `address` with `names`
+    pub fn add_address(&self, address: IpAddr, names: Vec<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.names.insert(address, names);
+    }
+
+    /// Answer `ResolveRecord` for `name` and record type `rtype` with
+    /// resource records in DNS wire format
+    pub fn add_records(&self, name: &str, rtype: u16, records: Vec<Vec<u8>>) {
+        let mut state = self.state.lock().unwrap();
+        state.records.insert((name.to_string(), rtype), records);
+    }
+}
+
+struct Manager {
+    recorder: Recorder,
+    state: SharedState,
+}
+
+impl Manager {
+    fn update(&self, index: i32, f: impl FnOnce(&mut LinkDnsSettings)) -> fdo::Result<()> {
+        if index <= 0 {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "invalid interface index {}",
+                index
+            )));
+        }
+        f(self.state.lock().unwrap().link(index));
+        Ok(())
+    }
+
+    /// Servers of every link plus the global ones (ifindex 0)
+    fn all_servers(&self, global: impl Fn(&GlobalDnsSettings) -> &Vec<IpAddr>) -> WireAddresses {
+        let state = self.state.lock().unwrap();
+        let links = state
+            .links
+            .iter()
+            .flat_map(|(index, link)| link.servers.iter().map(move |s| (*index, s)));
+        global(&state.global)
+            .iter()
+            .map(|s| (0, s))
+            .chain(links)
+            .map(|(index, server)| {
+                let (family, bytes) = family_and_bytes(server);
+                (index, family, bytes)
+            })
+            .collect()
+    }
+
+    fn not_found(name: &str) -> fdo::Error {
+        fdo::Error::Failed(format!("'{}' not found", name))
+    }
+}
+
+#[interface(name = "org.freedesktop.resolve1.Manager")]
+impl Manager {
+    #[zbus(name = "SetLinkDNS")]
+    fn set_link_dns(&self, ifindex: i32, addresses: Vec<(i32, Vec<u8>)>) -> fdo::Result<()> {
+        self.recorder.record("SetLinkDNS", (ifindex, &addresses))?;
+        let servers = addresses
+            .iter()
+            .map(|(family, bytes)| address_from_wire(*family, bytes))
+            .collect::<fdo::Result<Vec<_>>>()?;
+        self.update(ifindex, |link| {
+            link.current_server = servers.first().copied();
+            link.servers = servers;
+        })
+    }
+
+    fn set_link_domains(&self, ifindex: i32, domains: Vec<(String, bool)>) -> fdo::Result<()> {
+        self.recorder
+            .record("SetLinkDomains", (ifindex, &domains))?;
+        self.update(ifindex, |link| {
+            link.domains = domains
+                .into_iter()
+                .map(|(domain, routing_only)| DnsDomain {
+                    domain,
+                    routing_only,
+                })
+                .collect()
+        })
+    }
+
+    fn set_link_default_route(&self, ifindex: i32, enable: bool) -> fdo::Result<()> {
+        self.recorder
+            .record("SetLinkDefaultRoute", (ifindex, enable))?;
+        self.update(ifindex, |link| link.default_route = enable)
+    }
+
+    #[zbus(name = "SetLinkLLMNR")]
+    fn set_link_llmnr(&self, ifindex: i32, mode: &str) -> fdo::Result<()> {
+        self.recorder.record("SetLinkLLMNR", (ifindex, mode))?;
+        self.update(ifindex, |link| link.llmnr = mode.to_string())
+    }
+
+    #[zbus(name = "SetLinkMulticastDNS")]
+    fn set_link_multicast_dns(&self, ifindex: i32, mode: &str) -> fdo::Result<()> {
+        self.recorder
+            .record("SetLinkMulticastDNS", (ifindex, mode))?;
+        self.update(ifindex, |link| link.mdns = mode.to_string())
+    }
+
+    #[zbus(name = "SetLinkDNSSEC")]
+    fn set_link_dnssec(&self, ifindex: i32, mode: &str) -> fdo::Result<()> {
+        self.recorder.record("SetLinkDNSSEC", (ifindex, mode))?;
+        self.update(ifindex, |link| link.dnssec = mode.to_string())
+    }
+
+    #[zbus(name = "SetLinkDNSOverTLS")]
+    fn set_link_dns_over_tls(&self, ifindex: i32, mode: &str) -> fdo::Result<()> {
+        self.recorder.record("SetLinkDNSOverTLS", (ifindex, mode))?;
+        self.update(ifindex, |link| link.dns_over_tls = mode.to_string())
+    }
+
+    #[zbus(name = "SetLinkDNSSECNegativeTrustAnchors")]
+    fn set_link_dnssec_negative_trust_anchors(
+        &self,
+        ifindex: i32,
+        names: Vec<String>,
+    ) -> fdo::Result<()> {
+        self.recorder
+            .record("SetLinkDNSSECNegativeTrustAnchors", (ifindex, &names))?;
+        self.update(ifindex, |link| link.negative_trust_anchors = names)
+    }
+
+    async fn get_link(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+        ifindex: i32,
+    ) -> fdo::Result<OwnedObjectPath> {
+        self.recorder.record("GetLink", (ifindex,))?;
+        self.update(ifindex, |_| {})?;
+
+        let path = link_path(ifindex);
+        let link = Link {
+            index: ifindex,
+            state: Arc::clone(&self.state),
+        };
+        // Serving an already served path is a no-op
+        server.at(path.as_str(), link).await?;
+        OwnedObjectPath::try_from(path).map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    fn revert_link(&self, ifindex: i32) -> fdo::Result<()> {
+        self.recorder.record("RevertLink", (ifindex,))?;
+        self.update(ifindex, |link| {
+            *link = LinkDnsSettings {
+                ifindex: link.ifindex,
+                ..Default::default()
+            }
+        })
+    }
+
+    fn flush_caches(&self) -> fdo::Result<()> {
+        self.recorder.record("FlushCaches", ())
+    }
+
+    fn reset_statistics(&self) -> fdo::Result<()> {
+        self.recorder.record("ResetStatistics", ())?;
+        self.state.lock().unwrap().statistics = DnsStatistics::default();
+        Ok(())
+    }
+
+    fn resolve_hostname(
+        &self,
+        ifindex: i32,
+        name: &str,
+        family: i32,
+        flags: u64,
+    ) -> fdo::Result<(WireAddresses, String, u64)> {
+        self.recorder
+            .record("ResolveHostname", (ifindex, name, family, flags))?;
+        let state = self.state.lock().unwrap();
+        let addresses = state.hosts.get(name).ok_or_else(|| Self::not_found(name))?;
+        let addresses = addresses
+            .iter()
+            .map(|address| {
+                let (family, bytes) = family_and_bytes(address);
+                (ifindex, family, bytes)
+            })
+            .collect();
+        Ok((addresses, name.to_string(), 0))
+    }
+
+    fn resolve_address(
+        &self,
+        ifindex: i32,
+        family: i32,
+        address: Vec<u8>,
+        flags: u64,
+    ) -> fdo::Result<(Vec<(i32, String)>, u64)> {
+        self.recorder
+            .record("ResolveAddress", (ifindex, family, &address, flags))?;
+        let address = address_from_wire(family, &address)?;
+        let state = self.state.lock().unwrap();
+        let names = state
+            .names
+            .get(&address)
+            .ok_or_else(|| Self::not_found(&address.to_string()))?;
+        Ok((
+            names.iter().map(|name| (ifindex, name.clone())).collect(),
+            0,
+        ))
+    }
+
+    fn resolve_record(
+        &self,
+        ifindex: i32,
+        name: &str,
+        class: u16,
+        rtype: u16,
+        flags: u64,
+    ) -> fdo::Result<(WireRecords, u64)> {
+        self.recorder
+            .record("ResolveRecord", (ifindex, name, class, rtype, flags))?;
+        let state = self.state.lock().unwrap();
+        let records = state
+            .records
+            .get(&(name.to_string(), rtype))
+            .ok_or_else(|| Self::not_found(name))?;
+        Ok((
+            records
+                .iter()
+                .map(|wire| (ifindex, class, rtype, wire.clone()))
+                .collect(),
+            0,
+        ))
+    }
+
+    #[zbus(property, name = "DNS")]
+    fn dns(&self) -> WireAddresses {
+        self.all_servers(|global| &global.servers)
+    }
+
+    #[zbus(property, name = "FallbackDNS")]
+    fn fallback_dns(&self) -> WireAddresses {
+        let state = self.state.lock().unwrap();
+        state
+            .global
+            .fallback_servers
+            .iter()
+            .map(|server| {
+                let (family, bytes) = family_and_bytes(server);
+                (0, family, bytes)
+            })
+            .collect()
+    }
+
+    #[zbus(property, name = "CurrentDNSServer")]
+    fn current_dns_server(&self) -> (i32, i32, Vec<u8>) {
+        let state = self.state.lock().unwrap();
+        match &state.global.current_server {
+            Some(server) => {
+                let (family, bytes) = family_and_bytes(server);
+                (0, family, bytes)
+            }
+            None => (0, 0, Vec::new()),
+        }
+    }
+
+    #[zbus(property)]
+    fn domains(&self) -> Vec<(i32, String, bool)> {
+        let state = self.state.lock().unwrap();
+        let links = state.links.iter().flat_map(|(index, link)| {
+            link.domains
+                .iter()
+                .map(move |d| (*index, d.domain.clone(), d.routing_only))
+        });
+        state
+            .global
+            .domains
+            .iter()
+            .map(|d| (0, d.domain.clone(), d.routing_only))
+            .chain(links)
+            .collect()
+    }
+
+    #[zbus(property, name = "LLMNR")]
+    fn llmnr(&self) -> String {
+        self.state.lock().unwrap().global.llmnr.clone()
+    }
+
+    #[zbus(property, name = "MulticastDNS")]
+    fn multicast_dns(&self) -> String {
+        self.state.lock().unwrap().global.mdns.clone()
+    }
+
+    #[zbus(property, name = "DNSSEC")]
+    fn dnssec(&self) -> String {
+        self.state.lock().unwrap().global.dnssec.clone()
+    }
+
+    #[zbus(property, name = "DNSOverTLS")]
+    fn dns_over_tls(&self) -> String {
+        self.state.lock().unwrap().global.dns_over_tls.clone()
+    }
+
+    #[zbus(property)]
+    fn resolv_conf_mode(&self) -> String {
+        self.state.lock().unwrap().global.resolv_conf_mode.clone()
+    }
+
+    #[zbus(property)]
+    fn transaction_statistics(&self) -> (u64, u64) {
+        let s = self.state.lock().unwrap().statistics;
+        (s.current_transactions, s.total_transactions)
+    }
+
+    #[zbus(property)]
+    fn cache_statistics(&self) -> (u64, u64, u64) {
+        let s = self.state.lock().unwrap().statistics;
+        (s.cache_size, s.cache_hits, s.cache_misses)
+    }
+
+    #[zbus(property, name = "DNSSECStatistics")]
+    fn dnssec_statistics(&self) -> (u64, u64, u64, u64) {
+        let s = self.state.lock().unwrap().statistics;
+        (
+            s.dnssec_secure,
+            s.dnssec_insecure,
+            s.dnssec_bogus,
+            s.dnssec_indeterminate,
+        )
+    }
+}
+
+struct Link {
+    index: i32,
+    state: SharedState,
+}
+
+impl Link {
+    fn settings(&self) -> LinkDnsSettings {
+        self.state.lock().unwrap().link(self.index).clone()
+    }
+}
+
+#[interface(name = "org.freedesktop.resolve1.Link")]
+impl Link {
+    #[zbus(property, name = "DNS")]
+    fn dns(&self) -> Vec<(i32, Vec<u8>)> {
+        self.settings()
+            .servers
+            .iter()
+            .map(family_and_bytes)
+            .collect()
+    }
+
+    #[zbus(property, name = "CurrentDNSServer")]
+    fn current_dns_server(&self) -> (i32, Vec<u8>) {
+        match self.settings().current_server {
+            Some(server) => family_and_bytes(&server),
+            None => (0, Vec::new()),
+        }
+    }
+
+    #[zbus(property)]
+    fn domains(&self) -> Vec<(String, bool)> {
+        self.settings()
+            .domains
+            .into_iter()
+            .map(|d| (d.domain, d.routing_only))
+            .collect()
+    }
+
+    #[zbus(property)]
+    fn default_route(&self) -> bool {
+        self.settings().default_route
+    }
+
+    #[zbus(property, name = "LLMNR")]
+    fn llmnr(&self) -> String {
+        self.settings().llmnr
+    }
+
+    #[zbus(property, name = "MulticastDNS")]
+    fn multicast_dns(&self) -> String {
+        self.settings().mdns
+    }
+
+    #[zbus(property, name = "DNSSEC")]
+    fn dnssec(&self) -> String {
+        self.settings().dnssec
+    }
+
+    #[zbus(property, name = "DNSOverTLS")]
+    fn dns_over_tls(&self) -> String {
+        self.settings().dns_over_tls
+    }
+
+    #[zbus(property, name = "DNSSECNegativeTrustAnchors")]
+    fn dnssec_negative_trust_anchors(&self) -> Vec<String> {
+        self.settings().negative_trust_anchors
+    }
+
+    #[zbus(property, name = "DNSSECSupported")]
+    fn dnssec_supported(&self) -> bool {
+        self.settings().dnssec_supported
+    }
+}
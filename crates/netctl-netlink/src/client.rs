@@ -1,3 +1,4 @@
+use genetlink::GenetlinkHandle;
 use netctl_types::Result;
 use rtnetlink::Handle;
 use std::sync::Arc;
@@ -5,24 +6,31 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct NetlinkClient {
     handle: Arc<Handle>,
+    genl: GenetlinkHandle,
 }
 
 impl NetlinkClient {
     pub fn new() -> Result<(Self, impl std::future::Future<Output = ()>)> {
         let (conn, handle, _) = rtnetlink::new_connection()
             .map_err(|e| netctl_types::Error::netlink(format!("{}", e)))?;
+        let (genl_conn, genl, _) = genetlink::new_connection()
+            .map_err(|e| netctl_types::Error::netlink(format!("{}", e)))?;
 
         Ok((
             Self {
                 handle: Arc::new(handle),
+                genl,
+            },
+            async move {
+                futures::join!(conn, genl_conn);
             },
-            conn,
         ))
     }
 
     pub fn cloneable_handle(&self) -> NetlinkHandle {
         NetlinkHandle {
             handle: Arc::clone(&self.handle),
+            genl: self.genl.clone(),
         }
     }
 }
@@ -30,10 +38,16 @@ impl NetlinkClient {
 #[derive(Clone)]
 pub struct NetlinkHandle {
     handle: Arc<Handle>,
+    genl: GenetlinkHandle,
 }
 
 impl NetlinkHandle {
     pub fn handle(&self) -> &Handle {
         &self.handle
     }
+
+    /// Generic netlink handle, used for families such as `mptcp_pm`
+    pub fn genl_handle(&self) -> GenetlinkHandle {
+        self.genl.clone()
+    }
 }
@@ -0,0 +1,100 @@
+//! Kernel link, address and route notifications from the rtnetlink multicast
+//! groups.
+
+use crate::ops::address::address_of;
+use crate::ops::link::parse_link_message;
+use crate::ops::route::parse_route_message;
+use futures::stream::{BoxStream, StreamExt};
+use netctl_types::{Error, NetworkEvent, NetworkEventKind, Result};
+use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+use netlink_packet_route::link::LinkAttribute;
+use netlink_packet_route::RouteNetlinkMessage;
+use netlink_sys::{AsyncSocket, SocketAddr};
+use rtnetlink::constants::{
+    RTMGRP_IPV4_IFADDR, RTMGRP_IPV4_ROUTE, RTMGRP_IPV6_IFADDR, RTMGRP_IPV6_ROUTE, RTMGRP_LINK,
+};
+use tracing::{debug, info};
+
+/// Subscribe to link, address and route changes. Each subscription opens its
+/// own netlink socket bound to the multicast groups; the stream ends when the
+/// socket closes.
+pub fn kernel_events() -> Result<BoxStream<'static, NetworkEvent>> {
+    info!("subscribing to kernel link, address and route events");
+
+    let (mut conn, _handle, messages) =
+        rtnetlink::new_connection().map_err(|e| Error::netlink(format!("{}", e)))?;
+    let groups = RTMGRP_LINK
+        | RTMGRP_IPV4_IFADDR
+        | RTMGRP_IPV6_IFADDR
+        | RTMGRP_IPV4_ROUTE
+        | RTMGRP_IPV6_ROUTE;
+    conn.socket_mut()
+        .socket_mut()
+        .bind(&SocketAddr::new(0, groups))
+        .map_err(|e| Error::netlink(format!("Failed to join rtnetlink groups: {}", e)))?;
+
+    // The connection keeps running while the receiver below is alive, even
+    // though the request handle is dropped
+    tokio::spawn(conn);
+
+    Ok(messages
+        .filter_map(|(message, _)| async move { parse_event(message) })
+        .boxed())
+}
+
+fn parse_event(message: NetlinkMessage<RouteNetlinkMessage>) -> Option<NetworkEvent> {
+    let NetlinkPayload::InnerMessage(message) = message.payload else {
+        return None;
+    };
+
+    let kind = match message {
+        RouteNetlinkMessage::NewLink(link) => {
+            let info = parse_link_message(&link);
+            NetworkEventKind::LinkChanged {
+                index: info.index,
+                name: info.name,
+                state: info.state,
+                mtu: info.mtu,
+            }
+        }
+        RouteNetlinkMessage::DelLink(link) => NetworkEventKind::LinkRemoved {
+            index: link.header.index,
+            name: link
+                .attributes
+                .iter()
+                .find_map(|attr| match attr {
+                    LinkAttribute::IfName(name) => Some(name.clone()),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+        },
+        RouteNetlinkMessage::NewAddress(addr) => NetworkEventKind::AddressAdded {
+            index: addr.header.index,
+            address: address_of(&addr)?,
+        },
+        RouteNetlinkMessage::DelAddress(addr) => NetworkEventKind::AddressRemoved {
+            index: addr.header.index,
+            address: address_of(&addr)?,
+        },
+        RouteNetlinkMessage::NewRoute(route) => {
+            let route = parse_route_message(&route)?;
+            NetworkEventKind::RouteAdded {
+                index: route.index.unwrap_or_default(),
+                destination: route.destination?,
+                gateway: route.gateway,
+            }
+        }
+        RouteNetlinkMessage::DelRoute(route) => {
+            let route = parse_route_message(&route)?;
+            NetworkEventKind::RouteRemoved {
+                index: route.index.unwrap_or_default(),
+                destination: route.destination?,
+                gateway: route.gateway,
+            }
+        }
+        _ => return None,
+    };
+
+    debug!(event = %kind, "kernel event");
+    Some(NetworkEvent::now(kind))
+}
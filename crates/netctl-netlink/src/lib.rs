@@ -1,5 +1,8 @@
 pub mod client;
+mod events;
+mod mptcp;
 pub mod ops;
 
 pub use client::{NetlinkClient, NetlinkHandle};
-pub use ops::{AddressOps, LinkOps};
+pub use events::kernel_events;
+pub use ops::{AddressOps, LinkOps, MptcpOps, RouteOps, SriovOps};
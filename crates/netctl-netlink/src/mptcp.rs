@@ -0,0 +1,352 @@
+//! Message definitions for the `mptcp_pm` generic netlink family
+//! (include/uapi/linux/mptcp_pm.h).
+
+use netlink_packet_generic::{GenlFamily, GenlHeader};
+use netlink_packet_utils::nla::{DefaultNla, Nla, NlaBuffer, NlasIterator, NLA_F_NESTED};
+use netlink_packet_utils::parsers::{parse_i32, parse_ip, parse_u16, parse_u32, parse_u8};
+use netlink_packet_utils::{DecodeError, Emitable, Parseable, ParseableParametrized};
+use nix::libc::{AF_INET, AF_INET6};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const MPTCP_PM_NAME: &str = "mptcp_pm";
+const MPTCP_PM_VER: u8 = 1;
+
+const MPTCP_PM_CMD_ADD_ADDR: u8 = 1;
+const MPTCP_PM_CMD_DEL_ADDR: u8 = 2;
+const MPTCP_PM_CMD_GET_ADDR: u8 = 3;
+const MPTCP_PM_CMD_SET_LIMITS: u8 = 5;
+const MPTCP_PM_CMD_GET_LIMITS: u8 = 6;
+
+const MPTCP_PM_ATTR_ADDR: u16 = 1;
+const MPTCP_PM_ATTR_RCV_ADD_ADDRS: u16 = 2;
+const MPTCP_PM_ATTR_SUBFLOWS: u16 = 3;
+
+const MPTCP_PM_ADDR_ATTR_FAMILY: u16 = 1;
+const MPTCP_PM_ADDR_ATTR_ID: u16 = 2;
+const MPTCP_PM_ADDR_ATTR_ADDR4: u16 = 3;
+const MPTCP_PM_ADDR_ATTR_ADDR6: u16 = 4;
+const MPTCP_PM_ADDR_ATTR_PORT: u16 = 5;
+const MPTCP_PM_ADDR_ATTR_FLAGS: u16 = 6;
+const MPTCP_PM_ADDR_ATTR_IF_IDX: u16 = 7;
+
+pub(crate) const MPTCP_PM_ADDR_FLAG_SIGNAL: u32 = 1 << 0;
+pub(crate) const MPTCP_PM_ADDR_FLAG_SUBFLOW: u32 = 1 << 1;
+pub(crate) const MPTCP_PM_ADDR_FLAG_BACKUP: u32 = 1 << 2;
+pub(crate) const MPTCP_PM_ADDR_FLAG_FULLMESH: u32 = 1 << 3;
+pub(crate) const MPTCP_PM_ADDR_FLAG_IMPLICIT: u32 = 1 << 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MptcpPmCmd {
+    AddAddr,
+    DelAddr,
+    GetAddr,
+    SetLimits,
+    GetLimits,
+}
+
+impl From<MptcpPmCmd> for u8 {
+    fn from(cmd: MptcpPmCmd) -> u8 {
+        match cmd {
+            MptcpPmCmd::AddAddr => MPTCP_PM_CMD_ADD_ADDR,
+            MptcpPmCmd::DelAddr => MPTCP_PM_CMD_DEL_ADDR,
+            MptcpPmCmd::GetAddr => MPTCP_PM_CMD_GET_ADDR,
+            MptcpPmCmd::SetLimits => MPTCP_PM_CMD_SET_LIMITS,
+            MptcpPmCmd::GetLimits => MPTCP_PM_CMD_GET_LIMITS,
+        }
+    }
+}
+
+impl TryFrom<u8> for MptcpPmCmd {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, DecodeError> {
+        Ok(match value {
+            MPTCP_PM_CMD_ADD_ADDR => Self::AddAddr,
+            MPTCP_PM_CMD_DEL_ADDR => Self::DelAddr,
+            MPTCP_PM_CMD_GET_ADDR => Self::GetAddr,
+            MPTCP_PM_CMD_SET_LIMITS => Self::SetLimits,
+            MPTCP_PM_CMD_GET_LIMITS => Self::GetLimits,
+            cmd => return Err(format!("unknown mptcp_pm command {}", cmd).into()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MptcpPmAddrAttr {
+    Family(u16),
+    Id(u8),
+    Addr4(Ipv4Addr),
+    Addr6(Ipv6Addr),
+    Port(u16),
+    Flags(u32),
+    IfIndex(i32),
+    Other(DefaultNla),
+}
+
+impl MptcpPmAddrAttr {
+    pub(crate) fn family(address: IpAddr) -> Self {
+        match address {
+            IpAddr::V4(_) => Self::Family(AF_INET as u16),
+            IpAddr::V6(_) => Self::Family(AF_INET6 as u16),
+        }
+    }
+
+    pub(crate) fn address(address: IpAddr) -> Self {
+        match address {
+            IpAddr::V4(v4) => Self::Addr4(v4),
+            IpAddr::V6(v6) => Self::Addr6(v6),
+        }
+    }
+}
+
+impl Nla for MptcpPmAddrAttr {
+    fn value_len(&self) -> usize {
+        match self {
+            Self::Family(_) | Self::Port(_) => 2,
+            Self::Id(_) => 1,
+            Self::Addr4(_) | Self::Flags(_) | Self::IfIndex(_) => 4,
+            Self::Addr6(_) => 16,
+            Self::Other(attr) => attr.value_len(),
+        }
+    }
+
+    fn kind(&self) -> u16 {
+        match self {
+            Self::Family(_) => MPTCP_PM_ADDR_ATTR_FAMILY,
+            Self::Id(_) => MPTCP_PM_ADDR_ATTR_ID,
+            Self::Addr4(_) => MPTCP_PM_ADDR_ATTR_ADDR4,
+            Self::Addr6(_) => MPTCP_PM_ADDR_ATTR_ADDR6,
+            Self::Port(_) => MPTCP_PM_ADDR_ATTR_PORT,
+            Self::Flags(_) => MPTCP_PM_ADDR_ATTR_FLAGS,
+            Self::IfIndex(_) => MPTCP_PM_ADDR_ATTR_IF_IDX,
+            Self::Other(attr) => attr.kind(),
+        }
+    }
+
+    fn emit_value(&self, buffer: &mut [u8]) {
+        match self {
+            // Family and port are host byte order; the addresses are not
+            Self::Family(value) | Self::Port(value) => buffer.copy_from_slice(&value.to_ne_bytes()),
+            Self::Id(value) => buffer[0] = *value,
+            Self::Addr4(addr) => buffer.copy_from_slice(&addr.octets()),
+            Self::Addr6(addr) => buffer.copy_from_slice(&addr.octets()),
+            Self::Flags(value) => buffer.copy_from_slice(&value.to_ne_bytes()),
+            Self::IfIndex(value) => buffer.copy_from_slice(&value.to_ne_bytes()),
+            Self::Other(attr) => attr.emit_value(buffer),
+        }
+    }
+}
+
+impl<'a, T: AsRef<[u8]> + ?Sized> Parseable<NlaBuffer<&'a T>> for MptcpPmAddrAttr {
+    fn parse(buf: &NlaBuffer<&'a T>) -> Result<Self, DecodeError> {
+        let payload = buf.value();
+        Ok(match buf.kind() {
+            MPTCP_PM_ADDR_ATTR_FAMILY => Self::Family(parse_u16(payload)?),
+            MPTCP_PM_ADDR_ATTR_ID => Self::Id(parse_u8(payload)?),
+            MPTCP_PM_ADDR_ATTR_ADDR4 | MPTCP_PM_ADDR_ATTR_ADDR6 => match parse_ip(payload)? {
+                IpAddr::V4(v4) => Self::Addr4(v4),
+                IpAddr::V6(v6) => Self::Addr6(v6),
+            },
+            MPTCP_PM_ADDR_ATTR_PORT => Self::Port(parse_u16(payload)?),
+            MPTCP_PM_ADDR_ATTR_FLAGS => Self::Flags(parse_u32(payload)?),
+            MPTCP_PM_ADDR_ATTR_IF_IDX => Self::IfIndex(parse_i32(payload)?),
+            _ => Self::Other(DefaultNla::parse(buf)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MptcpPmAttr {
+    Addr(Vec<MptcpPmAddrAttr>),
+    RcvAddAddrs(u32),
+    Subflows(u32),
+    Other(DefaultNla),
+}
+
+impl Nla for MptcpPmAttr {
+    fn value_len(&self) -> usize {
+        match self {
+            Self::Addr(attrs) => attrs.as_slice().buffer_len(),
+            Self::RcvAddAddrs(_) | Self::Subflows(_) => 4,
+            Self::Other(attr) => attr.value_len(),
+        }
+    }
+
+    fn kind(&self) -> u16 {
+        match self {
+            Self::Addr(_) => MPTCP_PM_ATTR_ADDR | NLA_F_NESTED,
+            Self::RcvAddAddrs(_) => MPTCP_PM_ATTR_RCV_ADD_ADDRS,
+            Self::Subflows(_) => MPTCP_PM_ATTR_SUBFLOWS,
+            Self::Other(attr) => attr.kind(),
+        }
+    }
+
+    fn emit_value(&self, buffer: &mut [u8]) {
+        match self {
+            Self::Addr(attrs) => attrs.as_slice().emit(buffer),
+            Self::RcvAddAddrs(value) | Self::Subflows(value) => {
+                buffer.copy_from_slice(&value.to_ne_bytes())
+            }
+            Self::Other(attr) => attr.emit_value(buffer),
+        }
+    }
+}
+
+impl<'a, T: AsRef<[u8]> + ?Sized> Parseable<NlaBuffer<&'a T>> for MptcpPmAttr {
+    fn parse(buf: &NlaBuffer<&'a T>) -> Result<Self, DecodeError> {
+        let payload = buf.value();
+        Ok(match buf.kind() {
+            MPTCP_PM_ATTR_ADDR => {
+                let mut attrs = Vec::new();
+                for nla in NlasIterator::new(payload) {
+                    attrs.push(MptcpPmAddrAttr::parse(&nla?)?);
+                }
+                Self::Addr(attrs)
+            }
+            MPTCP_PM_ATTR_RCV_ADD_ADDRS => Self::RcvAddAddrs(parse_u32(payload)?),
+            MPTCP_PM_ATTR_SUBFLOWS => Self::Subflows(parse_u32(payload)?),
+            _ => Self::Other(DefaultNla::parse(buf)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MptcpPmMessage {
+    pub cmd: MptcpPmCmd,
+    pub attributes: Vec<MptcpPmAttr>,
+}
+
+impl MptcpPmMessage {
+    pub(crate) fn new(cmd: MptcpPmCmd, attributes: Vec<MptcpPmAttr>) -> Self {
+        Self { cmd, attributes }
+    }
+}
+
+impl GenlFamily for MptcpPmMessage {
+    fn family_name() -> &'static str {
+        MPTCP_PM_NAME
+    }
+
+    fn command(&self) -> u8 {
+        self.cmd.into()
+    }
+
+    fn version(&self) -> u8 {
+        MPTCP_PM_VER
+    }
+}
+
+impl Emitable for MptcpPmMessage {
+    fn buffer_len(&self) -> usize {
+        self.attributes.as_slice().buffer_len()
+    }
+
+    fn emit(&self, buffer: &mut [u8]) {
+        self.attributes.as_slice().emit(buffer)
+    }
+}
+
+impl ParseableParametrized<[u8], GenlHeader> for MptcpPmMessage {
+    fn parse_with_param(buf: &[u8], header: GenlHeader) -> Result<Self, DecodeError> {
+        let mut attributes = Vec::new();
+        for nla in NlasIterator::new(buf) {
+            attributes.push(MptcpPmAttr::parse(&nla?)?);
+        }
+        Ok(Self {
+            cmd: header.cmd.try_into()?,
+            attributes,
+        })
+    }
+}
+
+/// Serialize `message` as a request to the resolved family and parse it back
+#[cfg(test)]
+pub(crate) fn roundtrip(message: MptcpPmMessage) -> MptcpPmMessage {
+    use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+    use netlink_packet_generic::GenlMessage;
+
+    let mut genl = GenlMessage::from_payload(message);
+    genl.set_resolved_family_id(0x1d);
+    let mut packet = NetlinkMessage::from(genl);
+    packet.finalize();
+    let mut buf = vec![0; packet.buffer_len()];
+    packet.serialize(&mut buf);
+
+    match NetlinkMessage::<GenlMessage<MptcpPmMessage>>::deserialize(&buf)
+        .unwrap()
+        .payload
+    {
+        NetlinkPayload::InnerMessage(genl) => genl.payload,
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_addr_roundtrip() {
+        for address in ["192.0.2.1", "2001:db8::1"] {
+            let address: IpAddr = address.parse().unwrap();
+            let message = MptcpPmMessage::new(
+                MptcpPmCmd::AddAddr,
+                vec![MptcpPmAttr::Addr(vec![
+                    MptcpPmAddrAttr::family(address),
+                    MptcpPmAddrAttr::address(address),
+                    MptcpPmAddrAttr::Id(5),
+                    MptcpPmAddrAttr::Port(8080),
+                    MptcpPmAddrAttr::Flags(MPTCP_PM_ADDR_FLAG_SIGNAL),
+                    MptcpPmAddrAttr::IfIndex(2),
+                ])],
+            );
+            assert_eq!(roundtrip(message.clone()), message);
+        }
+    }
+
+    #[test]
+    fn test_addr_is_nested() {
+        let message = MptcpPmMessage::new(
+            MptcpPmCmd::DelAddr,
+            vec![MptcpPmAttr::Addr(vec![MptcpPmAddrAttr::Id(1)])],
+        );
+        let mut buf = vec![0; message.buffer_len()];
+        message.emit(&mut buf);
+
+        // The kernel rejects MPTCP_PM_ATTR_ADDR without NLA_F_NESTED
+        let nla = NlaBuffer::new(&buf);
+        assert_eq!(nla.kind(), MPTCP_PM_ATTR_ADDR);
+        assert!(nla.nested_flag());
+    }
+
+    #[test]
+    fn test_port_and_address_byte_order() {
+        let attr = MptcpPmAddrAttr::Port(8080);
+        let mut buf = vec![0; attr.buffer_len()];
+        attr.emit(&mut buf);
+        assert_eq!(NlaBuffer::new(&buf).value(), &8080u16.to_ne_bytes());
+
+        let attr = MptcpPmAddrAttr::address("192.0.2.1".parse().unwrap());
+        let mut buf = vec![0; attr.buffer_len()];
+        attr.emit(&mut buf);
+        assert_eq!(NlaBuffer::new(&buf).value(), &[192, 0, 2, 1]);
+    }
+
+    #[test]
+    fn test_limits_roundtrip() {
+        let message = MptcpPmMessage::new(
+            MptcpPmCmd::SetLimits,
+            vec![MptcpPmAttr::RcvAddAddrs(4), MptcpPmAttr::Subflows(2)],
+        );
+        assert_eq!(roundtrip(message.clone()), message);
+
+        let message = MptcpPmMessage::new(MptcpPmCmd::GetLimits, vec![]);
+        assert_eq!(roundtrip(message.clone()), message);
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        assert!(MptcpPmCmd::try_from(4).is_err());
+        assert_eq!(MptcpPmCmd::try_from(6).unwrap(), MptcpPmCmd::GetLimits);
+    }
+}
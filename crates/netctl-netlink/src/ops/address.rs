@@ -1,22 +1,53 @@
 use crate::NetlinkHandle;
 use async_trait::async_trait;
-use netctl_types::{Error, IpNetwork, Result};
-use tracing::{info, instrument};
+use netctl_types::{AddressFlag, AddressScope, AddressSpec, Error, IpNetwork, Result};
+use netlink_packet_route::address::{self as nl, AddressAttribute, AddressMessage, CacheInfo};
+use tracing::{debug, info, instrument};
+
+/// Lifetime value the kernel treats as "forever" (INFINITY_LIFE_TIME)
+const INFINITY_LIFE_TIME: u32 = u32::MAX;
 
 #[async_trait]
 pub trait AddressOps {
-    async fn add_address(&self, index: u32, network: IpNetwork) -> Result<()>;
+    /// Every configured address, with the index of its link
+    async fn list_addresses(&self) -> Result<Vec<(u32, IpNetwork)>>;
+    async fn add_address(&self, index: u32, spec: &AddressSpec) -> Result<()>;
     async fn delete_address(&self, index: u32, network: IpNetwork) -> Result<()>;
 }
 
 #[async_trait]
 impl AddressOps for NetlinkHandle {
     #[instrument(skip(self))]
-    async fn add_address(&self, index: u32, network: IpNetwork) -> Result<()> {
-        info!(%network, "adding address");
-        self.handle()
-            .address()
-            .add(index, network.addr, network.prefix_len)
+    async fn list_addresses(&self) -> Result<Vec<(u32, IpNetwork)>> {
+        info!("listing addresses");
+        use futures::TryStreamExt;
+
+        let mut messages = self.handle().address().get().execute();
+        let mut addresses = Vec::new();
+        while let Some(message) = messages
+            .try_next()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))?
+        {
+            if let Some(address) = address_of(&message) {
+                addresses.push((message.header.index, address));
+            }
+        }
+
+        debug!(count = addresses.len(), "addresses listed");
+        Ok(addresses)
+    }
+
+    #[instrument(skip(self, spec), fields(network = %spec.network))]
+    async fn add_address(&self, index: u32, spec: &AddressSpec) -> Result<()> {
+        info!(network = %spec.network, "adding address");
+        let mut request =
+            self.handle()
+                .address()
+                .add(index, spec.network.addr, spec.network.prefix_len);
+        apply_spec(request.message_mut(), spec);
+
+        request
             .execute()
             .await
             .map_err(|e| Error::netlink(format!("{}", e)))
@@ -32,3 +63,203 @@ impl AddressOps for NetlinkHandle {
         ))
     }
 }
+
+/// Set the attributes of `spec` on an RTM_NEWADDR message that already
+/// carries the address itself, as rtnetlink's add request does.
+fn apply_spec(message: &mut AddressMessage, spec: &AddressSpec) {
+    // With a peer, IFA_ADDRESS carries the remote end and IFA_LOCAL the
+    // local address, and no broadcast is derived.
+    if let Some(peer) = spec.peer {
+        message.attributes.retain(|attr| {
+            !matches!(
+                attr,
+                AddressAttribute::Address(_) | AddressAttribute::Broadcast(_)
+            )
+        });
+        message.attributes.push(AddressAttribute::Address(peer));
+    }
+
+    if let Some(broadcast) = spec.broadcast {
+        message
+            .attributes
+            .retain(|attr| !matches!(attr, AddressAttribute::Broadcast(_)));
+        message
+            .attributes
+            .push(AddressAttribute::Broadcast(broadcast));
+    }
+
+    if let Some(ref label) = spec.label {
+        message
+            .attributes
+            .push(AddressAttribute::Label(label.clone()));
+    }
+
+    if let Some(scope) = spec.scope {
+        message.header.scope = match scope {
+            AddressScope::Global => nl::AddressScope::Universe,
+            AddressScope::Site => nl::AddressScope::Site,
+            AddressScope::Link => nl::AddressScope::Link,
+            AddressScope::Host => nl::AddressScope::Host,
+        };
+    }
+
+    if spec.valid_lifetime.is_some() || spec.preferred_lifetime.is_some() {
+        let valid = spec.valid_lifetime.unwrap_or(INFINITY_LIFE_TIME);
+        let mut cache_info = CacheInfo::default();
+        cache_info.ifa_valid = valid;
+        cache_info.ifa_preferred = spec.preferred_lifetime.unwrap_or(valid);
+        message
+            .attributes
+            .push(AddressAttribute::CacheInfo(cache_info));
+    }
+
+    if !spec.flags.is_empty() {
+        let flags = spec
+            .flags
+            .iter()
+            .map(|flag| match flag {
+                AddressFlag::NoPrefixRoute => nl::AddressFlag::Noprefixroute,
+                AddressFlag::NoDad => nl::AddressFlag::Nodad,
+                AddressFlag::Home => nl::AddressFlag::Homeaddress,
+                AddressFlag::MngTmpAddr => nl::AddressFlag::Managetempaddr,
+                AddressFlag::Optimistic => nl::AddressFlag::Optimistic,
+            })
+            .collect();
+        message.attributes.push(AddressAttribute::Flags(flags));
+    }
+}
+
+/// The local address of an RTM_NEWADDR/RTM_DELADDR message. IFA_LOCAL wins
+/// over IFA_ADDRESS, which holds the peer on point-to-point links.
+pub(crate) fn address_of(message: &AddressMessage) -> Option<IpNetwork> {
+    let local = message.attributes.iter().find_map(|attr| match attr {
+        AddressAttribute::Local(addr) => Some(*addr),
+        _ => None,
+    });
+    let address = message.attributes.iter().find_map(|attr| match attr {
+        AddressAttribute::Address(addr) => Some(*addr),
+        _ => None,
+    });
+
+    IpNetwork::new(local.or(address)?, message.header.prefix_len).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::roundtrip;
+    use netlink_packet_route::{AddressFamily, RouteNetlinkMessage};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// RTM_NEWADDR for `spec`, starting from the attributes rtnetlink's add
+    /// request sets for an IPv4 address
+    fn new_address(spec: &AddressSpec) -> AddressMessage {
+        let IpAddr::V4(addr) = spec.network.addr else {
+            panic!("expected an IPv4 address");
+        };
+        let mut message = AddressMessage::default();
+        message.header.family = AddressFamily::Inet;
+        message.header.prefix_len = spec.network.prefix_len;
+        message.header.index = 3;
+        message
+            .attributes
+            .push(AddressAttribute::Address(spec.network.addr));
+        message
+            .attributes
+            .push(AddressAttribute::Local(spec.network.addr));
+        let host_bits = u32::MAX.checked_shr(spec.network.prefix_len.into());
+        let broadcast = u32::from(addr) | host_bits.unwrap_or(0);
+        message
+            .attributes
+            .push(AddressAttribute::Broadcast(Ipv4Addr::from(broadcast)));
+        apply_spec(&mut message, spec);
+
+        match roundtrip(RouteNetlinkMessage::NewAddress(message.clone())) {
+            RouteNetlinkMessage::NewAddress(parsed) => {
+                assert_eq!(parsed, message);
+                parsed
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    fn attr<T>(message: &AddressMessage, f: impl Fn(&AddressAttribute) -> Option<T>) -> Option<T> {
+        message.attributes.iter().find_map(f)
+    }
+
+    #[test]
+    fn test_plain_address() {
+        let spec = AddressSpec::new("192.168.1.10/24".parse().unwrap());
+        let message = new_address(&spec);
+
+        assert_eq!(address_of(&message), Some(spec.network));
+        assert_eq!(message.header.scope, nl::AddressScope::Universe);
+        let broadcast = attr(&message, |a| match a {
+            AddressAttribute::Broadcast(b) => Some(*b),
+            _ => None,
+        });
+        assert_eq!(broadcast, Some(Ipv4Addr::new(192, 168, 1, 255)));
+    }
+
+    #[test]
+    fn test_peer_address() {
+        let mut spec = AddressSpec::new("10.0.0.1/32".parse().unwrap());
+        spec.peer = Some("10.0.0.2".parse().unwrap());
+        let message = new_address(&spec);
+
+        // The peer replaces IFA_ADDRESS while IFA_LOCAL keeps the address
+        assert_eq!(address_of(&message), Some(spec.network));
+        let address = attr(&message, |a| match a {
+            AddressAttribute::Address(addr) => Some(*addr),
+            _ => None,
+        });
+        assert_eq!(address, spec.peer);
+        assert!(!message
+            .attributes
+            .iter()
+            .any(|a| matches!(a, AddressAttribute::Broadcast(_))));
+    }
+
+    #[test]
+    fn test_address_attributes() {
+        let mut spec = AddressSpec::new("192.168.1.10/24".parse().unwrap());
+        spec.broadcast = Some(Ipv4Addr::new(192, 168, 1, 127));
+        spec.label = Some("eth0:1".to_string());
+        spec.scope = Some(AddressScope::Link);
+        spec.valid_lifetime = Some(3600);
+        // In bit order, which is how the kernel's flags parse back
+        spec.flags = vec![AddressFlag::NoDad, AddressFlag::NoPrefixRoute];
+        let message = new_address(&spec);
+
+        assert_eq!(message.header.scope, nl::AddressScope::Link);
+        let broadcasts: Vec<_> = message
+            .attributes
+            .iter()
+            .filter_map(|a| match a {
+                AddressAttribute::Broadcast(b) => Some(*b),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(broadcasts, vec![Ipv4Addr::new(192, 168, 1, 127)]);
+        let label = attr(&message, |a| match a {
+            AddressAttribute::Label(l) => Some(l.clone()),
+            _ => None,
+        });
+        assert_eq!(label.as_deref(), Some("eth0:1"));
+        // Without a preferred lifetime the address stays preferred while valid
+        let lifetimes = attr(&message, |a| match a {
+            AddressAttribute::CacheInfo(c) => Some((c.ifa_valid, c.ifa_preferred)),
+            _ => None,
+        });
+        assert_eq!(lifetimes, Some((3600, 3600)));
+        let flags = attr(&message, |a| match a {
+            AddressAttribute::Flags(f) => Some(f.clone()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(
+            flags,
+            vec![nl::AddressFlag::Nodad, nl::AddressFlag::Noprefixroute]
+        );
+    }
+}
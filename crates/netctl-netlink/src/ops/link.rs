@@ -1,6 +1,14 @@
 use crate::NetlinkHandle;
 use async_trait::async_trait;
-use netctl_types::{Error, LinkInfo, LinkState, MacAddress, Result};
+use netctl_types::{
+    Error, LinkInfo, LinkKind, LinkState, MacAddress, Result, VfLinkState, VirtualFunction,
+};
+use netlink_packet_core::{NetlinkMessage, NetlinkPayload, NLM_F_ACK, NLM_F_REQUEST};
+use netlink_packet_route::link::{
+    self as nl, InfoKind, LinkAttribute, LinkExtentMask, LinkFlag, LinkInfo as NlLinkInfo,
+    LinkMessage, LinkVfInfo, Prop, VfInfo,
+};
+use netlink_packet_route::{AddressFamily, RouteNetlinkMessage};
 use tracing::{debug, info, instrument};
 
 #[async_trait]
@@ -11,6 +19,11 @@ pub trait LinkOps {
     async fn set_link_up(&self, index: u32) -> Result<()>;
     async fn set_link_down(&self, index: u32) -> Result<()>;
     async fn set_link_mtu(&self, index: u32, mtu: u32) -> Result<()>;
+    async fn add_link(&self, name: &str, kind: LinkKind) -> Result<()>;
+    async fn delete_link(&self, index: u32) -> Result<()>;
+    async fn set_link_name(&self, index: u32, name: &str) -> Result<()>;
+    async fn add_altname(&self, index: u32, altname: &str) -> Result<()>;
+    async fn delete_altname(&self, index: u32, altname: &str) -> Result<()>;
 }
 
 #[async_trait]
@@ -44,8 +57,6 @@ impl LinkOps for NetlinkHandle {
     async fn list_links(&self) -> Result<Vec<LinkInfo>> {
         info!("listing all links");
         use futures::TryStreamExt;
-        use netlink_packet_route::link::LinkAttribute;
-        use netlink_packet_route::link::LinkFlag;
 
         let mut links = self.handle().link().get().execute();
         let mut result = Vec::new();
@@ -55,42 +66,9 @@ impl LinkOps for NetlinkHandle {
             .await
             .map_err(|e| Error::netlink(format!("{}", e)))?
         {
-            let index = link.header.index;
-            let mut name = String::new();
-            let mut mtu = 0;
-            let mut mac = None;
-
-            // Parse link attributes
-            for attr in link.attributes.iter() {
-                match attr {
-                    LinkAttribute::IfName(n) => name = n.clone(),
-                    LinkAttribute::Mtu(m) => mtu = *m,
-                    LinkAttribute::Address(addr) if addr.len() == 6 => {
-                        let mut octets = [0u8; 6];
-                        octets.copy_from_slice(&addr[0..6]);
-                        mac = Some(MacAddress::new(octets));
-                    }
-                    _ => {}
-                }
-            }
-
-            // Check if UP flag is set
-            let state = if link.header.flags.contains(&LinkFlag::Up) {
-                LinkState::Up
-            } else {
-                LinkState::Down
-            };
-
-            debug!(index, name, mtu, "found link");
-
-            result.push(LinkInfo {
-                index,
-                name,
-                state,
-                mtu,
-                mac_address: mac,
-                addresses: Vec::new(), // Will be populated if needed
-            });
+            let info = parse_link_message(&link);
+            debug!(index = info.index, name = %info.name, mtu = info.mtu, "found link");
+            result.push(info);
         }
 
         Ok(result)
@@ -100,14 +78,14 @@ impl LinkOps for NetlinkHandle {
     async fn get_link_info(&self, name: &str) -> Result<LinkInfo> {
         info!(name = %name, "getting link info");
         use futures::TryStreamExt;
-        use netlink_packet_route::link::LinkAttribute;
-        use netlink_packet_route::link::LinkFlag;
 
+        // Ask for VF details too; they are only reported with RTEXT_FILTER_VF
         let mut links = self
             .handle()
             .link()
             .get()
             .match_name(name.to_string())
+            .set_filter_mask(AddressFamily::Unspec, vec![LinkExtentMask::Vf])
             .execute();
 
         if let Some(link) = links
@@ -115,39 +93,7 @@ impl LinkOps for NetlinkHandle {
             .await
             .map_err(|e| Error::netlink(format!("{}", e)))?
         {
-            let index = link.header.index;
-            let mut link_name = String::new();
-            let mut mtu = 0;
-            let mut mac = None;
-
-            for attr in link.attributes.iter() {
-                match attr {
-                    LinkAttribute::IfName(n) => link_name = n.clone(),
-                    LinkAttribute::Mtu(m) => mtu = *m,
-                    LinkAttribute::Address(addr) if addr.len() == 6 => {
-                        let mut octets = [0u8; 6];
-                        octets.copy_from_slice(&addr[0..6]);
-                        mac = Some(MacAddress::new(octets));
-                    }
-                    _ => {}
-                }
-            }
-
-            // Check if UP flag is set
-            let state = if link.header.flags.contains(&LinkFlag::Up) {
-                LinkState::Up
-            } else {
-                LinkState::Down
-            };
-
-            Ok(LinkInfo {
-                index,
-                name: link_name,
-                state,
-                mtu,
-                mac_address: mac,
-                addresses: Vec::new(), // TODO: Query addresses
-            })
+            Ok(parse_link_message(&link))
         } else {
             Err(Error::InterfaceNotFound {
                 name: name.to_string(),
@@ -190,4 +136,304 @@ impl LinkOps for NetlinkHandle {
             .await
             .map_err(|e| Error::netlink(format!("{}", e)))
     }
+
+    #[instrument(skip(self), fields(name = %name, kind = %kind))]
+    async fn add_link(&self, name: &str, kind: LinkKind) -> Result<()> {
+        info!(name = %name, kind = %kind, "creating link");
+        let mut request = self.handle().link().add().name(name.to_string());
+        request.message_mut().attributes.push(link_info(kind));
+        request
+            .execute()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_link(&self, index: u32) -> Result<()> {
+        info!("deleting link");
+        self.handle()
+            .link()
+            .del(index)
+            .execute()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn set_link_name(&self, index: u32, name: &str) -> Result<()> {
+        info!(%name, "renaming link");
+        self.handle()
+            .link()
+            .set(index)
+            .name(name.to_string())
+            .execute()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn add_altname(&self, index: u32, altname: &str) -> Result<()> {
+        info!(%altname, "adding alternative name");
+        self.handle()
+            .link()
+            .property_add(index)
+            .alt_ifname(&[altname])
+            .execute()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_altname(&self, index: u32, altname: &str) -> Result<()> {
+        info!(%altname, "deleting alternative name");
+        use futures::StreamExt;
+
+        // rtnetlink's property_del() sets NLM_F_EXCL, which shares its value
+        // with NLM_F_BULK and makes kernels >= 5.19 reject the request with
+        // EOPNOTSUPP, so build the message here.
+        let message = altname_message(index, altname);
+        let mut request = NetlinkMessage::from(RouteNetlinkMessage::DelLinkProp(message));
+        request.header.flags = NLM_F_REQUEST | NLM_F_ACK;
+
+        let mut handle = self.handle().clone();
+        let mut responses = handle
+            .request(request)
+            .map_err(|e| Error::netlink(format!("{}", e)))?;
+        while let Some(response) = responses.next().await {
+            if let NetlinkPayload::Error(err) = response.payload {
+                if err.code.is_some() {
+                    return Err(Error::netlink(format!("{}", err.to_io())));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// RTM_NEWLINKPROP/RTM_DELLINKPROP payload for `altname` on the link at `index`
+fn altname_message(index: u32, altname: &str) -> LinkMessage {
+    let mut message = LinkMessage::default();
+    message.header.index = index;
+    message
+        .attributes
+        .push(LinkAttribute::PropList(vec![Prop::AltIfName(
+            altname.to_string(),
+        )]));
+    message
+}
+
+/// IFLA_LINKINFO selecting the kernel driver for a new link of `kind`
+fn link_info(kind: LinkKind) -> LinkAttribute {
+    let info_kind = match kind {
+        LinkKind::Dummy => InfoKind::Dummy,
+        LinkKind::Ifb => InfoKind::Ifb,
+        LinkKind::Nlmon => InfoKind::Nlmon,
+    };
+    LinkAttribute::LinkInfo(vec![NlLinkInfo::Kind(info_kind)])
+}
+
+pub(crate) fn parse_link_message(link: &LinkMessage) -> LinkInfo {
+    let mut name = String::new();
+    let mut mtu = 0;
+    let mut mac = None;
+    let mut permanent_mac = None;
+    let mut altnames = Vec::new();
+    let mut kind = None;
+    let mut vfs = Vec::new();
+
+    for attr in link.attributes.iter() {
+        match attr {
+            LinkAttribute::IfName(n) => name = n.clone(),
+            LinkAttribute::Mtu(m) => mtu = *m,
+            LinkAttribute::Address(addr) => mac = parse_mac(addr),
+            LinkAttribute::PermAddress(addr) => permanent_mac = parse_mac(addr),
+            LinkAttribute::PropList(props) => {
+                altnames = props
+                    .iter()
+                    .filter_map(|prop| match prop {
+                        Prop::AltIfName(altname) => Some(altname.clone()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            LinkAttribute::LinkInfo(infos) => {
+                kind = infos.iter().find_map(|info| match info {
+                    NlLinkInfo::Kind(k) => Some(k.to_string()),
+                    _ => None,
+                });
+            }
+            LinkAttribute::VfInfoList(list) => {
+                vfs = list.iter().filter_map(parse_vf_info).collect();
+            }
+            _ => {}
+        }
+    }
+
+    // Check if UP flag is set
+    let state = if link.header.flags.contains(&LinkFlag::Up) {
+        LinkState::Up
+    } else {
+        LinkState::Down
+    };
+
+    LinkInfo {
+        index: link.header.index,
+        name,
+        state,
+        carrier: link.header.flags.contains(&LinkFlag::LowerUp),
+        flags: link
+            .header
+            .flags
+            .iter()
+            .fold(0, |flags, flag| flags | u32::from(*flag)),
+        mtu,
+        mac_address: mac,
+        permanent_mac_address: permanent_mac,
+        altnames,
+        addresses: Vec::new(), // TODO: Query addresses
+        kind,
+        vfs,
+    }
+}
+
+fn parse_mac(bytes: &[u8]) -> Option<MacAddress> {
+    let octets: [u8; 6] = bytes.try_into().ok()?;
+    Some(MacAddress::new(octets))
+}
+
+fn parse_vf_info(info: &LinkVfInfo) -> Option<VirtualFunction> {
+    let mut vf = VirtualFunction {
+        id: u32::MAX,
+        mac_address: None,
+        vlan: 0,
+        qos: 0,
+        spoof_check: None,
+        trust: None,
+        link_state: None,
+        min_tx_rate: 0,
+        max_tx_rate: 0,
+    };
+
+    for attr in info.0.iter() {
+        match attr {
+            VfInfo::Mac(mac) => {
+                vf.id = mac.vf_id;
+                let mut octets = [0u8; 6];
+                octets.copy_from_slice(&mac.mac[0..6]);
+                vf.mac_address = Some(MacAddress::new(octets));
+            }
+            VfInfo::Vlan(vlan) => {
+                vf.vlan = vlan.vlan_id as u16;
+                vf.qos = vlan.qos as u8;
+            }
+            VfInfo::Rate(rate) => {
+                vf.min_tx_rate = rate.min_tx_rate;
+                vf.max_tx_rate = rate.max_tx_rate;
+            }
+            VfInfo::SpoofCheck(spoofchk) => vf.spoof_check = Some(spoofchk.enabled),
+            VfInfo::Trust(trust) => vf.trust = Some(trust.enabled),
+            VfInfo::LinkState(state) => {
+                vf.link_state = match state.state {
+                    nl::VfLinkState::Auto => Some(VfLinkState::Auto),
+                    nl::VfLinkState::Enable => Some(VfLinkState::Enable),
+                    nl::VfLinkState::Disable => Some(VfLinkState::Disable),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    // IFLA_VF_MAC is always present; without it the entry is unusable
+    (vf.id != u32::MAX).then_some(vf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::roundtrip;
+
+    const RTM_NEWLINKPROP: u16 = 108;
+    const RTM_DELLINKPROP: u16 = 109;
+
+    #[test]
+    fn test_add_link_message() {
+        for kind in [LinkKind::Dummy, LinkKind::Ifb, LinkKind::Nlmon] {
+            let mut message = LinkMessage::default();
+            message
+                .attributes
+                .push(LinkAttribute::IfName("test0".to_string()));
+            message.attributes.push(link_info(kind));
+
+            let parsed = match roundtrip(RouteNetlinkMessage::NewLink(message.clone())) {
+                RouteNetlinkMessage::NewLink(parsed) => parsed,
+                other => panic!("unexpected message {:?}", other),
+            };
+            assert_eq!(parsed, message);
+
+            let info = parse_link_message(&parsed);
+            assert_eq!(info.name, "test0");
+            assert_eq!(info.kind, Some(kind.to_string()));
+        }
+    }
+
+    /// Serialize a link property request and parse it back. The kernel
+    /// never sends these, so netlink-packet-route only parses the payload.
+    fn prop_roundtrip(message: RouteNetlinkMessage) -> (u16, LinkMessage) {
+        use netlink_packet_core::NetlinkBuffer;
+        use netlink_packet_route::link::LinkMessageBuffer;
+        use netlink_packet_utils::Parseable;
+
+        let mut packet = NetlinkMessage::from(message);
+        packet.finalize();
+        let mut buf = vec![0; packet.buffer_len()];
+        packet.serialize(&mut buf);
+
+        let packet = NetlinkBuffer::new(&buf);
+        let payload = packet.payload().to_vec();
+        let parsed = LinkMessage::parse(&LinkMessageBuffer::new(&payload)).unwrap();
+        (packet.message_type(), parsed)
+    }
+
+    #[test]
+    fn test_altname_messages() {
+        let message = altname_message(7, "enp3s0-uplink");
+
+        let add = prop_roundtrip(RouteNetlinkMessage::NewLinkProp(message.clone()));
+        assert_eq!(add, (RTM_NEWLINKPROP, message.clone()));
+        let delete = prop_roundtrip(RouteNetlinkMessage::DelLinkProp(message.clone()));
+        assert_eq!(delete, (RTM_DELLINKPROP, message));
+    }
+
+    #[test]
+    fn test_parse_altnames() {
+        let mut message = LinkMessage::default();
+        message
+            .attributes
+            .push(LinkAttribute::IfName("eth0".to_string()));
+        message.attributes.push(LinkAttribute::PropList(vec![
+            Prop::AltIfName("enp3s0".to_string()),
+            Prop::AltIfName("uplink".to_string()),
+        ]));
+
+        let parsed = match roundtrip(RouteNetlinkMessage::NewLink(message)) {
+            RouteNetlinkMessage::NewLink(parsed) => parsed,
+            other => panic!("unexpected message {:?}", other),
+        };
+        assert_eq!(
+            parse_link_message(&parsed).altnames,
+            vec!["enp3s0", "uplink"]
+        );
+    }
+
+    #[test]
+    fn test_delete_link_message() {
+        let mut message = LinkMessage::default();
+        message.header.index = 7;
+
+        match roundtrip(RouteNetlinkMessage::DelLink(message)) {
+            RouteNetlinkMessage::DelLink(parsed) => assert_eq!(parsed.header.index, 7),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
 }
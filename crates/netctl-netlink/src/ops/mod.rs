@@ -1,5 +1,29 @@
 pub mod address;
 pub mod link;
+pub mod mptcp;
+pub mod route;
+pub mod sriov;
 
 pub use address::AddressOps;
 pub use link::LinkOps;
+pub use mptcp::MptcpOps;
+pub use route::RouteOps;
+pub use sriov::SriovOps;
+
+/// Serialize `message` the way it goes out on the socket and parse it back
+#[cfg(test)]
+pub(crate) fn roundtrip(
+    message: netlink_packet_route::RouteNetlinkMessage,
+) -> netlink_packet_route::RouteNetlinkMessage {
+    use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+
+    let mut packet = NetlinkMessage::from(message);
+    packet.finalize();
+    let mut buf = vec![0; packet.buffer_len()];
+    packet.serialize(&mut buf);
+
+    match NetlinkMessage::deserialize(&buf).unwrap().payload {
+        NetlinkPayload::InnerMessage(message) => message,
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+}
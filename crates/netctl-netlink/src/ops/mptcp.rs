@@ -0,0 +1,281 @@
+use crate::mptcp::{
+    MptcpPmAddrAttr, MptcpPmAttr, MptcpPmCmd, MptcpPmMessage, MPTCP_PM_ADDR_FLAG_BACKUP,
+    MPTCP_PM_ADDR_FLAG_FULLMESH, MPTCP_PM_ADDR_FLAG_IMPLICIT, MPTCP_PM_ADDR_FLAG_SIGNAL,
+    MPTCP_PM_ADDR_FLAG_SUBFLOW,
+};
+use crate::NetlinkHandle;
+use async_trait::async_trait;
+use futures::StreamExt;
+use netctl_types::{
+    Error, MptcpEndpoint, MptcpEndpointFlag, MptcpEndpointSpec, MptcpLimits, Result,
+};
+use netlink_packet_core::{NetlinkMessage, NetlinkPayload, NLM_F_ACK, NLM_F_DUMP, NLM_F_REQUEST};
+use netlink_packet_generic::GenlMessage;
+use std::net::IpAddr;
+use tracing::{info, instrument};
+
+const FLAG_BITS: [(MptcpEndpointFlag, u32); 5] = [
+    (MptcpEndpointFlag::Signal, MPTCP_PM_ADDR_FLAG_SIGNAL),
+    (MptcpEndpointFlag::Subflow, MPTCP_PM_ADDR_FLAG_SUBFLOW),
+    (MptcpEndpointFlag::Backup, MPTCP_PM_ADDR_FLAG_BACKUP),
+    (MptcpEndpointFlag::Fullmesh, MPTCP_PM_ADDR_FLAG_FULLMESH),
+    (MptcpEndpointFlag::Implicit, MPTCP_PM_ADDR_FLAG_IMPLICIT),
+];
+
+#[async_trait]
+pub trait MptcpOps {
+    async fn list_mptcp_endpoints(&self) -> Result<Vec<MptcpEndpoint>>;
+    async fn add_mptcp_endpoint(
+        &self,
+        spec: &MptcpEndpointSpec,
+        ifindex: Option<u32>,
+    ) -> Result<()>;
+    async fn delete_mptcp_endpoint(&self, id: u8) -> Result<()>;
+    async fn get_mptcp_limits(&self) -> Result<MptcpLimits>;
+    async fn set_mptcp_limits(&self, limits: MptcpLimits) -> Result<()>;
+}
+
+#[async_trait]
+impl MptcpOps for NetlinkHandle {
+    #[instrument(skip(self))]
+    async fn list_mptcp_endpoints(&self) -> Result<Vec<MptcpEndpoint>> {
+        info!("listing MPTCP endpoints");
+        let replies = self
+            .mptcp_request(MptcpPmMessage::new(MptcpPmCmd::GetAddr, vec![]), true)
+            .await?;
+
+        Ok(replies
+            .iter()
+            .flat_map(|msg| &msg.attributes)
+            .filter_map(|attr| match attr {
+                MptcpPmAttr::Addr(attrs) => parse_endpoint(attrs),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self, spec), fields(address = %spec.address))]
+    async fn add_mptcp_endpoint(
+        &self,
+        spec: &MptcpEndpointSpec,
+        ifindex: Option<u32>,
+    ) -> Result<()> {
+        info!(address = %spec.address, "adding MPTCP endpoint");
+        let attrs = endpoint_attrs(spec, ifindex);
+        self.mptcp_request(
+            MptcpPmMessage::new(MptcpPmCmd::AddAddr, vec![MptcpPmAttr::Addr(attrs)]),
+            false,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_mptcp_endpoint(&self, id: u8) -> Result<()> {
+        info!(id, "deleting MPTCP endpoint");
+        let attrs = vec![MptcpPmAddrAttr::Id(id)];
+        self.mptcp_request(
+            MptcpPmMessage::new(MptcpPmCmd::DelAddr, vec![MptcpPmAttr::Addr(attrs)]),
+            false,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_mptcp_limits(&self) -> Result<MptcpLimits> {
+        info!("getting MPTCP limits");
+        let replies = self
+            .mptcp_request(MptcpPmMessage::new(MptcpPmCmd::GetLimits, vec![]), false)
+            .await?;
+
+        let mut limits = MptcpLimits::default();
+        for attr in replies.iter().flat_map(|msg| &msg.attributes) {
+            match attr {
+                MptcpPmAttr::Subflows(value) => limits.subflows = *value,
+                MptcpPmAttr::RcvAddAddrs(value) => limits.add_addr_accepted = *value,
+                _ => {}
+            }
+        }
+        Ok(limits)
+    }
+
+    #[instrument(skip(self))]
+    async fn set_mptcp_limits(&self, limits: MptcpLimits) -> Result<()> {
+        info!(
+            subflows = limits.subflows,
+            add_addr_accepted = limits.add_addr_accepted,
+            "setting MPTCP limits"
+        );
+        let attrs = vec![
+            MptcpPmAttr::RcvAddAddrs(limits.add_addr_accepted),
+            MptcpPmAttr::Subflows(limits.subflows),
+        ];
+        self.mptcp_request(MptcpPmMessage::new(MptcpPmCmd::SetLimits, attrs), false)
+            .await
+            .map(|_| ())
+    }
+}
+
+impl NetlinkHandle {
+    /// Send an `mptcp_pm` request and collect the replies. Requests that do
+    /// not dump are acked, so kernel errors surface here.
+    async fn mptcp_request(
+        &self,
+        message: MptcpPmMessage,
+        dump: bool,
+    ) -> Result<Vec<MptcpPmMessage>> {
+        let mut request = NetlinkMessage::from(GenlMessage::from_payload(message));
+        request.header.flags = if dump {
+            NLM_F_REQUEST | NLM_F_DUMP
+        } else {
+            NLM_F_REQUEST | NLM_F_ACK
+        };
+
+        let mut responses = self
+            .genl_handle()
+            .request(request)
+            .await
+            .map_err(|e| Error::netlink(format!("MPTCP path manager unavailable: {}", e)))?;
+
+        let mut replies = Vec::new();
+        while let Some(response) = responses.next().await {
+            let response = response.map_err(|e| Error::netlink(format!("{}", e)))?;
+            match response.payload {
+                NetlinkPayload::InnerMessage(genl) => replies.push(genl.payload),
+                NetlinkPayload::Error(err) if err.code.is_some() => {
+                    return Err(Error::netlink(format!("{}", err.to_io())));
+                }
+                _ => {}
+            }
+        }
+        Ok(replies)
+    }
+}
+
+/// MPTCP_PM_ATTR_ADDR contents for adding the endpoint `spec` on the link
+/// at `ifindex`
+fn endpoint_attrs(spec: &MptcpEndpointSpec, ifindex: Option<u32>) -> Vec<MptcpPmAddrAttr> {
+    let mut attrs = vec![
+        MptcpPmAddrAttr::family(spec.address),
+        MptcpPmAddrAttr::address(spec.address),
+    ];
+    if let Some(id) = spec.id {
+        attrs.push(MptcpPmAddrAttr::Id(id));
+    }
+    if let Some(port) = spec.port {
+        attrs.push(MptcpPmAddrAttr::Port(port));
+    }
+    if let Some(index) = ifindex {
+        attrs.push(MptcpPmAddrAttr::IfIndex(index as i32));
+    }
+    let flags = FLAG_BITS
+        .iter()
+        .filter(|(flag, _)| spec.has_flag(*flag))
+        .fold(0, |acc, (_, bit)| acc | bit);
+    if flags != 0 {
+        attrs.push(MptcpPmAddrAttr::Flags(flags));
+    }
+    attrs
+}
+
+fn parse_endpoint(attrs: &[MptcpPmAddrAttr]) -> Option<MptcpEndpoint> {
+    let mut id = None;
+    let mut address: Option<IpAddr> = None;
+    let mut port = None;
+    let mut ifindex = None;
+    let mut flags = Vec::new();
+
+    for attr in attrs {
+        match attr {
+            MptcpPmAddrAttr::Id(value) => id = Some(*value),
+            MptcpPmAddrAttr::Addr4(addr) => address = Some((*addr).into()),
+            MptcpPmAddrAttr::Addr6(addr) => address = Some((*addr).into()),
+            MptcpPmAddrAttr::Port(value) if *value != 0 => port = Some(*value),
+            MptcpPmAddrAttr::IfIndex(value) if *value > 0 => ifindex = Some(*value as u32),
+            MptcpPmAddrAttr::Flags(bits) => {
+                flags = FLAG_BITS
+                    .iter()
+                    .filter(|(_, bit)| bits & bit != 0)
+                    .map(|(flag, _)| *flag)
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    Some(MptcpEndpoint {
+        id: id?,
+        address: address?,
+        port,
+        ifindex,
+        flags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mptcp::roundtrip;
+
+    /// Build the add request for `spec` and read the endpoint back the way
+    /// a dump reply is read
+    fn endpoint(spec: &MptcpEndpointSpec, ifindex: Option<u32>) -> Option<MptcpEndpoint> {
+        let message = MptcpPmMessage::new(
+            MptcpPmCmd::AddAddr,
+            vec![MptcpPmAttr::Addr(endpoint_attrs(spec, ifindex))],
+        );
+        match &roundtrip(message).attributes[..] {
+            [MptcpPmAttr::Addr(attrs)] => parse_endpoint(attrs),
+            attrs => panic!("unexpected attributes {:?}", attrs),
+        }
+    }
+
+    #[test]
+    fn test_endpoint_roundtrip() {
+        let mut spec = MptcpEndpointSpec::new("192.0.2.1".parse().unwrap());
+        spec.id = Some(3);
+        spec.port = Some(8080);
+        spec.flags = vec![MptcpEndpointFlag::Signal, MptcpEndpointFlag::Backup];
+
+        assert_eq!(
+            endpoint(&spec, Some(2)),
+            Some(MptcpEndpoint {
+                id: 3,
+                address: spec.address,
+                port: Some(8080),
+                ifindex: Some(2),
+                flags: vec![MptcpEndpointFlag::Signal, MptcpEndpointFlag::Backup],
+            })
+        );
+    }
+
+    #[test]
+    fn test_endpoint_defaults() {
+        let mut spec = MptcpEndpointSpec::new("2001:db8::1".parse().unwrap());
+        spec.id = Some(1);
+        let attrs = endpoint_attrs(&spec, None);
+
+        // No flags attribute when none are set; the kernel defaults to none
+        assert!(!attrs
+            .iter()
+            .any(|attr| matches!(attr, MptcpPmAddrAttr::Flags(_))));
+        assert_eq!(
+            endpoint(&spec, None),
+            Some(MptcpEndpoint {
+                id: 1,
+                address: spec.address,
+                port: None,
+                ifindex: None,
+                flags: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_endpoint_without_id() {
+        // Requests may leave the id to the kernel, but replies always carry one
+        let spec = MptcpEndpointSpec::new("192.0.2.1".parse().unwrap());
+        assert_eq!(endpoint(&spec, None), None);
+    }
+}
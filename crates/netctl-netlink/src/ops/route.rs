@@ -0,0 +1,160 @@
+use crate::NetlinkHandle;
+use async_trait::async_trait;
+use netctl_types::{Error, IpNetwork, Result, Route};
+use netlink_packet_route::route::{
+    RouteAddress, RouteAttribute, RouteHeader, RouteMessage, RouteType,
+};
+use netlink_packet_route::AddressFamily;
+use rtnetlink::IpVersion;
+use std::net::IpAddr;
+use tracing::{debug, info, instrument};
+
+#[async_trait]
+pub trait RouteOps {
+    /// Unicast routes of the main table, IPv4 and IPv6
+    async fn list_routes(&self) -> Result<Vec<Route>>;
+}
+
+#[async_trait]
+impl RouteOps for NetlinkHandle {
+    #[instrument(skip(self))]
+    async fn list_routes(&self) -> Result<Vec<Route>> {
+        info!("listing routes");
+        use futures::TryStreamExt;
+
+        let mut routes = Vec::new();
+        for version in [IpVersion::V4, IpVersion::V6] {
+            let mut messages = self.handle().route().get(version).execute();
+            while let Some(message) = messages
+                .try_next()
+                .await
+                .map_err(|e| Error::netlink(format!("{}", e)))?
+            {
+                routes.extend(parse_route_message(&message));
+            }
+        }
+
+        debug!(count = routes.len(), "routes listed");
+        Ok(routes)
+    }
+}
+
+/// Convert a unicast main-table route; other tables and route types (local,
+/// broadcast, ...) yield `None`. The destination is always set, as a /0
+/// network for default routes.
+pub(crate) fn parse_route_message(message: &RouteMessage) -> Option<Route> {
+    if message.header.kind != RouteType::Unicast {
+        return None;
+    }
+
+    let mut table = u32::from(message.header.table);
+    let mut destination = None;
+    let mut gateway = None;
+    let mut index = None;
+    for attr in &message.attributes {
+        match attr {
+            RouteAttribute::Table(t) => table = *t,
+            RouteAttribute::Destination(addr) => destination = route_address(addr),
+            RouteAttribute::Gateway(addr) => gateway = route_address(addr),
+            RouteAttribute::Oif(oif) => index = Some(*oif),
+            _ => {}
+        }
+    }
+    if table != u32::from(RouteHeader::RT_TABLE_MAIN) {
+        return None;
+    }
+
+    let destination = match destination {
+        Some(addr) => addr,
+        None => match message.header.address_family {
+            AddressFamily::Inet => IpAddr::from([0u8; 4]),
+            AddressFamily::Inet6 => IpAddr::from([0u8; 16]),
+            _ => return None,
+        },
+    };
+
+    Some(Route {
+        destination: Some(
+            IpNetwork::new(destination, message.header.destination_prefix_length).ok()?,
+        ),
+        gateway,
+        index,
+    })
+}
+
+fn route_address(addr: &RouteAddress) -> Option<IpAddr> {
+    match addr {
+        RouteAddress::Inet(v4) => Some(IpAddr::V4(*v4)),
+        RouteAddress::Inet6(v6) => Some(IpAddr::V6(*v6)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::roundtrip;
+    use netlink_packet_route::RouteNetlinkMessage;
+    use std::net::Ipv4Addr;
+
+    fn route_message(
+        destination: Option<(Ipv4Addr, u8)>,
+        gateway: Option<Ipv4Addr>,
+    ) -> RouteMessage {
+        let mut message = RouteMessage::default();
+        message.header.address_family = AddressFamily::Inet;
+        message.header.table = RouteHeader::RT_TABLE_MAIN;
+        message.header.kind = RouteType::Unicast;
+        if let Some((addr, prefix)) = destination {
+            message.header.destination_prefix_length = prefix;
+            message
+                .attributes
+                .push(RouteAttribute::Destination(RouteAddress::Inet(addr)));
+        }
+        if let Some(addr) = gateway {
+            message
+                .attributes
+                .push(RouteAttribute::Gateway(RouteAddress::Inet(addr)));
+        }
+        message.attributes.push(RouteAttribute::Oif(2));
+        message
+    }
+
+    fn parse(message: RouteMessage) -> Option<Route> {
+        match roundtrip(RouteNetlinkMessage::NewRoute(message.clone())) {
+            RouteNetlinkMessage::NewRoute(parsed) => {
+                assert_eq!(parsed, message);
+                parse_route_message(&parsed)
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_route() {
+        let route = parse(route_message(None, Some(Ipv4Addr::new(192, 168, 1, 1)))).unwrap();
+        assert_eq!(route.destination, Some("0.0.0.0/0".parse().unwrap()));
+        assert_eq!(route.gateway, Some("192.168.1.1".parse().unwrap()));
+        assert_eq!(route.index, Some(2));
+    }
+
+    #[test]
+    fn test_network_route() {
+        let message = route_message(Some((Ipv4Addr::new(10, 1, 0, 0), 16)), None);
+        let route = parse(message).unwrap();
+        assert_eq!(route.destination, Some("10.1.0.0/16".parse().unwrap()));
+        assert_eq!(route.gateway, None);
+    }
+
+    #[test]
+    fn test_other_routes_skipped() {
+        let mut local = route_message(Some((Ipv4Addr::new(127, 0, 0, 1), 32)), None);
+        local.header.kind = RouteType::Local;
+        assert_eq!(parse(local), None);
+
+        // Tables above 255 only fit in RTA_TABLE
+        let mut other_table = route_message(None, None);
+        other_table.attributes.push(RouteAttribute::Table(1000));
+        assert_eq!(parse(other_table), None);
+    }
+}
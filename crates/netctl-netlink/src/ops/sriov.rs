@@ -0,0 +1,219 @@
+use crate::NetlinkHandle;
+use async_trait::async_trait;
+use netctl_types::{Error, Result, VfLinkState, VfSettings};
+use netlink_packet_route::link::{
+    self as nl, LinkAttribute, LinkVfInfo, VfInfo, VfInfoLinkState, VfInfoMac, VfInfoRate,
+    VfInfoSpoofCheck, VfInfoTrust, VfInfoVlan,
+};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tracing::{debug, info, instrument};
+
+#[async_trait]
+pub trait SriovOps {
+    /// Apply settings to VF `vf` of the physical function at `index`.
+    ///
+    /// VLAN/QoS and min/max rate are sent to the kernel as pairs, so callers
+    /// must fill in both halves of a pair they want to preserve.
+    async fn set_vf(&self, index: u32, vf: u32, settings: &VfSettings) -> Result<()>;
+    async fn get_num_vfs(&self, name: &str) -> Result<u32>;
+    async fn get_total_vfs(&self, name: &str) -> Result<u32>;
+    async fn set_num_vfs(&self, name: &str, count: u32) -> Result<()>;
+}
+
+#[async_trait]
+impl SriovOps for NetlinkHandle {
+    #[instrument(skip(self, settings))]
+    async fn set_vf(&self, index: u32, vf: u32, settings: &VfSettings) -> Result<()> {
+        info!(vf, "configuring virtual function");
+        let attrs = vf_info(vf, settings);
+        if attrs.is_empty() {
+            debug!(vf, "no VF settings to apply");
+            return Ok(());
+        }
+
+        let mut request = self.handle().link().set(index);
+        request
+            .message_mut()
+            .attributes
+            .push(LinkAttribute::VfInfoList(vec![LinkVfInfo(attrs)]));
+        request
+            .execute()
+            .await
+            .map_err(|e| Error::netlink(format!("{}", e)))
+    }
+
+    #[instrument(skip(self))]
+    async fn get_num_vfs(&self, name: &str) -> Result<u32> {
+        read_sriov_attr(name, "sriov_numvfs").await
+    }
+
+    #[instrument(skip(self))]
+    async fn get_total_vfs(&self, name: &str) -> Result<u32> {
+        read_sriov_attr(name, "sriov_totalvfs").await
+    }
+
+    #[instrument(skip(self))]
+    async fn set_num_vfs(&self, name: &str, count: u32) -> Result<()> {
+        info!(count, "setting number of VFs");
+        let total = self.get_total_vfs(name).await?;
+        if count > total {
+            return Err(Error::Generic(format!(
+                "'{}' supports at most {} VFs",
+                name, total
+            )));
+        }
+
+        // The kernel refuses to change a non-zero VF count directly
+        let current = self.get_num_vfs(name).await?;
+        if current == count {
+            return Ok(());
+        }
+        if current != 0 && count != 0 {
+            write_sriov_attr(name, "sriov_numvfs", 0).await?;
+        }
+        write_sriov_attr(name, "sriov_numvfs", count).await
+    }
+}
+
+/// IFLA_VF_* attributes for the settings of VF `vf`, one per setting given
+fn vf_info(vf: u32, settings: &VfSettings) -> Vec<VfInfo> {
+    let mut attrs = Vec::new();
+
+    if let Some(mac) = settings.mac_address {
+        attrs.push(VfInfo::Mac(VfInfoMac::new(vf, &mac.octets())));
+    }
+    if settings.vlan.is_some() || settings.qos.is_some() {
+        attrs.push(VfInfo::Vlan(VfInfoVlan::new(
+            vf,
+            settings.vlan.unwrap_or(0).into(),
+            settings.qos.unwrap_or(0).into(),
+        )));
+    }
+    if settings.min_tx_rate.is_some() || settings.max_tx_rate.is_some() {
+        attrs.push(VfInfo::Rate(VfInfoRate::new(
+            vf,
+            settings.min_tx_rate.unwrap_or(0),
+            settings.max_tx_rate.unwrap_or(0),
+        )));
+    }
+    if let Some(enabled) = settings.spoof_check {
+        attrs.push(VfInfo::SpoofCheck(VfInfoSpoofCheck::new(vf, enabled)));
+    }
+    if let Some(enabled) = settings.trust {
+        attrs.push(VfInfo::Trust(VfInfoTrust::new(vf, enabled)));
+    }
+    if let Some(state) = settings.link_state {
+        let state = match state {
+            VfLinkState::Auto => nl::VfLinkState::Auto,
+            VfLinkState::Enable => nl::VfLinkState::Enable,
+            VfLinkState::Disable => nl::VfLinkState::Disable,
+        };
+        attrs.push(VfInfo::LinkState(VfInfoLinkState::new(vf, state)));
+    }
+    attrs
+}
+
+fn sriov_attr_path(name: &str, attr: &str) -> PathBuf {
+    PathBuf::from("/sys/class/net")
+        .join(name)
+        .join("device")
+        .join(attr)
+}
+
+fn map_sysfs_error(name: &str, e: std::io::Error) -> Error {
+    if e.kind() == ErrorKind::NotFound {
+        Error::Generic(format!("'{}' does not support SR-IOV", name))
+    } else {
+        Error::Io(e)
+    }
+}
+
+async fn read_sriov_attr(name: &str, attr: &str) -> Result<u32> {
+    let content = tokio::fs::read_to_string(sriov_attr_path(name, attr))
+        .await
+        .map_err(|e| map_sysfs_error(name, e))?;
+    content
+        .trim()
+        .parse()
+        .map_err(|_| Error::Generic(format!("unexpected {} value '{}'", attr, content.trim())))
+}
+
+async fn write_sriov_attr(name: &str, attr: &str, value: u32) -> Result<()> {
+    tokio::fs::write(sriov_attr_path(name, attr), value.to_string())
+        .await
+        .map_err(|e| map_sysfs_error(name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::link::parse_link_message;
+    use crate::ops::roundtrip;
+    use netctl_types::{MacAddress, VirtualFunction};
+    use netlink_packet_route::link::LinkMessage;
+    use netlink_packet_route::RouteNetlinkMessage;
+
+    fn vf_list(vf: u32, settings: &VfSettings) -> LinkAttribute {
+        LinkAttribute::VfInfoList(vec![LinkVfInfo(vf_info(vf, settings))])
+    }
+
+    #[test]
+    fn test_set_vf_message() {
+        let settings = VfSettings {
+            mac_address: Some(MacAddress::new([0x52, 0x54, 0x00, 0x12, 0x34, 0x56])),
+            vlan: Some(100),
+            qos: Some(3),
+            spoof_check: Some(true),
+            trust: Some(false),
+            link_state: Some(VfLinkState::Disable),
+            min_tx_rate: Some(10),
+            max_tx_rate: Some(1000),
+        };
+        let mut message = LinkMessage::default();
+        message.header.index = 4;
+        message.attributes.push(vf_list(2, &settings));
+
+        let parsed = match roundtrip(RouteNetlinkMessage::SetLink(message.clone())) {
+            RouteNetlinkMessage::SetLink(parsed) => parsed,
+            other => panic!("unexpected message {:?}", other),
+        };
+        assert_eq!(parsed, message);
+
+        // The kernel reports VFs in the same attributes it accepts
+        let info = parse_link_message(&parsed);
+        assert_eq!(
+            info.vfs,
+            vec![VirtualFunction {
+                id: 2,
+                mac_address: settings.mac_address,
+                vlan: 100,
+                qos: 3,
+                spoof_check: Some(true),
+                trust: Some(false),
+                link_state: Some(VfLinkState::Disable),
+                min_tx_rate: 10,
+                max_tx_rate: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_vf_pairs_filled_in() {
+        let settings = VfSettings {
+            vlan: Some(100),
+            max_tx_rate: Some(1000),
+            ..Default::default()
+        };
+        let attrs = vf_info(1, &settings);
+
+        assert_eq!(
+            attrs,
+            vec![
+                VfInfo::Vlan(VfInfoVlan::new(1, 100, 0)),
+                VfInfo::Rate(VfInfoRate::new(1, 0, 1000)),
+            ]
+        );
+        assert!(vf_info(1, &VfSettings::default()).is_empty());
+    }
+}
@@ -0,0 +1,742 @@
+//! DNS types used with systemd-resolved: lookup results and link/global
+//! settings.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+const DNS_CLASS_IN: u16 = 1;
+
+/// Record types `netctl dns query` can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DnsRecordType {
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Ns,
+    Ptr,
+    Srv,
+    Txt,
+}
+
+impl DnsRecordType {
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::A => 1,
+            Self::Ns => 2,
+            Self::Cname => 5,
+            Self::Ptr => 12,
+            Self::Mx => 15,
+            Self::Txt => 16,
+            Self::Aaaa => 28,
+            Self::Srv => 33,
+        }
+    }
+
+    pub const fn class(self) -> u16 {
+        DNS_CLASS_IN
+    }
+}
+
+impl fmt::Display for DnsRecordType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+            Self::Cname => "CNAME",
+            Self::Mx => "MX",
+            Self::Ns => "NS",
+            Self::Ptr => "PTR",
+            Self::Srv => "SRV",
+            Self::Txt => "TXT",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for DnsRecordType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "A" => Ok(Self::A),
+            "AAAA" => Ok(Self::Aaaa),
+            "CNAME" => Ok(Self::Cname),
+            "MX" => Ok(Self::Mx),
+            "NS" => Ok(Self::Ns),
+            "PTR" => Ok(Self::Ptr),
+            "SRV" => Ok(Self::Srv),
+            "TXT" => Ok(Self::Txt),
+            _ => Err(Error::Generic(format!(
+                "unsupported record type '{}' (expected A, AAAA, CNAME, MX, NS, PTR, SRV or TXT)",
+                s
+            ))),
+        }
+    }
+}
+
+/// How resolved answered a query, decoded from the `SD_RESOLVED_*` flags
+/// it returns with every lookup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolveFlags {
+    pub dns: bool,
+    pub llmnr: bool,
+    pub mdns: bool,
+    /// Every record was DNSSEC-validated or locally trusted
+    pub authenticated: bool,
+    /// Every record came over a local or encrypted transport
+    pub confidential: bool,
+    pub synthetic: bool,
+    pub from_cache: bool,
+    pub from_zone: bool,
+    pub from_trust_anchor: bool,
+    pub from_network: bool,
+}
+
+impl From<u64> for ResolveFlags {
+    fn from(bits: u64) -> Self {
+        let set = |bit: u32| bits & (1 << bit) != 0;
+        Self {
+            dns: set(0),
+            llmnr: set(1) || set(2),
+            mdns: set(3) || set(4),
+            authenticated: set(9),
+            confidential: set(18),
+            synthetic: set(19),
+            from_cache: set(20),
+            from_zone: set(21),
+            from_trust_anchor: set(22),
+            from_network: set(23),
+        }
+    }
+}
+
+impl ResolveFlags {
+    pub fn protocols(&self) -> Vec<&'static str> {
+        [
+            (self.dns, "DNS"),
+            (self.llmnr, "LLMNR"),
+            (self.mdns, "mDNS"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+
+    pub fn sources(&self) -> Vec<&'static str> {
+        [
+            (self.synthetic, "synthetic"),
+            (self.from_cache, "cache"),
+            (self.from_zone, "zone"),
+            (self.from_trust_anchor, "trust anchor"),
+            (self.from_network, "network"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+}
+
+/// A search or routing domain configured in resolved. Routing-only
+/// domains are written with a leading `~` (`~corp.example`, `~.`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsDomain {
+    pub domain: String,
+    /// Only route queries for the domain to the link, don't search it
+    pub routing_only: bool,
+}
+
+impl fmt::Display for DnsDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.routing_only {
+            f.write_str("~")?;
+        }
+        f.write_str(&self.domain)
+    }
+}
+
+impl FromStr for DnsDomain {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (domain, routing_only) = match s.strip_prefix('~') {
+            Some(domain) => (domain, true),
+            None => (s, false),
+        };
+        // "." only makes sense as a routing domain ("~.": route everything here)
+        let valid = if domain == "." {
+            routing_only
+        } else {
+            !domain.is_empty()
+                && domain.len() <= 253
+                && domain
+                    .trim_end_matches('.')
+                    .split('.')
+                    .all(|label| !label.is_empty() && label.len() <= 63)
+        };
+        if !valid {
+            return Err(Error::Generic(format!("invalid DNS domain '{}'", s)));
+        }
+        Ok(Self {
+            domain: domain.to_string(),
+            routing_only,
+        })
+    }
+}
+
+/// LLMNR and MulticastDNS support on a link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolveMode {
+    Yes,
+    No,
+    /// Resolve names but don't announce the local host
+    Resolve,
+}
+
+impl fmt::Display for ResolveMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::Resolve => "resolve",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for ResolveMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yes" => Ok(Self::Yes),
+            "no" => Ok(Self::No),
+            "resolve" => Ok(Self::Resolve),
+            _ => Err(Error::Generic(format!(
+                "invalid mode '{}' (expected yes, no or resolve)",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DnssecMode {
+    Yes,
+    No,
+    AllowDowngrade,
+}
+
+impl fmt::Display for DnssecMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::AllowDowngrade => "allow-downgrade",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for DnssecMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yes" => Ok(Self::Yes),
+            "no" => Ok(Self::No),
+            "allow-downgrade" => Ok(Self::AllowDowngrade),
+            _ => Err(Error::Generic(format!(
+                "invalid DNSSEC mode '{}' (expected yes, no or allow-downgrade)",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsOverTlsMode {
+    Yes,
+    No,
+    Opportunistic,
+}
+
+impl fmt::Display for DnsOverTlsMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+            Self::Opportunistic => "opportunistic",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for DnsOverTlsMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yes" => Ok(Self::Yes),
+            "no" => Ok(Self::No),
+            "opportunistic" => Ok(Self::Opportunistic),
+            _ => Err(Error::Generic(format!(
+                "invalid DNS-over-TLS mode '{}' (expected yes, no or opportunistic)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Per-link DNS configuration as reported by resolved. Modes are kept as
+/// the strings resolved reports; an empty string means the global setting
+/// applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkDnsSettings {
+    pub ifindex: u32,
+    pub servers: Vec<IpAddr>,
+    pub current_server: Option<IpAddr>,
+    pub domains: Vec<DnsDomain>,
+    pub default_route: bool,
+    pub llmnr: String,
+    pub mdns: String,
+    pub dnssec: String,
+    pub dns_over_tls: String,
+    pub negative_trust_anchors: Vec<String>,
+    pub dnssec_supported: bool,
+}
+
+/// Global DNS configuration as reported by resolved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalDnsSettings {
+    pub servers: Vec<IpAddr>,
+    pub fallback_servers: Vec<IpAddr>,
+    pub current_server: Option<IpAddr>,
+    pub domains: Vec<DnsDomain>,
+    pub llmnr: String,
+    pub mdns: String,
+    pub dnssec: String,
+    pub dns_over_tls: String,
+    pub resolv_conf_mode: String,
+}
+
+/// Counters from resolved's TransactionStatistics, CacheStatistics and
+/// DNSSECStatistics properties
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsStatistics {
+    pub current_transactions: u64,
+    pub total_transactions: u64,
+    pub cache_size: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub dnssec_secure: u64,
+    pub dnssec_insecure: u64,
+    pub dnssec_bogus: u64,
+    pub dnssec_indeterminate: u64,
+}
+
+impl DnsStatistics {
+    /// Share of cache lookups that hit, or `None` before the first lookup
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+
+    /// Answers that failed DNSSEC validation or couldn't be validated
+    pub fn dnssec_failures(&self) -> u64 {
+        self.dnssec_bogus + self.dnssec_indeterminate
+    }
+}
+
+/// Addresses found for a host name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostnameResolution {
+    pub name: String,
+    pub canonical_name: String,
+    pub addresses: Vec<ResolvedAddress>,
+    pub flags: ResolveFlags,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedAddress {
+    /// Link the answer came in on, if it was link-specific
+    pub ifindex: Option<u32>,
+    pub address: IpAddr,
+}
+
+/// Names found for an address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressResolution {
+    pub address: IpAddr,
+    pub names: Vec<ResolvedName>,
+    pub flags: ResolveFlags,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedName {
+    pub ifindex: Option<u32>,
+    pub name: String,
+}
+
+/// Records found by a raw record lookup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordResolution {
+    pub name: String,
+    pub record_type: DnsRecordType,
+    pub records: Vec<DnsRecord>,
+    pub flags: ResolveFlags,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DnsRecord {
+    pub ifindex: Option<u32>,
+    pub name: String,
+    pub ttl: u32,
+    pub data: DnsRecordData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "UPPERCASE")]
+pub enum DnsRecordData {
+    A {
+        address: Ipv4Addr,
+    },
+    Aaaa {
+        address: Ipv6Addr,
+    },
+    Cname {
+        target: String,
+    },
+    Mx {
+        preference: u16,
+        exchange: String,
+    },
+    Ns {
+        target: String,
+    },
+    Ptr {
+        target: String,
+    },
+    Srv {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    Txt {
+        strings: Vec<String>,
+    },
+    #[serde(rename = "OTHER")]
+    Other {
+        rtype: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl fmt::Display for DnsRecordData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::A { address } => write!(f, "A {}", address),
+            Self::Aaaa { address } => write!(f, "AAAA {}", address),
+            Self::Cname { target } => write!(f, "CNAME {}", target),
+            Self::Mx {
+                preference,
+                exchange,
+            } => write!(f, "MX {} {}", preference, exchange),
+            Self::Ns { target } => write!(f, "NS {}", target),
+            Self::Ptr { target } => write!(f, "PTR {}", target),
+            Self::Srv {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "SRV {} {} {} {}", priority, weight, port, target),
+            Self::Txt { strings } => {
+                f.write_str("TXT")?;
+                for s in strings {
+                    write!(f, " {:?}", s)?;
+                }
+                Ok(())
+            }
+            Self::Other { rtype, data } => write!(f, "TYPE{} ({} bytes)", rtype, data.len()),
+        }
+    }
+}
+
+impl DnsRecord {
+    /// Decode a resource record in DNS wire format, as returned by
+    /// resolved's `ResolveRecord`. Names may use compression pointers
+    /// relative to the start of `wire`.
+    pub fn from_wire(ifindex: Option<u32>, wire: &[u8]) -> Result<Self> {
+        let mut reader = WireReader { wire, pos: 0 };
+        let name = reader.name()?;
+        let rtype = reader.u16()?;
+        let _class = reader.u16()?;
+        let ttl = reader.u32()?;
+        let rdlength = reader.u16()? as usize;
+        let rdata_end = reader.pos + rdlength;
+        if rdata_end > wire.len() {
+            return Err(wire_error("truncated rdata"));
+        }
+
+        let data = match rtype {
+            1 => DnsRecordData::A {
+                address: Ipv4Addr::from(
+                    <[u8; 4]>::try_from(reader.bytes(rdlength)?)
+                        .map_err(|_| wire_error("A record must be 4 bytes"))?,
+                ),
+            },
+            28 => DnsRecordData::Aaaa {
+                address: Ipv6Addr::from(
+                    <[u8; 16]>::try_from(reader.bytes(rdlength)?)
+                        .map_err(|_| wire_error("AAAA record must be 16 bytes"))?,
+                ),
+            },
+            2 => DnsRecordData::Ns {
+                target: reader.name()?,
+            },
+            5 => DnsRecordData::Cname {
+                target: reader.name()?,
+            },
+            12 => DnsRecordData::Ptr {
+                target: reader.name()?,
+            },
+            15 => DnsRecordData::Mx {
+                preference: reader.u16()?,
+                exchange: reader.name()?,
+            },
+            33 => DnsRecordData::Srv {
+                priority: reader.u16()?,
+                weight: reader.u16()?,
+                port: reader.u16()?,
+                target: reader.name()?,
+            },
+            16 => {
+                let mut strings = Vec::new();
+                while reader.pos < rdata_end {
+                    let len = reader.u8()? as usize;
+                    strings.push(String::from_utf8_lossy(reader.bytes(len)?).into_owned());
+                }
+                DnsRecordData::Txt { strings }
+            }
+            _ => DnsRecordData::Other {
+                rtype,
+                data: reader.bytes(rdlength)?.to_vec(),
+            },
+        };
+
+        Ok(Self {
+            ifindex,
+            name,
+            ttl,
+            data,
+        })
+    }
+}
+
+fn wire_error(reason: &str) -> Error {
+    Error::Generic(format!("malformed DNS record: {}", reason))
+}
+
+struct WireReader<'a> {
+    wire: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WireReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .wire
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| wire_error("unexpected end of record"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes([self.u8()?, self.u8()?]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes([
+            self.u8()?,
+            self.u8()?,
+            self.u8()?,
+            self.u8()?,
+        ]))
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let mut labels = Vec::new();
+        let mut pos = self.pos;
+        let mut resume = None;
+        // Every pointer must go backwards, which bounds the loop
+        let mut limit = pos;
+
+        loop {
+            let len = *self
+                .wire
+                .get(pos)
+                .ok_or_else(|| wire_error("unexpected end of name"))?
+                as usize;
+            match len {
+                0 => {
+                    pos += 1;
+                    break;
+                }
+                l if l & 0xc0 == 0xc0 => {
+                    let low = *self
+                        .wire
+                        .get(pos + 1)
+                        .ok_or_else(|| wire_error("unexpected end of name"))?;
+                    let target = ((l & 0x3f) << 8) | low as usize;
+                    if target >= limit {
+                        return Err(wire_error("bad compression pointer"));
+                    }
+                    resume.get_or_insert(pos + 2);
+                    limit = target;
+                    pos = target;
+                }
+                l if l < 64 => {
+                    let label = self
+                        .wire
+                        .get(pos + 1..pos + 1 + l)
+                        .ok_or_else(|| wire_error("unexpected end of name"))?;
+                    labels.push(String::from_utf8_lossy(label).into_owned());
+                    pos += 1 + l;
+                }
+                _ => return Err(wire_error("bad label length")),
+            }
+        }
+
+        self.pos = resume.unwrap_or(pos);
+        if labels.is_empty() {
+            Ok(".".to_string())
+        } else {
+            Ok(labels.join("."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_name(name: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+        out.push(0);
+        out
+    }
+
+    fn encode_rr(name: &str, rtype: u16, rdata: &[u8]) -> Vec<u8> {
+        let mut out = encode_name(name);
+        out.extend_from_slice(&rtype.to_be_bytes());
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&300u32.to_be_bytes());
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        out.extend_from_slice(rdata);
+        out
+    }
+
+    #[test]
+    fn test_record_type_parse() {
+        assert_eq!(
+            "aaaa".parse::<DnsRecordType>().unwrap(),
+            DnsRecordType::Aaaa
+        );
+        assert_eq!(DnsRecordType::Srv.code(), 33);
+        assert!("SOA".parse::<DnsRecordType>().is_err());
+    }
+
+    #[test]
+    fn test_dns_domain_parse() {
+        let domain: DnsDomain = "~corp.example".parse().unwrap();
+        assert!(domain.routing_only);
+        assert_eq!(domain.domain, "corp.example");
+        assert_eq!(domain.to_string(), "~corp.example");
+
+        let domain: DnsDomain = "example.com".parse().unwrap();
+        assert!(!domain.routing_only);
+        assert!("~.".parse::<DnsDomain>().unwrap().routing_only);
+
+        assert!(".".parse::<DnsDomain>().is_err());
+        assert!("~".parse::<DnsDomain>().is_err());
+        assert!("a..b".parse::<DnsDomain>().is_err());
+    }
+
+    #[test]
+    fn test_dns_modes_roundtrip() {
+        for mode in ["yes", "no", "allow-downgrade"] {
+            assert_eq!(mode.parse::<DnssecMode>().unwrap().to_string(), mode);
+        }
+        assert_eq!(
+            "resolve".parse::<ResolveMode>().unwrap(),
+            ResolveMode::Resolve
+        );
+        assert!("maybe".parse::<DnsOverTlsMode>().is_err());
+    }
+
+    #[test]
+    fn test_dns_statistics() {
+        let mut stats = DnsStatistics::default();
+        assert_eq!(stats.cache_hit_ratio(), None);
+        stats.cache_hits = 3;
+        stats.cache_misses = 1;
+        assert_eq!(stats.cache_hit_ratio(), Some(0.75));
+        stats.dnssec_bogus = 2;
+        stats.dnssec_indeterminate = 1;
+        assert_eq!(stats.dnssec_failures(), 3);
+    }
+
+    #[test]
+    fn test_resolve_flags() {
+        let flags = ResolveFlags::from((1 << 0) | (1 << 9) | (1 << 20));
+        assert!(flags.authenticated);
+        assert_eq!(flags.protocols(), vec!["DNS"]);
+        assert_eq!(flags.sources(), vec!["cache"]);
+    }
+
+    #[test]
+    fn test_record_from_wire() {
+        let a = encode_rr("example.com", 1, &[93, 184, 216, 34]);
+        let record = DnsRecord::from_wire(Some(2), &a).unwrap();
+        assert_eq!(record.name, "example.com");
+        assert_eq!(record.ttl, 300);
+        assert_eq!(
+            record.data,
+            DnsRecordData::A {
+                address: Ipv4Addr::new(93, 184, 216, 34)
+            }
+        );
+
+        // MX exchange compressed against the owner name at offset 0
+        let mut rdata = 10u16.to_be_bytes().to_vec();
+        rdata.extend_from_slice(&[4, b'm', b'a', b'i', b'l', 0xc0, 0x00]);
+        let mx = encode_rr("example.com", 15, &rdata);
+        let record = DnsRecord::from_wire(None, &mx).unwrap();
+        assert_eq!(record.data.to_string(), "MX 10 mail.example.com");
+
+        let txt = encode_rr("example.com", 16, b"\x05hello\x05world");
+        let record = DnsRecord::from_wire(None, &txt).unwrap();
+        assert_eq!(record.data.to_string(), "TXT \"hello\" \"world\"");
+
+        let looped = encode_rr("example.com", 5, &[0xc0, 0x20]);
+        assert!(DnsRecord::from_wire(None, &looped).is_err());
+        assert!(DnsRecord::from_wire(None, &a[..a.len() - 1]).is_err());
+    }
+}
@@ -10,18 +10,54 @@ pub enum Error {
     #[diagnostic(code(netctl::not_found), help("Use 'netctl show' to list interfaces"))]
     InterfaceNotFound { name: String },
 
+    #[error("refusing to delete physical interface '{name}'")]
+    #[diagnostic(
+        code(netctl::physical_interface),
+        help("Only virtual links can be deleted; pass --force to override")
+    )]
+    PhysicalInterface { name: String },
+
     #[error("invalid CIDR: {input}")]
     InvalidCidr { input: String },
 
     #[error("invalid MAC: {input}")]
     InvalidMacAddress { input: String },
 
+    #[error("invalid address {address}: {reason}")]
+    #[diagnostic(
+        code(netctl::invalid_address),
+        help("See 'netctl addr add --help' for supported attribute combinations")
+    )]
+    InvalidAddress { address: String, reason: String },
+
     #[error("netlink error: {0}")]
     Netlink(String),
 
     #[error("D-Bus error: {0}")]
     Dbus(String),
 
+    #[error("not authorized to {action}: {reason}")]
+    #[diagnostic(
+        code(netctl::polkit_denied),
+        help(
+            "polkit refused the request. Run the command as root (sudo), run it from a \
+             terminal without --no-ask-password so polkit can ask for a password, or grant \
+             the action to your user with a polkit rule"
+        )
+    )]
+    PolkitDenied { action: String, reason: String },
+
+    #[error("{service} is not available: {reason}")]
+    #[diagnostic(
+        code(netctl::service_unavailable),
+        help(
+            "Link, address and MPTCP commands only need netlink and keep working. DNS, DHCP, \
+             hostname and service commands need the D-Bus system bus and the systemd service \
+             behind them; 'netctl doctor' shows what is missing"
+        )
+    )]
+    ServiceUnavailable { service: String, reason: String },
+
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
 
@@ -37,6 +73,13 @@ impl Error {
     pub fn dbus(msg: impl Into<String>) -> Self {
         Self::Dbus(msg.into())
     }
+
+    pub fn unavailable(service: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::ServiceUnavailable {
+            service: service.into(),
+            reason: reason.into(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -67,6 +110,16 @@ mod tests {
         assert!(err.to_string().contains("zz:bb:cc"));
     }
 
+    #[test]
+    fn test_error_invalid_address() {
+        let err = Error::InvalidAddress {
+            address: "10.0.0.1/24".to_string(),
+            reason: "broadcast is IPv4 only".to_string(),
+        };
+        assert!(err.to_string().contains("10.0.0.1/24"));
+        assert!(err.to_string().contains("broadcast"));
+    }
+
     #[test]
     fn test_error_netlink() {
         let err = Error::netlink("connection failed");
@@ -79,6 +132,29 @@ mod tests {
         assert!(err.to_string().contains("method call failed"));
     }
 
+    #[test]
+    fn test_error_polkit_denied() {
+        let err = Error::PolkitDenied {
+            action: "set DNS servers".to_string(),
+            reason: "Interactive authentication required.".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "not authorized to set DNS servers: Interactive authentication required."
+        );
+        assert!(err.help().is_some());
+    }
+
+    #[test]
+    fn test_error_service_unavailable() {
+        let err = Error::unavailable("D-Bus system bus", "No such file or directory");
+        assert_eq!(
+            err.to_string(),
+            "D-Bus system bus is not available: No such file or directory"
+        );
+        assert!(err.help().is_some());
+    }
+
     #[test]
     fn test_error_io_from() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
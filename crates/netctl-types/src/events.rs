@@ -0,0 +1,345 @@
+//! Network events from the kernel (rtnetlink multicast groups) and from
+//! systemd-networkd/resolved (D-Bus PropertiesChanged), merged into one
+//! timeline.
+
+use crate::network::{IpNetwork, LinkState};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+/// Something that changed, stamped with the time it was received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkEvent {
+    pub timestamp: SystemTime,
+    pub kind: NetworkEventKind,
+}
+
+impl NetworkEvent {
+    pub fn now(kind: NetworkEventKind) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            kind,
+        }
+    }
+
+    pub fn source(&self) -> EventSource {
+        self.kind.source()
+    }
+
+    /// Interface the event is about, if it is about one
+    pub fn ifindex(&self) -> Option<u32> {
+        match self.kind {
+            NetworkEventKind::LinkChanged { index, .. }
+            | NetworkEventKind::LinkRemoved { index, .. }
+            | NetworkEventKind::AddressAdded { index, .. }
+            | NetworkEventKind::AddressRemoved { index, .. }
+            | NetworkEventKind::RouteAdded { index, .. }
+            | NetworkEventKind::RouteRemoved { index, .. }
+            | NetworkEventKind::NetworkdLinkChanged { index, .. } => Some(index),
+            NetworkEventKind::ResolvedChanged { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSource {
+    Kernel,
+    Networkd,
+    Resolved,
+}
+
+impl fmt::Display for EventSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Kernel => "kernel",
+            Self::Networkd => "networkd",
+            Self::Resolved => "resolved",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum NetworkEventKind {
+    /// RTM_NEWLINK: a link appeared or its flags, MTU or name changed
+    LinkChanged {
+        index: u32,
+        name: String,
+        state: LinkState,
+        mtu: u32,
+    },
+    /// RTM_DELLINK
+    LinkRemoved { index: u32, name: String },
+    /// RTM_NEWADDR
+    AddressAdded { index: u32, address: IpNetwork },
+    /// RTM_DELADDR
+    AddressRemoved { index: u32, address: IpNetwork },
+    /// RTM_NEWROUTE for a unicast route in the main table
+    RouteAdded {
+        index: u32,
+        destination: IpNetwork,
+        gateway: Option<IpAddr>,
+    },
+    /// RTM_DELROUTE for a unicast route in the main table
+    RouteRemoved {
+        index: u32,
+        destination: IpNetwork,
+        gateway: Option<IpAddr>,
+    },
+    /// A property of an org.freedesktop.network1.Link object changed
+    NetworkdLinkChanged {
+        index: u32,
+        property: NetworkdLinkProperty,
+        value: String,
+    },
+    /// A property of the org.freedesktop.resolve1.Manager changed. `value`
+    /// is set for string-valued properties; re-read the settings for the
+    /// others.
+    ResolvedChanged {
+        property: ResolvedProperty,
+        value: Option<String>,
+    },
+}
+
+impl NetworkEventKind {
+    pub fn source(&self) -> EventSource {
+        match self {
+            Self::LinkChanged { .. }
+            | Self::LinkRemoved { .. }
+            | Self::AddressAdded { .. }
+            | Self::AddressRemoved { .. }
+            | Self::RouteAdded { .. }
+            | Self::RouteRemoved { .. } => EventSource::Kernel,
+            Self::NetworkdLinkChanged { .. } => EventSource::Networkd,
+            Self::ResolvedChanged { .. } => EventSource::Resolved,
+        }
+    }
+}
+
+/// What happened, without the interface it happened to (see
+/// [`NetworkEvent::ifindex`])
+impl fmt::Display for NetworkEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LinkChanged { state, mtu, .. } => {
+                let state = match state {
+                    LinkState::Up => "up",
+                    LinkState::Down => "down",
+                };
+                write!(f, "link {} mtu {}", state, mtu)
+            }
+            Self::LinkRemoved { .. } => f.write_str("link removed"),
+            Self::AddressAdded { address, .. } => write!(f, "address {} added", address),
+            Self::AddressRemoved { address, .. } => write!(f, "address {} removed", address),
+            Self::RouteAdded {
+                destination,
+                gateway,
+                ..
+            } => write!(f, "route {}{} added", destination, via(gateway)),
+            Self::RouteRemoved {
+                destination,
+                gateway,
+                ..
+            } => write!(f, "route {}{} removed", destination, via(gateway)),
+            Self::NetworkdLinkChanged {
+                property, value, ..
+            } => write!(f, "{} {}", property, value),
+            Self::ResolvedChanged { property, value } => match value {
+                Some(value) => write!(f, "{} {}", property, value),
+                None => write!(f, "{} changed", property),
+            },
+        }
+    }
+}
+
+fn via(gateway: &Option<IpAddr>) -> String {
+    gateway
+        .map(|gateway| format!(" via {}", gateway))
+        .unwrap_or_default()
+}
+
+/// Link properties networkd announces changes of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkdLinkProperty {
+    OperationalState,
+    CarrierState,
+    AddressState,
+    Ipv4AddressState,
+    Ipv6AddressState,
+    OnlineState,
+    /// Setup state: pending, configuring, configured, failed, unmanaged, ...
+    AdministrativeState,
+}
+
+impl NetworkdLinkProperty {
+    pub fn from_dbus_name(name: &str) -> Option<Self> {
+        match name {
+            "OperationalState" => Some(Self::OperationalState),
+            "CarrierState" => Some(Self::CarrierState),
+            "AddressState" => Some(Self::AddressState),
+            "IPv4AddressState" => Some(Self::Ipv4AddressState),
+            "IPv6AddressState" => Some(Self::Ipv6AddressState),
+            "OnlineState" => Some(Self::OnlineState),
+            "AdministrativeState" => Some(Self::AdministrativeState),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NetworkdLinkProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::OperationalState => "operational",
+            Self::CarrierState => "carrier",
+            Self::AddressState => "address",
+            Self::Ipv4AddressState => "ipv4-address",
+            Self::Ipv6AddressState => "ipv6-address",
+            Self::OnlineState => "online",
+            Self::AdministrativeState => "setup",
+        };
+        f.write_str(s)
+    }
+}
+
+/// resolved Manager properties worth reacting to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResolvedProperty {
+    Dns,
+    FallbackDns,
+    CurrentDnsServer,
+    Domains,
+    Llmnr,
+    MulticastDns,
+    Dnssec,
+    DnsOverTls,
+    ResolvConfMode,
+}
+
+impl ResolvedProperty {
+    /// Map a D-Bus property name; the `...Ex` variants fold into their
+    /// plain counterparts
+    pub fn from_dbus_name(name: &str) -> Option<Self> {
+        match name {
+            "DNS" | "DNSEx" => Some(Self::Dns),
+            "FallbackDNS" | "FallbackDNSEx" => Some(Self::FallbackDns),
+            "CurrentDNSServer" | "CurrentDNSServerEx" => Some(Self::CurrentDnsServer),
+            "Domains" => Some(Self::Domains),
+            "LLMNR" => Some(Self::Llmnr),
+            "MulticastDNS" => Some(Self::MulticastDns),
+            "DNSSEC" => Some(Self::Dnssec),
+            "DNSOverTLS" => Some(Self::DnsOverTls),
+            "ResolvConfMode" => Some(Self::ResolvConfMode),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ResolvedProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Dns => "dns-servers",
+            Self::FallbackDns => "fallback-dns-servers",
+            Self::CurrentDnsServer => "current-dns-server",
+            Self::Domains => "domains",
+            Self::Llmnr => "llmnr",
+            Self::MulticastDns => "mdns",
+            Self::Dnssec => "dnssec",
+            Self::DnsOverTls => "dns-over-tls",
+            Self::ResolvConfMode => "resolv-conf-mode",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Decode the interface index from a networkd link object path such as
+/// `/org/freedesktop/network1/link/_32` (bus-label escaped "2")
+pub fn ifindex_from_link_path(path: &str) -> Option<u32> {
+    let label = path.strip_prefix("/org/freedesktop/network1/link/")?;
+    bus_label_unescape(label)?.parse().ok()
+}
+
+/// Reverse sd-bus label escaping: `_xx` is the byte with hex value xx
+fn bus_label_unescape(label: &str) -> Option<String> {
+    let bytes = label.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'_' {
+            let hex = label.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ifindex_from_link_path() {
+        assert_eq!(
+            ifindex_from_link_path("/org/freedesktop/network1/link/_32"),
+            Some(2)
+        );
+        assert_eq!(
+            ifindex_from_link_path("/org/freedesktop/network1/link/_3123"),
+            Some(123)
+        );
+        assert_eq!(
+            ifindex_from_link_path("/org/freedesktop/network1/link/_3"),
+            None
+        );
+        assert_eq!(ifindex_from_link_path("/org/freedesktop/network1"), None);
+    }
+
+    #[test]
+    fn test_event_properties() {
+        assert_eq!(
+            NetworkdLinkProperty::from_dbus_name("IPv4AddressState"),
+            Some(NetworkdLinkProperty::Ipv4AddressState)
+        );
+        assert_eq!(NetworkdLinkProperty::from_dbus_name("BitRates"), None);
+        assert_eq!(
+            ResolvedProperty::from_dbus_name("CurrentDNSServerEx"),
+            Some(ResolvedProperty::CurrentDnsServer)
+        );
+
+        let event = NetworkEvent::now(NetworkEventKind::NetworkdLinkChanged {
+            index: 2,
+            property: NetworkdLinkProperty::AdministrativeState,
+            value: "configured".to_string(),
+        });
+        assert_eq!(event.source(), EventSource::Networkd);
+        assert_eq!(event.ifindex(), Some(2));
+        assert_eq!(event.kind.to_string(), "setup configured");
+
+        let json = serde_json::to_value(&event.kind).unwrap();
+        assert_eq!(json["event"], "networkd-link-changed");
+        assert_eq!(json["property"], "administrative-state");
+    }
+
+    #[test]
+    fn test_route_event() {
+        let event = NetworkEvent::now(NetworkEventKind::RouteAdded {
+            index: 3,
+            destination: "0.0.0.0/0".parse().unwrap(),
+            gateway: Some("192.0.2.1".parse().unwrap()),
+        });
+        assert_eq!(event.source(), EventSource::Kernel);
+        assert_eq!(event.ifindex(), Some(3));
+        assert_eq!(
+            event.kind.to_string(),
+            "route 0.0.0.0/0 via 192.0.2.1 added"
+        );
+    }
+}
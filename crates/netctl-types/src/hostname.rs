@@ -0,0 +1,181 @@
+//! Host identity as exposed by systemd-hostnamed.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Maximum hostname length (HOST_NAME_MAX)
+pub const HOSTNAME_MAX_LEN: usize = 64;
+
+/// Check `name` against the rules hostnamed applies to static and transient
+/// hostnames: dot-separated labels of ASCII letters, digits and '-'
+pub fn validate_hostname(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::Generic("hostname must not be empty".to_string()));
+    }
+    if name.len() > HOSTNAME_MAX_LEN {
+        return Err(Error::Generic(format!(
+            "hostname '{}' is longer than {} characters",
+            name, HOSTNAME_MAX_LEN
+        )));
+    }
+    if name.split('.').any(str::is_empty) {
+        return Err(Error::Generic(format!(
+            "hostname '{}' has an empty label",
+            name
+        )));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '-' || c == '.'))
+    {
+        return Err(Error::Generic(format!(
+            "hostname '{}' contains invalid character '{}'",
+            name, c
+        )));
+    }
+    Ok(())
+}
+
+/// Like [`validate_hostname`], but an empty name is accepted: hostnamed
+/// clears the static hostname when given one
+pub fn validate_static_hostname(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Ok(());
+    }
+    validate_hostname(name)
+}
+
+/// Pretty hostnames are free-form but must not contain control characters
+pub fn validate_pretty_hostname(name: &str) -> Result<()> {
+    if name.chars().any(char::is_control) {
+        return Err(Error::Generic(format!(
+            "pretty hostname '{}' contains control characters",
+            name.escape_debug()
+        )));
+    }
+    Ok(())
+}
+
+/// Chassis types hostnamed accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chassis {
+    Desktop,
+    Laptop,
+    Convertible,
+    Server,
+    Tablet,
+    Handset,
+    Watch,
+    Embedded,
+    Vm,
+    Container,
+}
+
+impl fmt::Display for Chassis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Desktop => "desktop",
+            Self::Laptop => "laptop",
+            Self::Convertible => "convertible",
+            Self::Server => "server",
+            Self::Tablet => "tablet",
+            Self::Handset => "handset",
+            Self::Watch => "watch",
+            Self::Embedded => "embedded",
+            Self::Vm => "vm",
+            Self::Container => "container",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Chassis {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "desktop" => Ok(Self::Desktop),
+            "laptop" => Ok(Self::Laptop),
+            "convertible" => Ok(Self::Convertible),
+            "server" => Ok(Self::Server),
+            "tablet" => Ok(Self::Tablet),
+            "handset" => Ok(Self::Handset),
+            "watch" => Ok(Self::Watch),
+            "embedded" => Ok(Self::Embedded),
+            "vm" => Ok(Self::Vm),
+            "container" => Ok(Self::Container),
+            _ => Err(Error::Generic(format!(
+                "invalid chassis '{}' (expected desktop, laptop, convertible, server, tablet, \
+                 handset, watch, embedded, vm or container)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Everything hostnamed knows about the host. Fields are `None` when unset
+/// or when the running hostnamed is too old to expose them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostnameInfo {
+    /// Transient (kernel) hostname
+    pub hostname: String,
+    pub static_hostname: Option<String>,
+    pub pretty_hostname: Option<String>,
+    /// Name used when no static hostname is configured
+    pub default_hostname: Option<String>,
+    /// Where the transient hostname came from: static, transient or default
+    pub hostname_source: Option<String>,
+    pub icon_name: Option<String>,
+    pub chassis: Option<String>,
+    pub deployment: Option<String>,
+    pub location: Option<String>,
+    pub kernel_name: Option<String>,
+    pub kernel_release: Option<String>,
+    pub kernel_version: Option<String>,
+    pub operating_system: Option<String>,
+    pub operating_system_cpe_name: Option<String>,
+    pub operating_system_home_url: Option<String>,
+    pub hardware_vendor: Option<String>,
+    pub hardware_model: Option<String>,
+    pub firmware_version: Option<String>,
+    pub machine_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_hostname() {
+        assert!(validate_hostname("myserver").is_ok());
+        assert!(validate_hostname("web-01.example-lab.com").is_ok());
+        assert!(validate_hostname("web_01").is_err());
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname(".leading").is_err());
+        assert!(validate_hostname("trailing.").is_err());
+        assert!(validate_hostname("a..b").is_err());
+        assert!(validate_hostname("has space").is_err());
+        assert!(validate_hostname("ünicode").is_err());
+        assert!(validate_hostname(&"a".repeat(65)).is_err());
+        assert!(validate_hostname(&"a".repeat(64)).is_ok());
+
+        assert!(validate_static_hostname("").is_ok());
+        assert!(validate_static_hostname("myserver").is_ok());
+        assert!(validate_static_hostname("web_01").is_err());
+
+        assert!(validate_pretty_hostname("Ada's Laptop").is_ok());
+        assert!(validate_pretty_hostname("tab\there").is_err());
+    }
+
+    #[test]
+    fn test_chassis_parse() {
+        for chassis in ["desktop", "vm", "container", "handset"] {
+            assert_eq!(chassis.parse::<Chassis>().unwrap().to_string(), chassis);
+        }
+        assert!("mainframe".parse::<Chassis>().is_err());
+        assert!("VM".parse::<Chassis>().is_err());
+    }
+}
@@ -1,10 +1,40 @@
 //! Core types for netctl
 
+pub mod dns;
 pub mod error;
+pub mod events;
+pub mod hostname;
 pub mod logging;
 pub mod network;
+pub mod networkd;
+pub mod nm;
+pub mod online;
 pub mod traits;
+pub mod unit;
 
+pub use dns::{
+    AddressResolution, DnsDomain, DnsOverTlsMode, DnsRecord, DnsRecordData, DnsRecordType,
+    DnsStatistics, DnssecMode, GlobalDnsSettings, HostnameResolution, LinkDnsSettings,
+    RecordResolution, ResolveFlags, ResolveMode,
+};
 pub use error::{Error, Result};
-pub use network::{DhcpMode, IpNetwork, LinkInfo, LinkState, MacAddress, Route};
+pub use events::{
+    EventSource, NetworkEvent, NetworkEventKind, NetworkdLinkProperty, ResolvedProperty,
+};
+pub use hostname::{
+    validate_hostname, validate_pretty_hostname, validate_static_hostname, Chassis, HostnameInfo,
+};
+pub use network::{
+    validate_altname, validate_ifname, AddressFlag, AddressScope, AddressSpec, DhcpMode, IpNetwork,
+    LinkInfo, LinkKind, LinkState, MacAddress, MptcpEndpoint, MptcpEndpointFlag, MptcpEndpointSpec,
+    MptcpLimits, NetworkdLinkState, Route, VfLinkState, VfSettings, VirtualFunction,
+};
+pub use networkd::{Dhcp4Lease, LinkDescription, NetworkdDescription};
+pub use nm::{
+    LinkBackend, NmActiveConnection, NmConnectionState, NmDevice, NmDeviceState, NmIpConfig,
+};
+pub use online::{
+    InterfaceRequirement, LinkReadiness, OperationalState, WaitOnlineSpec, WaitOnlineStatus,
+};
 pub use traits::NetworkDevice;
+pub use unit::{ServiceUnit, UnitStatus};
@@ -1,10 +1,12 @@
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::str::FromStr;
 
+/// Which DHCP clients to run, as networkd's `DHCP=`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
 pub enum DhcpMode {
     #[default]
     No,
@@ -13,6 +15,27 @@ pub enum DhcpMode {
     Ipv6,
 }
 
+impl DhcpMode {
+    pub fn ipv4(self) -> bool {
+        matches!(self, Self::Yes | Self::Ipv4)
+    }
+
+    pub fn ipv6(self) -> bool {
+        matches!(self, Self::Yes | Self::Ipv6)
+    }
+}
+
+impl fmt::Display for DhcpMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::No => "no",
+            Self::Yes => "yes",
+            Self::Ipv4 => "ipv4",
+            Self::Ipv6 => "ipv6",
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IpNetwork {
     pub addr: IpAddr,
@@ -108,20 +131,606 @@ pub enum LinkState {
     Down,
 }
 
+/// Virtual link kinds netctl can create
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Dummy,
+    Ifb,
+    Nlmon,
+}
+
+impl fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Dummy => "dummy",
+            Self::Ifb => "ifb",
+            Self::Nlmon => "nlmon",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for LinkKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dummy" => Ok(Self::Dummy),
+            "ifb" => Ok(Self::Ifb),
+            "nlmon" => Ok(Self::Nlmon),
+            _ => Err(Error::Generic(format!(
+                "unsupported link kind '{}' (expected dummy, ifb or nlmon)",
+                s
+            ))),
+        }
+    }
+}
+
+/// The link is a loopback device (IFF_LOOPBACK)
+pub const IFF_LOOPBACK: u32 = 0x8;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkInfo {
     pub index: u32,
     pub name: String,
     pub state: LinkState,
+    /// Lower layer up (IFF_LOWER_UP), i.e. the link has carrier
+    #[serde(default)]
+    pub carrier: bool,
+    /// Interface flags (IFF_*) from the link's ifinfomsg
+    #[serde(default)]
+    pub flags: u32,
     pub mtu: u32,
     pub mac_address: Option<MacAddress>,
+    /// Burned-in hardware address (IFLA_PERM_ADDRESS), if the driver reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permanent_mac_address: Option<MacAddress>,
+    /// Alternative names (IFLA_ALT_IFNAME)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub altnames: Vec<String>,
     pub addresses: Vec<IpNetwork>,
+    /// Kernel link kind (IFLA_INFO_KIND); `None` for physical devices and loopback
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// SR-IOV virtual functions, populated when querying a single link
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vfs: Vec<VirtualFunction>,
+}
+
+impl LinkInfo {
+    pub fn is_loopback(&self) -> bool {
+        self.flags & IFF_LOOPBACK != 0
+    }
+}
+
+/// systemd-networkd's view of a link (org.freedesktop.network1.Link).
+///
+/// States are kept as the strings networkd reports, since new values are
+/// added between systemd releases.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct NetworkdLinkState {
+    /// Setup state: pending, initialized, configuring, configured, unmanaged, failed or linger
+    pub administrative_state: String,
+    pub operational_state: String,
+    pub carrier_state: String,
+    pub address_state: String,
+    pub ipv4_address_state: String,
+    pub ipv6_address_state: String,
+    pub online_state: String,
+}
+
+impl NetworkdLinkState {
+    /// Whether a .network file matched the link
+    pub fn is_managed(&self) -> bool {
+        !matches!(self.administrative_state.as_str(), "" | "unmanaged")
+    }
+}
+
+/// Administrative link state of an SR-IOV virtual function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VfLinkState {
+    Auto,
+    Enable,
+    Disable,
+}
+
+impl fmt::Display for VfLinkState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Auto => "auto",
+            Self::Enable => "enable",
+            Self::Disable => "disable",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for VfLinkState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "enable" => Ok(Self::Enable),
+            "disable" => Ok(Self::Disable),
+            _ => Err(Error::Generic(format!(
+                "invalid VF link state '{}' (expected auto, enable or disable)",
+                s
+            ))),
+        }
+    }
+}
+
+/// An SR-IOV virtual function as reported in IFLA_VFINFO_LIST.
+///
+/// Rates are in Mbps; 0 means unlimited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VirtualFunction {
+    pub id: u32,
+    pub mac_address: Option<MacAddress>,
+    pub vlan: u16,
+    pub qos: u8,
+    pub spoof_check: Option<bool>,
+    pub trust: Option<bool>,
+    pub link_state: Option<VfLinkState>,
+    pub min_tx_rate: u32,
+    pub max_tx_rate: u32,
+}
+
+/// Changes to apply to a virtual function; `None` leaves a setting untouched
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VfSettings {
+    pub mac_address: Option<MacAddress>,
+    pub vlan: Option<u16>,
+    pub qos: Option<u8>,
+    pub spoof_check: Option<bool>,
+    pub trust: Option<bool>,
+    pub link_state: Option<VfLinkState>,
+    pub min_tx_rate: Option<u32>,
+    pub max_tx_rate: Option<u32>,
+}
+
+impl VfSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(mac) = self.mac_address {
+            if mac.octets()[0] & 0x01 != 0 {
+                return Err(Error::Generic(format!(
+                    "VF MAC {} is a multicast address",
+                    mac
+                )));
+            }
+        }
+        if let Some(vlan) = self.vlan {
+            if vlan > 4094 {
+                return Err(Error::Generic(format!(
+                    "VF VLAN {} is out of range (0-4094)",
+                    vlan
+                )));
+            }
+        }
+        if let Some(qos) = self.qos {
+            if qos > 7 {
+                return Err(Error::Generic(format!(
+                    "VF QoS {} is out of range (0-7)",
+                    qos
+                )));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_tx_rate, self.max_tx_rate) {
+            if max != 0 && min > max {
+                return Err(Error::Generic(format!(
+                    "VF min tx rate {} exceeds max tx rate {}",
+                    min, max
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Route {
+    /// `None` or a /0 network for the default route
     pub destination: Option<IpNetwork>,
     pub gateway: Option<IpAddr>,
+    /// Outgoing interface (RTA_OIF)
+    #[serde(default)]
+    pub index: Option<u32>,
+}
+
+impl Route {
+    pub fn is_default(&self) -> bool {
+        self.destination.map_or(true, |dest| dest.prefix_len == 0)
+    }
+
+    /// Address family, if the destination or gateway tells
+    pub fn is_ipv6(&self) -> Option<bool> {
+        self.destination
+            .map(|dest| dest.addr)
+            .or(self.gateway)
+            .map(|addr| addr.is_ipv6())
+    }
+}
+
+/// Maximum interface name length, excluding the trailing NUL (IFNAMSIZ - 1)
+pub const IFNAME_MAX_LEN: usize = 15;
+
+/// Maximum alternative name length, excluding the trailing NUL (ALTIFNAMSIZ - 1)
+pub const ALTNAME_MAX_LEN: usize = 127;
+
+/// Check `name` against the kernel's rules for interface names (dev_valid_name)
+pub fn validate_ifname(name: &str) -> Result<()> {
+    validate_name(name, IFNAME_MAX_LEN, "interface name")
+}
+
+/// Like [`validate_ifname`], but with the longer limit for alternative names
+pub fn validate_altname(name: &str) -> Result<()> {
+    validate_name(name, ALTNAME_MAX_LEN, "alternative name")
+}
+
+fn validate_name(name: &str, max_len: usize, what: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(Error::Generic(format!("invalid {} '{}'", what, name)));
+    }
+    if name.len() > max_len {
+        return Err(Error::Generic(format!(
+            "{} '{}' is longer than {} characters",
+            what, name, max_len
+        )));
+    }
+    if name
+        .chars()
+        .any(|c| c == '/' || c == ':' || c.is_whitespace())
+    {
+        return Err(Error::Generic(format!(
+            "{} '{}' must not contain '/', ':' or whitespace",
+            what, name
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressScope {
+    Global,
+    Site,
+    Link,
+    Host,
+}
+
+impl fmt::Display for AddressScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Global => "global",
+            Self::Site => "site",
+            Self::Link => "link",
+            Self::Host => "host",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for AddressScope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "global" | "universe" => Ok(Self::Global),
+            "site" => Ok(Self::Site),
+            "link" => Ok(Self::Link),
+            "host" => Ok(Self::Host),
+            _ => Err(Error::Generic(format!(
+                "invalid address scope '{}' (expected global, site, link or host)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Address flags that can be requested when an address is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFlag {
+    NoPrefixRoute,
+    NoDad,
+    Home,
+    MngTmpAddr,
+    Optimistic,
+}
+
+impl AddressFlag {
+    /// Whether the kernel only honours this flag for IPv6 addresses
+    pub fn is_ipv6_only(&self) -> bool {
+        !matches!(self, Self::NoPrefixRoute)
+    }
+}
+
+impl fmt::Display for AddressFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::NoPrefixRoute => "noprefixroute",
+            Self::NoDad => "nodad",
+            Self::Home => "home",
+            Self::MngTmpAddr => "mngtmpaddr",
+            Self::Optimistic => "optimistic",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for AddressFlag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "noprefixroute" => Ok(Self::NoPrefixRoute),
+            "nodad" => Ok(Self::NoDad),
+            "home" => Ok(Self::Home),
+            "mngtmpaddr" => Ok(Self::MngTmpAddr),
+            "optimistic" => Ok(Self::Optimistic),
+            _ => Err(Error::Generic(format!(
+                "invalid address flag '{}' (expected noprefixroute, nodad, home, mngtmpaddr or optimistic)",
+                s
+            ))),
+        }
+    }
+}
+
+/// An address together with the optional attributes used when creating it.
+///
+/// Lifetimes are in seconds; `None` means forever.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressSpec {
+    pub network: IpNetwork,
+    pub peer: Option<IpAddr>,
+    pub broadcast: Option<Ipv4Addr>,
+    pub label: Option<String>,
+    pub scope: Option<AddressScope>,
+    pub valid_lifetime: Option<u32>,
+    pub preferred_lifetime: Option<u32>,
+    pub flags: Vec<AddressFlag>,
+}
+
+impl AddressSpec {
+    pub fn new(network: IpNetwork) -> Self {
+        Self {
+            network,
+            peer: None,
+            broadcast: None,
+            label: None,
+            scope: None,
+            valid_lifetime: None,
+            preferred_lifetime: None,
+            flags: Vec::new(),
+        }
+    }
+
+    pub fn has_flag(&self, flag: AddressFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Check that the attributes form a combination the kernel will accept
+    /// for an address on `ifname`.
+    pub fn validate(&self, ifname: &str) -> Result<()> {
+        let invalid = |reason: String| Error::InvalidAddress {
+            address: self.network.to_string(),
+            reason,
+        };
+        let is_ipv4 = self.network.addr.is_ipv4();
+
+        let max_prefix = if is_ipv4 { 32 } else { 128 };
+        if self.network.prefix_len > max_prefix {
+            return Err(invalid(format!("prefix length must be 0-{}", max_prefix)));
+        }
+
+        if let Some(peer) = self.peer {
+            if peer.is_ipv4() != is_ipv4 {
+                return Err(invalid(format!(
+                    "peer {} is not the same address family",
+                    peer
+                )));
+            }
+            if self.broadcast.is_some() {
+                return Err(invalid(
+                    "broadcast cannot be combined with a point-to-point peer".to_string(),
+                ));
+            }
+        }
+
+        if self.broadcast.is_some() && !is_ipv4 {
+            return Err(invalid("broadcast is only valid for IPv4".to_string()));
+        }
+
+        if let Some(ref label) = self.label {
+            if !is_ipv4 {
+                return Err(invalid("labels are only valid for IPv4".to_string()));
+            }
+            if label.len() > IFNAME_MAX_LEN {
+                return Err(invalid(format!(
+                    "label '{}' is too long (max {} characters)",
+                    label, IFNAME_MAX_LEN
+                )));
+            }
+            if !label.starts_with(ifname) {
+                return Err(invalid(format!(
+                    "label '{}' must start with the interface name '{}'",
+                    label, ifname
+                )));
+            }
+        }
+
+        if self.valid_lifetime == Some(0) {
+            return Err(invalid("valid lifetime must be greater than 0".to_string()));
+        }
+        if let (Some(valid), Some(preferred)) = (self.valid_lifetime, self.preferred_lifetime) {
+            if preferred > valid {
+                return Err(invalid(format!(
+                    "preferred lifetime {}s exceeds valid lifetime {}s",
+                    preferred, valid
+                )));
+            }
+        }
+
+        if is_ipv4 {
+            if let Some(flag) = self.flags.iter().find(|f| f.is_ipv6_only()) {
+                return Err(invalid(format!("flag '{}' is only valid for IPv6", flag)));
+            }
+        }
+        if self.has_flag(AddressFlag::NoDad) && self.has_flag(AddressFlag::Optimistic) {
+            return Err(invalid(
+                "'nodad' and 'optimistic' are mutually exclusive".to_string(),
+            ));
+        }
+        if self.has_flag(AddressFlag::MngTmpAddr) && self.network.prefix_len != 64 {
+            return Err(invalid("'mngtmpaddr' requires a /64 prefix".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<IpNetwork> for AddressSpec {
+    fn from(network: IpNetwork) -> Self {
+        Self::new(network)
+    }
+}
+
+/// Flags of an MPTCP path-manager endpoint (`MPTCP_PM_ADDR_FLAG_*`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MptcpEndpointFlag {
+    Signal,
+    Subflow,
+    Backup,
+    Fullmesh,
+    /// Set by the kernel on endpoints it created for incoming subflows
+    Implicit,
+}
+
+impl fmt::Display for MptcpEndpointFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Signal => "signal",
+            Self::Subflow => "subflow",
+            Self::Backup => "backup",
+            Self::Fullmesh => "fullmesh",
+            Self::Implicit => "implicit",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for MptcpEndpointFlag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "signal" => Ok(Self::Signal),
+            "subflow" => Ok(Self::Subflow),
+            "backup" => Ok(Self::Backup),
+            "fullmesh" => Ok(Self::Fullmesh),
+            _ => Err(Error::Generic(format!(
+                "invalid MPTCP endpoint flag '{}' (expected signal, subflow, backup or fullmesh)",
+                s
+            ))),
+        }
+    }
+}
+
+/// An MPTCP endpoint as reported by the in-kernel path manager
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MptcpEndpoint {
+    pub id: u8,
+    pub address: IpAddr,
+    pub port: Option<u16>,
+    pub ifindex: Option<u32>,
+    pub flags: Vec<MptcpEndpointFlag>,
+}
+
+/// An MPTCP endpoint to create. The kernel picks an id when `id` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MptcpEndpointSpec {
+    pub address: IpAddr,
+    pub id: Option<u8>,
+    pub port: Option<u16>,
+    pub interface: Option<String>,
+    pub flags: Vec<MptcpEndpointFlag>,
+}
+
+impl MptcpEndpointSpec {
+    pub fn new(address: IpAddr) -> Self {
+        Self {
+            address,
+            id: None,
+            port: None,
+            interface: None,
+            flags: Vec::new(),
+        }
+    }
+
+    pub fn has_flag(&self, flag: MptcpEndpointFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.id == Some(0) {
+            return Err(Error::Generic(
+                "MPTCP endpoint id 0 is reserved for the initial subflow".to_string(),
+            ));
+        }
+        if self.has_flag(MptcpEndpointFlag::Implicit) {
+            return Err(Error::Generic(
+                "the implicit flag is set by the kernel and cannot be requested".to_string(),
+            ));
+        }
+        if self.has_flag(MptcpEndpointFlag::Signal) && self.has_flag(MptcpEndpointFlag::Fullmesh) {
+            return Err(Error::Generic(
+                "MPTCP endpoint flags signal and fullmesh cannot be combined".to_string(),
+            ));
+        }
+        if self.port.is_some() && !self.has_flag(MptcpEndpointFlag::Signal) {
+            return Err(Error::Generic(
+                "an MPTCP endpoint port requires the signal flag".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Maximum value the kernel accepts for either MPTCP limit (MPTCP_PM_ADDR_MAX)
+pub const MPTCP_LIMIT_MAX: u32 = 8;
+
+/// Per-connection limits of the in-kernel MPTCP path manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MptcpLimits {
+    /// Maximum additional subflows per connection
+    pub subflows: u32,
+    /// Maximum ADD_ADDR announcements accepted per connection
+    pub add_addr_accepted: u32,
+}
+
+impl MptcpLimits {
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("subflows", self.subflows),
+            ("add_addr_accepted", self.add_addr_accepted),
+        ] {
+            if value > MPTCP_LIMIT_MAX {
+                return Err(Error::Generic(format!(
+                    "MPTCP {} limit {} exceeds the maximum of {}",
+                    name, value, MPTCP_LIMIT_MAX
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -220,9 +829,11 @@ mod tests {
                 IpNetwork::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)), 24).unwrap(),
             ),
             gateway: Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))),
+            index: None,
         };
         assert!(route.destination.is_some());
         assert!(route.gateway.is_some());
+        assert!(!route.is_default());
     }
 
     #[test]
@@ -230,8 +841,175 @@ mod tests {
         let route = Route {
             destination: None, // Default route
             gateway: Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+            index: Some(2),
         };
         assert!(route.destination.is_none());
         assert!(route.gateway.is_some());
+        assert!(route.is_default());
+        assert_eq!(route.is_ipv6(), Some(false));
+    }
+
+    #[test]
+    fn test_link_kind_roundtrip() {
+        for name in ["dummy", "ifb", "nlmon"] {
+            let kind: LinkKind = name.parse().unwrap();
+            assert_eq!(kind.to_string(), name);
+        }
+        assert!("eth".parse::<LinkKind>().is_err());
+    }
+
+    #[test]
+    fn test_vf_settings_validate() {
+        let mut settings = VfSettings {
+            vlan: Some(100),
+            qos: Some(3),
+            min_tx_rate: Some(100),
+            max_tx_rate: Some(1000),
+            ..Default::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.vlan = Some(4095);
+        assert!(settings.validate().is_err());
+        settings.vlan = Some(100);
+
+        settings.min_tx_rate = Some(2000);
+        assert!(settings.validate().is_err());
+        settings.max_tx_rate = Some(0);
+        assert!(settings.validate().is_ok());
+
+        settings.mac_address = Some("01:00:5e:00:00:01".parse().unwrap());
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_address_flag_roundtrip() {
+        for name in ["noprefixroute", "nodad", "home", "mngtmpaddr", "optimistic"] {
+            let flag: AddressFlag = name.parse().unwrap();
+            assert_eq!(flag.to_string(), name);
+        }
+        assert!("bogus".parse::<AddressFlag>().is_err());
+    }
+
+    #[test]
+    fn test_address_spec_plain_is_valid() {
+        let spec = AddressSpec::from("192.168.1.10/24".parse::<IpNetwork>().unwrap());
+        assert!(spec.validate("eth0").is_ok());
+    }
+
+    #[test]
+    fn test_address_spec_peer_and_lifetimes() {
+        let mut spec = AddressSpec::new("10.0.0.1/32".parse().unwrap());
+        spec.peer = Some("10.0.0.2".parse().unwrap());
+        spec.valid_lifetime = Some(3600);
+        spec.preferred_lifetime = Some(1800);
+        spec.label = Some("eth0:ptp".to_string());
+        assert!(spec.validate("eth0").is_ok());
+    }
+
+    #[test]
+    fn test_address_spec_rejects_peer_family_mismatch() {
+        let mut spec = AddressSpec::new("10.0.0.1/32".parse().unwrap());
+        spec.peer = Some("2001:db8::2".parse().unwrap());
+        assert!(spec.validate("eth0").is_err());
+    }
+
+    #[test]
+    fn test_address_spec_rejects_ipv6_broadcast_and_label() {
+        let mut spec = AddressSpec::new("2001:db8::1/64".parse().unwrap());
+        spec.broadcast = Some(Ipv4Addr::new(10, 0, 0, 255));
+        assert!(spec.validate("eth0").is_err());
+
+        let mut spec = AddressSpec::new("2001:db8::1/64".parse().unwrap());
+        spec.label = Some("eth0:1".to_string());
+        assert!(spec.validate("eth0").is_err());
+    }
+
+    #[test]
+    fn test_address_spec_rejects_foreign_label() {
+        let mut spec = AddressSpec::new("10.0.0.1/24".parse().unwrap());
+        spec.label = Some("eth1:vip".to_string());
+        assert!(spec.validate("eth0").is_err());
+    }
+
+    #[test]
+    fn test_address_spec_rejects_preferred_over_valid() {
+        let mut spec = AddressSpec::new("10.0.0.1/24".parse().unwrap());
+        spec.valid_lifetime = Some(60);
+        spec.preferred_lifetime = Some(120);
+        assert!(spec.validate("eth0").is_err());
+    }
+
+    #[test]
+    fn test_address_spec_flag_rules() {
+        let mut spec = AddressSpec::new("10.0.0.1/24".parse().unwrap());
+        spec.flags = vec![AddressFlag::NoPrefixRoute];
+        assert!(spec.validate("eth0").is_ok());
+        spec.flags = vec![AddressFlag::NoDad];
+        assert!(spec.validate("eth0").is_err());
+
+        let mut spec = AddressSpec::new("2001:db8::1/64".parse().unwrap());
+        spec.flags = vec![AddressFlag::NoDad, AddressFlag::Optimistic];
+        assert!(spec.validate("eth0").is_err());
+
+        let mut spec = AddressSpec::new("2001:db8::1/56".parse().unwrap());
+        spec.flags = vec![AddressFlag::MngTmpAddr];
+        assert!(spec.validate("eth0").is_err());
+    }
+
+    #[test]
+    fn test_mptcp_endpoint_spec_validate() {
+        let mut spec = MptcpEndpointSpec::new("10.0.0.1".parse().unwrap());
+        spec.flags = vec![MptcpEndpointFlag::Signal];
+        spec.port = Some(8080);
+        assert!(spec.validate().is_ok());
+
+        spec.flags.push(MptcpEndpointFlag::Fullmesh);
+        assert!(spec.validate().is_err());
+
+        spec.flags = vec![MptcpEndpointFlag::Subflow];
+        assert!(spec.validate().is_err());
+
+        spec.port = None;
+        spec.id = Some(0);
+        assert!(spec.validate().is_err());
+        spec.id = Some(1);
+        assert!(spec.validate().is_ok());
+
+        assert!("implicit".parse::<MptcpEndpointFlag>().is_err());
+    }
+
+    #[test]
+    fn test_mptcp_limits_validate() {
+        let mut limits = MptcpLimits {
+            subflows: 2,
+            add_addr_accepted: 8,
+        };
+        assert!(limits.validate().is_ok());
+        limits.subflows = 9;
+        assert!(limits.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_ifname() {
+        assert!(validate_ifname("wan0").is_ok());
+        assert!(validate_ifname("enp0s31f6.100").is_ok());
+        assert!(validate_ifname("").is_err());
+        assert!(validate_ifname("..").is_err());
+        assert!(validate_ifname("eth0:1").is_err());
+        assert!(validate_ifname("has space").is_err());
+        assert!(validate_ifname("averyveryverylongname").is_err());
+        assert!(validate_altname("averyveryverylongname").is_ok());
+    }
+
+    #[test]
+    fn test_networkd_link_state_is_managed() {
+        let mut state = NetworkdLinkState {
+            administrative_state: "configured".to_string(),
+            ..Default::default()
+        };
+        assert!(state.is_managed());
+        state.administrative_state = "unmanaged".to_string();
+        assert!(!state.is_managed());
     }
 }
@@ -0,0 +1,498 @@
+//! Typed view of the JSON returned by systemd-networkd's `Describe` and
+//! `DescribeLink` methods.
+//!
+//! networkd encodes addresses as byte arrays. They are decoded into
+//! `IpAddr`/`MacAddress` here and serialized back as strings. Keys netctl
+//! doesn't model are ignored, so newer networkd versions still parse.
+
+use crate::network::MacAddress;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Output of the networkd Manager `Describe` method
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkdDescription {
+    #[serde(default)]
+    pub interfaces: Vec<LinkDescription>,
+}
+
+/// Output of the networkd Manager `DescribeLink` method
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LinkDescription {
+    pub index: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternative_names: Vec<String>,
+    #[serde(default, rename = "Type")]
+    pub link_type: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub driver: Option<String>,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub setup_state: Option<String>,
+    #[serde(default)]
+    pub operational_state: Option<String>,
+    #[serde(default)]
+    pub carrier_state: Option<String>,
+    #[serde(default)]
+    pub address_state: Option<String>,
+    #[serde(default, rename = "IPv4AddressState")]
+    pub ipv4_address_state: Option<String>,
+    #[serde(default, rename = "IPv6AddressState")]
+    pub ipv6_address_state: Option<String>,
+    #[serde(default)]
+    pub online_state: Option<String>,
+    /// The .network file networkd matched to the link
+    #[serde(default)]
+    pub network_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_file_dropins: Vec<String>,
+    #[serde(default)]
+    pub link_file: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "encoded::opt_mac",
+        serialize_with = "encoded::serialize_opt_mac"
+    )]
+    pub hardware_address: Option<MacAddress>,
+    #[serde(default, rename = "MTU")]
+    pub mtu: Option<u32>,
+    #[serde(default, rename = "DNS")]
+    pub dns: Vec<DnsServer>,
+    #[serde(default, rename = "NTP")]
+    pub ntp: Vec<NtpServer>,
+    #[serde(default)]
+    pub search_domains: Vec<SearchDomain>,
+    #[serde(default)]
+    pub addresses: Vec<AddressDescription>,
+    #[serde(default)]
+    pub routes: Vec<RouteDescription>,
+    #[serde(default, rename = "DHCPv4Client")]
+    pub dhcpv4_client: Option<Dhcp4Client>,
+    #[serde(default, rename = "DHCPv6Client")]
+    pub dhcpv6_client: Option<Dhcp6Client>,
+    #[serde(default, rename = "LLDP")]
+    pub lldp_neighbors: Vec<LldpNeighbor>,
+}
+
+impl LinkDescription {
+    /// Routes to the default destination that go through a gateway
+    pub fn gateways(&self) -> impl Iterator<Item = &RouteDescription> {
+        self.routes
+            .iter()
+            .filter(|route| route.destination_prefix_length == 0 && route.gateway.is_some())
+    }
+
+    /// The DHCPv4 lease networkd holds, pieced together from the lease timers
+    /// and what networkd configured from the lease. `booted_at`, in seconds
+    /// since the Unix epoch, dates the lease; networkd reports CLOCK_BOOTTIME.
+    pub fn dhcp4_lease(&self, booted_at: Option<u64>) -> Option<Dhcp4Lease> {
+        const SOURCE: &str = "DHCPv4";
+        let timers = self.dhcpv4_client.as_ref()?.lease.as_ref()?;
+        let from_lease = |source: &Option<String>| source.as_deref() == Some(SOURCE);
+        let (address, lease_address) =
+            self.addresses.iter().find_map(|addr| match addr.address {
+                IpAddr::V4(v4) if from_lease(&addr.config_source) => Some((v4, addr)),
+                _ => None,
+            })?;
+        let v4 = |addr: IpAddr| match addr {
+            IpAddr::V4(v4) => Some(v4),
+            IpAddr::V6(_) => None,
+        };
+        let secs = |usec: u64| usec / 1_000_000;
+
+        let lifetime = match (
+            lease_address.valid_lifetime_usec,
+            timers.lease_timestamp_usec,
+        ) {
+            (Some(until), Some(since)) => until.checked_sub(since).map(secs),
+            _ => None,
+        };
+        let acquired = match (booted_at, timers.lease_timestamp_usec) {
+            (Some(booted_at), Some(since)) => Some(booted_at + secs(since)),
+            _ => None,
+        };
+
+        Some(Dhcp4Lease {
+            address,
+            prefix_len: Some(lease_address.prefix_length),
+            server_address: lease_address.config_provider.and_then(v4),
+            routers: self
+                .gateways()
+                .filter(|route| from_lease(&route.config_source))
+                .filter_map(|route| route.gateway.and_then(v4))
+                .collect(),
+            lifetime,
+            t1: timers.t1_usec.map(secs),
+            t2: timers.t2_usec.map(secs),
+            dns: self
+                .dns
+                .iter()
+                .filter(|server| from_lease(&server.config_source))
+                .filter_map(|server| v4(server.address))
+                .collect(),
+            ntp: self
+                .ntp
+                .iter()
+                .filter(|server| from_lease(&server.config_source))
+                .filter_map(|server| server.address.and_then(v4))
+                .collect(),
+            domains: self
+                .search_domains
+                .iter()
+                .filter(|domain| from_lease(&domain.config_source))
+                .map(|domain| domain.domain.clone())
+                .collect(),
+            acquired,
+        })
+    }
+}
+
+/// A DNS server and where networkd got it from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DnsServer {
+    #[serde(deserialize_with = "encoded::ip")]
+    pub address: IpAddr,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub server_name: Option<String>,
+    #[serde(default)]
+    pub config_source: Option<String>,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub config_provider: Option<IpAddr>,
+}
+
+/// An NTP server, given either as an address or a host name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NtpServer {
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub address: Option<IpAddr>,
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub config_source: Option<String>,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub config_provider: Option<IpAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SearchDomain {
+    pub domain: String,
+    #[serde(default)]
+    pub config_source: Option<String>,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub config_provider: Option<IpAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AddressDescription {
+    #[serde(deserialize_with = "encoded::ip")]
+    pub address: IpAddr,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub peer: Option<IpAddr>,
+    pub prefix_length: u8,
+    #[serde(default)]
+    pub scope_string: Option<String>,
+    #[serde(default)]
+    pub flags_string: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Static, DHCPv4, DHCPv6, NDisc, IPv4LL, ...
+    #[serde(default)]
+    pub config_source: Option<String>,
+    #[serde(default)]
+    pub config_state: Option<String>,
+    /// Server or router the address was learned from
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub config_provider: Option<IpAddr>,
+    /// When the address expires, in microseconds on CLOCK_BOOTTIME
+    #[serde(default, rename = "ValidLifetimeUSec")]
+    pub valid_lifetime_usec: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RouteDescription {
+    #[serde(deserialize_with = "encoded::ip")]
+    pub destination: IpAddr,
+    pub destination_prefix_length: u8,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub gateway: Option<IpAddr>,
+    #[serde(default)]
+    pub table: Option<u32>,
+    #[serde(default)]
+    pub table_string: Option<String>,
+    #[serde(default)]
+    pub priority: Option<u32>,
+    #[serde(default)]
+    pub protocol_string: Option<String>,
+    #[serde(default)]
+    pub scope_string: Option<String>,
+    #[serde(default)]
+    pub type_string: Option<String>,
+    #[serde(default)]
+    pub config_source: Option<String>,
+    #[serde(default)]
+    pub config_state: Option<String>,
+    #[serde(default, deserialize_with = "encoded::opt_ip")]
+    pub config_provider: Option<IpAddr>,
+}
+
+/// Lease timers in microseconds
+///
+/// The timestamp is on CLOCK_BOOTTIME; T1 and T2 are relative to it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DhcpLease {
+    #[serde(default, rename = "LeaseTimestampUSec")]
+    pub lease_timestamp_usec: Option<u64>,
+    #[serde(default, rename = "Timeout1USec")]
+    pub t1_usec: Option<u64>,
+    #[serde(default, rename = "Timeout2USec")]
+    pub t2_usec: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Dhcp4Client {
+    #[serde(default)]
+    pub lease: Option<DhcpLease>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Dhcp6Client {
+    #[serde(default)]
+    pub lease: Option<DhcpLease>,
+    /// Prefixes delegated to this link
+    #[serde(default)]
+    pub prefixes: Vec<DelegatedPrefix>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DelegatedPrefix {
+    #[serde(deserialize_with = "encoded::ip")]
+    pub prefix: IpAddr,
+    pub prefix_length: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LldpNeighbor {
+    #[serde(default, rename = "ChassisID")]
+    pub chassis_id: Option<String>,
+    #[serde(default, rename = "PortID")]
+    pub port_id: Option<String>,
+    #[serde(default, rename = "PortDescription")]
+    pub port_description: Option<String>,
+    #[serde(default, rename = "SystemName")]
+    pub system_name: Option<String>,
+    #[serde(default, rename = "SystemDescription")]
+    pub system_description: Option<String>,
+}
+
+/// A DHCPv4 lease as networkd describes it; see [`LinkDescription::dhcp4_lease`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dhcp4Lease {
+    pub address: Ipv4Addr,
+    pub prefix_len: Option<u8>,
+    pub server_address: Option<Ipv4Addr>,
+    #[serde(default)]
+    pub routers: Vec<Ipv4Addr>,
+    /// Lease time in seconds
+    pub lifetime: Option<u64>,
+    /// Renewal (T1) and rebinding (T2) times in seconds
+    pub t1: Option<u64>,
+    pub t2: Option<u64>,
+    #[serde(default)]
+    pub dns: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub ntp: Vec<Ipv4Addr>,
+    /// Domain name and search domains
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// When the lease was last acquired or renewed, in seconds since the
+    /// Unix epoch
+    pub acquired: Option<u64>,
+}
+
+impl Dhcp4Lease {
+    /// Expiry time in seconds since the Unix epoch, if known
+    pub fn expires(&self) -> Option<u64> {
+        Some(self.acquired? + self.lifetime?)
+    }
+}
+
+/// Decoders for the byte-array encoding networkd uses for addresses. The
+/// string form is accepted too so netctl's own JSON output parses back.
+mod encoded {
+    use crate::network::MacAddress;
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Encoded {
+        Bytes(Vec<u8>),
+        Text(String),
+    }
+
+    fn decode_ip(value: Encoded) -> Result<IpAddr, String> {
+        match value {
+            Encoded::Bytes(bytes) => match bytes.len() {
+                4 => Ok(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).into()),
+                16 => {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(&bytes);
+                    Ok(Ipv6Addr::from(octets).into())
+                }
+                len => Err(format!("invalid address length {}", len)),
+            },
+            Encoded::Text(text) => text
+                .parse()
+                .map_err(|_| format!("invalid address '{}'", text)),
+        }
+    }
+
+    fn decode_mac(value: Encoded) -> Result<MacAddress, String> {
+        match value {
+            Encoded::Bytes(bytes) => <[u8; 6]>::try_from(bytes.as_slice())
+                .map(MacAddress::new)
+                .map_err(|_| format!("invalid hardware address length {}", bytes.len())),
+            Encoded::Text(text) => text.parse().map_err(|e| format!("{}", e)),
+        }
+    }
+
+    pub(super) fn ip<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpAddr, D::Error> {
+        decode_ip(Encoded::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub(super) fn opt_ip<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<IpAddr>, D::Error> {
+        Option::<Encoded>::deserialize(deserializer)?
+            .map(decode_ip)
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+
+    pub(super) fn opt_mac<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<MacAddress>, D::Error> {
+        Option::<Encoded>::deserialize(deserializer)?
+            .map(decode_mac)
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+
+    pub(super) fn serialize_opt_mac<S: Serializer>(
+        mac: &Option<MacAddress>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match mac {
+            Some(mac) => serializer.collect_str(mac),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIBE_LINK: &str = r#"{
+        "Index": 2,
+        "Name": "eth0",
+        "AlternativeNames": ["enp1s0"],
+        "Type": "ether",
+        "Driver": "virtio_net",
+        "SetupState": "configured",
+        "OperationalState": "routable",
+        "CarrierState": "carrier",
+        "AddressState": "routable",
+        "IPv4AddressState": "routable",
+        "IPv6AddressState": "degraded",
+        "OnlineState": "online",
+        "NetworkFile": "/etc/systemd/network/20-wired.network",
+        "LinkFile": "/usr/lib/systemd/network/99-default.link",
+        "HardwareAddress": [82, 84, 0, 18, 52, 86],
+        "MTU": 1500,
+        "DNS": [{"Family": 2, "Address": [10, 0, 0, 1], "ConfigSource": "DHCPv4", "ConfigProvider": [10, 0, 0, 1]}],
+        "NTP": [{"Server": "pool.ntp.org", "ConfigSource": "static"}],
+        "SearchDomains": [{"Domain": "lan", "ConfigSource": "DHCPv4", "ConfigProvider": [10, 0, 0, 1]}],
+        "Addresses": [{
+            "Family": 2, "Address": [10, 0, 0, 5], "PrefixLength": 24, "Scope": 0,
+            "ScopeString": "global", "PreferredLifetimeUsec": 1, "PreferredLifetimeUSec": 1,
+            "ValidLifetimeUSec": 3605000000,
+            "ConfigSource": "DHCPv4", "ConfigState": "configured", "ConfigProvider": [10, 0, 0, 1]
+        }],
+        "Routes": [{
+            "Family": 2, "Destination": [0, 0, 0, 0], "DestinationPrefixLength": 0,
+            "Gateway": [10, 0, 0, 1], "Table": 254, "TableString": "main", "Priority": 1024,
+            "ProtocolString": "dhcp", "ConfigSource": "DHCPv4", "ConfigState": "configured"
+        }],
+        "DHCPv4Client": {"Lease": {"LeaseTimestampUSec": 5000000, "Timeout1USec": 1800000000, "Timeout2USec": 3150000000}},
+        "DHCPv6Client": {"Prefixes": [{"Prefix": [32, 1, 13, 184, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], "PrefixLength": 56}]}
+    }"#;
+
+    #[test]
+    fn test_link_description_parse() {
+        let link: LinkDescription = serde_json::from_str(DESCRIBE_LINK).unwrap();
+        assert_eq!(link.name, "eth0");
+        assert_eq!(link.link_type.as_deref(), Some("ether"));
+        assert_eq!(
+            link.hardware_address,
+            Some("52:54:00:12:34:56".parse().unwrap())
+        );
+        assert_eq!(link.dns[0].address, "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(link.ntp[0].server.as_deref(), Some("pool.ntp.org"));
+        assert_eq!(link.addresses[0].prefix_length, 24);
+        assert_eq!(link.gateways().count(), 1);
+
+        let lease = link.dhcpv4_client.as_ref().unwrap().lease.as_ref().unwrap();
+        assert_eq!(lease.t1_usec, Some(1_800_000_000));
+        let prefixes = &link.dhcpv6_client.as_ref().unwrap().prefixes;
+        assert_eq!(prefixes[0].prefix, "2001:db8::".parse::<IpAddr>().unwrap());
+
+        // netctl's own JSON output uses strings and must parse back
+        let json = serde_json::to_string(&link).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LinkDescription>(&json).unwrap(),
+            link
+        );
+    }
+
+    #[test]
+    fn test_dhcp4_lease() {
+        let mut link: LinkDescription = serde_json::from_str(DESCRIBE_LINK).unwrap();
+        let lease = link.dhcp4_lease(Some(1000)).unwrap();
+        assert_eq!(lease.address, Ipv4Addr::new(10, 0, 0, 5));
+        assert_eq!(lease.prefix_len, Some(24));
+        assert_eq!(lease.server_address, Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(lease.routers, vec![Ipv4Addr::new(10, 0, 0, 1)]);
+        assert_eq!(lease.dns, vec![Ipv4Addr::new(10, 0, 0, 1)]);
+        assert!(lease.ntp.is_empty());
+        assert_eq!(lease.domains, vec!["lan".to_string()]);
+        assert_eq!((lease.t1, lease.t2), (Some(1800), Some(3150)));
+        assert_eq!(lease.lifetime, Some(3600));
+        assert_eq!(lease.acquired, Some(1005));
+        assert_eq!(lease.expires(), Some(4605));
+
+        assert_eq!(link.dhcp4_lease(None).unwrap().expires(), None);
+
+        link.addresses[0].config_source = Some("static".to_string());
+        assert_eq!(link.dhcp4_lease(None), None);
+    }
+}
@@ -0,0 +1,228 @@
+//! NetworkManager devices, active connections and IP configuration, and
+//! which daemon manages a link.
+
+use crate::network::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+
+/// The daemon that configures a link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkBackend {
+    Networkd,
+    NetworkManager,
+    /// Neither daemon manages the link; only runtime kernel settings apply
+    Unmanaged,
+}
+
+impl fmt::Display for LinkBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Networkd => "systemd-networkd",
+            Self::NetworkManager => "NetworkManager",
+            Self::Unmanaged => "unmanaged",
+        };
+        f.write_str(s)
+    }
+}
+
+/// NMDeviceState
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NmDeviceState {
+    Unknown,
+    Unmanaged,
+    Unavailable,
+    Disconnected,
+    Prepare,
+    Config,
+    NeedAuth,
+    IpConfig,
+    IpCheck,
+    Secondaries,
+    Activated,
+    Deactivating,
+    Failed,
+}
+
+impl NmDeviceState {
+    pub fn from_u32(state: u32) -> Self {
+        match state {
+            10 => Self::Unmanaged,
+            20 => Self::Unavailable,
+            30 => Self::Disconnected,
+            40 => Self::Prepare,
+            50 => Self::Config,
+            60 => Self::NeedAuth,
+            70 => Self::IpConfig,
+            80 => Self::IpCheck,
+            90 => Self::Secondaries,
+            100 => Self::Activated,
+            110 => Self::Deactivating,
+            120 => Self::Failed,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Whether NetworkManager is in charge of the device
+    pub fn is_managed(self) -> bool {
+        !matches!(self, Self::Unknown | Self::Unmanaged)
+    }
+}
+
+impl fmt::Display for NmDeviceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Unknown => "unknown",
+            Self::Unmanaged => "unmanaged",
+            Self::Unavailable => "unavailable",
+            Self::Disconnected => "disconnected",
+            Self::Prepare => "prepare",
+            Self::Config => "config",
+            Self::NeedAuth => "need-auth",
+            Self::IpConfig => "ip-config",
+            Self::IpCheck => "ip-check",
+            Self::Secondaries => "secondaries",
+            Self::Activated => "activated",
+            Self::Deactivating => "deactivating",
+            Self::Failed => "failed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// NMActiveConnectionState
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NmConnectionState {
+    Unknown,
+    Activating,
+    Activated,
+    Deactivating,
+    Deactivated,
+}
+
+impl NmConnectionState {
+    pub fn from_u32(state: u32) -> Self {
+        match state {
+            1 => Self::Activating,
+            2 => Self::Activated,
+            3 => Self::Deactivating,
+            4 => Self::Deactivated,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for NmConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Unknown => "unknown",
+            Self::Activating => "activating",
+            Self::Activated => "activated",
+            Self::Deactivating => "deactivating",
+            Self::Deactivated => "deactivated",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Name of an NMDeviceType, as nmcli prints it
+pub fn nm_device_type_name(device_type: u32) -> &'static str {
+    match device_type {
+        1 => "ethernet",
+        2 => "wifi",
+        5 => "bt",
+        6 => "olpc-mesh",
+        7 => "wimax",
+        8 => "modem",
+        9 => "infiniband",
+        10 => "bond",
+        11 => "vlan",
+        12 => "adsl",
+        13 => "bridge",
+        14 => "generic",
+        15 => "team",
+        16 => "tun",
+        17 => "ip-tunnel",
+        18 => "macvlan",
+        19 => "vxlan",
+        20 => "veth",
+        21 => "macsec",
+        22 => "dummy",
+        23 => "ppp",
+        24 => "ovs-interface",
+        25 => "ovs-port",
+        26 => "ovs-bridge",
+        27 => "wpan",
+        28 => "6lowpan",
+        29 => "wireguard",
+        30 => "wifi-p2p",
+        31 => "vrf",
+        32 => "loopback",
+        33 => "hsr",
+        _ => "unknown",
+    }
+}
+
+/// An IP4Config or IP6Config object of a device
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct NmIpConfig {
+    pub addresses: Vec<IpNetwork>,
+    pub gateway: Option<IpAddr>,
+    pub nameservers: Vec<IpAddr>,
+    /// Search and routing domains
+    pub domains: Vec<String>,
+}
+
+/// A network device known to NetworkManager
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NmDevice {
+    pub ifname: String,
+    pub device_type: String,
+    pub driver: String,
+    pub state: NmDeviceState,
+    /// ID of the connection active on the device
+    pub connection: Option<String>,
+    pub ip4: Option<NmIpConfig>,
+    pub ip6: Option<NmIpConfig>,
+}
+
+/// A connection profile that is currently active on one or more devices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NmActiveConnection {
+    pub id: String,
+    pub uuid: String,
+    /// Setting type, e.g. 802-3-ethernet, 802-11-wireless, bridge
+    pub connection_type: String,
+    pub state: NmConnectionState,
+    /// Interface names of the devices the connection is active on
+    pub devices: Vec<String>,
+    /// Whether this connection owns the default IPv4/IPv6 route
+    pub default4: bool,
+    pub default6: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_state() {
+        assert_eq!(NmDeviceState::from_u32(100), NmDeviceState::Activated);
+        assert_eq!(NmDeviceState::from_u32(7), NmDeviceState::Unknown);
+        assert!(!NmDeviceState::from_u32(10).is_managed());
+        assert!(NmDeviceState::from_u32(30).is_managed());
+        assert_eq!(NmDeviceState::NeedAuth.to_string(), "need-auth");
+    }
+
+    #[test]
+    fn test_connection_state_and_device_type() {
+        assert_eq!(NmConnectionState::from_u32(2), NmConnectionState::Activated);
+        assert_eq!(NmConnectionState::from_u32(9), NmConnectionState::Unknown);
+        assert_eq!(nm_device_type_name(1), "ethernet");
+        assert_eq!(nm_device_type_name(29), "wireguard");
+        assert_eq!(nm_device_type_name(999), "unknown");
+    }
+}
@@ -0,0 +1,403 @@
+//! Link readiness for `netctl wait-online`: what state each link is in, and
+//! whether a set of requirements is met.
+
+use crate::error::{Error, Result};
+use crate::network::{IpNetwork, LinkInfo, LinkState, NetworkdLinkState, Route};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// networkd's operational states, in increasing order of readiness
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperationalState {
+    Off,
+    NoCarrier,
+    Dormant,
+    DegradedCarrier,
+    Carrier,
+    /// Carrier and link-local addresses only
+    Degraded,
+    /// Carrier and enslaved to a bond or bridge
+    Enslaved,
+    /// Carrier and a routable address
+    Routable,
+}
+
+impl OperationalState {
+    /// Derive the state from kernel link state the way networkd does, for
+    /// links networkd doesn't manage
+    pub fn from_kernel(link: &LinkInfo) -> Self {
+        if link.state == LinkState::Down {
+            return Self::Off;
+        }
+        if !link.carrier {
+            return Self::NoCarrier;
+        }
+
+        let addresses: Vec<&IpNetwork> = link
+            .addresses
+            .iter()
+            .filter(|a| !a.addr.is_loopback())
+            .collect();
+        if addresses.iter().any(|a| is_routable(a)) {
+            Self::Routable
+        } else if !addresses.is_empty() {
+            Self::Degraded
+        } else {
+            Self::Carrier
+        }
+    }
+}
+
+impl fmt::Display for OperationalState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Off => "off",
+            Self::NoCarrier => "no-carrier",
+            Self::Dormant => "dormant",
+            Self::DegradedCarrier => "degraded-carrier",
+            Self::Carrier => "carrier",
+            Self::Degraded => "degraded",
+            Self::Enslaved => "enslaved",
+            Self::Routable => "routable",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for OperationalState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            // networkd reports "missing" for links that are gone
+            "off" | "missing" => Ok(Self::Off),
+            "no-carrier" => Ok(Self::NoCarrier),
+            "dormant" => Ok(Self::Dormant),
+            "degraded-carrier" => Ok(Self::DegradedCarrier),
+            "carrier" => Ok(Self::Carrier),
+            "degraded" => Ok(Self::Degraded),
+            "enslaved" => Ok(Self::Enslaved),
+            "routable" => Ok(Self::Routable),
+            _ => Err(Error::Generic(format!(
+                "invalid operational state '{}' (expected off, no-carrier, dormant, \
+                 degraded-carrier, carrier, degraded, enslaved or routable)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Not loopback and not link-local
+fn is_routable(network: &IpNetwork) -> bool {
+    match network.addr {
+        IpAddr::V4(v4) => !v4.is_loopback() && !v4.is_link_local(),
+        IpAddr::V6(v6) => !v6.is_loopback() && (v6.segments()[0] & 0xffc0) != 0xfe80,
+    }
+}
+
+/// Everything `wait-online` checks about one link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkReadiness {
+    pub index: u32,
+    pub name: String,
+    pub operational_state: OperationalState,
+    /// networkd's online state (offline, partial, online), if networkd
+    /// manages the link
+    pub online_state: Option<String>,
+    /// A routable IPv4 / IPv6 address is configured
+    pub has_ipv4: bool,
+    pub has_ipv6: bool,
+    /// A default route goes out through this link
+    pub default_route_v4: bool,
+    pub default_route_v6: bool,
+    pub loopback: bool,
+}
+
+impl LinkReadiness {
+    /// Combine kernel state with networkd's view, which wins for the links
+    /// networkd manages
+    pub fn new(link: &LinkInfo, routes: &[Route], networkd: Option<&NetworkdLinkState>) -> Self {
+        let networkd = networkd.filter(|state| state.is_managed());
+        let operational_state = networkd
+            .and_then(|state| state.operational_state.parse().ok())
+            .unwrap_or_else(|| OperationalState::from_kernel(link));
+
+        let routable = |v6: bool| {
+            link.addresses
+                .iter()
+                .any(|a| a.addr.is_ipv6() == v6 && is_routable(a))
+        };
+        let default_route = |v6: bool| {
+            routes.iter().any(|route| {
+                route.index == Some(link.index) && route.is_default() && route.is_ipv6() == Some(v6)
+            })
+        };
+
+        Self {
+            index: link.index,
+            name: link.name.clone(),
+            operational_state,
+            online_state: networkd
+                .map(|state| state.online_state.clone())
+                .filter(|state| !state.is_empty()),
+            has_ipv4: routable(false),
+            has_ipv6: routable(true),
+            default_route_v4: default_route(false),
+            default_route_v6: default_route(true),
+            loopback: link.is_loopback(),
+        }
+    }
+}
+
+/// An interface to wait for, optionally with its own minimum state:
+/// `NAME` or `NAME:STATE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceRequirement {
+    pub name: String,
+    pub min_state: Option<OperationalState>,
+}
+
+impl FromStr for InterfaceRequirement {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, min_state) = match s.split_once(':') {
+            Some((name, state)) => (name, Some(state.parse()?)),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(Error::Generic(format!(
+                "invalid interface '{}' (expected NAME or NAME:STATE)",
+                s
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            min_state,
+        })
+    }
+}
+
+/// The conditions `wait-online` waits for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitOnlineSpec {
+    /// Interfaces to wait for; empty means every managed link
+    pub interfaces: Vec<InterfaceRequirement>,
+    /// Links never waited for
+    pub ignore: Vec<String>,
+    /// One ready link is enough instead of all of them
+    pub any: bool,
+    pub min_state: OperationalState,
+    /// networkd must report the link online
+    pub require_online: bool,
+    pub ipv4: bool,
+    pub ipv6: bool,
+    pub default_route: bool,
+}
+
+impl Default for WaitOnlineSpec {
+    fn default() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            ignore: Vec::new(),
+            any: false,
+            min_state: OperationalState::Degraded,
+            require_online: false,
+            ipv4: false,
+            ipv6: false,
+            default_route: false,
+        }
+    }
+}
+
+/// Outcome of checking a [`WaitOnlineSpec`] against the current links
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WaitOnlineStatus {
+    pub ready: bool,
+    /// Links waited for that aren't ready (or don't exist) yet
+    pub pending: Vec<String>,
+}
+
+impl WaitOnlineSpec {
+    pub fn evaluate(&self, links: &[LinkReadiness]) -> WaitOnlineStatus {
+        let mut ready = Vec::new();
+        let mut pending = Vec::new();
+
+        if self.interfaces.is_empty() {
+            // Links networkd manages, or every link that is up when networkd
+            // manages none (e.g. it isn't running)
+            let managed: Vec<&LinkReadiness> = links
+                .iter()
+                .filter(|link| link.online_state.is_some())
+                .collect();
+            let candidates = if managed.is_empty() {
+                links
+                    .iter()
+                    .filter(|link| link.operational_state > OperationalState::Off)
+                    .collect()
+            } else {
+                managed
+            };
+
+            for link in candidates {
+                if link.loopback || self.ignore.contains(&link.name) {
+                    continue;
+                }
+                if self.link_ready(link, self.min_state) {
+                    ready.push(link.name.clone());
+                } else {
+                    pending.push(link.name.clone());
+                }
+            }
+        } else {
+            for requirement in &self.interfaces {
+                let min_state = requirement.min_state.unwrap_or(self.min_state);
+                match links.iter().find(|link| link.name == requirement.name) {
+                    Some(link) if self.link_ready(link, min_state) => ready.push(link.name.clone()),
+                    _ => pending.push(requirement.name.clone()),
+                }
+            }
+        }
+
+        let ready = if self.any {
+            !ready.is_empty()
+        } else {
+            !ready.is_empty() && pending.is_empty()
+        };
+        WaitOnlineStatus { ready, pending }
+    }
+
+    fn link_ready(&self, link: &LinkReadiness, min_state: OperationalState) -> bool {
+        let default_route = match (self.ipv4, self.ipv6) {
+            (true, false) => link.default_route_v4,
+            (false, true) => link.default_route_v6,
+            (true, true) => link.default_route_v4 && link.default_route_v6,
+            (false, false) => link.default_route_v4 || link.default_route_v6,
+        };
+
+        link.operational_state >= min_state
+            && (!self.require_online || link.online_state.as_deref() == Some("online"))
+            && (!self.ipv4 || link.has_ipv4)
+            && (!self.ipv6 || link.has_ipv6)
+            && (!self.default_route || default_route)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::IFF_LOOPBACK;
+
+    fn link(index: u32, name: &str, carrier: bool, addresses: &[&str]) -> LinkInfo {
+        LinkInfo {
+            index,
+            name: name.to_string(),
+            state: LinkState::Up,
+            carrier,
+            flags: if name == "lo" { IFF_LOOPBACK } else { 0 },
+            mtu: 1500,
+            mac_address: None,
+            permanent_mac_address: None,
+            altnames: Vec::new(),
+            addresses: addresses.iter().map(|a| a.parse().unwrap()).collect(),
+            kind: None,
+            vfs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_operational_state_from_kernel() {
+        assert_eq!(
+            OperationalState::from_kernel(&link(2, "eth0", false, &[])),
+            OperationalState::NoCarrier
+        );
+        assert_eq!(
+            OperationalState::from_kernel(&link(2, "eth0", true, &[])),
+            OperationalState::Carrier
+        );
+        assert_eq!(
+            OperationalState::from_kernel(&link(2, "eth0", true, &["fe80::1/64"])),
+            OperationalState::Degraded
+        );
+        assert_eq!(
+            OperationalState::from_kernel(&link(2, "eth0", true, &["fe80::1/64", "10.0.0.2/24"])),
+            OperationalState::Routable
+        );
+        assert!(OperationalState::Routable > OperationalState::Degraded);
+        assert_eq!(
+            "degraded-carrier".parse::<OperationalState>().unwrap(),
+            OperationalState::DegradedCarrier
+        );
+        assert!("up".parse::<OperationalState>().is_err());
+    }
+
+    #[test]
+    fn test_readiness_prefers_networkd() {
+        let eth0 = link(2, "eth0", true, &["10.0.0.2/24"]);
+        let routes = vec![Route {
+            destination: Some("0.0.0.0/0".parse().unwrap()),
+            gateway: Some("10.0.0.1".parse().unwrap()),
+            index: Some(2),
+        }];
+        let networkd = NetworkdLinkState {
+            administrative_state: "configuring".to_string(),
+            operational_state: "degraded".to_string(),
+            online_state: "partial".to_string(),
+            ..Default::default()
+        };
+
+        let readiness = LinkReadiness::new(&eth0, &routes, Some(&networkd));
+        assert_eq!(readiness.operational_state, OperationalState::Degraded);
+        assert_eq!(readiness.online_state.as_deref(), Some("partial"));
+        assert!(readiness.has_ipv4 && !readiness.has_ipv6);
+        assert!(readiness.default_route_v4 && !readiness.default_route_v6);
+
+        let unmanaged = LinkReadiness::new(&eth0, &routes, None);
+        assert_eq!(unmanaged.operational_state, OperationalState::Routable);
+        assert_eq!(unmanaged.online_state, None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let routes = Vec::new();
+        let links = vec![
+            LinkReadiness::new(&link(1, "lo", true, &["127.0.0.1/8"]), &routes, None),
+            LinkReadiness::new(&link(2, "eth0", true, &["10.0.0.2/24"]), &routes, None),
+            LinkReadiness::new(&link(3, "eth1", false, &[]), &routes, None),
+        ];
+
+        let spec = WaitOnlineSpec::default();
+        let status = spec.evaluate(&links);
+        assert!(!status.ready);
+        assert_eq!(status.pending, vec!["eth1".to_string()]);
+
+        let spec = WaitOnlineSpec {
+            any: true,
+            ..Default::default()
+        };
+        assert!(spec.evaluate(&links).ready);
+
+        let spec = WaitOnlineSpec {
+            interfaces: vec!["eth0:routable".parse().unwrap()],
+            ipv4: true,
+            ..Default::default()
+        };
+        assert!(spec.evaluate(&links).ready);
+
+        let spec = WaitOnlineSpec {
+            interfaces: vec!["eth0".parse().unwrap()],
+            default_route: true,
+            ..Default::default()
+        };
+        assert!(!spec.evaluate(&links).ready);
+
+        let spec = WaitOnlineSpec {
+            interfaces: vec!["wlan0".parse().unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(spec.evaluate(&links).pending, vec!["wlan0".to_string()]);
+    }
+}
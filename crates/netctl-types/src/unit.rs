@@ -0,0 +1,131 @@
+//! systemd units netctl depends on, and their state as reported by systemd.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The systemd services netctl talks to or waits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServiceUnit {
+    Networkd,
+    Resolved,
+    WaitOnline,
+}
+
+impl ServiceUnit {
+    pub const fn unit_name(self) -> &'static str {
+        match self {
+            Self::Networkd => "systemd-networkd.service",
+            Self::Resolved => "systemd-resolved.service",
+            Self::WaitOnline => "systemd-networkd-wait-online.service",
+        }
+    }
+}
+
+impl fmt::Display for ServiceUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Networkd => "networkd",
+            Self::Resolved => "resolved",
+            Self::WaitOnline => "wait-online",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for ServiceUnit {
+    type Err = Error;
+
+    /// Accepts the short name as well as the full or suffix-less unit name
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim_end_matches(".service") {
+            "networkd" | "systemd-networkd" => Ok(Self::Networkd),
+            "resolved" | "systemd-resolved" => Ok(Self::Resolved),
+            "wait-online" | "systemd-networkd-wait-online" => Ok(Self::WaitOnline),
+            _ => Err(Error::Generic(format!(
+                "unknown service '{}' (expected networkd, resolved or wait-online)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Load and activation state of a unit (org.freedesktop.systemd1.Unit)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitStatus {
+    pub name: String,
+    pub description: String,
+    /// loaded, not-found, masked, ...
+    pub load_state: String,
+    /// active, inactive, failed, activating, deactivating, ...
+    pub active_state: String,
+    /// Unit-type specific state, e.g. running, exited, dead
+    pub sub_state: String,
+    /// enabled, disabled, static, ...; empty for transient units
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_file_state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_pid: Option<u32>,
+    /// When the unit last entered the active state, in microseconds since
+    /// the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_enter_timestamp: Option<u64>,
+}
+
+impl UnitStatus {
+    pub fn is_active(&self) -> bool {
+        self.active_state == "active" || self.active_state == "reloading"
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.load_state == "loaded"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_unit_parse() {
+        assert_eq!(
+            "networkd".parse::<ServiceUnit>().unwrap(),
+            ServiceUnit::Networkd
+        );
+        assert_eq!(
+            "systemd-resolved.service".parse::<ServiceUnit>().unwrap(),
+            ServiceUnit::Resolved
+        );
+        assert_eq!(
+            "wait-online".parse::<ServiceUnit>().unwrap().unit_name(),
+            "systemd-networkd-wait-online.service"
+        );
+        assert!("sshd".parse::<ServiceUnit>().is_err());
+    }
+
+    #[test]
+    fn test_unit_status_states() {
+        let mut status = UnitStatus {
+            name: "systemd-networkd.service".to_string(),
+            description: "Network Configuration".to_string(),
+            load_state: "loaded".to_string(),
+            active_state: "active".to_string(),
+            sub_state: "running".to_string(),
+            unit_file_state: Some("enabled".to_string()),
+            main_pid: Some(412),
+            active_enter_timestamp: None,
+        };
+        assert!(status.is_active());
+        assert!(status.is_loaded());
+
+        status.active_state = "failed".to_string();
+        assert!(!status.is_active());
+        assert!(status.is_failed());
+    }
+}
@@ -1,7 +1,8 @@
 use clap::{Args, Subcommand};
 use miette::Result;
 use netctl_core::NetworkManager;
-use netctl_types::IpNetwork;
+use netctl_types::{AddressFlag, AddressScope, AddressSpec, IpNetwork};
+use std::net::{IpAddr, Ipv4Addr};
 
 #[derive(Args)]
 pub struct AddressCommand {
@@ -22,6 +23,34 @@ pub struct AddArgs {
 
     /// IP address with prefix (e.g., 192.168.1.10/24)
     address: String,
+
+    /// Remote address of a point-to-point link
+    #[arg(long)]
+    peer: Option<IpAddr>,
+
+    /// IPv4 broadcast address (derived from the prefix by default)
+    #[arg(long)]
+    broadcast: Option<Ipv4Addr>,
+
+    /// IPv4 address label (must start with the interface name)
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Address scope (global, site, link, host)
+    #[arg(long)]
+    scope: Option<String>,
+
+    /// Valid lifetime in seconds, or 'forever'
+    #[arg(long = "valid-lft")]
+    valid_lft: Option<String>,
+
+    /// Preferred lifetime in seconds, or 'forever'
+    #[arg(long = "preferred-lft")]
+    preferred_lft: Option<String>,
+
+    /// Address flags (noprefixroute, nodad, home, mngtmpaddr, optimistic)
+    #[arg(long = "flag", value_delimiter = ',')]
+    flags: Vec<String>,
 }
 
 impl AddressCommand {
@@ -34,15 +63,47 @@ impl AddressCommand {
 
 impl AddArgs {
     pub async fn execute(self) -> Result<()> {
-        let network: IpNetwork = self
-            .address
-            .parse()
-            .map_err(|e: netctl_types::Error| miette::miette!("{}", e))?;
+        let spec = self.to_spec()?;
+        let network = spec.network;
 
         let mgr = NetworkManager::new().await?;
-        mgr.add_address(&self.interface, network).await?;
+        mgr.add_address(&self.interface, spec).await?;
 
         println!("✓ Address {} added to {}", network, self.interface);
         Ok(())
     }
+
+    fn to_spec(&self) -> Result<AddressSpec> {
+        let network: IpNetwork = self.address.parse()?;
+
+        let mut spec = AddressSpec::new(network);
+        spec.peer = self.peer;
+        spec.broadcast = self.broadcast;
+        spec.label = self.label.clone();
+        spec.scope = self
+            .scope
+            .as_deref()
+            .map(str::parse::<AddressScope>)
+            .transpose()?;
+        spec.valid_lifetime = parse_lifetime(self.valid_lft.as_deref())?;
+        spec.preferred_lifetime = parse_lifetime(self.preferred_lft.as_deref())?;
+        spec.flags = self
+            .flags
+            .iter()
+            .map(|f| f.parse::<AddressFlag>())
+            .collect::<netctl_types::Result<_>>()?;
+
+        Ok(spec)
+    }
+}
+
+/// Parse a lifetime argument; `None` and "forever" both mean no expiry.
+fn parse_lifetime(value: Option<&str>) -> Result<Option<u32>> {
+    match value {
+        None | Some("forever") => Ok(None),
+        Some(secs) => secs
+            .parse()
+            .map(Some)
+            .map_err(|_| miette::miette!("Invalid lifetime '{}'. Use seconds or 'forever'", secs)),
+    }
 }
@@ -1,55 +1,326 @@
 use clap::Args;
 use colored::Colorize;
 use miette::{IntoDiagnostic, Result};
+use netctl_config::model::{AdminState, DhcpMode, NetworkConfig};
+use netctl_config::systemd::{UnitFiles, NETWORK_DIR};
 use netctl_core::NetworkManager;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use netctl_types::{
+    AddressSpec, Chassis, DnsDomain, DnsOverTlsMode, DnssecMode, LinkInfo, LinkKind, ResolveMode,
+};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// What hostname changes are reported against
+const HOSTNAME_TARGET: &str = "hostname";
 
 #[derive(Args)]
 pub struct ApplyArgs {
-    /// Path to configuration file (YAML or TOML)
+    /// Path to configuration file (YAML, TOML or JSON)
     file: PathBuf,
 
     /// Dry run - show what would be applied without making changes
     #[arg(long, short = 'n')]
     dry_run: bool,
+
+    /// Also write systemd-networkd files to /etc/systemd/network and reload
+    /// networkd, so the configuration survives reboots
+    #[arg(long)]
+    persist: bool,
+}
+
+/// One step of applying a configuration
+#[derive(Debug)]
+pub enum Change {
+    CreateDevice(LinkKind),
+    LinkUp,
+    LinkDown,
+    Mtu(u32),
+    Address(AddressSpec),
+    DnsServers(Vec<IpAddr>),
+    DnsDomains(Vec<DnsDomain>),
+    DnsDefaultRoute(bool),
+    Llmnr(ResolveMode),
+    Mdns(ResolveMode),
+    Dnssec(DnssecMode),
+    DnsOverTls(DnsOverTlsMode),
+    StaticHostname(String),
+    PrettyHostname(String),
+    Chassis(Chassis),
+    Deployment(String),
+    Location(String),
+}
+
+impl Change {
+    /// Make the change. `target` is the interface or device name; hostname
+    /// changes ignore it.
+    pub async fn apply(&self, mgr: &NetworkManager, target: &str) -> netctl_types::Result<()> {
+        match self {
+            Self::CreateDevice(kind) => mgr.add_link(target, *kind).await,
+            Self::LinkUp => mgr.set_link_up(target).await,
+            Self::LinkDown => mgr.set_link_down(target).await,
+            Self::Mtu(mtu) => mgr.set_mtu(target, *mtu).await,
+            Self::Address(spec) => mgr.add_address(target, spec.clone()).await,
+            Self::DnsServers(servers) => mgr.set_dns_servers(target, servers.clone()).await,
+            Self::DnsDomains(domains) => mgr.set_dns_domains(target, domains.clone()).await,
+            Self::DnsDefaultRoute(enable) => mgr.set_dns_default_route(target, *enable).await,
+            Self::Llmnr(mode) => mgr.set_llmnr(target, *mode).await,
+            Self::Mdns(mode) => mgr.set_mdns(target, *mode).await,
+            Self::Dnssec(mode) => mgr.set_dnssec(target, *mode).await,
+            Self::DnsOverTls(mode) => mgr.set_dns_over_tls(target, *mode).await,
+            Self::StaticHostname(name) => mgr.set_static_hostname(name).await,
+            Self::PrettyHostname(name) => mgr.set_pretty_hostname(name).await,
+            Self::Chassis(chassis) => mgr.set_chassis(*chassis).await,
+            Self::Deployment(deployment) => mgr.set_deployment(deployment).await,
+            Self::Location(location) => mgr.set_location(location).await,
+        }
+    }
+
+    /// What the change did, once applied
+    pub fn done(&self) -> String {
+        match self {
+            Self::CreateDevice(kind) => format!("Created {} device", kind),
+            Self::LinkUp => "Interface is now up".to_string(),
+            Self::LinkDown => "Interface is now down".to_string(),
+            Self::Mtu(mtu) => format!("MTU set to {}", mtu),
+            Self::Address(spec) => format!("Added address {}", spec.network),
+            Self::StaticHostname(name) => format!("Static hostname set to {}", name),
+            change => format!("Done: {}", change),
+        }
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CreateDevice(kind) => write!(f, "create {} device", kind),
+            Self::LinkUp => write!(f, "bring interface up"),
+            Self::LinkDown => write!(f, "bring interface down"),
+            Self::Mtu(mtu) => write!(f, "set MTU to {}", mtu),
+            Self::Address(spec) => write!(f, "add address {}", spec.network),
+            Self::DnsServers(servers) => write!(f, "set DNS servers to {}", join(servers)),
+            Self::DnsDomains(domains) => write!(f, "set DNS domains to {}", join(domains)),
+            Self::DnsDefaultRoute(enable) => write!(f, "set DNS default route to {}", enable),
+            Self::Llmnr(mode) => write!(f, "set LLMNR to {}", mode),
+            Self::Mdns(mode) => write!(f, "set mDNS to {}", mode),
+            Self::Dnssec(mode) => write!(f, "set DNSSEC to {}", mode),
+            Self::DnsOverTls(mode) => write!(f, "set DNS over TLS to {}", mode),
+            Self::StaticHostname(name) => write!(f, "set static hostname to {}", name),
+            Self::PrettyHostname(name) => write!(f, "set pretty hostname to '{}'", name),
+            Self::Chassis(chassis) => write!(f, "set chassis to {}", chassis),
+            Self::Deployment(deployment) => write!(f, "set deployment to {}", deployment),
+            Self::Location(location) => write!(f, "set location to '{}'", location),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct NetworkConfig {
-    interfaces: Vec<InterfaceConfig>,
+/// A change and what it applies to
+#[derive(Debug)]
+pub struct Step {
+    pub target: String,
+    pub change: Change,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct InterfaceConfig {
-    name: String,
-    #[serde(default)]
-    state: Option<String>,
-    #[serde(default)]
-    mtu: Option<u32>,
-    #[serde(default)]
-    addresses: Vec<String>,
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.target, self.change)
+    }
+}
+
+/// The steps that apply `config` to a system with `links`, in order:
+/// hostname, devices that don't exist yet, then each interface that exists
+/// or is created. Addresses a link already has are left out. Also returns
+/// warnings about the parts that are skipped.
+pub fn plan(config: &NetworkConfig, links: &[LinkInfo]) -> (Vec<Step>, Vec<String>) {
+    let mut steps = Vec::new();
+    let mut warnings = Vec::new();
+    let mut push = |target: &str, change| {
+        steps.push(Step {
+            target: target.to_string(),
+            change,
+        })
+    };
+
+    if let Some(hostname) = &config.hostname {
+        if let Some(name) = &hostname.static_hostname {
+            push(HOSTNAME_TARGET, Change::StaticHostname(name.clone()));
+        }
+        if let Some(name) = &hostname.pretty_hostname {
+            push(HOSTNAME_TARGET, Change::PrettyHostname(name.clone()));
+        }
+        if let Some(chassis) = hostname.chassis {
+            push(HOSTNAME_TARGET, Change::Chassis(chassis));
+        }
+        if let Some(deployment) = &hostname.deployment {
+            push(HOSTNAME_TARGET, Change::Deployment(deployment.clone()));
+        }
+        if let Some(location) = &hostname.location {
+            push(HOSTNAME_TARGET, Change::Location(location.clone()));
+        }
+    }
+
+    for device in &config.devices {
+        if links.iter().any(|link| link.name == device.name) {
+            continue;
+        }
+        match device.kind.link_kind() {
+            Some(kind) => push(&device.name, Change::CreateDevice(kind)),
+            None => warnings.push(format!(
+                "{}: {} devices are only created by systemd-networkd (--persist), skipping",
+                device.name, device.kind
+            )),
+        }
+    }
+
+    for iface in &config.interfaces {
+        let name = &iface.name;
+        let created = config
+            .devices
+            .iter()
+            .any(|device| &device.name == name && device.kind.link_kind().is_some());
+        let link = links.iter().find(|link| &link.name == name);
+        if !created && link.is_none() {
+            warnings.push(format!("{}: interface doesn't exist, skipping", name));
+            continue;
+        }
+
+        match iface.state {
+            Some(AdminState::Up) => push(name, Change::LinkUp),
+            Some(AdminState::Down) => push(name, Change::LinkDown),
+            None => {}
+        }
+
+        if let Some(mtu) = iface.mtu {
+            push(name, Change::Mtu(mtu));
+        }
+
+        for addr in &iface.addresses {
+            match addr
+                .to_spec()
+                .and_then(|spec| spec.validate(name).map(|_| spec))
+            {
+                // Adding an address the link has fails with EEXIST
+                Ok(spec) if link.is_some_and(|link| link.addresses.contains(&spec.network)) => {}
+                Ok(spec) => push(name, Change::Address(spec)),
+                Err(e) => warnings.push(format!("{}: {}, skipping", name, e)),
+            }
+        }
+
+        for route in &iface.routes {
+            warnings.push(format!(
+                "{}: route to {} is only applied by systemd-networkd (--persist), skipping",
+                name, route.to
+            ));
+        }
+        if iface.dhcp.is_some_and(|dhcp| dhcp != DhcpMode::No) {
+            warnings.push(format!(
+                "{}: DHCP is only started by systemd-networkd (--persist), skipping",
+                name
+            ));
+        }
+
+        if let Some(dns) = &iface.dns {
+            if !dns.servers.is_empty() {
+                push(name, Change::DnsServers(dns.servers.clone()));
+            }
+            match dns.parsed_domains() {
+                Ok(domains) if !domains.is_empty() => push(name, Change::DnsDomains(domains)),
+                Ok(_) => {}
+                Err(e) => warnings.push(format!("{}: {}, skipping", name, e)),
+            }
+            if let Some(enable) = dns.default_route {
+                push(name, Change::DnsDefaultRoute(enable));
+            }
+            if let Some(mode) = dns.llmnr {
+                push(name, Change::Llmnr(mode));
+            }
+            if let Some(mode) = dns.mdns {
+                push(name, Change::Mdns(mode));
+            }
+            if let Some(mode) = dns.dnssec {
+                push(name, Change::Dnssec(mode));
+            }
+            if let Some(mode) = dns.dns_over_tls {
+                push(name, Change::DnsOverTls(mode));
+            }
+        }
+    }
+
+    (steps, warnings)
 }
 
 impl ApplyArgs {
     pub async fn execute(self) -> Result<()> {
-        // Read and parse configuration file
-        let config = self.read_config()?;
+        let config = NetworkConfig::load(&self.file)?;
 
         println!("{}", "Network Configuration Apply".bold().cyan());
         println!("{}", "=".repeat(60));
         println!("File: {}", self.file.display());
 
+        let errors: Vec<_> = config
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .collect();
+        if !errors.is_empty() {
+            println!();
+            for error in &errors {
+                println!("  {} {}", "✗".red(), error);
+            }
+            return Err(miette::miette!(
+                "configuration has {} error(s); run 'netctl validate' for details",
+                errors.len()
+            ));
+        }
+
+        // Render before changing anything so a file that can't be persisted
+        // fails up front
+        let unit_files = if self.persist {
+            Some(UnitFiles::render(&config)?)
+        } else {
+            None
+        };
+
         if self.dry_run {
             println!("{}\n", "DRY RUN - No changes will be made".yellow().bold());
         }
         println!();
 
         let mgr = NetworkManager::new().await?;
+        let links = mgr.list_links_with_addresses().await.into_diagnostic()?;
+        let (steps, warnings) = plan(&config, &links);
 
-        for iface in &config.interfaces {
-            self.apply_interface(&mgr, iface).await?;
+        // With --persist, networkd applies what can't be changed at runtime
+        if unit_files.is_none() {
+            for warning in warnings {
+                println!("{} {}", "⚠".yellow(), warning);
+            }
+        }
+
+        let mut target = None;
+        for step in &steps {
+            if target != Some(&step.target) {
+                println!("{} {}", "Configuring".cyan().bold(), step.target.cyan());
+                target = Some(&step.target);
+            }
+            if self.dry_run {
+                println!("  {} Would {}", "→".blue(), step.change);
+            } else {
+                step.change.apply(&mgr, &step.target).await?;
+                println!("  {} {}", "✓".green(), step.change.done());
+            }
+        }
+
+        if let Some(files) = &unit_files {
+            self.persist(&mgr, files).await?;
         }
 
         if self.dry_run {
@@ -61,75 +332,107 @@ impl ApplyArgs {
         Ok(())
     }
 
-    fn read_config(&self) -> Result<NetworkConfig> {
-        let content = fs::read_to_string(&self.file).into_diagnostic()?;
-
-        let config = if self.file.extension().and_then(|s| s.to_str()) == Some("toml") {
-            toml::from_str(&content).into_diagnostic()?
-        } else {
-            // Default to YAML
-            serde_yaml::from_str(&content).into_diagnostic()?
-        };
-
-        Ok(config)
-    }
-
-    async fn apply_interface(&self, mgr: &NetworkManager, iface: &InterfaceConfig) -> Result<()> {
-        println!("{} {}", "Configuring".cyan().bold(), iface.name.cyan());
+    async fn persist(&self, mgr: &NetworkManager, files: &UnitFiles) -> Result<()> {
+        println!("{} {}", "Persisting to".cyan().bold(), NETWORK_DIR.cyan());
+        for warning in &files.warnings {
+            println!("  {} {}", "⚠".yellow(), warning);
+        }
 
-        // Apply state
-        if let Some(state) = &iface.state {
-            match state.to_lowercase().as_str() {
-                "up" => {
-                    if self.dry_run {
-                        println!("  {} Would bring interface up", "→".blue());
-                    } else {
-                        mgr.set_link_up(&iface.name).await?;
-                        println!("  {} Interface is now up", "✓".green());
-                    }
-                }
-                "down" => {
-                    if self.dry_run {
-                        println!("  {} Would bring interface down", "→".blue());
+        if self.dry_run {
+            for (name, contents) in files.files() {
+                println!("  {} Would write {}/{}", "→".blue(), NETWORK_DIR, name);
+                for line in contents.lines() {
+                    if line.is_empty() {
+                        println!();
                     } else {
-                        mgr.set_link_down(&iface.name).await?;
-                        println!("  {} Interface is now down", "✓".green());
+                        println!("      {}", line.dimmed());
                     }
                 }
-                _ => {
-                    println!("  {} Invalid state '{}', skipping", "⚠".yellow(), state);
-                }
             }
+            for path in files.stale_files(Path::new(NETWORK_DIR)).await? {
+                println!("  {} Would remove {}", "→".blue(), path.display());
+            }
+            println!("  {} Would reload systemd-networkd", "→".blue());
+            return Ok(());
         }
 
-        // Apply MTU
-        if let Some(mtu) = iface.mtu {
-            if self.dry_run {
-                println!("  {} Would set MTU to {}", "→".blue(), mtu);
-            } else {
-                mgr.set_mtu(&iface.name, mtu).await?;
-                println!("  {} MTU set to {}", "✓".green(), mtu);
-            }
+        let installed = mgr.persist_networkd_files(files).await?;
+        for path in &installed.written {
+            println!("  {} Wrote {}", "✓".green(), path.display());
+        }
+        for path in &installed.removed {
+            println!("  {} Removed {}", "✓".green(), path.display());
         }
+        println!("  {} Reloaded systemd-networkd", "✓".green());
+        if !files.links.is_empty() {
+            println!(
+                "  {} Interface names from .link files apply from the next boot",
+                "→".blue()
+            );
+        }
+        Ok(())
+    }
+}
 
-        // Apply addresses
-        for addr in &iface.addresses {
-            if self.dry_run {
-                println!("  {} Would add address {}", "→".blue(), addr);
-            } else {
-                // Parse address
-                match addr.parse() {
-                    Ok(ip_net) => {
-                        mgr.add_address(&iface.name, ip_net).await?;
-                        println!("  {} Added address {}", "✓".green(), addr);
-                    }
-                    Err(_) => {
-                        println!("  {} Invalid address '{}', skipping", "⚠".yellow(), addr);
-                    }
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netctl_config::model::ConfigFormat;
+    use netctl_types::LinkState;
+
+    fn link(index: u32, name: &str, addresses: &[&str]) -> LinkInfo {
+        LinkInfo {
+            index,
+            name: name.to_string(),
+            state: LinkState::Up,
+            carrier: true,
+            flags: 0,
+            mtu: 1500,
+            mac_address: None,
+            permanent_mac_address: None,
+            altnames: Vec::new(),
+            addresses: addresses.iter().map(|addr| addr.parse().unwrap()).collect(),
+            kind: None,
+            vfs: Vec::new(),
         }
+    }
 
-        Ok(())
+    fn addresses(steps: &[Step]) -> Vec<String> {
+        steps
+            .iter()
+            .filter_map(|step| match &step.change {
+                Change::Address(spec) => Some(format!("{} {}", step.target, spec.network)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exported_config_applies_cleanly() {
+        let links = [
+            link(1, "lo", &["127.0.0.1/8", "::1/128"]),
+            link(2, "eth0", &["192.0.2.10/24", "fe80::1/64"]),
+        ];
+        let exported = super::super::config::runtime_config(&links)
+            .to_string(ConfigFormat::Yaml)
+            .unwrap();
+        let config = NetworkConfig::parse(&exported, ConfigFormat::Yaml).unwrap();
+
+        let (steps, warnings) = plan(&config, &links);
+        assert!(addresses(&steps).is_empty(), "{:?}", addresses(&steps));
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_only_missing_addresses_added() {
+        let links = [link(2, "eth0", &["192.0.2.10/24"])];
+        let config = NetworkConfig::parse(
+            "interfaces:\n  - name: eth0\n    addresses: [192.0.2.10/24, 192.0.2.11/24]\n",
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+
+        let (steps, _) = plan(&config, &links);
+        assert_eq!(addresses(&steps), ["eth0 192.0.2.11/24"]);
     }
 }
@@ -1,11 +1,15 @@
 use clap::{Args, Subcommand};
 use colored::Colorize;
 use miette::{IntoDiagnostic, Result};
+use netctl_config::model::NetworkConfig;
 use netctl_core::NetworkManager;
+use netctl_types::LinkInfo;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use super::profile::load_interface;
+
 #[derive(Args)]
 pub struct BackupCommand {
     #[command(subcommand)]
@@ -71,17 +75,20 @@ struct Backup {
     name: String,
     description: Option<String>,
     created_at: String,
-    interfaces: Vec<InterfaceBackup>,
+    #[serde(flatten)]
+    config: NetworkConfig,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct InterfaceBackup {
-    name: String,
-    index: u32,
-    state: String,
-    mtu: u32,
-    mac_address: Option<String>,
-    addresses: Vec<String>,
+impl Backup {
+    /// A backup of the current state of `links`
+    fn of_links(name: String, description: Option<String>, links: &[LinkInfo]) -> Self {
+        Self {
+            name,
+            description,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            config: NetworkConfig::from_links(links),
+        }
+    }
 }
 
 impl BackupCommand {
@@ -103,24 +110,7 @@ impl CreateArgs {
         let mgr = NetworkManager::new().await?;
         let links = mgr.list_links().await?;
 
-        let interfaces: Vec<InterfaceBackup> = links
-            .into_iter()
-            .map(|link| InterfaceBackup {
-                name: link.name,
-                index: link.index,
-                state: format!("{:?}", link.state),
-                mtu: link.mtu,
-                mac_address: link.mac_address.map(|m| m.to_string()),
-                addresses: link.addresses.iter().map(|a| a.to_string()).collect(),
-            })
-            .collect();
-
-        let backup = Backup {
-            name: self.name.clone(),
-            description: self.description,
-            created_at: chrono::Utc::now().to_rfc3339(),
-            interfaces,
-        };
+        let backup = Backup::of_links(self.name.clone(), self.description, &links);
 
         let backup_dir = get_backup_dir()?;
         fs::create_dir_all(&backup_dir).into_diagnostic()?;
@@ -131,7 +121,7 @@ impl CreateArgs {
 
         println!("{} Backup '{}' created successfully", "✓".green(), self.name.green());
         println!("  Location: {}", backup_path.display());
-        println!("  Interfaces: {}", backup.interfaces.len());
+        println!("  Interfaces: {}", backup.config.interfaces.len());
         println!();
 
         Ok(())
@@ -150,7 +140,7 @@ impl RestoreArgs {
             println!("Description: {}", desc);
         }
         println!("Created: {}", backup.created_at);
-        println!("Interfaces: {}", backup.interfaces.len());
+        println!("Interfaces: {}", backup.config.interfaces.len());
         println!();
 
         if !self.yes {
@@ -174,22 +164,11 @@ impl RestoreArgs {
         let mgr = NetworkManager::new().await?;
         let current_links = mgr.list_links().await?;
 
-        let auto_backup = Backup {
-            name: auto_backup_name.clone(),
-            description: Some(format!("Automatic backup before restoring '{}'", backup.name)),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            interfaces: current_links
-                .into_iter()
-                .map(|link| InterfaceBackup {
-                    name: link.name,
-                    index: link.index,
-                    state: format!("{:?}", link.state),
-                    mtu: link.mtu,
-                    mac_address: link.mac_address.map(|m| m.to_string()),
-                    addresses: link.addresses.iter().map(|a| a.to_string()).collect(),
-                })
-                .collect(),
-        };
+        let auto_backup = Backup::of_links(
+            auto_backup_name.clone(),
+            Some(format!("Automatic backup before restoring '{}'", backup.name)),
+            &current_links,
+        );
 
         let backup_dir = get_backup_dir()?;
         let auto_backup_path = backup_dir.join(format!("{}.json", auto_backup_name));
@@ -201,24 +180,11 @@ impl RestoreArgs {
 
         // Apply backup configuration
         println!("Restoring configuration...");
-        for iface in &backup.interfaces {
+        for iface in &backup.config.interfaces {
             println!("  {} {}", "→".blue(), iface.name);
 
-            // Set MTU
-            mgr.set_mtu(&iface.name, iface.mtu).await?;
-
-            // Set state
-            match iface.state.as_str() {
-                "Up" => {
-                    mgr.set_link_up(&iface.name).await?;
-                }
-                "Down" => {
-                    mgr.set_link_down(&iface.name).await?;
-                }
-                _ => {}
-            }
-
             // Note: Address restoration would require address deletion support
+            load_interface(&mgr, iface).await?;
         }
 
         println!();
@@ -302,7 +268,7 @@ async fn list_backups() -> Result<()> {
             println!("  Description: {}", desc);
         }
         println!("  Created: {}", backup.created_at);
-        println!("  Interfaces: {}", backup.interfaces.len());
+        println!("  Interfaces: {}", backup.config.interfaces.len());
         println!();
     }
 
@@ -0,0 +1,127 @@
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::Result;
+use netctl_config::model::{ConfigFormat, DeviceConfig, NetworkConfig};
+use netctl_config::systemd::{NetworkdConfig, Resolved};
+use netctl_core::NetworkManager;
+use netctl_types::{Error, LinkInfo, LinkKind};
+use std::str::FromStr;
+
+#[derive(Args)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    command: ConfigSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigSubcommand {
+    /// Show the configuration of the running system or of networkd's files
+    Show(ShowArgs),
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Where to read the configuration from (runtime, networkd)
+    #[arg(long, default_value = "runtime")]
+    source: Source,
+
+    /// Output format (yaml, toml, json)
+    #[arg(short, long, default_value = "yaml")]
+    format: String,
+}
+
+/// Where a configuration is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The kernel's current state
+    Runtime,
+    /// The systemd-networkd files on disk
+    Networkd,
+}
+
+impl FromStr for Source {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "runtime" => Ok(Self::Runtime),
+            "networkd" => Ok(Self::Networkd),
+            _ => Err(Error::Generic(format!(
+                "unknown source '{}', expected runtime or networkd",
+                s
+            ))),
+        }
+    }
+}
+
+impl ConfigCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            ConfigSubcommand::Show(args) => args.execute().await,
+        }
+    }
+}
+
+impl ShowArgs {
+    pub async fn execute(self) -> Result<()> {
+        let format: ConfigFormat = self.format.parse()?;
+        let mgr = NetworkManager::new().await?;
+        let links = mgr.list_links().await?;
+
+        let config = match self.source {
+            Source::Runtime => runtime_config(&links),
+            Source::Networkd => {
+                let resolved = networkd_config(&links).await?;
+                for warning in &resolved.warnings {
+                    eprintln!("{} {}", "⚠".yellow(), warning);
+                }
+                // JSON has no comments to say where settings came from
+                if format != ConfigFormat::Json {
+                    for assignment in &resolved.assignments {
+                        let dropins: Vec<_> = assignment
+                            .dropins
+                            .iter()
+                            .map(|path| path.display().to_string())
+                            .collect();
+                        if dropins.is_empty() {
+                            println!("# {}: {}", assignment.ifname, assignment.path.display());
+                        } else {
+                            println!(
+                                "# {}: {} (+ {})",
+                                assignment.ifname,
+                                assignment.path.display(),
+                                dropins.join(", ")
+                            );
+                        }
+                    }
+                }
+                resolved.config
+            }
+        };
+
+        print!("{}", config.to_string(format)?);
+        if format == ConfigFormat::Json {
+            println!();
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of `links` as they are now, including the virtual
+/// devices netctl can create
+pub fn runtime_config(links: &[LinkInfo]) -> NetworkConfig {
+    let mut config = NetworkConfig::from_links(links);
+    config.devices = links
+        .iter()
+        .filter_map(|link| {
+            let kind = link.kind.as_deref()?.parse::<LinkKind>().ok()?;
+            Some(DeviceConfig::new(&link.name, kind.into()))
+        })
+        .collect();
+    config
+}
+
+/// What the networkd files on disk configure for `links`
+pub async fn networkd_config(links: &[LinkInfo]) -> Result<Resolved> {
+    Ok(NetworkdConfig::load().await?.resolve(links))
+}
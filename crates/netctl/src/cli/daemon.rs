@@ -0,0 +1,11 @@
+use clap::Args;
+use miette::Result;
+
+#[derive(Args)]
+pub struct DaemonArgs {}
+
+impl DaemonArgs {
+    pub async fn execute(self) -> Result<()> {
+        crate::daemon::run().await
+    }
+}
@@ -0,0 +1,157 @@
+use super::status::format_duration;
+use chrono::{Local, TimeZone};
+use clap::{Args, Subcommand};
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::Dhcp4Lease;
+
+#[derive(Args)]
+pub struct DhcpCommand {
+    #[command(subcommand)]
+    command: DhcpSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum DhcpSubcommand {
+    /// Renew the DHCP lease of an interface
+    Renew(DhcpRenewArgs),
+
+    /// Ask clients of the DHCP server on an interface to renew (FORCERENEW)
+    ForceRenew(DhcpForceRenewArgs),
+
+    /// Show the current DHCPv4 lease
+    #[command(alias = "show")]
+    Status(DhcpStatusArgs),
+}
+
+#[derive(Args)]
+pub struct DhcpRenewArgs {
+    /// Interface name
+    interface: String,
+}
+
+#[derive(Args)]
+pub struct DhcpForceRenewArgs {
+    /// Interface running the networkd DHCP server
+    interface: String,
+}
+
+#[derive(Args)]
+pub struct DhcpStatusArgs {
+    /// Interface name
+    interface: String,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+impl DhcpCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            DhcpSubcommand::Renew(args) => args.execute().await,
+            DhcpSubcommand::ForceRenew(args) => args.execute().await,
+            DhcpSubcommand::Status(args) => args.execute().await,
+        }
+    }
+}
+
+impl DhcpRenewArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.renew_dhcp(&self.interface).await?;
+
+        println!("✓ DHCP renewal requested on {}", self.interface);
+        Ok(())
+    }
+}
+
+impl DhcpForceRenewArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.force_renew_dhcp(&self.interface).await?;
+
+        println!("✓ FORCERENEW sent to DHCP clients on {}", self.interface);
+        Ok(())
+    }
+}
+
+impl DhcpStatusArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let lease = mgr.get_dhcp_lease(&self.interface).await?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&lease)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            print_lease(&self.interface, &lease);
+        }
+        Ok(())
+    }
+}
+
+fn print_lease(ifname: &str, lease: &Dhcp4Lease) {
+    println!("DHCPv4 lease on {}:", ifname);
+    match lease.prefix_len {
+        Some(prefix_len) => println!("  Address: {}/{}", lease.address, prefix_len),
+        None => println!("  Address: {}", lease.address),
+    }
+    if let Some(server) = lease.server_address {
+        println!("  Server: {}", server);
+    }
+    if !lease.routers.is_empty() {
+        println!("  Routers: {}", join(&lease.routers));
+    }
+
+    if let Some(lifetime) = lease.lifetime {
+        let mut timers = Vec::new();
+        if let Some(t1) = lease.t1 {
+            timers.push(format!("renew after {}", format_duration(t1)));
+        }
+        if let Some(t2) = lease.t2 {
+            timers.push(format!("rebind after {}", format_duration(t2)));
+        }
+        if timers.is_empty() {
+            println!("  Lease time: {}", format_duration(lifetime));
+        } else {
+            println!(
+                "  Lease time: {} ({})",
+                format_duration(lifetime),
+                timers.join(", ")
+            );
+        }
+    }
+    if let Some(acquired) = lease.acquired {
+        println!("  Acquired: {}", format_timestamp(acquired));
+    }
+    if let Some(expires) = lease.expires() {
+        println!("  Expires: {}", format_timestamp(expires));
+    }
+
+    if !lease.dns.is_empty() {
+        println!("  DNS: {}", join(&lease.dns));
+    }
+    if !lease.ntp.is_empty() {
+        println!("  NTP: {}", join(&lease.ntp));
+    }
+    if !lease.domains.is_empty() {
+        println!("  Domains: {}", lease.domains.join(", "));
+    }
+}
+
+fn format_timestamp(secs: u64) -> String {
+    match Local.timestamp_opt(secs as i64, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => secs.to_string(),
+    }
+}
+
+fn join<T: ToString>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
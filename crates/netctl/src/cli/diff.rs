@@ -1,12 +1,17 @@
+use super::config::networkd_config;
 use clap::Args;
 use colored::Colorize;
 use miette::Result;
+use netctl_config::model::{AdminState, DhcpMode, InterfaceConfig, NetworkConfig};
 use netctl_core::NetworkManager;
+use netctl_types::IpNetwork;
 use std::collections::HashSet;
+use std::net::IpAddr;
 
 #[derive(Args)]
 pub struct DiffArgs {
-    /// First profile/state to compare ('current' for current state)
+    /// First profile/state to compare ('current' for current state,
+    /// 'networkd' for the systemd-networkd files on disk)
     first: String,
 
     /// Second profile/state to compare
@@ -33,15 +38,25 @@ impl DiffArgs {
             let mgr = NetworkManager::new().await?;
             let links = mgr.list_links().await?;
 
-            Ok(links
+            Ok(NetworkConfig::from_links(&links)
+                .interfaces
                 .into_iter()
-                .map(|link| InterfaceState {
-                    name: link.name,
-                    state: format!("{:?}", link.state),
-                    mtu: link.mtu,
-                    mac: link.mac_address.map(|m| m.to_string()),
-                    addresses: link.addresses.iter().map(|a| a.to_string()).collect(),
-                })
+                .map(InterfaceState::from)
+                .collect())
+        } else if name == "networkd" {
+            // What networkd's files configure for the links there are now
+            let mgr = NetworkManager::new().await?;
+            let links = mgr.list_links().await?;
+            let resolved = networkd_config(&links).await?;
+            for warning in &resolved.warnings {
+                eprintln!("{} {}", "⚠".yellow(), warning);
+            }
+
+            Ok(resolved
+                .config
+                .interfaces
+                .into_iter()
+                .map(InterfaceState::from)
                 .collect())
         } else {
             // Load from profile
@@ -49,15 +64,10 @@ impl DiffArgs {
             let profile = load_profile(name)?;
 
             Ok(profile
+                .config
                 .interfaces
                 .into_iter()
-                .map(|iface| InterfaceState {
-                    name: iface.name,
-                    state: iface.state,
-                    mtu: iface.mtu,
-                    mac: iface.mac_address,
-                    addresses: iface.addresses,
-                })
+                .map(InterfaceState::from)
                 .collect())
         }
     }
@@ -88,62 +98,184 @@ impl DiffArgs {
         Ok(())
     }
 
+    /// Against networkd's files, settings only one side sets aren't
+    /// compared: a networkd file that leaves the MTU alone says nothing
+    /// about what it should be. Addresses the kernel or DHCP adds are
+    /// ignored the same way.
     fn compare_interface(&self, iface1: &InterfaceState, iface2: &InterfaceState) -> Result<()> {
+        let lenient = self.first == "networkd" || self.second == "networkd";
+        let state = differs(iface1.state, iface2.state, lenient);
+        let mtu = differs(iface1.mtu, iface2.mtu, lenient);
+        let mac = differs(iface1.mac.as_deref(), iface2.mac.as_deref(), lenient);
+        let removed = iface1.extra_addresses(iface2, lenient);
+        let added = iface2.extra_addresses(iface1, lenient);
+
         // Check for differences
-        if iface1.state != iface2.state
-            || iface1.mtu != iface2.mtu
-            || iface1.mac != iface2.mac
-            || iface1.addresses != iface2.addresses
-        {
+        if state || mtu || mac || !removed.is_empty() || !added.is_empty() {
             println!("\n{} {}:", "~".yellow(), iface1.name.yellow());
         }
 
-        if iface1.state != iface2.state {
+        if state {
             println!("  {} state: {} → {}",
                 "~".yellow(),
-                iface1.state.red(),
-                iface2.state.green()
+                show(iface1.state).red(),
+                show(iface2.state).green()
             );
         }
 
-        if iface1.mtu != iface2.mtu {
+        if mtu {
             println!("  {} mtu: {} → {}",
                 "~".yellow(),
-                iface1.mtu.to_string().red(),
-                iface2.mtu.to_string().green()
+                show(iface1.mtu).red(),
+                show(iface2.mtu).green()
             );
         }
 
-        if iface1.mac != iface2.mac {
+        if mac {
             println!("  {} mac: {} → {}",
                 "~".yellow(),
-                iface1.mac.as_deref().unwrap_or("none").red(),
-                iface2.mac.as_deref().unwrap_or("none").green()
+                show(iface1.mac.as_deref()).red(),
+                show(iface2.mac.as_deref()).green()
             );
         }
 
-        if iface1.addresses != iface2.addresses {
-            let addr1_set: HashSet<_> = iface1.addresses.iter().collect();
-            let addr2_set: HashSet<_> = iface2.addresses.iter().collect();
-
-            for addr in addr1_set.difference(&addr2_set) {
-                println!("  {} address: {}", "-".red(), addr.red());
-            }
+        for addr in removed {
+            println!("  {} address: {}", "-".red(), addr.red());
+        }
 
-            for addr in addr2_set.difference(&addr1_set) {
-                println!("  {} address: {}", "+".green(), addr.green());
-            }
+        for addr in added {
+            println!("  {} address: {}", "+".green(), addr.green());
         }
 
         Ok(())
     }
 }
 
+/// Whether two settings differ; if `lenient`, only when both are set
+fn differs<T: PartialEq>(first: Option<T>, second: Option<T>, lenient: bool) -> bool {
+    match (first, second) {
+        (Some(first), Some(second)) => first != second,
+        (first, second) => !lenient && first.is_some() != second.is_some(),
+    }
+}
+
 #[derive(Debug, Clone)]
 struct InterfaceState {
     name: String,
-    state: String,
-    mtu: u32,
+    state: Option<AdminState>,
+    mtu: Option<u32>,
     mac: Option<String>,
     addresses: Vec<String>,
+    dhcp: Option<DhcpMode>,
+}
+
+impl InterfaceState {
+    /// Addresses this side has that `other` doesn't. If `lenient`, IPv6
+    /// link-local ones and those of a family `other` leaves to DHCP are
+    /// left out.
+    fn extra_addresses(&self, other: &InterfaceState, lenient: bool) -> Vec<&str> {
+        let dhcp = other.dhcp.unwrap_or(DhcpMode::No);
+        let mut extra: Vec<&str> = self
+            .addresses
+            .iter()
+            .filter(|addr| !other.addresses.contains(addr))
+            .filter(|addr| {
+                !lenient
+                    || match addr.parse::<IpNetwork>().map(|net| net.addr) {
+                        Ok(IpAddr::V4(_)) => !dhcp.ipv4(),
+                        Ok(IpAddr::V6(addr)) => {
+                            !dhcp.ipv6() && addr.segments()[0] & 0xffc0 != 0xfe80
+                        }
+                        Err(_) => true,
+                    }
+            })
+            .map(String::as_str)
+            .collect();
+        extra.sort_unstable();
+        extra.dedup();
+        extra
+    }
+}
+
+impl From<InterfaceConfig> for InterfaceState {
+    fn from(iface: InterfaceConfig) -> Self {
+        Self {
+            state: iface.state,
+            mtu: iface.mtu,
+            mac: iface.mac_address,
+            // Parsed and printed again, so that one spelling of an IPv6
+            // address matches another
+            addresses: iface
+                .addresses
+                .iter()
+                .map(|addr| match addr.address().parse::<IpNetwork>() {
+                    Ok(net) => net.to_string(),
+                    Err(_) => addr.address().to_string(),
+                })
+                .collect(),
+            dhcp: iface.dhcp,
+            name: iface.name,
+        }
+    }
+}
+
+/// A setting for display; unset ones show as "none"
+fn show<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(addresses: &[&str], dhcp: Option<DhcpMode>) -> InterfaceState {
+        InterfaceState {
+            name: "eth0".to_string(),
+            state: None,
+            mtu: None,
+            mac: None,
+            addresses: addresses.iter().map(|addr| addr.to_string()).collect(),
+            dhcp,
+        }
+    }
+
+    #[test]
+    fn test_differs() {
+        assert!(differs(Some(1500), Some(9000), false));
+        assert!(differs(Some(1500), Some(9000), true));
+        assert!(!differs(Some(1500), Some(1500), false));
+        assert!(differs(Some(1500), None, false));
+        assert!(differs(None, Some(1500), false));
+        assert!(!differs(Some(1500), None, true));
+        assert!(!differs(None, Some(1500), true));
+        assert!(!differs(None::<u32>, None, false));
+    }
+
+    #[test]
+    fn test_extra_addresses() {
+        let runtime = state(
+            &[
+                "192.0.2.10/24",
+                "198.51.100.7/24",
+                "2001:db8::10/64",
+                "fe80::1/64",
+            ],
+            None,
+        );
+        let files = state(&["192.0.2.10/24", "2001:db8::10/64"], Some(DhcpMode::Ipv4));
+
+        assert_eq!(
+            runtime.extra_addresses(&files, false),
+            ["198.51.100.7/24", "fe80::1/64"]
+        );
+        // The IPv4 one is DHCP's, the IPv6 one link-local
+        assert!(runtime.extra_addresses(&files, true).is_empty());
+
+        let static_only = state(&["192.0.2.10/24"], Some(DhcpMode::No));
+        assert_eq!(
+            runtime.extra_addresses(&static_only, true),
+            ["198.51.100.7/24", "2001:db8::10/64"]
+        );
+        assert!(files.extra_addresses(&runtime, false).is_empty());
+    }
 }
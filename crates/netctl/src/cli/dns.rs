@@ -0,0 +1,539 @@
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{
+    AddressResolution, DnsDomain, DnsOverTlsMode, DnsRecordType, DnsStatistics, DnssecMode,
+    GlobalDnsSettings, HostnameResolution, LinkDnsSettings, RecordResolution, ResolveFlags,
+    ResolveMode,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+#[derive(Args)]
+pub struct DnsCommand {
+    #[command(subcommand)]
+    command: DnsSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum DnsSubcommand {
+    /// Resolve a name, address or record through systemd-resolved
+    #[command(alias = "resolve")]
+    Query(DnsQueryArgs),
+
+    /// Show global and per-link DNS settings
+    #[command(alias = "status")]
+    Show(DnsShowArgs),
+
+    /// Set DNS servers and resolver options of an interface
+    Set(DnsSetArgs),
+
+    /// Set search and routing domains of an interface
+    Domains(DnsDomainsArgs),
+
+    /// Drop all DNS settings made for an interface
+    Revert(DnsRevertArgs),
+
+    /// Flush the resolver caches
+    Flush,
+
+    /// Show transaction, cache and DNSSEC statistics
+    #[command(alias = "statistics")]
+    Stats(DnsStatsArgs),
+}
+
+#[derive(Args)]
+pub struct DnsShowArgs {
+    /// Interface name (if not specified, shows global settings and all links)
+    interface: Option<String>,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct DnsSetArgs {
+    /// Interface name
+    interface: String,
+
+    /// DNS servers
+    servers: Vec<IpAddr>,
+
+    /// Send queries matching no routing domain to this link
+    #[arg(long, value_parser = clap::builder::BoolishValueParser::new())]
+    default_route: Option<bool>,
+
+    /// LLMNR support (yes, no, resolve)
+    #[arg(long)]
+    llmnr: Option<ResolveMode>,
+
+    /// MulticastDNS support (yes, no, resolve)
+    #[arg(long)]
+    mdns: Option<ResolveMode>,
+
+    /// DNSSEC validation (yes, no, allow-downgrade)
+    #[arg(long)]
+    dnssec: Option<DnssecMode>,
+
+    /// DNS-over-TLS (yes, no, opportunistic)
+    #[arg(long = "dns-over-tls")]
+    dns_over_tls: Option<DnsOverTlsMode>,
+
+    /// Domains to skip DNSSEC validation for
+    #[arg(long = "negative-trust-anchor", value_delimiter = ',')]
+    negative_trust_anchors: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct DnsDomainsArgs {
+    /// Interface name
+    interface: String,
+
+    /// Domains; prefix with ~ for routing-only (~corp.example, ~. for all
+    /// queries). Giving none clears them.
+    domains: Vec<DnsDomain>,
+}
+
+#[derive(Args)]
+pub struct DnsRevertArgs {
+    /// Interface name
+    interface: String,
+}
+
+#[derive(Args)]
+pub struct DnsStatsArgs {
+    /// Zero the counters after printing them
+    #[arg(long)]
+    reset: bool,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct DnsQueryArgs {
+    /// Host name, or an address for a reverse lookup
+    name: String,
+
+    /// Record type to look up (A, AAAA, CNAME, MX, NS, PTR, SRV, TXT)
+    #[arg(short = 't', long = "type")]
+    record_type: Option<DnsRecordType>,
+
+    /// Only resolve on this interface
+    #[arg(short, long)]
+    interface: Option<String>,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+impl DnsCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            DnsSubcommand::Query(args) => args.execute().await,
+            DnsSubcommand::Show(args) => args.execute().await,
+            DnsSubcommand::Set(args) => args.execute().await,
+            DnsSubcommand::Domains(args) => args.execute().await,
+            DnsSubcommand::Revert(args) => args.execute().await,
+            DnsSubcommand::Flush => {
+                let mgr = NetworkManager::new().await?;
+                mgr.flush_dns_caches().await?;
+                println!("✓ DNS caches flushed");
+                Ok(())
+            }
+            DnsSubcommand::Stats(args) => args.execute().await,
+        }
+    }
+}
+
+impl DnsQueryArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let ifname = self.interface.as_deref();
+        let start = Instant::now();
+
+        match (self.record_type, self.name.parse::<IpAddr>()) {
+            (Some(record_type), _) => {
+                let result = mgr.resolve_record(&self.name, record_type, ifname).await?;
+                let elapsed = start.elapsed();
+                if self.json {
+                    return print_json(&result);
+                }
+                print_record_resolution(&result, &link_names(&mgr).await);
+                print_answer_info(&result.flags, elapsed);
+            }
+            (None, Ok(address)) => {
+                let result = mgr.resolve_address(address, ifname).await?;
+                let elapsed = start.elapsed();
+                if self.json {
+                    return print_json(&result);
+                }
+                print_address_resolution(&result, &link_names(&mgr).await);
+                print_answer_info(&result.flags, elapsed);
+            }
+            (None, Err(_)) => {
+                let result = mgr.resolve_hostname(&self.name, ifname).await?;
+                let elapsed = start.elapsed();
+                if self.json {
+                    return print_json(&result);
+                }
+                print_hostname_resolution(&result, &link_names(&mgr).await);
+                print_answer_info(&result.flags, elapsed);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct NamedLinkDnsSettings {
+    name: String,
+    #[serde(flatten)]
+    settings: LinkDnsSettings,
+}
+
+#[derive(Serialize)]
+struct DnsStatus {
+    global: GlobalDnsSettings,
+    links: Vec<NamedLinkDnsSettings>,
+}
+
+impl DnsShowArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+
+        if let Some(ref ifname) = self.interface {
+            let settings = mgr.get_link_dns_settings(ifname).await?;
+            if self.json {
+                return print_json(&settings);
+            }
+            print_link_settings(ifname, &settings);
+            return Ok(());
+        }
+
+        let global = mgr.get_global_dns_settings().await?;
+        let mut links = Vec::new();
+        let mut failed = Vec::new();
+        for link in mgr.list_links().await? {
+            // One link resolved can't describe shouldn't hide the others
+            match mgr.get_link_dns_settings(&link.name).await {
+                Ok(settings) => links.push(NamedLinkDnsSettings {
+                    name: link.name,
+                    settings,
+                }),
+                Err(e) => failed.push(format!("{}: {}", link.name, e)),
+            }
+        }
+
+        if self.json {
+            for failure in &failed {
+                eprintln!("{} {}", "⚠".yellow(), failure);
+            }
+            return print_json(&DnsStatus { global, links });
+        }
+
+        print_global_settings(&global);
+        for link in &links {
+            println!();
+            print_link_settings(&link.name, &link.settings);
+        }
+        if !failed.is_empty() {
+            println!();
+            for failure in &failed {
+                eprintln!("{} {}", "⚠".yellow(), failure);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_global_settings(settings: &GlobalDnsSettings) {
+    println!("{}", "Global".bold());
+    println!("  DNS servers: {}", join_addrs(&settings.servers));
+    println!(
+        "  Fallback DNS servers: {}",
+        join_addrs(&settings.fallback_servers)
+    );
+    if let Some(server) = settings.current_server {
+        println!("  Current DNS server: {}", server);
+    }
+    println!("  Domains: {}", join_domains(&settings.domains));
+    println!(
+        "  LLMNR: {}, mDNS: {}, DNSSEC: {}, DNS-over-TLS: {}",
+        settings.llmnr, settings.mdns, settings.dnssec, settings.dns_over_tls
+    );
+    println!("  resolv.conf mode: {}", settings.resolv_conf_mode);
+}
+
+fn print_link_settings(ifname: &str, settings: &LinkDnsSettings) {
+    println!("{} ({})", ifname.bold(), settings.ifindex);
+    println!("  DNS servers: {}", join_addrs(&settings.servers));
+    if let Some(server) = settings.current_server {
+        println!("  Current DNS server: {}", server);
+    }
+    println!("  Domains: {}", join_domains(&settings.domains));
+    println!(
+        "  Default route: {}",
+        if settings.default_route { "yes" } else { "no" }
+    );
+    println!(
+        "  LLMNR: {}, mDNS: {}, DNSSEC: {}, DNS-over-TLS: {}",
+        or_global(&settings.llmnr),
+        or_global(&settings.mdns),
+        or_global(&settings.dnssec),
+        or_global(&settings.dns_over_tls)
+    );
+    if !settings.negative_trust_anchors.is_empty() {
+        println!(
+            "  DNSSEC negative trust anchors: {}",
+            settings.negative_trust_anchors.join(", ")
+        );
+    }
+}
+
+fn join_addrs(addrs: &[IpAddr]) -> String {
+    if addrs.is_empty() {
+        return "-".to_string();
+    }
+    addrs
+        .iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn join_domains(domains: &[DnsDomain]) -> String {
+    if domains.is_empty() {
+        return "-".to_string();
+    }
+    domains
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Per-link modes are empty when the global setting applies
+fn or_global(mode: &str) -> &str {
+    if mode.is_empty() {
+        "global"
+    } else {
+        mode
+    }
+}
+
+impl DnsSetArgs {
+    pub async fn execute(self) -> Result<()> {
+        let nothing_to_set = self.servers.is_empty()
+            && self.default_route.is_none()
+            && self.llmnr.is_none()
+            && self.mdns.is_none()
+            && self.dnssec.is_none()
+            && self.dns_over_tls.is_none()
+            && self.negative_trust_anchors.is_empty();
+        if nothing_to_set {
+            return Err(miette::miette!(
+                "Nothing to set. Give DNS servers and/or resolver options (see --help)"
+            ));
+        }
+
+        let mgr = NetworkManager::new().await?;
+        let ifname = &self.interface;
+
+        if !self.servers.is_empty() {
+            mgr.set_dns_servers(ifname, self.servers.clone()).await?;
+            println!(
+                "✓ DNS servers of {} set to {}",
+                ifname,
+                join_addrs(&self.servers)
+            );
+        }
+        if let Some(enable) = self.default_route {
+            mgr.set_dns_default_route(ifname, enable).await?;
+            println!("✓ DNS default route {}", if enable { "on" } else { "off" });
+        }
+        if let Some(mode) = self.llmnr {
+            mgr.set_llmnr(ifname, mode).await?;
+            println!("✓ LLMNR set to {}", mode);
+        }
+        if let Some(mode) = self.mdns {
+            mgr.set_mdns(ifname, mode).await?;
+            println!("✓ MulticastDNS set to {}", mode);
+        }
+        if let Some(mode) = self.dnssec {
+            mgr.set_dnssec(ifname, mode).await?;
+            println!("✓ DNSSEC set to {}", mode);
+        }
+        if let Some(mode) = self.dns_over_tls {
+            mgr.set_dns_over_tls(ifname, mode).await?;
+            println!("✓ DNS-over-TLS set to {}", mode);
+        }
+        if !self.negative_trust_anchors.is_empty() {
+            let count = self.negative_trust_anchors.len();
+            mgr.set_dnssec_negative_trust_anchors(ifname, self.negative_trust_anchors)
+                .await?;
+            println!("✓ {} DNSSEC negative trust anchor(s) set", count);
+        }
+        Ok(())
+    }
+}
+
+impl DnsDomainsArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let summary = join_domains(&self.domains);
+        mgr.set_dns_domains(&self.interface, self.domains).await?;
+
+        println!("✓ DNS domains of {} set to {}", self.interface, summary);
+        Ok(())
+    }
+}
+
+impl DnsRevertArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.revert_dns(&self.interface).await?;
+
+        println!("✓ DNS settings of {} reverted", self.interface);
+        Ok(())
+    }
+}
+
+impl DnsStatsArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let stats = mgr.get_dns_statistics().await?;
+
+        if self.json {
+            print_json(&stats)?;
+        } else {
+            print_statistics(&stats);
+        }
+
+        if self.reset {
+            mgr.reset_dns_statistics().await?;
+            if !self.json {
+                println!("\n✓ DNS statistics reset");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_statistics(stats: &DnsStatistics) {
+    println!("{}", "Transactions".bold());
+    println!("  Current: {}", stats.current_transactions);
+    println!("  Total: {}", stats.total_transactions);
+
+    println!("{}", "Cache".bold());
+    println!("  Size: {}", stats.cache_size);
+    println!("  Hits: {}", stats.cache_hits);
+    println!("  Misses: {}", stats.cache_misses);
+    match stats.cache_hit_ratio() {
+        Some(ratio) => println!("  Hit ratio: {:.1}%", ratio * 100.0),
+        None => println!("  Hit ratio: -"),
+    }
+
+    println!("{}", "DNSSEC verdicts".bold());
+    println!("  Secure: {}", stats.dnssec_secure);
+    println!("  Insecure: {}", stats.dnssec_insecure);
+    println!("  Bogus: {}", stats.dnssec_bogus);
+    println!("  Indeterminate: {}", stats.dnssec_indeterminate);
+    let failures = stats.dnssec_failures();
+    if failures > 0 {
+        println!("  Failures: {}", failures.to_string().red());
+    } else {
+        println!("  Failures: 0");
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Interface names keyed by index, for labelling answering links
+pub(super) async fn link_names(mgr: &NetworkManager) -> HashMap<u32, String> {
+    mgr.list_links()
+        .await
+        .map(|links| links.into_iter().map(|l| (l.index, l.name)).collect())
+        .unwrap_or_default()
+}
+
+fn link_label(ifindex: Option<u32>, names: &HashMap<u32, String>) -> String {
+    match ifindex {
+        Some(index) => format!(
+            " (link: {})",
+            names
+                .get(&index)
+                .cloned()
+                .unwrap_or_else(|| index.to_string())
+        ),
+        None => String::new(),
+    }
+}
+
+pub(super) fn print_hostname_resolution(result: &HostnameResolution, names: &HashMap<u32, String>) {
+    println!("{}:", result.name.bold());
+    for addr in &result.addresses {
+        println!("  {}{}", addr.address, link_label(addr.ifindex, names));
+    }
+    if !result.canonical_name.is_empty() && result.canonical_name != result.name {
+        println!("  Canonical name: {}", result.canonical_name);
+    }
+}
+
+fn print_address_resolution(result: &AddressResolution, names: &HashMap<u32, String>) {
+    println!("{}:", result.address.to_string().bold());
+    for name in &result.names {
+        println!("  {}{}", name.name, link_label(name.ifindex, names));
+    }
+}
+
+fn print_record_resolution(result: &RecordResolution, names: &HashMap<u32, String>) {
+    println!("{} {}:", result.name.bold(), result.record_type);
+    for record in &result.records {
+        println!(
+            "  {} {} {}{}",
+            record.name,
+            record.ttl,
+            record.data,
+            link_label(record.ifindex, names)
+        );
+    }
+}
+
+fn print_answer_info(flags: &ResolveFlags, elapsed: Duration) {
+    println!();
+    println!("  Protocol: {}", join_or_dash(&flags.protocols()));
+    println!("  Source: {}", join_or_dash(&flags.sources()));
+    println!(
+        "  Authenticated (DNSSEC): {}",
+        if flags.authenticated {
+            "yes".green()
+        } else {
+            "no".yellow()
+        }
+    );
+    println!(
+        "  Confidential transport: {}",
+        if flags.confidential { "yes" } else { "no" }
+    );
+    println!("  Resolved in {:.1}ms", elapsed.as_secs_f64() * 1000.0);
+}
+
+fn join_or_dash(items: &[&str]) -> String {
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join(", ")
+    }
+}
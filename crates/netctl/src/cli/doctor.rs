@@ -2,6 +2,7 @@ use clap::Args;
 use colored::Colorize;
 use miette::Result;
 use netctl_core::NetworkManager;
+use netctl_types::ServiceUnit;
 use std::process::Command;
 
 #[derive(Args)]
@@ -23,10 +24,10 @@ impl DoctorArgs {
         all_ok &= self.check_interfaces().await;
 
         // Check 2: systemd services
-        all_ok &= self.check_systemd_services();
+        all_ok &= self.check_systemd_services().await;
 
         // Check 3: D-Bus availability
-        all_ok &= self.check_dbus();
+        all_ok &= self.check_dbus().await;
 
         // Check 4: Permissions
         all_ok &= self.check_permissions();
@@ -35,7 +36,7 @@ impl DoctorArgs {
         all_ok &= self.check_connectivity();
 
         // Check 6: DNS resolution
-        all_ok &= self.check_dns();
+        all_ok &= self.check_dns().await;
 
         println!();
         println!("{}", "=".repeat(60));
@@ -81,33 +82,39 @@ impl DoctorArgs {
         }
     }
 
-    fn check_systemd_services(&self) -> bool {
+    async fn check_systemd_services(&self) -> bool {
         print!("{} Checking systemd services... ", "→".blue());
 
-        let services = vec!["systemd-networkd", "systemd-resolved"];
+        let services = [ServiceUnit::Networkd, ServiceUnit::Resolved];
         let mut all_running = true;
 
-        for service in &services {
-            let output = Command::new("systemctl")
-                .args(&["is-active", service])
-                .output();
-
-            match output {
-                Ok(out) => {
-                    let status = String::from_utf8_lossy(&out.stdout);
-                    let is_active = status.trim() == "active";
-
-                    if !is_active {
-                        all_running = false;
-                        if self.verbose {
-                            println!();
-                            println!("    {} is not running", service);
+        match NetworkManager::new().await {
+            Ok(mgr) => {
+                for service in services {
+                    match mgr.get_unit_status(service).await {
+                        Ok(status) if status.is_active() => {}
+                        Ok(status) => {
+                            all_running = false;
+                            if self.verbose {
+                                println!();
+                                println!(
+                                    "    {} is not running ({}/{})",
+                                    status.name, status.active_state, status.sub_state
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            all_running = false;
+                            if self.verbose {
+                                println!();
+                                println!("    {}: {}", service.unit_name(), e);
+                            }
                         }
                     }
                 }
-                Err(_) => {
-                    all_running = false;
-                }
+            }
+            Err(_) => {
+                all_running = false;
             }
         }
 
@@ -115,27 +122,28 @@ impl DoctorArgs {
             println!("{}", "✓".green());
             if self.verbose {
                 for service in services {
-                    println!("    {} is active", service);
+                    println!("    {} is active", service.unit_name());
                 }
             }
         } else {
             println!("{}", "⚠".yellow());
             println!("    Some systemd services are not running");
-            println!("    This may limit functionality");
+            println!("    See 'netctl service status' for details");
         }
 
         true // Don't fail on this, just warn
     }
 
-    fn check_dbus(&self) -> bool {
+    async fn check_dbus(&self) -> bool {
         print!("{} Checking D-Bus connection... ", "→".blue());
 
-        let output = Command::new("busctl")
-            .args(&["list"])
-            .output();
+        let result = match NetworkManager::new().await {
+            Ok(mgr) => mgr.connect_dbus().await,
+            Err(e) => Err(e),
+        };
 
-        match output {
-            Ok(_) => {
+        match result {
+            Ok(()) => {
                 println!("{}", "✓".green());
                 if self.verbose {
                     println!("    D-Bus system bus is accessible");
@@ -154,11 +162,7 @@ impl DoctorArgs {
         print!("{} Checking permissions... ", "→".blue());
 
         // Check if running as root or with capabilities
-        let is_root = Command::new("id")
-            .args(&["-u"])
-            .output()
-            .map(|out| String::from_utf8_lossy(&out.stdout).trim() == "0")
-            .unwrap_or(false);
+        let is_root = nix::unistd::geteuid().is_root();
 
         if is_root {
             println!("{}", "✓".green());
@@ -179,7 +183,7 @@ impl DoctorArgs {
 
         // Ping a reliable host
         let output = Command::new("ping")
-            .args(&["-c", "1", "-W", "2", "8.8.8.8"])
+            .args(["-c", "1", "-W", "2", "8.8.8.8"])
             .output();
 
         match output {
@@ -205,31 +209,29 @@ impl DoctorArgs {
         }
     }
 
-    fn check_dns(&self) -> bool {
+    async fn check_dns(&self) -> bool {
         print!("{} Checking DNS resolution... ", "→".blue());
 
-        let output = Command::new("host")
-            .args(&["www.google.com"])
-            .output();
+        let result = match NetworkManager::new().await {
+            Ok(mgr) => mgr.resolve_hostname("www.google.com", None).await,
+            Err(e) => Err(e),
+        };
 
-        match output {
-            Ok(out) => {
-                if out.status.success() {
-                    println!("{}", "✓".green());
-                    if self.verbose {
-                        println!("    DNS resolution working");
-                    }
-                    true
-                } else {
-                    println!("{}", "⚠".yellow());
-                    println!("    DNS resolution failed");
-                    println!("    Check /etc/resolv.conf or systemd-resolved configuration");
-                    true // Don't fail
+        match result {
+            Ok(resolution) => {
+                println!("{}", "✓".green());
+                if self.verbose {
+                    println!(
+                        "    DNS resolution working ({} address(es) via systemd-resolved)",
+                        resolution.addresses.len()
+                    );
                 }
+                true
             }
-            Err(_) => {
+            Err(e) => {
                 println!("{}", "⚠".yellow());
-                println!("    Could not test DNS (host command not available)");
+                println!("    DNS resolution failed: {}", e);
+                println!("    Check systemd-resolved with 'netctl dns query <name>'");
                 true // Don't fail
             }
         }
@@ -1,8 +1,8 @@
 use clap::Args;
 use colored::Colorize;
 use miette::{IntoDiagnostic, Result};
+use netctl_config::model::{ConfigFormat, Metadata};
 use netctl_core::NetworkManager;
-use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
@@ -24,33 +24,10 @@ pub struct ExportArgs {
     pretty: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ExportConfig {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    metadata: Option<ExportMetadata>,
-    interfaces: Vec<InterfaceExport>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ExportMetadata {
-    exported_at: String,
-    exported_by: String,
-    hostname: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct InterfaceExport {
-    name: String,
-    state: String,
-    mtu: u32,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    mac_address: Option<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    addresses: Vec<String>,
-}
-
 impl ExportArgs {
     pub async fn execute(self) -> Result<()> {
+        let format: ConfigFormat = self.format.parse()?;
+
         println!("{}", "Exporting network configuration...".cyan().bold());
         println!();
 
@@ -70,46 +47,35 @@ impl ExportArgs {
             return Ok(());
         }
 
-        let interfaces: Vec<InterfaceExport> = filtered_links
-            .into_iter()
-            .map(|link| InterfaceExport {
-                name: link.name,
-                state: format!("{:?}", link.state).to_lowercase(),
-                mtu: link.mtu,
-                mac_address: link.mac_address.map(|m| m.to_string()),
-                addresses: link.addresses.iter().map(|a| a.to_string()).collect(),
-            })
-            .collect();
-
-        let hostname = std::env::var("HOSTNAME")
-            .or_else(|_| std::env::var("HOST"))
-            .ok();
-
-        let config = ExportConfig {
-            metadata: Some(ExportMetadata {
-                exported_at: chrono::Utc::now().to_rfc3339(),
-                exported_by: "netctl".to_string(),
-                hostname,
-            }),
-            interfaces,
+        // Virtual devices netctl can create are exported so that applying the
+        // file elsewhere recreates them
+        let mut config = super::config::runtime_config(&filtered_links);
+
+        // $HOSTNAME is a shell variable and usually isn't exported; ask
+        // hostnamed, falling back to the kernel's idea of the name
+        let hostname = match mgr.get_hostname().await {
+            Ok(hostname) => Some(hostname),
+            Err(_) => fs::read_to_string("/proc/sys/kernel/hostname")
+                .ok()
+                .map(|name| name.trim().to_string()),
         };
 
-        let content = match self.format.to_lowercase().as_str() {
-            "json" => {
-                if self.pretty {
-                    serde_json::to_string_pretty(&config).into_diagnostic()?
-                } else {
-                    serde_json::to_string(&config).into_diagnostic()?
-                }
-            }
-            "toml" => toml::to_string_pretty(&config).into_diagnostic()?,
-            "yaml" | _ => serde_yaml::to_string(&config).into_diagnostic()?,
+        config.metadata = Some(Metadata {
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            exported_by: "netctl".to_string(),
+            hostname,
+        });
+
+        let content = if format == ConfigFormat::Json && !self.pretty {
+            serde_json::to_string(&config).into_diagnostic()?
+        } else {
+            config.to_string(format)?
         };
 
         fs::write(&self.output, content).into_diagnostic()?;
 
         println!("{} Configuration exported successfully", "✓".green());
-        println!("  Format: {}", self.format.yellow());
+        println!("  Format: {}", format.to_string().yellow());
         println!("  Interfaces: {}", config.interfaces.len());
         println!("  Output: {}", self.output.display());
         println!();
@@ -1,6 +1,8 @@
 use clap::{Args, Subcommand};
 use colored::Colorize;
 use miette::{IntoDiagnostic, Result};
+use netctl_core::NetworkManager;
+use netctl_types::{LinkInfo, LinkState};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -162,16 +164,19 @@ impl RollbackArgs {
             }
         }
 
-        // Save current state before rollback
-        save_history_snapshot("rollback", &format!("Rollback to {}", entry.id)).await?;
+        let mgr = NetworkManager::new().await?;
+        let (snapshot, restored) = rollback(&mgr, &entry).await?;
 
-        // Parse and apply the saved state
-        // Note: This is a simplified implementation
-        // In production, you would parse the state JSON and apply it
-        println!("{} Current state saved", "✓".green());
+        println!("{} Current state saved as {}", "✓".green(), snapshot);
+        for ifname in &restored {
+            println!("{} Restored {}", "✓".green(), ifname);
+        }
         println!("{} Configuration rolled back to {}", "✓".green(), entry.id.green());
         println!();
-        println!("{}", "Note: Rollback completed. Review changes with 'netctl show'".yellow());
+        println!(
+            "{}",
+            "Note: Addresses aren't restored. Review changes with 'netctl show'".yellow()
+        );
 
         Ok(())
     }
@@ -210,7 +215,7 @@ fn get_history_dir() -> Result<PathBuf> {
     Ok(PathBuf::from(home).join(".config/netctl/history"))
 }
 
-fn load_all_history() -> Result<Vec<HistoryEntry>> {
+pub fn load_all_history() -> Result<Vec<HistoryEntry>> {
     let history_dir = get_history_dir()?;
 
     if !history_dir.exists() {
@@ -235,7 +240,7 @@ fn load_all_history() -> Result<Vec<HistoryEntry>> {
     Ok(entries)
 }
 
-fn load_history_entry(id: &str) -> Result<HistoryEntry> {
+pub fn load_history_entry(id: &str) -> Result<HistoryEntry> {
     let history_dir = get_history_dir()?;
     let path = history_dir.join(format!("{}.json", id));
 
@@ -249,13 +254,14 @@ fn load_history_entry(id: &str) -> Result<HistoryEntry> {
     Ok(entry)
 }
 
-pub async fn save_history_snapshot(operation: &str, description: &str) -> Result<String> {
-    use netctl_core::NetworkManager;
-
+pub async fn save_history_snapshot(
+    mgr: &NetworkManager,
+    operation: &str,
+    description: &str,
+) -> Result<String> {
     let history_dir = get_history_dir()?;
     fs::create_dir_all(&history_dir).into_diagnostic()?;
 
-    let mgr = NetworkManager::new().await?;
     let links = mgr.list_links().await?;
 
     let timestamp = chrono::Utc::now();
@@ -275,3 +281,40 @@ pub async fn save_history_snapshot(operation: &str, description: &str) -> Result
 
     Ok(id)
 }
+
+/// Save the current state, then bring every link in `entry` that still exists
+/// back to its recorded admin state and MTU. Returns the new snapshot's ID and
+/// the links that were changed.
+pub async fn rollback(mgr: &NetworkManager, entry: &HistoryEntry) -> Result<(String, Vec<String>)> {
+    let saved: Vec<LinkInfo> = serde_json::from_str(&entry.state)
+        .map_err(|e| miette::miette!("History entry '{}' has an invalid state: {}", entry.id, e))?;
+
+    let snapshot =
+        save_history_snapshot(mgr, "rollback", &format!("Rollback to {}", entry.id)).await?;
+
+    let current = mgr.list_links().await?;
+    let mut restored = Vec::new();
+    for link in &saved {
+        let Some(now) = current.iter().find(|l| l.name == link.name) else {
+            continue;
+        };
+
+        let mut changed = false;
+        if now.state != link.state {
+            match link.state {
+                LinkState::Up => mgr.set_link_up(&link.name).await?,
+                LinkState::Down => mgr.set_link_down(&link.name).await?,
+            }
+            changed = true;
+        }
+        if now.mtu != link.mtu {
+            mgr.set_mtu(&link.name, link.mtu).await?;
+            changed = true;
+        }
+        if changed {
+            restored.push(link.name.clone());
+        }
+    }
+
+    Ok((snapshot, restored))
+}
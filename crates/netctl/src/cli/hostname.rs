@@ -0,0 +1,192 @@
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{Chassis, HostnameInfo};
+
+#[derive(Args)]
+pub struct HostnameCommand {
+    #[command(subcommand)]
+    command: HostnameSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum HostnameSubcommand {
+    /// Show hostnames, machine, OS and hardware information
+    #[command(alias = "status")]
+    Show(HostnameShowArgs),
+
+    /// Set hostnames and machine metadata
+    Set(HostnameSetArgs),
+}
+
+#[derive(Args)]
+pub struct HostnameShowArgs {
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct HostnameSetArgs {
+    /// New hostname (without --static/--pretty/--transient, sets the static
+    /// and transient hostname)
+    name: Option<String>,
+
+    /// Set the static hostname ("" clears it)
+    #[arg(long = "static")]
+    static_: bool,
+
+    /// Set the pretty hostname (free-form; "" clears it)
+    #[arg(long)]
+    pretty: bool,
+
+    /// Set the transient hostname
+    #[arg(long)]
+    transient: bool,
+
+    /// Chassis type (desktop, laptop, server, vm, container, ...)
+    #[arg(long)]
+    chassis: Option<Chassis>,
+
+    /// Deployment environment (e.g. development, staging, production)
+    #[arg(long)]
+    deployment: Option<String>,
+
+    /// Physical location (e.g. "Berlin, rack 3")
+    #[arg(long)]
+    location: Option<String>,
+
+    /// Icon name following the XDG icon naming spec
+    #[arg(long)]
+    icon_name: Option<String>,
+}
+
+impl HostnameCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            HostnameSubcommand::Show(args) => args.execute().await,
+            HostnameSubcommand::Set(args) => args.execute().await,
+        }
+    }
+}
+
+impl HostnameShowArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let info = mgr.get_hostname_info().await?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&info)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            print_hostname_info(&info);
+        }
+        Ok(())
+    }
+}
+
+impl HostnameSetArgs {
+    pub async fn execute(self) -> Result<()> {
+        let classes_given = self.static_ || self.pretty || self.transient;
+        if classes_given && self.name.is_none() {
+            return Err(miette::miette!(
+                "--static, --pretty and --transient need a hostname to set"
+            ));
+        }
+        let nothing_to_set = self.name.is_none()
+            && self.chassis.is_none()
+            && self.deployment.is_none()
+            && self.location.is_none()
+            && self.icon_name.is_none();
+        if nothing_to_set {
+            return Err(miette::miette!(
+                "Nothing to set. Give a hostname and/or machine metadata (see --help)"
+            ));
+        }
+
+        let mgr = NetworkManager::new().await?;
+
+        if let Some(ref name) = self.name {
+            let (set_static, set_transient) = if classes_given {
+                (self.static_, self.transient)
+            } else {
+                (true, true)
+            };
+
+            if set_static {
+                mgr.set_static_hostname(name).await?;
+                if name.is_empty() {
+                    println!("✓ Static hostname cleared");
+                } else {
+                    println!("✓ Static hostname set to {}", name);
+                }
+            }
+            if set_transient {
+                mgr.set_transient_hostname(name).await?;
+                println!("✓ Transient hostname set to {}", name);
+            }
+            if self.pretty {
+                mgr.set_pretty_hostname(name).await?;
+                if name.is_empty() {
+                    println!("✓ Pretty hostname cleared");
+                } else {
+                    println!("✓ Pretty hostname set to {}", name);
+                }
+            }
+        }
+
+        if let Some(chassis) = self.chassis {
+            mgr.set_chassis(chassis).await?;
+            println!("✓ Chassis set to {}", chassis);
+        }
+        if let Some(ref deployment) = self.deployment {
+            mgr.set_deployment(deployment).await?;
+            println!("✓ Deployment set to {}", deployment);
+        }
+        if let Some(ref location) = self.location {
+            mgr.set_location(location).await?;
+            println!("✓ Location set to {}", location);
+        }
+        if let Some(ref icon) = self.icon_name {
+            mgr.set_icon_name(icon).await?;
+            println!("✓ Icon name set to {}", icon);
+        }
+        Ok(())
+    }
+}
+
+fn print_hostname_info(info: &HostnameInfo) {
+    println!("{}", info.hostname.bold());
+
+    let kernel = match (&info.kernel_name, &info.kernel_release) {
+        (Some(name), Some(release)) => Some(format!("{} {}", name, release)),
+        (name, release) => name.clone().or_else(|| release.clone()),
+    };
+
+    let fields = [
+        ("Static hostname", &info.static_hostname),
+        ("Pretty hostname", &info.pretty_hostname),
+        ("Default hostname", &info.default_hostname),
+        ("Hostname source", &info.hostname_source),
+        ("Icon name", &info.icon_name),
+        ("Chassis", &info.chassis),
+        ("Deployment", &info.deployment),
+        ("Location", &info.location),
+        ("Machine ID", &info.machine_id),
+        ("Operating system", &info.operating_system),
+        ("OS CPE name", &info.operating_system_cpe_name),
+        ("OS home URL", &info.operating_system_home_url),
+        ("Kernel", &kernel),
+        ("Hardware vendor", &info.hardware_vendor),
+        ("Hardware model", &info.hardware_model),
+        ("Firmware version", &info.firmware_version),
+    ];
+
+    for (label, value) in fields {
+        if let Some(value) = value {
+            println!("  {}: {}", label, value);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::{IntoDiagnostic, Result};
+use netctl_config::import::{ifupdown, netplan, Imported};
+use netctl_config::model::ConfigFormat;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ImportCommand {
+    #[command(subcommand)]
+    command: ImportSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ImportSubcommand {
+    /// Convert a netplan YAML file (/etc/netplan/*.yaml)
+    Netplan(ImportArgs),
+
+    /// Convert ifupdown's /etc/network/interfaces and the files it sources
+    Ifupdown(ImportArgs),
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// File to convert
+    file: PathBuf,
+
+    /// Write the configuration to this file instead of standard output
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Output format (yaml, toml, json); defaults to the output file's extension
+    #[arg(short, long)]
+    format: Option<String>,
+}
+
+impl ImportCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            ImportSubcommand::Netplan(args) => {
+                let content = fs::read_to_string(&args.file).into_diagnostic()?;
+                let imported = netplan::parse(&content)?;
+                args.write(imported)
+            }
+            ImportSubcommand::Ifupdown(args) => {
+                let imported = ifupdown::load(&args.file)?;
+                args.write(imported)
+            }
+        }
+    }
+}
+
+impl ImportArgs {
+    /// Report what didn't convert, then write the configuration out
+    fn write(&self, imported: Imported) -> Result<()> {
+        let format = match (&self.format, &self.output) {
+            (Some(format), _) => format.parse()?,
+            (None, Some(output)) => ConfigFormat::from_path(output),
+            (None, None) => ConfigFormat::Yaml,
+        };
+
+        // Diagnostics go to stderr so that the configuration can be piped
+        for warning in &imported.warnings {
+            eprintln!("{} {}", "⚠".yellow(), warning);
+        }
+        for issue in imported.config.validate() {
+            if issue.is_error() {
+                eprintln!("{} {}", "✗".red(), issue);
+            } else {
+                eprintln!("{} {}", "⚠".yellow(), issue);
+            }
+        }
+
+        let content = imported.config.to_string(format)?;
+        let Some(output) = &self.output else {
+            print!("{}", content);
+            return Ok(());
+        };
+        fs::write(output, content).into_diagnostic()?;
+
+        println!(
+            "{} Imported {} interface(s) and {} device(s) from {}",
+            "✓".green(),
+            imported.config.interfaces.len(),
+            imported.config.devices.len(),
+            self.file.display()
+        );
+        println!("  Output: {}", output.display());
+        println!(
+            "  {} Review it, then run 'netctl apply {} --persist'",
+            "→".blue(),
+            output.display()
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use clap::{Args, Subcommand};
 use miette::Result;
 use netctl_core::NetworkManager;
+use netctl_types::{LinkKind, MacAddress, VfLinkState, VfSettings};
 
 #[derive(Args)]
 pub struct LinkCommand {
@@ -12,6 +13,23 @@ pub struct LinkCommand {
 pub enum LinkSubcommand {
     /// Set link properties
     Set(SetArgs),
+
+    /// Create a virtual link
+    Add(AddArgs),
+
+    /// Delete a virtual link
+    #[command(alias = "delete")]
+    Del(DelArgs),
+
+    /// Configure an SR-IOV virtual function
+    Vf(VfArgs),
+
+    /// Rename a link
+    Rename(RenameArgs),
+
+    /// Manage alternative names
+    #[command(subcommand)]
+    Altname(AltnameSubcommand),
 }
 
 #[derive(Args)]
@@ -19,18 +37,226 @@ pub struct SetArgs {
     /// Interface name
     interface: String,
 
-    /// Property to set (state, mtu, mac)
+    /// Property to set (state, mtu, mac, numvfs)
     property: String,
 
     /// Value to set
     value: String,
 }
 
+#[derive(Args)]
+pub struct AddArgs {
+    /// Name of the new interface
+    name: String,
+
+    /// Link kind (dummy, ifb, nlmon)
+    #[arg(long = "type", short = 't')]
+    kind: String,
+}
+
+#[derive(Args)]
+pub struct DelArgs {
+    /// Interface name
+    name: String,
+
+    /// Allow deleting a physical device
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct RenameArgs {
+    /// Current interface name
+    interface: String,
+
+    /// New interface name
+    new_name: String,
+
+    /// Also write a systemd .link file so the name survives reboots
+    #[arg(long)]
+    persist: bool,
+
+    /// Match the .link file on the device path instead of the permanent MAC
+    #[arg(long, requires = "persist")]
+    match_path: bool,
+}
+
+#[derive(Subcommand)]
+pub enum AltnameSubcommand {
+    /// Add an alternative name
+    Add(AltnameArgs),
+
+    /// Delete an alternative name
+    #[command(alias = "delete")]
+    Del(AltnameArgs),
+}
+
+#[derive(Args)]
+pub struct AltnameArgs {
+    /// Interface name
+    interface: String,
+
+    /// Alternative name
+    altname: String,
+}
+
+#[derive(Args)]
+pub struct VfArgs {
+    /// Physical function interface name
+    interface: String,
+
+    /// Virtual function number
+    vf: u32,
+
+    /// VF MAC address
+    #[arg(long)]
+    mac: Option<MacAddress>,
+
+    /// VLAN ID (0 disables VLAN tagging)
+    #[arg(long)]
+    vlan: Option<u16>,
+
+    /// VLAN QoS priority (0-7)
+    #[arg(long)]
+    qos: Option<u8>,
+
+    /// MAC spoof checking (on/off)
+    #[arg(long, value_parser = clap::builder::BoolishValueParser::new())]
+    spoofchk: Option<bool>,
+
+    /// Trusted mode (on/off)
+    #[arg(long, value_parser = clap::builder::BoolishValueParser::new())]
+    trust: Option<bool>,
+
+    /// Link state (auto, enable, disable)
+    #[arg(long)]
+    state: Option<VfLinkState>,
+
+    /// Minimum TX rate in Mbps (0 disables)
+    #[arg(long = "min-tx-rate")]
+    min_tx_rate: Option<u32>,
+
+    /// Maximum TX rate in Mbps (0 disables)
+    #[arg(long = "max-tx-rate")]
+    max_tx_rate: Option<u32>,
+}
+
 impl LinkCommand {
     pub async fn execute(self) -> Result<()> {
         match self.command {
             LinkSubcommand::Set(args) => args.execute().await,
+            LinkSubcommand::Add(args) => args.execute().await,
+            LinkSubcommand::Del(args) => args.execute().await,
+            LinkSubcommand::Vf(args) => args.execute().await,
+            LinkSubcommand::Rename(args) => args.execute().await,
+            LinkSubcommand::Altname(cmd) => match cmd {
+                AltnameSubcommand::Add(args) => args.add().await,
+                AltnameSubcommand::Del(args) => args.delete().await,
+            },
+        }
+    }
+}
+
+impl AddArgs {
+    pub async fn execute(self) -> Result<()> {
+        let kind: LinkKind = self.kind.parse()?;
+
+        let mgr = NetworkManager::new().await?;
+        mgr.add_link(&self.name, kind).await?;
+
+        println!("✓ Created {} link {}", kind, self.name);
+        Ok(())
+    }
+}
+
+impl DelArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.delete_link(&self.name, self.force).await?;
+
+        println!("✓ Deleted link {}", self.name);
+        Ok(())
+    }
+}
+
+impl RenameArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+
+        // Build the .link file first so a missing match key fails before renaming
+        let link_file = if self.persist {
+            Some(
+                mgr.link_file(&self.interface, &self.new_name, self.match_path)
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        mgr.rename_link(&self.interface, &self.new_name).await?;
+        println!("✓ Renamed {} to {}", self.interface, self.new_name);
+
+        if let Some(file) = link_file {
+            let installed = mgr.write_link_file(&file).await?;
+            for path in &installed.written {
+                println!("✓ Wrote {}", path.display());
+            }
+            for path in &installed.removed {
+                println!("✓ Removed {}", path.display());
+            }
+            println!("→ The name applies from the next boot; regenerate the initramfs if it configures this device");
         }
+        Ok(())
+    }
+}
+
+impl AltnameArgs {
+    pub async fn add(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.add_altname(&self.interface, &self.altname).await?;
+
+        println!(
+            "✓ Added alternative name {} to {}",
+            self.altname, self.interface
+        );
+        Ok(())
+    }
+
+    pub async fn delete(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.delete_altname(&self.interface, &self.altname).await?;
+
+        println!(
+            "✓ Deleted alternative name {} from {}",
+            self.altname, self.interface
+        );
+        Ok(())
+    }
+}
+
+impl VfArgs {
+    pub async fn execute(self) -> Result<()> {
+        let settings = VfSettings {
+            mac_address: self.mac,
+            vlan: self.vlan,
+            qos: self.qos,
+            spoof_check: self.spoofchk,
+            trust: self.trust,
+            link_state: self.state,
+            min_tx_rate: self.min_tx_rate,
+            max_tx_rate: self.max_tx_rate,
+        };
+        if settings.is_empty() {
+            return Err(miette::miette!(
+                "Nothing to set. Use --mac, --vlan, --qos, --spoofchk, --trust, --state or a tx rate"
+            ));
+        }
+
+        let mgr = NetworkManager::new().await?;
+        mgr.set_vf(&self.interface, self.vf, settings).await?;
+
+        println!("✓ Configured VF {} on {}", self.vf, self.interface);
+        Ok(())
     }
 }
 
@@ -73,9 +299,16 @@ impl SetArgs {
                     self.value
                 ));
             }
+            "numvfs" => {
+                let count: u32 = self.value.parse().map_err(|_| {
+                    miette::miette!("Invalid VF count '{}'. Must be a number", self.value)
+                })?;
+                mgr.set_num_vfs(&self.interface, count).await?;
+                println!("✓ {} VFs enabled on {}", count, self.interface);
+            }
             _ => {
                 return Err(miette::miette!(
-                    "Unknown property '{}'. Valid properties: state, mtu, mac",
+                    "Unknown property '{}'. Valid properties: state, mtu, mac, numvfs",
                     self.property
                 ))
             }
@@ -2,22 +2,35 @@ pub mod address;
 pub mod apply;
 pub mod backup;
 pub mod completion;
+pub mod config;
+pub mod daemon;
+pub mod dhcp;
+pub mod dns;
 pub mod diff;
 pub mod doctor;
 pub mod export;
 pub mod history;
+pub mod hostname;
+pub mod import;
 pub mod link;
+pub mod mptcp;
+pub mod nm;
 pub mod profile;
+pub mod service;
 pub mod show;
 pub mod stats;
+pub mod status;
 pub mod test;
 pub mod tui;
 pub mod validate;
+pub mod wait_online;
 pub mod watch;
 pub mod wizard;
 
 use clap::{Parser, Subcommand};
 use miette::Result;
+use netctl_core::polkit;
+use std::io::IsTerminal;
 
 #[derive(Parser)]
 #[command(name = "netctl")]
@@ -26,6 +39,10 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Don't ask for a password when polkit requires authentication
+    #[arg(long, global = true)]
+    no_ask_password: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,6 +52,9 @@ pub enum Commands {
     /// Show network interfaces
     Show(show::ShowArgs),
 
+    /// Show networkd's view of a link (matched files, DHCP leases, DNS, ...)
+    Status(status::StatusArgs),
+
     /// Manage network links
     Link(link::LinkCommand),
 
@@ -42,12 +62,33 @@ pub enum Commands {
     #[command(name = "addr")]
     Address(address::AddressCommand),
 
+    /// Renew DHCP leases and show the current lease
+    Dhcp(dhcp::DhcpCommand),
+
+    /// Query DNS through systemd-resolved
+    Dns(dns::DnsCommand),
+
+    /// Show and set hostnames through systemd-hostnamed
+    Hostname(hostname::HostnameCommand),
+
+    /// Manage NetworkManager devices and connections
+    Nm(nm::NmCommand),
+
+    /// Show and restart systemd-networkd and systemd-resolved
+    Service(service::ServiceCommand),
+
+    /// Manage MPTCP endpoints and limits
+    Mptcp(mptcp::MptcpCommand),
+
     /// Real-time TUI dashboard (interactive interface)
     Tui(tui::TuiArgs),
 
     /// Watch interfaces (continuous monitoring)
     Watch(watch::WatchArgs),
 
+    /// Wait until interfaces are up and configured, for scripts and boot ordering
+    WaitOnline(wait_online::WaitOnlineArgs),
+
     /// Manage network profiles (save/load configurations)
     Profile(profile::ProfileCommand),
 
@@ -69,6 +110,9 @@ pub enum Commands {
     /// Validate configuration file
     Validate(validate::ValidateArgs),
 
+    /// Show the configuration of the running system or of networkd's files
+    Config(config::ConfigCommand),
+
     /// Generate shell completion scripts
     Completion(completion::CompletionArgs),
 
@@ -83,16 +127,43 @@ pub enum Commands {
 
     /// Export network configuration to file
     Export(export::ExportArgs),
+
+    /// Convert another tool's network configuration into netctl's format
+    Import(import::ImportCommand),
+
+    /// Serve netctl's operations on the system bus as org.netctl1
+    Daemon(daemon::DaemonArgs),
 }
 
 impl Cli {
     pub async fn execute(self) -> Result<()> {
+        polkit::set_interactive(!self.no_ask_password && std::io::stdin().is_terminal());
+
+        // Full-screen and streaming modes only read state, and a password
+        // prompt would garble their output
+        let _agent = match self.command {
+            Commands::Tui(_)
+            | Commands::Watch(_)
+            | Commands::WaitOnline(_)
+            | Commands::Completion(_)
+            | Commands::Daemon(_) => None,
+            _ => Some(polkit::enable_agent()),
+        };
+
         match self.command {
             Commands::Show(args) => args.execute().await,
+            Commands::Status(args) => args.execute().await,
             Commands::Link(cmd) => cmd.execute().await,
             Commands::Address(cmd) => cmd.execute().await,
+            Commands::Dhcp(cmd) => cmd.execute().await,
+            Commands::Dns(cmd) => cmd.execute().await,
+            Commands::Hostname(cmd) => cmd.execute().await,
+            Commands::Nm(cmd) => cmd.execute().await,
+            Commands::Service(cmd) => cmd.execute().await,
+            Commands::Mptcp(cmd) => cmd.execute().await,
             Commands::Tui(args) => args.execute().await,
             Commands::Watch(args) => args.execute().await,
+            Commands::WaitOnline(args) => args.execute().await,
             Commands::Profile(cmd) => cmd.execute().await,
             Commands::Apply(args) => args.execute().await,
             Commands::Diff(args) => args.execute().await,
@@ -100,11 +171,14 @@ impl Cli {
             Commands::Doctor(args) => args.execute().await,
             Commands::Stats(args) => args.execute().await,
             Commands::Validate(args) => args.execute().await,
+            Commands::Config(cmd) => cmd.execute().await,
             Commands::Completion(args) => args.execute().await,
             Commands::History(cmd) => cmd.execute().await,
             Commands::Backup(cmd) => cmd.execute().await,
             Commands::Test(cmd) => cmd.execute().await,
             Commands::Export(args) => args.execute().await,
+            Commands::Import(cmd) => cmd.execute().await,
+            Commands::Daemon(args) => args.execute().await,
         }
     }
 }
@@ -0,0 +1,248 @@
+use clap::{Args, Subcommand};
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{MptcpEndpoint, MptcpEndpointFlag, MptcpEndpointSpec};
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+#[derive(Args)]
+pub struct MptcpCommand {
+    #[command(subcommand)]
+    command: MptcpSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum MptcpSubcommand {
+    /// Manage path-manager endpoints
+    #[command(subcommand, alias = "endpoints")]
+    Endpoint(EndpointSubcommand),
+
+    /// Show or set path-manager limits
+    #[command(subcommand)]
+    Limits(LimitsSubcommand),
+}
+
+#[derive(Subcommand)]
+pub enum EndpointSubcommand {
+    /// List endpoints
+    #[command(alias = "list")]
+    Show(EndpointShowArgs),
+
+    /// Add an endpoint
+    Add(EndpointAddArgs),
+
+    /// Delete an endpoint by id
+    #[command(alias = "delete")]
+    Del(EndpointDelArgs),
+}
+
+#[derive(Args)]
+pub struct EndpointShowArgs {
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct EndpointAddArgs {
+    /// Local address of the endpoint
+    address: IpAddr,
+
+    /// Endpoint id (1-255, assigned by the kernel if omitted)
+    #[arg(long)]
+    id: Option<u8>,
+
+    /// Port to announce (requires the signal flag)
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// Interface used for subflows from this endpoint
+    #[arg(long)]
+    dev: Option<String>,
+
+    /// Endpoint flags (signal, subflow, backup, fullmesh)
+    #[arg(long = "flag", value_delimiter = ',')]
+    flags: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct EndpointDelArgs {
+    /// Endpoint id
+    id: u8,
+}
+
+#[derive(Subcommand)]
+pub enum LimitsSubcommand {
+    /// Show the current limits
+    Show(LimitsShowArgs),
+
+    /// Set limits (unset values are left unchanged)
+    Set(LimitsSetArgs),
+}
+
+#[derive(Args)]
+pub struct LimitsShowArgs {
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct LimitsSetArgs {
+    /// Maximum additional subflows per connection (0-8)
+    #[arg(long)]
+    subflows: Option<u32>,
+
+    /// Maximum ADD_ADDR announcements accepted per connection (0-8)
+    #[arg(long = "add-addr-accepted")]
+    add_addr_accepted: Option<u32>,
+}
+
+impl MptcpCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            MptcpSubcommand::Endpoint(cmd) => match cmd {
+                EndpointSubcommand::Show(args) => args.execute().await,
+                EndpointSubcommand::Add(args) => args.execute().await,
+                EndpointSubcommand::Del(args) => args.execute().await,
+            },
+            MptcpSubcommand::Limits(cmd) => match cmd {
+                LimitsSubcommand::Show(args) => args.execute().await,
+                LimitsSubcommand::Set(args) => args.execute().await,
+            },
+        }
+    }
+}
+
+impl EndpointShowArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let endpoints = mgr.list_mptcp_endpoints().await?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&endpoints)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+            return Ok(());
+        }
+
+        let names: HashMap<u32, String> = mgr
+            .list_links()
+            .await?
+            .into_iter()
+            .map(|link| (link.index, link.name))
+            .collect();
+        print_endpoints_table(&endpoints, &names);
+        Ok(())
+    }
+}
+
+fn print_endpoints_table(endpoints: &[MptcpEndpoint], names: &HashMap<u32, String>) {
+    println!(
+        "{:<4} {:<40} {:<6} {:<15} {:<20}",
+        "ID", "ADDRESS", "PORT", "DEVICE", "FLAGS"
+    );
+    println!("{}", "-".repeat(85));
+
+    for endpoint in endpoints {
+        let port = endpoint
+            .port
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let dev = endpoint
+            .ifindex
+            .map(|index| {
+                names
+                    .get(&index)
+                    .cloned()
+                    .unwrap_or_else(|| index.to_string())
+            })
+            .unwrap_or_else(|| "-".to_string());
+        let flags = if endpoint.flags.is_empty() {
+            "-".to_string()
+        } else {
+            endpoint
+                .flags
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        println!(
+            "{:<4} {:<40} {:<6} {:<15} {:<20}",
+            endpoint.id, endpoint.address, port, dev, flags
+        );
+    }
+
+    println!("\nTotal: {} endpoint(s)", endpoints.len());
+}
+
+impl EndpointAddArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mut spec = MptcpEndpointSpec::new(self.address);
+        spec.id = self.id;
+        spec.port = self.port;
+        spec.interface = self.dev;
+        spec.flags = self
+            .flags
+            .iter()
+            .map(|f| f.parse::<MptcpEndpointFlag>())
+            .collect::<netctl_types::Result<_>>()?;
+
+        let mgr = NetworkManager::new().await?;
+        mgr.add_mptcp_endpoint(spec).await?;
+
+        println!("✓ MPTCP endpoint {} added", self.address);
+        Ok(())
+    }
+}
+
+impl EndpointDelArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.delete_mptcp_endpoint(self.id).await?;
+
+        println!("✓ MPTCP endpoint {} deleted", self.id);
+        Ok(())
+    }
+}
+
+impl LimitsShowArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let limits = mgr.get_mptcp_limits().await?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&limits)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            println!("MPTCP limits:");
+            println!("  Subflows: {}", limits.subflows);
+            println!("  ADD_ADDR accepted: {}", limits.add_addr_accepted);
+        }
+        Ok(())
+    }
+}
+
+impl LimitsSetArgs {
+    pub async fn execute(self) -> Result<()> {
+        if self.subflows.is_none() && self.add_addr_accepted.is_none() {
+            return Err(miette::miette!(
+                "Nothing to set. Use --subflows and/or --add-addr-accepted"
+            ));
+        }
+
+        let mgr = NetworkManager::new().await?;
+        let limits = mgr
+            .set_mptcp_limits(self.subflows, self.add_addr_accepted)
+            .await?;
+
+        println!(
+            "✓ MPTCP limits set: subflows {}, add_addr_accepted {}",
+            limits.subflows, limits.add_addr_accepted
+        );
+        Ok(())
+    }
+}
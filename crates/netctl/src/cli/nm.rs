@@ -0,0 +1,230 @@
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{NmActiveConnection, NmDevice, NmDeviceState, NmIpConfig};
+
+#[derive(Args)]
+pub struct NmCommand {
+    #[command(subcommand)]
+    command: NmSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum NmSubcommand {
+    /// List devices and their NetworkManager state
+    #[command(alias = "dev")]
+    Devices(NmDevicesArgs),
+
+    /// List active connections
+    #[command(alias = "con")]
+    Connections(NmConnectionsArgs),
+
+    /// Activate a saved connection
+    Up(NmUpArgs),
+
+    /// Deactivate an active connection
+    Down(NmDownArgs),
+}
+
+#[derive(Args)]
+pub struct NmDevicesArgs {
+    /// Only show this interface, with its IP configuration
+    interface: Option<String>,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct NmConnectionsArgs {
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct NmUpArgs {
+    /// Connection ID or UUID
+    connection: String,
+
+    /// Interface to activate the connection on
+    #[arg(long, short)]
+    device: Option<String>,
+}
+
+#[derive(Args)]
+pub struct NmDownArgs {
+    /// Connection ID or UUID
+    connection: String,
+}
+
+impl NmCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            NmSubcommand::Devices(args) => args.execute().await,
+            NmSubcommand::Connections(args) => args.execute().await,
+            NmSubcommand::Up(args) => args.execute().await,
+            NmSubcommand::Down(args) => args.execute().await,
+        }
+    }
+}
+
+impl NmDevicesArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+
+        if let Some(ref ifname) = self.interface {
+            let device = mgr.get_nm_device(ifname).await?;
+            if self.json {
+                let json = serde_json::to_string_pretty(&device)
+                    .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+                println!("{}", json);
+            } else {
+                print_device(&device);
+            }
+            return Ok(());
+        }
+
+        let devices = mgr.list_nm_devices().await?;
+        if self.json {
+            let json = serde_json::to_string_pretty(&devices)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            print_device_table(&devices);
+        }
+        Ok(())
+    }
+}
+
+impl NmConnectionsArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        let connections = mgr.list_nm_connections().await?;
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&connections)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            print_connection_table(&connections);
+        }
+        Ok(())
+    }
+}
+
+impl NmUpArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.activate_nm_connection(&self.connection, self.device.as_deref())
+            .await?;
+
+        match self.device {
+            Some(ref device) => {
+                println!("✓ Connection {} activated on {}", self.connection, device)
+            }
+            None => println!("✓ Connection {} activated", self.connection),
+        }
+        Ok(())
+    }
+}
+
+impl NmDownArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.deactivate_nm_connection(&self.connection).await?;
+
+        println!("✓ Connection {} deactivated", self.connection);
+        Ok(())
+    }
+}
+
+fn print_device_table(devices: &[NmDevice]) {
+    println!(
+        "{:<15} {:<12} {:<14} {:<25}",
+        "DEVICE", "TYPE", "STATE", "CONNECTION"
+    );
+    println!("{}", "-".repeat(66));
+
+    for device in devices {
+        println!(
+            "{:<15} {:<12} {:<14} {:<25}",
+            device.ifname,
+            device.device_type,
+            device.state.to_string(),
+            device.connection.as_deref().unwrap_or("-")
+        );
+    }
+
+    println!("\nTotal: {} device(s)", devices.len());
+}
+
+fn print_connection_table(connections: &[NmActiveConnection]) {
+    println!(
+        "{:<25} {:<37} {:<18} {:<12} {:<15}",
+        "NAME", "UUID", "TYPE", "STATE", "DEVICES"
+    );
+    println!("{}", "-".repeat(108));
+
+    for connection in connections {
+        let devices = if connection.devices.is_empty() {
+            "-".to_string()
+        } else {
+            connection.devices.join(",")
+        };
+        println!(
+            "{:<25} {:<37} {:<18} {:<12} {:<15}",
+            connection.id,
+            connection.uuid,
+            connection.connection_type,
+            connection.state.to_string(),
+            devices
+        );
+    }
+
+    println!("\nTotal: {} active connection(s)", connections.len());
+}
+
+/// Details of one NetworkManager device, like `netctl status` for networkd
+pub fn print_device(device: &NmDevice) {
+    let bullet = match device.state {
+        NmDeviceState::Activated => "●".green(),
+        NmDeviceState::Unmanaged | NmDeviceState::Unavailable | NmDeviceState::Failed => "●".red(),
+        _ => "●".yellow(),
+    };
+    println!("{} {}", bullet, device.ifname.bold());
+
+    println!("  Managed by: NetworkManager");
+    println!("  State: {}", device.state);
+    println!("  Type: {}", device.device_type);
+    if !device.driver.is_empty() {
+        println!("  Driver: {}", device.driver);
+    }
+    if let Some(ref connection) = device.connection {
+        println!("  Connection: {}", connection);
+    }
+
+    if let Some(ref ip4) = device.ip4 {
+        print_ip_config("IPv4", ip4);
+    }
+    if let Some(ref ip6) = device.ip6 {
+        print_ip_config("IPv6", ip6);
+    }
+}
+
+fn print_ip_config(family: &str, config: &NmIpConfig) {
+    for address in &config.addresses {
+        println!("  {} address: {}", family, address);
+    }
+    if let Some(gateway) = config.gateway {
+        println!("  {} gateway: {}", family, gateway);
+    }
+    for server in &config.nameservers {
+        println!("  {} DNS: {}", family, server);
+    }
+    if !config.domains.is_empty() {
+        println!("  {} domains: {}", family, config.domains.join(" "));
+    }
+}
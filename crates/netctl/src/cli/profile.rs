@@ -1,5 +1,6 @@
 use clap::{Args, Subcommand};
 use miette::{IntoDiagnostic, Result};
+use netctl_config::model::{AdminState, InterfaceConfig, NetworkConfig};
 use netctl_core::NetworkManager;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -58,16 +59,8 @@ pub struct NetworkProfile {
     pub name: String,
     pub description: Option<String>,
     pub created_at: String,
-    pub interfaces: Vec<InterfaceConfig>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct InterfaceConfig {
-    pub name: String,
-    pub state: String,
-    pub mtu: u32,
-    pub mac_address: Option<String>,
-    pub addresses: Vec<String>,
+    #[serde(flatten)]
+    pub config: NetworkConfig,
 }
 
 impl ProfileCommand {
@@ -85,35 +78,10 @@ impl ProfileCommand {
 impl SaveArgs {
     pub async fn execute(self) -> Result<()> {
         let mgr = NetworkManager::new().await?;
-        let links = mgr.list_links().await?;
-
-        let interfaces: Vec<InterfaceConfig> = links
-            .into_iter()
-            .map(|link| InterfaceConfig {
-                name: link.name,
-                state: format!("{:?}", link.state),
-                mtu: link.mtu,
-                mac_address: link.mac_address.map(|m| m.to_string()),
-                addresses: link.addresses.iter().map(|a| a.to_string()).collect(),
-            })
-            .collect();
-
-        let profile = NetworkProfile {
-            name: self.name.clone(),
-            description: self.description,
-            created_at: chrono::Utc::now().to_rfc3339(),
-            interfaces,
-        };
-
-        let profile_dir = get_profile_dir()?;
-        fs::create_dir_all(&profile_dir).into_diagnostic()?;
-
-        let profile_path = profile_dir.join(format!("{}.yaml", self.name));
-        let yaml = serde_yaml::to_string(&profile).into_diagnostic()?;
-        fs::write(&profile_path, yaml).into_diagnostic()?;
+        let (profile, profile_path) = save_profile(&mgr, &self.name, self.description).await?;
 
         println!("✓ Profile '{}' saved to {}", self.name, profile_path.display());
-        println!("  {} interface(s) saved", profile.interfaces.len());
+        println!("  {} interface(s) saved", profile.config.interfaces.len());
 
         Ok(())
     }
@@ -130,31 +98,22 @@ impl LoadArgs {
 
         let mgr = NetworkManager::new().await?;
 
-        for iface in &profile.interfaces {
+        for iface in &profile.config.interfaces {
             println!("\nConfiguring {}...", iface.name);
+            load_interface(&mgr, iface).await?;
 
-            // Set state
-            match iface.state.to_lowercase().as_str() {
-                "up" => {
-                    mgr.set_link_up(&iface.name).await?;
-                    println!("  ✓ State: up");
-                }
-                "down" => {
-                    mgr.set_link_down(&iface.name).await?;
-                    println!("  ✓ State: down");
-                }
-                _ => {}
+            if let Some(state) = iface.state {
+                println!("  ✓ State: {}", state);
+            }
+            if let Some(mtu) = iface.mtu {
+                println!("  ✓ MTU: {}", mtu);
             }
-
-            // Set MTU
-            mgr.set_mtu(&iface.name, iface.mtu).await?;
-            println!("  ✓ MTU: {}", iface.mtu);
 
             // TODO: Set addresses
             if !iface.addresses.is_empty() {
                 println!("  ⚠ Address configuration not yet implemented");
                 for addr in &iface.addresses {
-                    println!("    - {}", addr);
+                    println!("    - {}", addr.address());
                 }
             }
         }
@@ -166,13 +125,7 @@ impl LoadArgs {
 
 impl DeleteArgs {
     pub async fn execute(self) -> Result<()> {
-        let profile_path = get_profile_dir()?.join(format!("{}.yaml", self.name));
-
-        if !profile_path.exists() {
-            return Err(miette::miette!("Profile '{}' not found", self.name));
-        }
-
-        fs::remove_file(&profile_path).into_diagnostic()?;
+        delete_profile(&self.name)?;
         println!("✓ Profile '{}' deleted", self.name);
 
         Ok(())
@@ -190,17 +143,21 @@ impl ShowArgs {
         println!("Created: {}", profile.created_at);
         println!("\nInterfaces:");
 
-        for iface in &profile.interfaces {
+        for iface in &profile.config.interfaces {
             println!("\n  {}:", iface.name);
-            println!("    State: {}", iface.state);
-            println!("    MTU: {}", iface.mtu);
+            if let Some(state) = iface.state {
+                println!("    State: {}", state);
+            }
+            if let Some(mtu) = iface.mtu {
+                println!("    MTU: {}", mtu);
+            }
             if let Some(mac) = &iface.mac_address {
                 println!("    MAC: {}", mac);
             }
             if !iface.addresses.is_empty() {
                 println!("    Addresses:");
                 for addr in &iface.addresses {
-                    println!("      - {}", addr);
+                    println!("      - {}", addr.address());
                 }
             }
         }
@@ -210,13 +167,35 @@ impl ShowArgs {
 }
 
 async fn list_profiles() -> Result<()> {
-    let profile_dir = get_profile_dir()?;
+    let profiles = list_saved_profiles()?;
 
-    if !profile_dir.exists() {
+    if profiles.is_empty() {
         println!("No profiles found");
         return Ok(());
     }
 
+    println!("Available profiles:\n");
+    for profile in profiles {
+        println!("  {}", profile.name);
+        if let Some(desc) = profile.description {
+            println!("    Description: {}", desc);
+        }
+        println!("    Interfaces: {}", profile.config.interfaces.len());
+        println!("    Created: {}", profile.created_at);
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Every readable profile in the profile directory
+pub fn list_saved_profiles() -> Result<Vec<NetworkProfile>> {
+    let profile_dir = get_profile_dir()?;
+
+    if !profile_dir.exists() {
+        return Ok(Vec::new());
+    }
+
     let entries = fs::read_dir(&profile_dir).into_diagnostic()?;
     let mut profiles = Vec::new();
 
@@ -233,32 +212,82 @@ async fn list_profiles() -> Result<()> {
         }
     }
 
-    if profiles.is_empty() {
-        println!("No profiles found");
-        return Ok(());
+    Ok(profiles)
+}
+
+/// Capture the current links as profile `name` and write it to disk
+pub async fn save_profile(
+    mgr: &NetworkManager,
+    name: &str,
+    description: Option<String>,
+) -> Result<(NetworkProfile, PathBuf)> {
+    let profile_path = profile_path(name)?;
+    let links = mgr.list_links().await?;
+
+    let profile = NetworkProfile {
+        name: name.to_string(),
+        description,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        config: NetworkConfig::from_links(&links),
+    };
+
+    fs::create_dir_all(get_profile_dir()?).into_diagnostic()?;
+
+    let yaml = serde_yaml::to_string(&profile).into_diagnostic()?;
+    fs::write(&profile_path, yaml).into_diagnostic()?;
+
+    Ok((profile, profile_path))
+}
+
+/// Set one profile interface's state and MTU. Addresses aren't restored yet.
+pub async fn load_interface(mgr: &NetworkManager, iface: &InterfaceConfig) -> Result<()> {
+    match iface.state {
+        Some(AdminState::Up) => mgr.set_link_up(&iface.name).await?,
+        Some(AdminState::Down) => mgr.set_link_down(&iface.name).await?,
+        None => {}
     }
+    if let Some(mtu) = iface.mtu {
+        mgr.set_mtu(&iface.name, mtu).await?;
+    }
+    Ok(())
+}
 
-    println!("Available profiles:\n");
-    for profile in profiles {
-        println!("  {}", profile.name);
-        if let Some(desc) = profile.description {
-            println!("    Description: {}", desc);
-        }
-        println!("    Interfaces: {}", profile.interfaces.len());
-        println!("    Created: {}", profile.created_at);
-        println!();
+pub fn delete_profile(name: &str) -> Result<()> {
+    let profile_path = profile_path(name)?;
+
+    if !profile_path.exists() {
+        return Err(miette::miette!("Profile '{}' not found", name));
     }
 
+    fs::remove_file(&profile_path).into_diagnostic()?;
+    Ok(())
+}
+
+/// Check that `name` can be used as a profile's file name. Names reach here
+/// from D-Bus callers of the root daemon, so they must not leave the
+/// profile directory.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains('/') || name.contains("..") {
+        return Err(miette::miette!(
+            "Invalid profile name '{}': it must be non-empty, can't start with '.' and can't contain '/' or '..'",
+            name
+        ));
+    }
     Ok(())
 }
 
+fn profile_path(name: &str) -> Result<PathBuf> {
+    validate_profile_name(name)?;
+    Ok(get_profile_dir()?.join(format!("{}.yaml", name)))
+}
+
 fn get_profile_dir() -> Result<PathBuf> {
     let home = std::env::var("HOME").into_diagnostic()?;
     Ok(PathBuf::from(home).join(".config/netctl/profiles"))
 }
 
 pub fn load_profile(name: &str) -> Result<NetworkProfile> {
-    let profile_path = get_profile_dir()?.join(format!("{}.yaml", name));
+    let profile_path = profile_path(name)?;
 
     if !profile_path.exists() {
         return Err(miette::miette!("Profile '{}' not found", name));
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Local};
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{ServiceUnit, UnitStatus};
+
+#[derive(Args)]
+pub struct ServiceCommand {
+    #[command(subcommand)]
+    command: ServiceSubcommand,
+}
+
+#[derive(Subcommand)]
+pub enum ServiceSubcommand {
+    /// Show the state of systemd-networkd and systemd-resolved
+    Status(ServiceStatusArgs),
+
+    /// Restart a service and wait for the restart to finish
+    Restart(ServiceRestartArgs),
+}
+
+#[derive(Args)]
+pub struct ServiceStatusArgs {
+    /// Service to show (networkd, resolved or wait-online; default: networkd
+    /// and resolved)
+    unit: Option<ServiceUnit>,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct ServiceRestartArgs {
+    /// Service to restart (networkd, resolved or wait-online)
+    unit: ServiceUnit,
+}
+
+impl ServiceCommand {
+    pub async fn execute(self) -> Result<()> {
+        match self.command {
+            ServiceSubcommand::Status(args) => args.execute().await,
+            ServiceSubcommand::Restart(args) => args.execute().await,
+        }
+    }
+}
+
+impl ServiceStatusArgs {
+    pub async fn execute(self) -> Result<()> {
+        let units = match self.unit {
+            Some(unit) => vec![unit],
+            None => vec![ServiceUnit::Networkd, ServiceUnit::Resolved],
+        };
+
+        let mgr = NetworkManager::new().await?;
+        let mut statuses = Vec::with_capacity(units.len());
+        for unit in units {
+            statuses.push(mgr.get_unit_status(unit).await?);
+        }
+
+        if self.json {
+            let json = serde_json::to_string_pretty(&statuses)
+                .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+            println!("{}", json);
+        } else {
+            for (i, status) in statuses.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print_unit_status(status);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ServiceRestartArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+        mgr.restart_unit(self.unit).await?;
+        println!("✓ Restarted {}", self.unit.unit_name());
+        Ok(())
+    }
+}
+
+fn print_unit_status(status: &UnitStatus) {
+    let bullet = if status.is_active() {
+        "●".green()
+    } else if status.is_failed() {
+        "●".red()
+    } else {
+        "○".normal()
+    };
+    println!("{} {} - {}", bullet, status.name.bold(), status.description);
+
+    match &status.unit_file_state {
+        Some(file_state) => println!("  Loaded: {} ({})", status.load_state, file_state),
+        None => println!("  Loaded: {}", status.load_state),
+    }
+
+    let state = format!("{} ({})", status.active_state, status.sub_state);
+    let state = if status.is_active() {
+        state.green()
+    } else if status.is_failed() {
+        state.red()
+    } else {
+        state.normal()
+    };
+    match status.active_enter_timestamp.and_then(timestamp) {
+        Some(since) if status.is_active() => println!(
+            "  Active: {} since {}",
+            state,
+            since.format("%a %Y-%m-%d %H:%M:%S %Z")
+        ),
+        _ => println!("  Active: {}", state),
+    }
+
+    if let Some(pid) = status.main_pid {
+        println!("  Main PID: {}", pid);
+    }
+}
+
+/// systemd timestamps are microseconds since the epoch
+fn timestamp(usec: u64) -> Option<DateTime<Local>> {
+    let usec = i64::try_from(usec).ok()?;
+    DateTime::from_timestamp_micros(usec).map(|t| t.with_timezone(&Local))
+}
@@ -1,7 +1,8 @@
 use clap::Args;
 use miette::Result;
 use netctl_core::NetworkManager;
-use netctl_types::{LinkInfo, LinkState};
+use netctl_types::{LinkInfo, LinkState, NetworkdLinkState};
+use std::collections::HashMap;
 
 #[derive(Args)]
 pub struct ShowArgs {
@@ -27,6 +28,7 @@ impl ShowArgs {
                 println!("{}", json);
             } else {
                 print_link_details(&link);
+                print_networkd_state(mgr.get_networkd_state(ifname).await);
             }
         } else {
             // Show all interfaces
@@ -37,7 +39,9 @@ impl ShowArgs {
                     .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
                 println!("{}", json);
             } else {
-                print_links_table(&links);
+                // networkd may not be running; show the kernel view regardless
+                let states = mgr.list_networkd_states().await.unwrap_or_default();
+                print_links_table(&links, &states);
             }
         }
 
@@ -45,12 +49,12 @@ impl ShowArgs {
     }
 }
 
-fn print_links_table(links: &[LinkInfo]) {
+fn print_links_table(links: &[LinkInfo], states: &HashMap<u32, NetworkdLinkState>) {
     println!(
-        "{:<5} {:<15} {:<8} {:<8} {:<20}",
-        "INDEX", "NAME", "STATE", "MTU", "MAC ADDRESS"
+        "{:<5} {:<15} {:<8} {:<8} {:<20} {:<12} {:<12}",
+        "INDEX", "NAME", "STATE", "MTU", "MAC ADDRESS", "OPERATIONAL", "SETUP"
     );
-    println!("{}", "-".repeat(60));
+    println!("{}", "-".repeat(86));
 
     for link in links {
         let state_str = match link.state {
@@ -63,9 +67,19 @@ fn print_links_table(links: &[LinkInfo]) {
             .map(|m| m.to_string())
             .unwrap_or_else(|| "-".to_string());
 
+        let (operational, setup) = states
+            .get(&link.index)
+            .map(|s| {
+                (
+                    s.operational_state.as_str(),
+                    s.administrative_state.as_str(),
+                )
+            })
+            .unwrap_or(("-", "-"));
+
         println!(
-            "{:<5} {:<15} {:<8} {:<8} {:<20}",
-            link.index, link.name, state_str, link.mtu, mac_str
+            "{:<5} {:<15} {:<8} {:<8} {:<20} {:<12} {:<12}",
+            link.index, link.name, state_str, link.mtu, mac_str, operational, setup
         );
     }
 
@@ -84,14 +98,82 @@ fn print_link_details(link: &LinkInfo) {
     );
     println!("  MTU: {}", link.mtu);
 
+    if let Some(ref kind) = link.kind {
+        println!("  Kind: {}", kind);
+    }
+
     if let Some(mac) = link.mac_address {
         println!("  MAC Address: {}", mac);
     }
 
+    if let Some(mac) = link.permanent_mac_address {
+        if link.mac_address != Some(mac) {
+            println!("  Permanent MAC: {}", mac);
+        }
+    }
+
+    if !link.altnames.is_empty() {
+        println!("  Altnames: {}", link.altnames.join(", "));
+    }
+
     if !link.addresses.is_empty() {
         println!("  Addresses:");
         for addr in &link.addresses {
             println!("    {}", addr);
         }
     }
+
+    if !link.vfs.is_empty() {
+        println!("  Virtual Functions:");
+        for vf in &link.vfs {
+            let mut line = format!("    vf {}", vf.id);
+            if let Some(mac) = vf.mac_address {
+                line.push_str(&format!(" mac {}", mac));
+            }
+            if vf.vlan != 0 {
+                line.push_str(&format!(" vlan {} qos {}", vf.vlan, vf.qos));
+            }
+            if let Some(spoof_check) = vf.spoof_check {
+                line.push_str(&format!(" spoofchk {}", on_off(spoof_check)));
+            }
+            if let Some(trust) = vf.trust {
+                line.push_str(&format!(" trust {}", on_off(trust)));
+            }
+            if let Some(state) = vf.link_state {
+                line.push_str(&format!(" link-state {}", state));
+            }
+            if vf.min_tx_rate != 0 || vf.max_tx_rate != 0 {
+                line.push_str(&format!(
+                    " tx-rate {}-{} Mbps",
+                    vf.min_tx_rate, vf.max_tx_rate
+                ));
+            }
+            println!("{}", line);
+        }
+    }
+}
+
+fn print_networkd_state(state: netctl_types::Result<NetworkdLinkState>) {
+    match state {
+        Ok(state) => {
+            println!("  networkd:");
+            println!("    Setup: {}", state.administrative_state);
+            println!("    Operational: {}", state.operational_state);
+            println!("    Carrier: {}", state.carrier_state);
+            println!(
+                "    Address: {} (IPv4 {}, IPv6 {})",
+                state.address_state, state.ipv4_address_state, state.ipv6_address_state
+            );
+            println!("    Online: {}", state.online_state);
+        }
+        Err(e) => println!("  networkd: unavailable ({})", e),
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
 }
@@ -0,0 +1,264 @@
+use clap::Args;
+use colored::Colorize;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::networkd::DhcpLease;
+use netctl_types::{LinkBackend, LinkDescription};
+use std::net::IpAddr;
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Interface name (if not specified, summarizes all links networkd knows).
+    /// Links managed by NetworkManager show NetworkManager's view instead
+    interface: Option<String>,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+impl StatusArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+
+        if let Some(ref ifname) = self.interface {
+            if mgr.link_backend(ifname).await? == LinkBackend::NetworkManager {
+                let device = mgr.get_nm_device(ifname).await?;
+                if self.json {
+                    let json = serde_json::to_string_pretty(&device)
+                        .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+                    println!("{}", json);
+                } else {
+                    super::nm::print_device(&device);
+                }
+                return Ok(());
+            }
+
+            let link = mgr.describe_networkd_link(ifname).await?;
+
+            if self.json {
+                let json = serde_json::to_string_pretty(&link)
+                    .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+                println!("{}", json);
+            } else {
+                print_link_status(&link);
+            }
+        } else {
+            let description = mgr.describe_networkd().await?;
+
+            if self.json {
+                let json = serde_json::to_string_pretty(&description)
+                    .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+                println!("{}", json);
+            } else {
+                print_status_table(&description.interfaces);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn print_status_table(links: &[LinkDescription]) {
+    println!(
+        "{:<5} {:<15} {:<10} {:<12} {:<12} {:<30}",
+        "INDEX", "NAME", "TYPE", "OPERATIONAL", "SETUP", "NETWORK FILE"
+    );
+    println!("{}", "-".repeat(89));
+
+    for link in links {
+        println!(
+            "{:<5} {:<15} {:<10} {:<12} {:<12} {:<30}",
+            link.index,
+            link.name,
+            or_dash(&link.link_type),
+            or_dash(&link.operational_state),
+            or_dash(&link.setup_state),
+            or_dash(&link.network_file)
+        );
+    }
+
+    println!("\nTotal: {} link(s)", links.len());
+}
+
+fn print_link_status(link: &LinkDescription) {
+    let operational = or_dash(&link.operational_state);
+    let bullet = match operational {
+        "routable" | "enslaved" => "●".green(),
+        "degraded" | "carrier" | "degraded-carrier" => "●".yellow(),
+        _ => "●".red(),
+    };
+    println!("{} {}: {}", bullet, link.index, link.name.bold());
+
+    println!("  State: {} ({})", operational, or_dash(&link.setup_state));
+    if let Some(ref online) = link.online_state {
+        println!("  Online: {}", online);
+    }
+    if let Some(ref network_file) = link.network_file {
+        println!("  Network file: {}", network_file);
+        for dropin in &link.network_file_dropins {
+            println!("    Drop-in: {}", dropin);
+        }
+    }
+    if let Some(ref link_file) = link.link_file {
+        println!("  Link file: {}", link_file);
+    }
+    if let Some(ref link_type) = link.link_type {
+        match link.kind {
+            Some(ref kind) => println!("  Type: {} ({})", link_type, kind),
+            None => println!("  Type: {}", link_type),
+        }
+    }
+    if let Some(ref driver) = link.driver {
+        println!("  Driver: {}", driver);
+    }
+    if !link.alternative_names.is_empty() {
+        println!("  Altnames: {}", link.alternative_names.join(", "));
+    }
+    if let Some(mac) = link.hardware_address {
+        println!("  MAC: {}", mac);
+    }
+    if let Some(mtu) = link.mtu {
+        println!("  MTU: {}", mtu);
+    }
+
+    if !link.addresses.is_empty() {
+        println!("  Addresses:");
+        for addr in &link.addresses {
+            println!(
+                "    {}/{}{}",
+                addr.address,
+                addr.prefix_length,
+                source(&addr.config_source, addr.config_provider)
+            );
+        }
+    }
+
+    let gateways: Vec<_> = link.gateways().collect();
+    if !gateways.is_empty() {
+        println!("  Gateways:");
+        for route in gateways {
+            if let Some(gateway) = route.gateway {
+                println!(
+                    "    {}{}",
+                    gateway,
+                    source(&route.config_source, route.config_provider)
+                );
+            }
+        }
+    }
+
+    if !link.dns.is_empty() {
+        println!("  DNS:");
+        for server in &link.dns {
+            let mut server_str = server.address.to_string();
+            if let Some(port) = server.port {
+                server_str = format!("{}:{}", server_str, port);
+            }
+            if let Some(ref name) = server.server_name {
+                server_str = format!("{}#{}", server_str, name);
+            }
+            println!(
+                "    {}{}",
+                server_str,
+                source(&server.config_source, server.config_provider)
+            );
+        }
+    }
+
+    if !link.search_domains.is_empty() {
+        let domains: Vec<&str> = link
+            .search_domains
+            .iter()
+            .map(|d| d.domain.as_str())
+            .collect();
+        println!("  Search domains: {}", domains.join(", "));
+    }
+
+    if !link.ntp.is_empty() {
+        println!("  NTP:");
+        for server in &link.ntp {
+            let name = match (&server.server, server.address) {
+                (Some(name), _) => name.clone(),
+                (None, Some(address)) => address.to_string(),
+                (None, None) => continue,
+            };
+            println!(
+                "    {}{}",
+                name,
+                source(&server.config_source, server.config_provider)
+            );
+        }
+    }
+
+    if let Some(lease) = link.dhcpv4_client.as_ref().and_then(|c| c.lease.as_ref()) {
+        print_lease("DHCPv4", lease);
+    }
+    if let Some(ref client) = link.dhcpv6_client {
+        if let Some(ref lease) = client.lease {
+            print_lease("DHCPv6", lease);
+        }
+        for prefix in &client.prefixes {
+            println!(
+                "  Delegated prefix: {}/{}",
+                prefix.prefix, prefix.prefix_length
+            );
+        }
+    }
+
+    if !link.lldp_neighbors.is_empty() {
+        println!("  LLDP neighbors:");
+        for neighbor in &link.lldp_neighbors {
+            let system = neighbor
+                .system_name
+                .as_deref()
+                .or(neighbor.chassis_id.as_deref())
+                .unwrap_or("-");
+            let port = neighbor
+                .port_description
+                .as_deref()
+                .or(neighbor.port_id.as_deref())
+                .unwrap_or("-");
+            println!("    {} port {}", system, port);
+        }
+    }
+}
+
+fn print_lease(family: &str, lease: &DhcpLease) {
+    let mut parts = Vec::new();
+    if let Some(t1) = lease.t1_usec {
+        parts.push(format!("renew after {}", format_duration(t1 / 1_000_000)));
+    }
+    if let Some(t2) = lease.t2_usec {
+        parts.push(format!("rebind after {}", format_duration(t2 / 1_000_000)));
+    }
+    if parts.is_empty() {
+        println!("  {} lease: acquired", family);
+    } else {
+        println!("  {} lease: {}", family, parts.join(", "));
+    }
+}
+
+/// " (DHCPv4 from 10.0.0.1)" style suffix describing where a setting came from
+fn source(config_source: &Option<String>, provider: Option<IpAddr>) -> String {
+    match (config_source, provider) {
+        (Some(src), Some(provider)) => format!(" ({} from {})", src, provider),
+        (Some(src), None) => format!(" ({})", src),
+        (None, _) => String::new(),
+    }
+}
+
+pub(super) fn format_duration(secs: u64) -> String {
+    let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match (hours, mins, secs) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}min", m),
+        (0, m, s) => format!("{}min {}s", m, s),
+        (h, 0, 0) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}min", h, m),
+    }
+}
+
+fn or_dash(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("-")
+}
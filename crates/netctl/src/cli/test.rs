@@ -1,7 +1,9 @@
+use super::dns::{link_names, print_hostname_resolution};
 use clap::{Args, Subcommand};
 use colored::Colorize;
 use miette::{IntoDiagnostic, Result};
 use netctl_core::NetworkManager;
+use netctl_types::ServiceUnit;
 use std::process::Command;
 use std::time::Instant;
 
@@ -126,7 +128,7 @@ impl ConnectivityArgs {
     fn test_interface_connectivity(&self, _interface: &str) -> Result<bool> {
         // Test basic connectivity with ping
         let output = Command::new("ping")
-            .args(&["-c", "1", "-W", "2", "8.8.8.8"])
+            .args(["-c", "1", "-W", "2", "8.8.8.8"])
             .output()
             .into_diagnostic()?;
 
@@ -142,25 +144,20 @@ impl DnsArgs {
 
         println!("Resolving {} ...", self.hostname.cyan());
 
+        let mgr = NetworkManager::new().await?;
         let start = Instant::now();
-        let output = Command::new("host")
-            .arg(&self.hostname)
-            .output()
-            .into_diagnostic()?;
+        let result = mgr.resolve_hostname(&self.hostname, None).await;
         let duration = start.elapsed();
 
-        if output.status.success() {
-            let result = String::from_utf8_lossy(&output.stdout);
-            println!("{} Resolved in {:.3}s", "✓".green(), duration.as_secs_f64());
-            println!();
-            for line in result.lines() {
-                println!("  {}", line);
+        match result {
+            Ok(resolution) => {
+                println!("{} Resolved in {:.3}s", "✓".green(), duration.as_secs_f64());
+                println!();
+                print_hostname_resolution(&resolution, &link_names(&mgr).await);
             }
-        } else {
-            println!("{} Resolution failed", "✗".red());
-            let error = String::from_utf8_lossy(&output.stderr);
-            if !error.is_empty() {
-                println!("  Error: {}", error);
+            Err(e) => {
+                println!("{} Resolution failed", "✗".red());
+                println!("  Error: {}", e);
             }
         }
 
@@ -179,10 +176,10 @@ impl PingArgs {
         println!();
 
         let mut cmd = Command::new("ping");
-        cmd.args(&["-c", &self.count.to_string(), &self.host]);
+        cmd.args(["-c", &self.count.to_string(), &self.host]);
 
         if let Some(ref iface) = self.interface {
-            cmd.args(&["-I", iface]);
+            cmd.args(["-I", iface]);
         }
 
         let output = cmd.output().into_diagnostic()?;
@@ -239,7 +236,7 @@ impl AllArgs {
         // Test 2: Internet connectivity
         println!("{} {}", "2.".cyan(), "Testing internet connectivity...".bold());
         let output = Command::new("ping")
-            .args(&["-c", "2", "-W", "3", "8.8.8.8"])
+            .args(["-c", "2", "-W", "3", "8.8.8.8"])
             .output()
             .into_diagnostic()?;
 
@@ -254,12 +251,7 @@ impl AllArgs {
 
         // Test 3: DNS resolution
         println!("{} {}", "3.".cyan(), "Testing DNS resolution...".bold());
-        let output = Command::new("host")
-            .arg("www.google.com")
-            .output()
-            .into_diagnostic()?;
-
-        if output.status.success() {
+        if mgr.resolve_hostname("www.google.com", None).await.is_ok() {
             println!("   {} DNS resolution working", "✓".green());
             results.push(("DNS resolution", true));
         } else {
@@ -270,12 +262,10 @@ impl AllArgs {
 
         // Test 4: systemd-networkd
         println!("{} {}", "4.".cyan(), "Testing systemd-networkd...".bold());
-        let output = Command::new("systemctl")
-            .args(&["is-active", "systemd-networkd"])
-            .output()
-            .into_diagnostic()?;
-
-        let is_active = String::from_utf8_lossy(&output.stdout).trim() == "active";
+        let is_active = mgr
+            .get_unit_status(ServiceUnit::Networkd)
+            .await
+            .is_ok_and(|status| status.is_active());
         if is_active {
             println!("   {} systemd-networkd is active", "✓".green());
             results.push(("systemd-networkd", true));
@@ -287,12 +277,10 @@ impl AllArgs {
 
         // Test 5: systemd-resolved
         println!("{} {}", "5.".cyan(), "Testing systemd-resolved...".bold());
-        let output = Command::new("systemctl")
-            .args(&["is-active", "systemd-resolved"])
-            .output()
-            .into_diagnostic()?;
-
-        let is_active = String::from_utf8_lossy(&output.stdout).trim() == "active";
+        let is_active = mgr
+            .get_unit_status(ServiceUnit::Resolved)
+            .await
+            .is_ok_and(|status| status.is_active());
         if is_active {
             println!("   {} systemd-resolved is active", "✓".green());
             results.push(("systemd-resolved", true));
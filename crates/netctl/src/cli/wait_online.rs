@@ -0,0 +1,90 @@
+use clap::Args;
+use miette::Result;
+use netctl_core::NetworkManager;
+use netctl_types::{InterfaceRequirement, OperationalState, WaitOnlineSpec};
+use std::time::Duration;
+
+/// Exit status when the timeout passes before the network is online
+const EXIT_TIMEOUT: i32 = 3;
+
+#[derive(Args)]
+#[command(after_help = "Exit status: 0 online, 1 error, 2 invalid usage, 3 timed out")]
+pub struct WaitOnlineArgs {
+    /// Interface to wait for, optionally with its own minimum state
+    /// (e.g. eth0 or eth0:routable); may be repeated. Without it, waits for
+    /// every link networkd manages
+    #[arg(short, long = "interface", value_name = "IFACE[:STATE]")]
+    interfaces: Vec<InterfaceRequirement>,
+
+    /// Interface to never wait for; may be repeated
+    #[arg(long, value_name = "IFACE")]
+    ignore: Vec<String>,
+
+    /// Return as soon as one interface is ready instead of all of them
+    #[arg(long)]
+    any: bool,
+
+    /// Minimum operational state (no-carrier, dormant, degraded-carrier,
+    /// carrier, degraded, enslaved, routable)
+    #[arg(short = 'o', long, default_value = "degraded")]
+    operational_state: OperationalState,
+
+    /// Also require networkd to report the interface online
+    #[arg(long)]
+    online: bool,
+
+    /// Require a routable IPv4 address
+    #[arg(short = '4', long)]
+    ipv4: bool,
+
+    /// Require a routable IPv6 address
+    #[arg(short = '6', long)]
+    ipv6: bool,
+
+    /// Require a default route through the interface (of the families
+    /// given with -4/-6, otherwise of either)
+    #[arg(long)]
+    default_route: bool,
+
+    /// Seconds to wait before giving up; 0 waits forever
+    #[arg(short, long, default_value = "120")]
+    timeout: u64,
+
+    /// Don't print anything, only set the exit status
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+impl WaitOnlineArgs {
+    pub async fn execute(self) -> Result<()> {
+        let mgr = NetworkManager::new().await?;
+
+        let spec = WaitOnlineSpec {
+            interfaces: self.interfaces,
+            ignore: self.ignore,
+            any: self.any,
+            min_state: self.operational_state,
+            require_online: self.online,
+            ipv4: self.ipv4,
+            ipv6: self.ipv6,
+            default_route: self.default_route,
+        };
+        let timeout = (self.timeout > 0).then(|| Duration::from_secs(self.timeout));
+
+        let status = mgr.wait_online(&spec, timeout).await?;
+        if status.ready {
+            if !self.quiet {
+                println!("✓ Network is online");
+            }
+            return Ok(());
+        }
+
+        if !self.quiet {
+            eprintln!("Timed out after {}s waiting for the network", self.timeout);
+            if !status.pending.is_empty() {
+                eprintln!("Not ready: {}", status.pending.join(", "));
+            }
+        }
+        std::process::exit(EXIT_TIMEOUT);
+    }
+}
@@ -1,6 +1,9 @@
 use clap::Args;
+use futures::stream::{BoxStream, StreamExt};
 use miette::Result;
 use netctl_core::NetworkManager;
+use netctl_types::{NetworkEvent, NetworkEventKind};
+use std::collections::HashMap;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -9,14 +12,32 @@ pub struct WatchArgs {
     /// Interface to watch (optional, watches all if not specified)
     interface: Option<String>,
 
-    /// Refresh interval in seconds
+    /// Refresh interval in seconds (the table also refreshes on every event)
     #[arg(short, long, default_value = "1")]
     interval: u64,
+
+    /// Print a timeline of kernel, networkd and resolved events instead of
+    /// the interface table
+    #[arg(short, long)]
+    events: bool,
+
+    /// With --events, print one JSON object per event
+    #[arg(long, requires = "events")]
+    json: bool,
 }
 
 impl WatchArgs {
     pub async fn execute(self) -> Result<()> {
         let mgr = NetworkManager::new().await?;
+
+        if self.events {
+            let events = mgr.subscribe_events().await?;
+            return self.print_events(&mgr, events).await;
+        }
+
+        // Without events (e.g. no netlink multicast access) fall back to
+        // plain polling
+        let mut events = mgr.subscribe_events().await.ok();
         let interval = Duration::from_secs(self.interval);
 
         println!("Watching network interfaces (Ctrl+C to stop)");
@@ -59,7 +80,87 @@ impl WatchArgs {
                 }
             }
 
-            sleep(interval).await;
+            // Redraw on the next event, or after the interval at the latest
+            let next_event = async {
+                match events.as_mut() {
+                    Some(stream) => stream.next().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let ended = tokio::select! {
+                _ = sleep(interval) => false,
+                event = next_event => event.is_none(),
+            };
+            if ended {
+                events = None;
+            }
+        }
+    }
+
+    async fn print_events(
+        &self,
+        mgr: &NetworkManager,
+        mut events: BoxStream<'static, NetworkEvent>,
+    ) -> Result<()> {
+        let mut names: HashMap<u32, String> = mgr
+            .list_links()
+            .await
+            .map(|links| links.into_iter().map(|l| (l.index, l.name)).collect())
+            .unwrap_or_default();
+
+        if !self.json {
+            println!("Watching network events (Ctrl+C to stop)\n");
         }
+
+        while let Some(event) = events.next().await {
+            match &event.kind {
+                NetworkEventKind::LinkChanged { index, name, .. } => {
+                    names.insert(*index, name.clone());
+                }
+                NetworkEventKind::LinkRemoved { index, .. } => {
+                    names.remove(index);
+                }
+                _ => {}
+            }
+
+            let label = event.ifindex().map(|index| match &event.kind {
+                NetworkEventKind::LinkRemoved { name, .. } if !name.is_empty() => name.clone(),
+                _ => names
+                    .get(&index)
+                    .cloned()
+                    .unwrap_or_else(|| index.to_string()),
+            });
+            if let (Some(ref wanted), Some(ref label)) = (&self.interface, &label) {
+                if wanted != label {
+                    continue;
+                }
+            }
+
+            if self.json {
+                let json = serde_json::to_string(&event)
+                    .map_err(|e| miette::miette!("JSON serialization failed: {}", e))?;
+                println!("{}", json);
+            } else {
+                let time = chrono::DateTime::<chrono::Local>::from(event.timestamp);
+                let source = format!("[{}]", event.source());
+                match label {
+                    Some(label) => println!(
+                        "{} {:<10} {}: {}",
+                        time.format("%H:%M:%S%.3f"),
+                        source,
+                        label,
+                        event.kind
+                    ),
+                    None => println!(
+                        "{} {:<10} {}",
+                        time.format("%H:%M:%S%.3f"),
+                        source,
+                        event.kind
+                    ),
+                }
+            }
+        }
+
+        Ok(())
     }
 }
@@ -3,6 +3,7 @@ use colored::Colorize;
 use inquire::{Confirm, Select, Text};
 use miette::{IntoDiagnostic, Result};
 use netctl_core::NetworkManager;
+use netctl_types::IpNetwork;
 
 #[derive(Args)]
 pub struct WizardArgs {}
@@ -79,9 +80,9 @@ impl WizardArgs {
             .prompt()
             .into_diagnostic()?
         {
-            match full_address.parse() {
+            match full_address.parse::<IpNetwork>() {
                 Ok(ip_net) => {
-                    mgr.add_address(interface, ip_net).await?;
+                    mgr.add_address(interface, ip_net.into()).await?;
                     println!("\n{} Configuration applied!", "✓".green());
                 }
                 Err(_) => {
@@ -165,7 +166,7 @@ impl WizardArgs {
     async fn wizard_dhcp(&self, mgr: &NetworkManager, interface: &str) -> Result<()> {
         println!("\n{}", "Prepare Interface for DHCP".bold());
         println!("This will bring the interface up and set standard MTU.");
-        println!("Note: Actual DHCP configuration requires systemd-networkd.");
+        println!("The lease is then renewed through systemd-networkd.");
 
         if Confirm::new("Proceed?")
             .with_default(true)
@@ -181,7 +182,18 @@ impl WizardArgs {
             println!("  {} Interface is up", "✓".green());
 
             println!("\n{} Interface prepared for DHCP", "✓".green());
-            println!("Configure systemd-networkd for actual DHCP client.");
+
+            match mgr.renew_dhcp(interface).await {
+                Ok(()) => println!(
+                    "  {} DHCP renewal requested (check with 'netctl dhcp status {}')",
+                    "✓".green(),
+                    interface
+                ),
+                Err(e) => {
+                    println!("  {} Could not start DHCP: {}", "⚠".yellow(), e);
+                    println!("  Enable DHCP= in the interface's systemd-networkd .network file.");
+                }
+            }
         }
 
         Ok(())
@@ -242,9 +254,9 @@ impl WizardArgs {
 
             // Apply address if static
             if let Some(addr) = address {
-                match addr.parse() {
+                match addr.parse::<IpNetwork>() {
                     Ok(ip_net) => {
-                        mgr.add_address(interface, ip_net).await?;
+                        mgr.add_address(interface, ip_net.into()).await?;
                         println!("  {} Address configured", "✓".green());
                     }
                     Err(_) => {
@@ -0,0 +1,497 @@
+//! `netctl daemon`: netctl's operations as the `org.netctl1` service on the
+//! system bus, so GUIs and automation can drive netctl without exec'ing the
+//! CLI. Queries return JSON, like networkd's `Describe()`. Changes are
+//! authorized through polkit and announced with the `Changed` signal; kernel,
+//! networkd and resolved events are forwarded as `NetworkEvent`.
+
+use crate::cli::{apply, history, profile};
+use futures::stream::{BoxStream, StreamExt};
+use miette::Result;
+use netctl_config::model::{ConfigFormat, NetworkConfig};
+use netctl_core::{polkit, NetworkManager};
+use netctl_types::{Error, NetworkEvent};
+use serde::Serialize;
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{info, warn};
+use zbus::message::{Flags, Header};
+use zbus::object_server::SignalContext;
+use zbus::{fdo, interface, Connection};
+
+pub const BUS_NAME: &str = "org.netctl1";
+pub const OBJECT_PATH: &str = "/org/netctl1";
+
+/// polkit action for changes to the running configuration
+const ACTION_CONFIGURE: &str = "org.netctl1.configure";
+/// polkit action for saving and deleting profiles
+const ACTION_MANAGE_PROFILES: &str = "org.netctl1.manage-profiles";
+
+pub struct Daemon {
+    mgr: NetworkManager,
+}
+
+#[interface(name = "org.netctl1.Manager")]
+impl Daemon {
+    /// All links, as a JSON array
+    async fn list_links(&self) -> fdo::Result<String> {
+        to_json(&self.mgr.list_links().await.map_err(fdo_error)?)
+    }
+
+    /// One link, as a JSON object
+    async fn get_link(&self, name: &str) -> fdo::Result<String> {
+        to_json(&self.mgr.get_link_info(name).await.map_err(fdo_error)?)
+    }
+
+    /// Apply a YAML, TOML or JSON configuration, like `netctl apply`.
+    /// Returns the changes made, or with `dry_run` the changes that would be
+    /// made.
+    async fn apply_config(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        config: &str,
+        format: &str,
+        dry_run: bool,
+    ) -> fdo::Result<Vec<String>> {
+        let invalid = |e: Error| fdo::Error::InvalidArgs(e.to_string());
+        let format: ConfigFormat = format.parse().map_err(invalid)?;
+        let config = NetworkConfig::parse(config, format).map_err(invalid)?;
+        let errors: Vec<_> = config
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .map(|issue| issue.message)
+            .collect();
+        if !errors.is_empty() {
+            return Err(fdo::Error::InvalidArgs(errors.join("; ")));
+        }
+        if !dry_run {
+            authorize(connection, &header, ACTION_CONFIGURE).await?;
+        }
+
+        let links = self
+            .mgr
+            .list_links_with_addresses()
+            .await
+            .map_err(fdo_error)?;
+        let (steps, warnings) = apply::plan(&config, &links);
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+
+        if !dry_run {
+            history::save_history_snapshot(&self.mgr, "apply", "Before applying configuration")
+                .await
+                .map_err(fdo_error)?;
+        }
+
+        let mut applied = Vec::new();
+        for step in steps {
+            if !dry_run {
+                step.change
+                    .apply(&self.mgr, &step.target)
+                    .await
+                    .map_err(fdo_error)?;
+            }
+            applied.push(step.to_string());
+        }
+
+        if !dry_run {
+            let description = format!("{} change(s) applied", applied.len());
+            Self::changed(&ctxt, "apply", &description).await?;
+        }
+        Ok(applied)
+    }
+
+    /// Saved profiles, as a JSON array
+    async fn list_profiles(&self) -> fdo::Result<String> {
+        to_json(&profile::list_saved_profiles().map_err(fdo_error)?)
+    }
+
+    /// Save the current links as a profile; an empty description means none
+    async fn save_profile(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+        description: &str,
+    ) -> fdo::Result<()> {
+        check_profile_name(name)?;
+        authorize(connection, &header, ACTION_MANAGE_PROFILES).await?;
+        let description = (!description.is_empty()).then(|| description.to_string());
+        profile::save_profile(&self.mgr, name, description)
+            .await
+            .map_err(fdo_error)?;
+
+        Self::changed(&ctxt, "profile-save", &format!("Profile '{}' saved", name)).await?;
+        Ok(())
+    }
+
+    async fn load_profile(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        check_profile_name(name)?;
+        authorize(connection, &header, ACTION_CONFIGURE).await?;
+        let saved = profile::load_profile(name).map_err(fdo_error)?;
+        history::save_history_snapshot(
+            &self.mgr,
+            "profile-load",
+            &format!("Before loading profile '{}'", name),
+        )
+        .await
+        .map_err(fdo_error)?;
+        for iface in &saved.config.interfaces {
+            profile::load_interface(&self.mgr, iface)
+                .await
+                .map_err(fdo_error)?;
+        }
+
+        Self::changed(&ctxt, "profile-load", &format!("Profile '{}' loaded", name)).await?;
+        Ok(())
+    }
+
+    async fn delete_profile(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        name: &str,
+    ) -> fdo::Result<()> {
+        check_profile_name(name)?;
+        authorize(connection, &header, ACTION_MANAGE_PROFILES).await?;
+        profile::delete_profile(name).map_err(fdo_error)?;
+
+        Self::changed(
+            &ctxt,
+            "profile-delete",
+            &format!("Profile '{}' deleted", name),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Configuration history, newest first, as a JSON array
+    async fn list_history(&self) -> fdo::Result<String> {
+        let mut entries = history::load_all_history().map_err(fdo_error)?;
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        to_json(&entries)
+    }
+
+    /// Roll back to a history entry, like `netctl history rollback`. Returns
+    /// the ID of the snapshot taken first.
+    async fn rollback(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        id: &str,
+    ) -> fdo::Result<String> {
+        authorize(connection, &header, ACTION_CONFIGURE).await?;
+        let entry = history::load_history_entry(id).map_err(fdo_error)?;
+        let (snapshot, _) = history::rollback(&self.mgr, &entry)
+            .await
+            .map_err(fdo_error)?;
+
+        Self::changed(&ctxt, "rollback", &format!("Rolled back to {}", id)).await?;
+        Ok(snapshot)
+    }
+
+    /// netctl's version
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn version(&self) -> &str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// A method call changed the configuration. `operation` is apply,
+    /// profile-save, profile-load, profile-delete or rollback.
+    #[zbus(signal)]
+    async fn changed(
+        ctxt: &SignalContext<'_>,
+        operation: &str,
+        description: &str,
+    ) -> zbus::Result<()>;
+
+    /// A kernel, networkd or resolved event, as in `netctl watch --events`.
+    /// `ifindex` is 0 for events not about a link; `event` is JSON.
+    #[zbus(signal)]
+    async fn network_event(
+        ctxt: &SignalContext<'_>,
+        source: &str,
+        ifindex: u32,
+        event: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Serve `org.netctl1` on the system bus until SIGINT or SIGTERM
+pub async fn run() -> Result<()> {
+    let mgr = NetworkManager::new().await?;
+    // Subscribe before serving so the first changes made through the
+    // service are already forwarded
+    let events = mgr.subscribe_events().await?;
+
+    let connection = zbus::connection::Builder::system()
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, Daemon { mgr }))
+        .and_then(|builder| builder.name(BUS_NAME))
+        .map_err(|e| Error::dbus(format!("Failed to set up {}: {}", BUS_NAME, e)))?
+        .build()
+        .await
+        .map_err(|e| {
+            Error::dbus(format!(
+                "Failed to serve {} on the system bus: {}",
+                BUS_NAME, e
+            ))
+        })?;
+    info!(
+        name = BUS_NAME,
+        path = OBJECT_PATH,
+        "serving on the system bus"
+    );
+
+    let iface = connection
+        .object_server()
+        .interface::<_, Daemon>(OBJECT_PATH)
+        .await
+        .map_err(|e| Error::dbus(format!("Failed to look up {}: {}", OBJECT_PATH, e)))?;
+
+    let mut terminate = signal(SignalKind::terminate())
+        .map_err(|e| miette::miette!("Failed to install the SIGTERM handler: {}", e))?;
+    tokio::select! {
+        _ = forward_events(events, iface.signal_context()) => {
+            warn!("event stream ended");
+        }
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+
+    info!("shutting down");
+    Ok(())
+}
+
+async fn forward_events(mut events: BoxStream<'static, NetworkEvent>, ctxt: &SignalContext<'_>) {
+    while let Some(event) = events.next().await {
+        let json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!(error = %e, "failed to serialize event");
+                continue;
+            }
+        };
+        let source = event.source().to_string();
+        let ifindex = event.ifindex().unwrap_or(0);
+        if let Err(e) = Daemon::network_event(ctxt, &source, ifindex, &json).await {
+            warn!(error = %e, "failed to emit NetworkEvent");
+        }
+    }
+}
+
+/// Check the caller against polkit, allowing a password prompt when the call
+/// was sent with ALLOW_INTERACTIVE_AUTHORIZATION
+async fn authorize(connection: &Connection, header: &Header<'_>, action: &str) -> fdo::Result<()> {
+    let sender = header
+        .sender()
+        .ok_or_else(|| fdo::Error::AccessDenied("call has no sender".to_string()))?;
+    let interactive = header
+        .primary()
+        .flags()
+        .contains(Flags::AllowInteractiveAuth);
+
+    polkit::check_authorization(connection, sender.as_str(), action, interactive)
+        .await
+        .map_err(fdo_error)
+}
+
+/// Refuse profile names that aren't a plain file name before asking polkit,
+/// so no caller can reach files outside the profile directory
+fn check_profile_name(name: &str) -> fdo::Result<()> {
+    profile::validate_profile_name(name).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))
+}
+
+fn to_json<T: Serialize>(value: &T) -> fdo::Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| fdo::Error::Failed(format!("JSON serialization failed: {}", e)))
+}
+
+/// Map netctl errors to the standard D-Bus errors clients know how to handle
+fn fdo_error(e: impl Into<miette::Report>) -> fdo::Error {
+    let report = e.into();
+    match report.downcast_ref::<Error>() {
+        Some(Error::PolkitDenied { .. }) => fdo::Error::AccessDenied(report.to_string()),
+        Some(Error::InterfaceNotFound { .. } | Error::InvalidAddress { .. }) => {
+            fdo::Error::InvalidArgs(report.to_string())
+        }
+        _ => fdo::Error::Failed(report.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netctl_dbus::testing::{FakeBus, FakePolkit};
+
+    const MISSING: &str = "netctl-test-missing";
+
+    /// Serve the daemon on the client side of a fake bus, so its caller
+    /// lookups and polkit checks reach the fakes
+    async fn setup() -> (FakeBus, FakePolkit) {
+        // Keep history snapshots and profiles out of the real home
+        static HOME: std::sync::Once = std::sync::Once::new();
+        HOME.call_once(|| {
+            let home = std::env::temp_dir().join(format!("netctl-daemon-{}", std::process::id()));
+            std::env::set_var("HOME", home);
+        });
+        let bus = FakeBus::new().await.unwrap();
+        let polkit = bus.add_polkit().await.unwrap();
+        let mgr = NetworkManager::with_dbus(bus.client()).await.unwrap();
+        bus.serve(OBJECT_PATH, Daemon { mgr }).await.unwrap();
+        (bus, polkit)
+    }
+
+    async fn call<B, R>(bus: &FakeBus, method: &str, interactive: bool, body: &B) -> fdo::Result<R>
+    where
+        B: Serialize + zbus::zvariant::DynamicType,
+        R: for<'d> zbus::zvariant::DynamicDeserialize<'d>,
+    {
+        bus.call(
+            OBJECT_PATH,
+            "org.netctl1.Manager",
+            method,
+            interactive,
+            body,
+        )
+        .await
+        .map_err(fdo::Error::from)
+    }
+
+    async fn call_err(bus: &FakeBus, method: &str, arg: &str) -> fdo::Error {
+        call::<_, ()>(bus, method, false, &(arg,))
+            .await
+            .unwrap_err()
+    }
+
+    /// The (action_id, flags) of each CheckAuthorization
+    fn checks(polkit: &FakePolkit) -> Vec<String> {
+        polkit.calls().into_iter().map(|call| call.args).collect()
+    }
+
+    #[tokio::test]
+    async fn test_denied_before_reading() {
+        let (bus, polkit) = setup().await;
+
+        for method in ["LoadProfile", "Rollback", "DeleteProfile"] {
+            let err = call_err(&bus, method, MISSING).await;
+            assert!(
+                matches!(err, fdo::Error::AccessDenied(_)),
+                "{}: {:?}",
+                method,
+                err
+            );
+        }
+        assert_eq!(
+            checks(&polkit),
+            [
+                r#"("org.netctl1.configure", 0)"#,
+                r#"("org.netctl1.configure", 0)"#,
+                r#"("org.netctl1.manage-profiles", 0)"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_allowed_reads_after_authorizing() {
+        let (bus, polkit) = setup().await;
+        polkit.set_authorized(true);
+
+        for method in ["LoadProfile", "Rollback"] {
+            let err = call_err(&bus, method, MISSING).await;
+            assert!(
+                matches!(err, fdo::Error::Failed(ref m) if m.contains("not found")),
+                "{}: {:?}",
+                method,
+                err
+            );
+        }
+        assert_eq!(polkit.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_profile_names_outside_profile_dir_refused() {
+        let (bus, polkit) = setup().await;
+        polkit.set_authorized(true);
+
+        for name in ["../../etc/foo", "a/b", ".hidden", "", "a..b"] {
+            for method in ["LoadProfile", "DeleteProfile"] {
+                let err = call_err(&bus, method, name).await;
+                assert!(
+                    matches!(err, fdo::Error::InvalidArgs(_)),
+                    "{} {:?}: {:?}",
+                    method,
+                    name,
+                    err
+                );
+            }
+            let err = call::<_, ()>(&bus, "SaveProfile", false, &(name, ""))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, fdo::Error::InvalidArgs(_)), "{:?}", err);
+        }
+        // Refused before polkit was asked
+        assert!(polkit.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_root_skips_polkit() {
+        let (bus, polkit) = setup().await;
+        bus.set_caller_uid(0);
+
+        let err = call_err(&bus, "LoadProfile", MISSING).await;
+        assert!(matches!(err, fdo::Error::Failed(_)), "{:?}", err);
+        assert!(polkit.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_interactive_authorization_passed_on() {
+        let (bus, polkit) = setup().await;
+
+        let err = call::<_, ()>(&bus, "LoadProfile", true, &(MISSING,))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, fdo::Error::AccessDenied(ref m) if m.contains("interactive")),
+            "{:?}",
+            err
+        );
+        assert_eq!(checks(&polkit), [r#"("org.netctl1.configure", 1)"#]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_config() {
+        let (bus, polkit) = setup().await;
+        let config = "interfaces:\n  - name: lo\n    state: up\n";
+
+        // A dry run changes nothing, so needs no authorization
+        let _: Vec<String> = call(&bus, "ApplyConfig", false, &(config, "yaml", true))
+            .await
+            .unwrap();
+        assert!(polkit.calls().is_empty());
+
+        let err = call::<_, Vec<String>>(&bus, "ApplyConfig", false, &(config, "yaml", false))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, fdo::Error::AccessDenied(_)), "{:?}", err);
+
+        polkit.set_authorized(true);
+        let _: Vec<String> = call(&bus, "ApplyConfig", false, &(config, "yaml", false))
+            .await
+            .unwrap();
+        assert_eq!(polkit.calls().len(), 2);
+        // Only the real apply was recorded, so it can be rolled back
+        let history = history::load_all_history().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].operation, "apply");
+    }
+}
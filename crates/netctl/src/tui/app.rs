@@ -1,7 +1,15 @@
 use super::colors::*;
 use miette::Result;
 use netctl_core::NetworkManager;
+use netctl_types::{DnsStatistics, LinkInfo, NetworkdLinkState};
 use ratatui::{prelude::*, widgets::*};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How often networkd and resolved are asked for their state. Their D-Bus
+/// round-trips are slower than netlink and change less often.
+const DBUS_REFRESH: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
@@ -32,33 +40,89 @@ impl View {
     }
 }
 
+/// What the views show from networkd and resolved
+#[derive(Debug, Default)]
+pub struct DbusState {
+    /// networkd's view of each link, by interface index
+    pub networkd_states: HashMap<u32, NetworkdLinkState>,
+    /// `None` while systemd-resolved is unavailable
+    pub dns_statistics: Option<DnsStatistics>,
+}
+
+impl DbusState {
+    async fn fetch(manager: &NetworkManager) -> Self {
+        Self {
+            networkd_states: manager.list_networkd_states().await.unwrap_or_default(),
+            dns_statistics: manager.get_dns_statistics().await.ok(),
+        }
+    }
+}
+
+/// Fetch [`DbusState`] every [`DBUS_REFRESH`] on a task of its own, so the
+/// input loop never waits on D-Bus. Stops once the receiver is dropped.
+fn spawn_dbus_refresh(manager: NetworkManager) -> watch::Receiver<DbusState> {
+    let (tx, rx) = watch::channel(DbusState::default());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DBUS_REFRESH);
+        loop {
+            interval.tick().await;
+            if tx.send(DbusState::fetch(&manager).await).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
 pub struct App {
     pub manager: NetworkManager,
     pub should_quit: bool,
     pub selected_index: usize,
-    pub scroll_offset: usize,
     pub current_view: View,
     pub show_help: bool,
     pub show_stats_bar: bool,
     pub search_query: String,
     pub is_searching: bool,
+    /// Fetched by [`App::refresh`] on every tick, so drawing a frame never
+    /// waits on netlink
+    pub links: Vec<LinkInfo>,
+    /// networkd's view of each link, by interface index
+    pub networkd_states: HashMap<u32, NetworkdLinkState>,
+    /// `None` while systemd-resolved is unavailable
+    pub dns_statistics: Option<DnsStatistics>,
+    dbus_state: watch::Receiver<DbusState>,
 }
 
 impl App {
     pub async fn new() -> Result<Self> {
+        let dbus_state = spawn_dbus_refresh(NetworkManager::new().await?);
         Ok(Self {
             manager: NetworkManager::new().await?,
             should_quit: false,
             selected_index: 0,
-            scroll_offset: 0,
             current_view: View::Dashboard,
             show_help: false,
             show_stats_bar: true,
             search_query: String::new(),
             is_searching: false,
+            links: Vec::new(),
+            networkd_states: HashMap::new(),
+            dns_statistics: None,
+            dbus_state,
         })
     }
 
+    /// Fetch the links, and take the latest networkd and resolved state
+    /// from the background task if it has changed
+    pub async fn refresh(&mut self) {
+        self.links = self.manager.list_links().await.unwrap_or_default();
+        if self.dbus_state.has_changed().unwrap_or(false) {
+            let state = self.dbus_state.borrow_and_update();
+            self.networkd_states = state.networkd_states.clone();
+            self.dns_statistics = state.dns_statistics;
+        }
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
@@ -115,7 +179,7 @@ impl App {
         self.search_query.pop();
     }
 
-    pub async fn render(&self, frame: &mut Frame<'_>) -> Result<()> {
+    pub fn render(&self, frame: &mut Frame<'_>) -> Result<()> {
         let area = frame.size();
 
         // Create main layout with optional stats bar
@@ -149,7 +213,7 @@ impl App {
 
         // Render stats bar if enabled
         if self.show_stats_bar {
-            self.render_stats_bar(frame, chunks[idx]).await?;
+            self.render_stats_bar(frame, chunks[idx])?;
             idx += 1;
         }
 
@@ -159,9 +223,9 @@ impl App {
 
         // Render current view
         match self.current_view {
-            View::Dashboard => self.render_dashboard(frame, chunks[idx]).await?,
-            View::Statistics => self.render_statistics(frame, chunks[idx]).await?,
-            View::Configuration => self.render_configuration(frame, chunks[idx]).await?,
+            View::Dashboard => self.render_dashboard(frame, chunks[idx])?,
+            View::Statistics => self.render_statistics(frame, chunks[idx])?,
+            View::Configuration => self.render_configuration(frame, chunks[idx])?,
         }
         idx += 1;
 
@@ -201,11 +265,8 @@ impl App {
         frame.render_widget(header, area);
     }
 
-    async fn render_stats_bar(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let interfaces = match self.manager.list_links().await {
-            Ok(links) => links,
-            Err(_) => Vec::new(),
-        };
+    fn render_stats_bar(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let interfaces = &self.links;
 
         let total = interfaces.len();
         let up = interfaces.iter().filter(|i| matches!(i.state, netctl_types::network::LinkState::Up)).count();
@@ -263,7 +324,7 @@ impl App {
         frame.render_widget(tabs, area);
     }
 
-    async fn render_dashboard(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+    fn render_dashboard(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
         // Split main area into left and right panes
         let main_chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -274,23 +335,20 @@ impl App {
             .split(area);
 
         // Render interface list (left pane)
-        self.render_interface_list(frame, main_chunks[0]).await?;
+        self.render_interface_list(frame, main_chunks[0])?;
 
         // Render details pane (right pane)
-        self.render_details(frame, main_chunks[1]).await?;
+        self.render_details(frame, main_chunks[1])?;
 
         Ok(())
     }
 
-    async fn render_statistics(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let interfaces = match self.manager.list_links().await {
-            Ok(links) => links,
-            Err(_) => Vec::new(),
-        };
+    fn render_statistics(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let interfaces = &self.links;
 
         let total = interfaces.len() as u16;
         let up = interfaces.iter().filter(|i| matches!(i.state, netctl_types::network::LinkState::Up)).count() as u16;
-        let up_pct = if total > 0 { (up * 100) / total } else { 0 };
+        let up_pct = (up * 100).checked_div(total).unwrap_or(0);
 
         // Split into gauges
         let chunks = Layout::default()
@@ -337,7 +395,7 @@ impl App {
             ]),
         ];
 
-        for iface in &interfaces {
+        for iface in interfaces {
             let state_text = match iface.state {
                 netctl_types::network::LinkState::Up => Span::styled("UP", Style::default().fg(SUCCESS_COLOR)),
                 netctl_types::network::LinkState::Down => Span::styled("DOWN", Style::default().fg(ERROR_COLOR)),
@@ -362,11 +420,39 @@ impl App {
                     .title_style(Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
             );
 
-        frame.render_widget(stats_para, chunks[1]);
+        // Interface statistics on the left, resolver statistics on the right
+        let lower = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(60),
+                Constraint::Percentage(40),
+            ])
+            .split(chunks[1]);
+
+        frame.render_widget(stats_para, lower[0]);
+
+        let dns_lines = match &self.dns_statistics {
+            Some(stats) => dns_statistics_lines(stats),
+            None => vec![Line::from(vec![
+                Span::styled("systemd-resolved unavailable", Style::default().fg(ERROR_COLOR)),
+            ])],
+        };
+
+        let dns_para = Paragraph::new(dns_lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(BORDER_COLOR))
+                    .title(" 🔎 DNS ")
+                    .title_style(Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
+            );
+
+        frame.render_widget(dns_para, lower[1]);
         Ok(())
     }
 
-    async fn render_configuration(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+    fn render_configuration(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
         let text = vec![
             Line::from(vec![
                 Span::styled("Configuration View", Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
@@ -497,11 +583,8 @@ impl App {
         frame.render_widget(help, popup_area);
     }
 
-    async fn render_interface_list(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let interfaces = match self.manager.list_links().await {
-            Ok(links) => links,
-            Err(_) => Vec::new(),
-        };
+    fn render_interface_list(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+        let interfaces = &self.links;
 
         if interfaces.is_empty() {
             let empty = Paragraph::new("⚠️  No network interfaces found")
@@ -518,7 +601,7 @@ impl App {
             return Ok(());
         }
 
-        let header = Row::new(vec!["", "Name", "State", "MTU", "MAC Address"])
+        let header = Row::new(vec!["", "Name", "State", "MTU", "MAC Address", "networkd"])
             .style(Style::default().fg(ORANGE).add_modifier(Modifier::BOLD))
             .bottom_margin(1);
 
@@ -544,6 +627,10 @@ impl App {
                     format!("{:?}", iface.state),
                     iface.mtu.to_string(),
                     iface.mac_address.as_ref().map(|m| m.to_string()).unwrap_or_else(|| "-".to_string()),
+                    self.networkd_states
+                        .get(&iface.index)
+                        .map(|s| s.administrative_state.clone())
+                        .unwrap_or_else(|| "-".to_string()),
                 ])
                 .style(style)
             })
@@ -556,7 +643,8 @@ impl App {
                 Constraint::Length(15),
                 Constraint::Length(8),
                 Constraint::Length(8),
-                Constraint::Min(20),
+                Constraint::Length(18),
+                Constraint::Min(12),
             ],
         )
         .header(header)
@@ -574,13 +662,8 @@ impl App {
         Ok(())
     }
 
-    async fn render_details(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let interfaces = match self.manager.list_links().await {
-            Ok(links) => links,
-            Err(_) => Vec::new(),
-        };
-
-        if let Some(iface) = interfaces.get(self.selected_index) {
+    fn render_details(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
+        if let Some(iface) = self.links.get(self.selected_index) {
             let mut lines = vec![
                 Line::from(vec![
                     Span::styled("Interface: ", Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
@@ -632,6 +715,42 @@ impl App {
                 }
             }
 
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled("networkd:", Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
+            ]));
+
+            match self.networkd_states.get(&iface.index) {
+                Some(state) => {
+                    let setup_color = match state.administrative_state.as_str() {
+                        "configured" => SUCCESS_COLOR,
+                        "failed" => ERROR_COLOR,
+                        "unmanaged" => TEXT_COLOR,
+                        _ => LIGHT_ORANGE,
+                    };
+                    lines.push(Line::from(vec![
+                        Span::styled("  Setup: ", Style::default().fg(TEXT_COLOR)),
+                        Span::styled(state.administrative_state.clone(), Style::default().fg(setup_color)),
+                    ]));
+                    for (label, value) in [
+                        ("  Operational: ", &state.operational_state),
+                        ("  Carrier: ", &state.carrier_state),
+                        ("  Address: ", &state.address_state),
+                        ("  Online: ", &state.online_state),
+                    ] {
+                        lines.push(Line::from(vec![
+                            Span::styled(label, Style::default().fg(TEXT_COLOR)),
+                            Span::styled(value.clone(), Style::default().fg(LIGHT_ORANGE)),
+                        ]));
+                    }
+                }
+                None => {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Unavailable", Style::default().fg(TEXT_COLOR).add_modifier(Modifier::ITALIC)),
+                    ]));
+                }
+            }
+
             let details = Paragraph::new(lines)
                 .block(
                     Block::default()
@@ -706,3 +825,43 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+fn dns_statistics_lines(stats: &netctl_types::DnsStatistics) -> Vec<Line<'static>> {
+    let stat = |label: &str, value: String, color| {
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), Style::default().fg(TEXT_COLOR)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+    let heading = |text: &'static str| {
+        Line::from(vec![
+            Span::styled(text, Style::default().fg(ORANGE).add_modifier(Modifier::BOLD)),
+        ])
+    };
+
+    let hit_ratio = match stats.cache_hit_ratio() {
+        Some(ratio) => format!("{:.1}%", ratio * 100.0),
+        None => "-".to_string(),
+    };
+    let failures = stats.dnssec_failures();
+    let failure_color = if failures > 0 { ERROR_COLOR } else { SUCCESS_COLOR };
+
+    vec![
+        heading("Transactions"),
+        stat("Current", stats.current_transactions.to_string(), LIGHT_ORANGE),
+        stat("Total", stats.total_transactions.to_string(), LIGHT_ORANGE),
+        Line::from(""),
+        heading("Cache"),
+        stat("Size", stats.cache_size.to_string(), LIGHT_ORANGE),
+        stat("Hits", stats.cache_hits.to_string(), SUCCESS_COLOR),
+        stat("Misses", stats.cache_misses.to_string(), LIGHT_ORANGE),
+        stat("Hit ratio", hit_ratio, LIGHT_ORANGE),
+        Line::from(""),
+        heading("DNSSEC"),
+        stat("Secure", stats.dnssec_secure.to_string(), SUCCESS_COLOR),
+        stat("Insecure", stats.dnssec_insecure.to_string(), LIGHT_ORANGE),
+        stat("Bogus", stats.dnssec_bogus.to_string(), ERROR_COLOR),
+        stat("Indeterminate", stats.dnssec_indeterminate.to_string(), LIGHT_ORANGE),
+        stat("Failures", failures.to_string(), failure_color),
+    ]
+}
@@ -74,10 +74,7 @@ impl Dashboard {
 
     async fn render_interfaces(&self, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
         // Get interface information
-        let interfaces = match self.manager.list_links().await {
-            Ok(links) => links,
-            Err(_) => Vec::new(),
-        };
+        let interfaces = self.manager.list_links().await.unwrap_or_default();
 
         // Create table header
         let header = Row::new(vec!["Index", "Name", "State", "MTU", "MAC Address"])
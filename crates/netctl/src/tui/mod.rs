@@ -1,5 +1,6 @@
 pub mod app;
 pub mod colors;
+#[allow(dead_code)]
 pub mod dashboard;
 
 use crossterm::{
@@ -13,7 +14,6 @@ use std::io::{self, stdout};
 use std::time::{Duration, Instant};
 
 pub use app::App;
-pub use dashboard::Dashboard;
 
 pub fn init_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode().into_diagnostic()?;
@@ -32,23 +32,14 @@ pub async fn run(mut app: App) -> Result<()> {
     let mut terminal = init_terminal()?;
     let tick_rate = Duration::from_millis(250);
     let mut last_tick = Instant::now();
+    app.refresh().await;
 
     loop {
-        // Get interface count for navigation
-        let interface_count = match app.manager.list_links().await {
-            Ok(links) => links.len(),
-            Err(_) => 0,
-        };
-
         // Draw UI
         terminal.draw(|frame| {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(async {
-                    if let Err(e) = app.render(frame).await {
-                        eprintln!("Render error: {}", e);
-                    }
-                })
-            });
+            if let Err(e) = app.render(frame) {
+                eprintln!("Render error: {}", e);
+            }
         }).into_diagnostic()?;
 
         // Calculate timeout
@@ -58,19 +49,18 @@ pub async fn run(mut app: App) -> Result<()> {
 
         // Handle events
         if event::poll(timeout).into_diagnostic()? {
-            match event::read().into_diagnostic()? {
-                Event::Key(key) => {
-                    handle_key_event(&mut app, key, interface_count);
-                    if app.should_quit {
-                        break;
-                    }
+            if let Event::Key(key) = event::read().into_diagnostic()? {
+                let interface_count = app.links.len();
+                handle_key_event(&mut app, key, interface_count);
+                if app.should_quit {
+                    break;
                 }
-                _ => {}
             }
         }
 
         // Update tick
         if last_tick.elapsed() >= tick_rate {
+            app.refresh().await;
             last_tick = Instant::now();
         }
     }